@@ -0,0 +1,128 @@
+// Defines the public @ember/* module api(RFC 176) as AMD modules that read from the Ember global,
+// the same mapping babel-plugin-ember-modules-api-polyfill applies at build time in ember-cli.
+// NOTE: the leading semicolon keeps the production ember build, which has no trailing semicolon, from calling this iife
+;(function() {
+  var MODULES = {
+    "ember": { default: "" },
+    "@ember/application": {
+      default: "Application", getOwner: "getOwner", setOwner: "setOwner", onLoad: "onLoad", runLoadHooks: "runLoadHooks"
+    },
+    "@ember/application/deprecations": { deprecate: "deprecate", deprecateFunc: "deprecateFunc" },
+    "@ember/application/globals-resolver": { default: "DefaultResolver" },
+    "@ember/application/instance": { default: "ApplicationInstance" },
+    "@ember/application/namespace": { default: "Namespace" },
+    "@ember/application/resolver": { default: "Resolver" },
+    "@ember/array": { default: "Array", A: "A", isArray: "isArray", makeArray: "makeArray" },
+    "@ember/array/mutable": { default: "MutableArray" },
+    "@ember/array/proxy": { default: "ArrayProxy" },
+    "@ember/component": {
+      default: "Component", setComponentManager: "_setComponentManager",
+      capabilities: "_componentManagerCapabilities", getComponentTemplate: "_getComponentTemplate",
+      setComponentTemplate: "_setComponentTemplate"
+    },
+    "@ember/component/checkbox": { default: "Checkbox" },
+    "@ember/component/helper": { default: "Helper", helper: "Helper.helper" },
+    "@ember/component/template-only": { default: "_templateOnlyComponent" },
+    "@ember/component/text-area": { default: "TextArea" },
+    "@ember/component/text-field": { default: "TextField" },
+    "@ember/controller": { default: "Controller", inject: "inject.controller" },
+    "@ember/debug": {
+      assert: "assert", debug: "debug", inspect: "inspect", runInDebug: "runInDebug", warn: "warn",
+      registerDeprecationHandler: "Debug.registerDeprecationHandler", registerWarnHandler: "Debug.registerWarnHandler"
+    },
+    "@ember/debug/container-debug-adapter": { default: "ContainerDebugAdapter" },
+    "@ember/debug/data-adapter": { default: "DataAdapter" },
+    "@ember/engine": { default: "Engine", getEngineParent: "getEngineParent" },
+    "@ember/engine/instance": { default: "EngineInstance" },
+    "@ember/error": { default: "Error" },
+    "@ember/instrumentation": {
+      instrument: "instrument", reset: "Instrumentation.reset", subscribe: "Instrumentation.subscribe",
+      unsubscribe: "Instrumentation.unsubscribe"
+    },
+    "@ember/modifier": { setModifierManager: "_setModifierManager", capabilities: "_modifierManagerCapabilities" },
+    "@ember/object": {
+      default: "Object", action: "_action", aliasMethod: "aliasMethod", computed: "computed",
+      defineProperty: "defineProperty", get: "get", getProperties: "getProperties", getWithDefault: "getWithDefault",
+      notifyPropertyChange: "notifyPropertyChange", observer: "observer", set: "set", setProperties: "setProperties",
+      trySet: "trySet"
+    },
+    "@ember/object/compat": { dependentKeyCompat: "_dependentKeyCompat" },
+    "@ember/object/computed": {
+      default: "ComputedProperty", alias: "computed.alias", and: "computed.and", bool: "computed.bool",
+      collect: "computed.collect", deprecatingAlias: "computed.deprecatingAlias", empty: "computed.empty",
+      equal: "computed.equal", expandProperties: "expandProperties", filter: "computed.filter",
+      filterBy: "computed.filterBy", gt: "computed.gt", gte: "computed.gte", intersect: "computed.intersect",
+      lt: "computed.lt", lte: "computed.lte", map: "computed.map", mapBy: "computed.mapBy", match: "computed.match",
+      max: "computed.max", min: "computed.min", none: "computed.none", not: "computed.not",
+      notEmpty: "computed.notEmpty", oneWay: "computed.oneWay", or: "computed.or", readOnly: "computed.readOnly",
+      reads: "computed.reads", setDiff: "computed.setDiff", sort: "computed.sort", sum: "computed.sum",
+      union: "computed.union", uniq: "computed.uniq", uniqBy: "computed.uniqBy"
+    },
+    "@ember/object/core": { default: "CoreObject" },
+    "@ember/object/evented": { default: "Evented", on: "on" },
+    "@ember/object/events": { addListener: "addListener", removeListener: "removeListener", sendEvent: "sendEvent" },
+    "@ember/object/internals": { cacheFor: "cacheFor", copy: "copy", guidFor: "guidFor" },
+    "@ember/object/mixin": { default: "Mixin" },
+    "@ember/object/observable": { default: "Observable" },
+    "@ember/object/observers": { addObserver: "addObserver", removeObserver: "removeObserver" },
+    "@ember/object/promise-proxy-mixin": { default: "PromiseProxyMixin" },
+    "@ember/object/proxy": { default: "ObjectProxy" },
+    "@ember/polyfills": { assign: "assign", merge: "merge" },
+    "@ember/routing/auto-location": { default: "AutoLocation" },
+    "@ember/routing/hash-location": { default: "HashLocation" },
+    "@ember/routing/history-location": { default: "HistoryLocation" },
+    "@ember/routing/link-component": { default: "LinkComponent" },
+    "@ember/routing/location": { default: "Location" },
+    "@ember/routing/none-location": { default: "NoneLocation" },
+    "@ember/routing/route": { default: "Route" },
+    "@ember/routing/router": { default: "Router" },
+    "@ember/runloop": {
+      begin: "run.begin", bind: "run.bind", cancel: "run.cancel", debounce: "run.debounce", end: "run.end",
+      join: "run.join", later: "run.later", next: "run.next", once: "run.once", run: "run", schedule: "run.schedule",
+      scheduleOnce: "run.scheduleOnce", throttle: "run.throttle", _backburner: "run.backburner"
+    },
+    "@ember/service": { default: "Service", inject: "inject.service" },
+    "@ember/string": {
+      camelize: "String.camelize", capitalize: "String.capitalize", classify: "String.classify",
+      dasherize: "String.dasherize", decamelize: "String.decamelize", fmt: "String.fmt", htmlSafe: "String.htmlSafe",
+      isHTMLSafe: "String.isHTMLSafe", loc: "String.loc", underscore: "String.underscore", w: "String.w"
+    },
+    "@ember/template": { htmlSafe: "String.htmlSafe", isHTMLSafe: "String.isHTMLSafe" },
+    "@ember/test": {
+      registerAsyncHelper: "Test.registerAsyncHelper", registerHelper: "Test.registerHelper",
+      registerWaiter: "Test.registerWaiter", unregisterHelper: "Test.unregisterHelper",
+      unregisterWaiter: "Test.unregisterWaiter"
+    },
+    "@ember/test/adapter": { default: "Test.Adapter" },
+    "@ember/utils": {
+      compare: "compare", isBlank: "isBlank", isEmpty: "isEmpty", isEqual: "isEqual", isNone: "isNone",
+      isPresent: "isPresent", tryInvoke: "tryInvoke", typeOf: "typeOf"
+    },
+    "@ember/version": { VERSION: "VERSION" },
+    "@glimmer/tracking": { tracked: "_tracked" },
+    "rsvp": { default: "RSVP", Promise: "RSVP.Promise", all: "RSVP.all", hash: "RSVP.hash", resolve: "RSVP.resolve", reject: "RSVP.reject", defer: "RSVP.defer" }
+  };
+
+  function lookup(path) {
+    return path === "" ? Ember : path.split(".").reduce(function(value, key) {
+      return value === undefined || value === null ? undefined : value[key];
+    }, Ember);
+  }
+
+  Object.keys(MODULES).forEach(function(moduleName) {
+    if (window.requirejs.entries[moduleName]) {
+      return;
+    }
+
+    window.define(moduleName, ["exports"], function(_exports) {
+      Object.defineProperty(_exports, "__esModule", { value: true });
+
+      Object.keys(MODULES[moduleName]).forEach(function(exportName) {
+        Object.defineProperty(_exports, exportName, {
+          enumerable: true,
+          get: function() { return lookup(MODULES[moduleName][exportName]); }
+        });
+      });
+    });
+  });
+})();
@@ -68,6 +68,17 @@ if (typeof FastBoot !== "undefined") {
     exports.Response = nodeFetch.Response;
     exports.AbortController = AbortControllerPolyfill.AbortController;
   });
+} else {
+  define("fetch", ["exports"], function(exports) {
+    exports.__esModule = true;
+    exports.default = function() {
+      return window.fetch.apply(window, arguments);
+    };
+    exports.Headers = window.Headers;
+    exports.Request = window.Request;
+    exports.Response = window.Response;
+    exports.AbortController = window.AbortController;
+  });
 }
 
 define("fetch/ajax", ["exports"], function() {
@@ -122,6 +133,10 @@ define("{{applicationName}}/instance-initializers/setup-fetch", [
    * from the fastboot service. Then we set the protocol and host to fetch module.
    */
   function patchFetchForRelativeURLs(instance) {
+    if (typeof FastBoot === "undefined") {
+      return;
+    }
+
     const fastboot = instance.lookup("service:fastboot");
     const request = fastboot.get("request"); // Prember is not sending protocol
 
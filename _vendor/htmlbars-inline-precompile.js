@@ -0,0 +1,19 @@
+// Runtime replacement for the hbs`` build time precompilation of ember-cli-htmlbars-inline-precompile,
+// needs ember-template-compiler.js to be loaded after ember.
+(function() {
+  function hbs(strings) {
+    var source = typeof strings === "string" ? strings : String.raw.apply(String, arguments);
+    var compiler = Ember.__loader.require("ember-template-compiler");
+
+    return Ember.HTMLBars.template(JSON.parse(compiler.precompile(source)));
+  }
+
+  ["htmlbars-inline-precompile", "ember-cli-htmlbars-inline-precompile", "ember-cli-htmlbars"].forEach(function(moduleName) {
+    window.define(moduleName, ["exports"], function(_exports) {
+      Object.defineProperty(_exports, "__esModule", { value: true });
+
+      _exports.default = hbs;
+      _exports.hbs = hbs;
+    });
+  });
+})();
@@ -0,0 +1,94 @@
+// Boots the built tmp/index.html of an ember application in a headless DOM and starts a node.js repl inside it.
+// Options are given as JSON in the first argument: { projectRoot, url, historyPath, reloadExitCode }.
+// The booted application is available as window.App, .reload exits with reloadExitCode so mber can rebuild and reboot.
+// Expects mber-dom/context.js to be defined before this file.
+var repl = require('repl');
+
+var options = JSON.parse(process.argv[process.argv.length - 1]);
+var buildFolder = path.join(options.projectRoot, 'tmp');
+var indexHTMLPath = path.join(buildFolder, 'index.html');
+
+function isRecoverable(error) {
+  return error && error.name === 'SyntaxError' &&
+    /^(Unexpected end of input|Unterminated template literal|missing \) after argument list|Unterminated string constant)/.test(error.message);
+}
+
+function bootApplication(context) {
+  runScripts(context, buildFolder, indexHTMLPath);
+  dispatchLoadEvents(context);
+
+  var Ember = context.Ember;
+  var application = Ember && Ember.Namespace.NAMESPACES.find((namespace) => namespace instanceof Ember.Application);
+
+  if (!application) {
+    throw new Error('no ember application was created by tmp/assets/application.js');
+  }
+
+  context.App = application;
+
+  return application;
+}
+
+function startREPL(context, application) {
+  // NOTE: {} inputs are evaluated as object literals like the default node.js repl does
+  function evaluate(code, _replContext, fileName, callback) {
+    var result;
+
+    try {
+      result = /^\s*\{/.test(code) && /\}\s*$/.test(code) ? evaluateObjectLiteral(code, fileName) :
+        vm.runInContext(code, context, { filename: fileName });
+    } catch (error) {
+      return callback(isRecoverable(error) ? new repl.Recoverable(error) : error);
+    }
+
+    if (result && typeof result.then === 'function') {
+      return result.then((value) => callback(null, value), (error) => callback(error));
+    }
+
+    callback(null, result);
+  }
+
+  function evaluateObjectLiteral(code, fileName) {
+    try {
+      return vm.runInContext(`(${code.trim()})`, context, { filename: fileName });
+    } catch (error) {
+      return vm.runInContext(code, context, { filename: fileName });
+    }
+  }
+
+  function exit(exitCode) {
+    process.exitCode = exitCode;
+    setTimeout(() => process.exit(exitCode), 100).unref(); // NOTE: waits for the history file to be written
+  }
+
+  var server = repl.start({ prompt: `${application.modulePrefix || 'ember'}> `, eval: evaluate });
+  var exitCode = 0;
+
+  server.context = context; // NOTE: tab completion reads from the repl context
+  server.defineCommand('reload', {
+    help: 'Rebuild the application and boot it again',
+    action() {
+      exitCode = options.reloadExitCode;
+      server.close();
+    }
+  });
+  server.on('exit', () => exit(exitCode));
+
+  if (options.historyPath) {
+    server.setupHistory(options.historyPath, (error) => error && console.error(error.message));
+  }
+
+  return server;
+}
+
+try {
+  var context = createDOMContext(fs.readFileSync(indexHTMLPath, 'utf8'), options.url);
+
+  process.on('uncaughtException', (error) => console.error(error.stack || String(error)));
+  process.on('unhandledRejection', (reason) => console.error(reason && reason.stack ? reason.stack : String(reason)));
+
+  startREPL(context, bootApplication(context));
+} catch (error) {
+  console.error(error.stack || String(error));
+  process.exit(1);
+}
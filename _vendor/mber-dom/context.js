@@ -0,0 +1,52 @@
+// Creates a node.js vm context that acts as a browser window for a built html file and runs its scripts in order.
+// Expects DOM_SOURCE(the source of dom.js) to be defined before this file.
+var fs = require('fs');
+var path = require('path');
+var vm = require('vm');
+
+function createDOMContext(html, url) {
+  var context = vm.createContext({
+    console: new console.Console(process.stderr, process.stderr),
+    setTimeout: setTimeout,
+    clearTimeout: clearTimeout,
+    setInterval: setInterval,
+    clearInterval: clearInterval,
+    setImmediate: setImmediate,
+    clearImmediate: clearImmediate,
+    queueMicrotask: queueMicrotask,
+    URL: URL,
+    URLSearchParams: URLSearchParams,
+    TextEncoder: TextEncoder,
+    TextDecoder: TextDecoder,
+    AbortController: AbortController,
+    performance: performance,
+    btoa: btoa,
+    atob: atob
+  });
+
+  vm.runInContext(DOM_SOURCE + '\n;createWindow(this.__html, this.__url, this); delete this.__html; delete this.__url;',
+    Object.assign(context, { __html: html, __url: url }), { filename: 'mber-dom.js' });
+
+  return context;
+}
+
+// NOTE: script srcs are read from the build folder, onScript runs after each script for extra setup
+function runScripts(context, buildFolder, htmlPath, onScript) {
+  Array.from(context.document.querySelectorAll('script')).forEach((script) => {
+    var source = (script.getAttribute('src') || '').split('?')[0];
+    var fileName = source ? path.join(buildFolder, source) : htmlPath;
+    var code = source ? fs.readFileSync(fileName, 'utf8') : script.textContent;
+
+    vm.runInContext(code, context, { filename: fileName });
+
+    if (onScript) {
+      onScript(source);
+    }
+  });
+}
+
+function dispatchLoadEvents(context) {
+  context.document.readyState = 'complete';
+  context.document.dispatchEvent(new context.Event('DOMContentLoaded', { bubbles: true }));
+  context.dispatchEvent(new context.Event('load'));
+}
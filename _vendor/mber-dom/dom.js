@@ -0,0 +1,1447 @@
+// Minimal DOM implementation for running ember applications and their tests headlessly in node.
+// It implements the parts of the DOM that ember, glimmer, qunit and @ember/test-helpers rely on.
+// When a global object is given it becomes the window, so scripts see document, location etc. as globals.
+function createWindow(html, url, globalObject) {
+  var HTML_NAMESPACE = 'http://www.w3.org/1999/xhtml';
+  var VOID_ELEMENTS = ['area', 'base', 'br', 'col', 'embed', 'hr', 'img', 'input', 'link', 'meta', 'param', 'source', 'track', 'wbr'];
+  var RAW_TEXT_ELEMENTS = ['script', 'style', 'textarea', 'title'];
+  var REFLECTED_ATTRIBUTES = {
+    id: 'id', className: 'class', title: 'title', name: 'name', type: 'type', src: 'src', alt: 'alt',
+    rel: 'rel', placeholder: 'placeholder', htmlFor: 'for', role: 'role', lang: 'lang', dir: 'dir', action: 'action',
+    method: 'method', target: 'target'
+  };
+  var BOOLEAN_ATTRIBUTES = ['disabled', 'readOnly', 'required', 'hidden', 'multiple', 'autofocus'];
+  var document;
+
+  class Event {
+    constructor(type, options) {
+      options = options || {};
+      this.type = type;
+      this.bubbles = !!options.bubbles;
+      this.cancelable = !!options.cancelable;
+      this.composed = !!options.composed;
+      this.defaultPrevented = false;
+      this.target = null;
+      this.currentTarget = null;
+      this.eventPhase = 0;
+      this.timeStamp = Date.now();
+      this.isTrusted = false;
+      this._stopped = false;
+      this._immediatelyStopped = false;
+      Object.keys(options).forEach((key) => {
+        if (!(key in this)) {
+          this[key] = options[key];
+        }
+      });
+    }
+    initEvent(type, bubbles, cancelable) {
+      this.type = type;
+      this.bubbles = !!bubbles;
+      this.cancelable = !!cancelable;
+    }
+    initUIEvent(type, bubbles, cancelable, view, detail) {
+      this.initEvent(type, bubbles, cancelable);
+      this.view = view;
+      this.detail = detail;
+    }
+    initCustomEvent(type, bubbles, cancelable, detail) {
+      this.initEvent(type, bubbles, cancelable);
+      this.detail = detail;
+    }
+    initMouseEvent(type, bubbles, cancelable, view, detail, screenX, screenY, clientX, clientY, ctrlKey, altKey, shiftKey, metaKey, button, relatedTarget) {
+      this.initUIEvent(type, bubbles, cancelable, view, detail);
+      Object.assign(this, {
+        screenX: screenX, screenY: screenY, clientX: clientX, clientY: clientY, ctrlKey: ctrlKey, altKey: altKey,
+        shiftKey: shiftKey, metaKey: metaKey, button: button, relatedTarget: relatedTarget
+      });
+    }
+    initKeyboardEvent(type, bubbles, cancelable, view, key, location, ctrlKey, altKey, shiftKey, metaKey) {
+      this.initUIEvent(type, bubbles, cancelable, view, 0);
+      Object.assign(this, { key: key, location: location, ctrlKey: ctrlKey, altKey: altKey, shiftKey: shiftKey, metaKey: metaKey });
+    }
+    initKeyEvent(type, bubbles, cancelable, view, ctrlKey, altKey, shiftKey, metaKey, keyCode, charCode) {
+      this.initUIEvent(type, bubbles, cancelable, view, 0);
+      Object.assign(this, {
+        ctrlKey: ctrlKey, altKey: altKey, shiftKey: shiftKey, metaKey: metaKey, keyCode: keyCode, charCode: charCode
+      });
+    }
+    preventDefault() {
+      if (this.cancelable) {
+        this.defaultPrevented = true;
+      }
+    }
+    stopPropagation() {
+      this._stopped = true;
+    }
+    stopImmediatePropagation() {
+      this._stopped = true;
+      this._immediatelyStopped = true;
+    }
+    composedPath() {
+      var path = [];
+      for (var node = this.target; node; node = node.parentNode || (node === document ? window : null)) {
+        path.push(node);
+      }
+      return path;
+    }
+  }
+  class UIEvent extends Event {}
+  class CustomEvent extends Event {}
+  class MouseEvent extends UIEvent {}
+  class KeyboardEvent extends UIEvent {}
+  class FocusEvent extends UIEvent {}
+
+  class EventTarget {
+    addEventListener(type, listener, options) {
+      if (!listener) {
+        return;
+      }
+      var capture = typeof options === 'boolean' ? options : !!(options && options.capture);
+      var once = !!(options && options.once);
+      this._listeners = this._listeners || {};
+      this._listeners[type] = this._listeners[type] || [];
+      if (!this._listeners[type].some((entry) => entry.listener === listener && entry.capture === capture)) {
+        this._listeners[type].push({ listener: listener, capture: capture, once: once });
+      }
+    }
+    removeEventListener(type, listener, options) {
+      var capture = typeof options === 'boolean' ? options : !!(options && options.capture);
+      if (this._listeners && this._listeners[type]) {
+        this._listeners[type] = this._listeners[type]
+          .filter((entry) => !(entry.listener === listener && entry.capture === capture));
+      }
+    }
+    dispatchEvent(event) {
+      var ancestors = [];
+      event.target = this;
+      for (var node = this.parentNode || (this === document ? window : null); node; node = node.parentNode || (node === document ? window : null)) {
+        ancestors.push(node);
+      }
+      event.eventPhase = 1;
+      for (var i = ancestors.length - 1; i >= 0 && !event._stopped; i--) {
+        invokeListeners(ancestors[i], event, true);
+      }
+      event.eventPhase = 2;
+      if (!event._stopped) {
+        invokeListeners(this, event, true);
+      }
+      if (!event._stopped) {
+        invokeListeners(this, event, false);
+      }
+      if (!event._stopped) {
+        runEventHandlerProperty(this, event);
+      }
+      event.eventPhase = 3;
+      for (var j = 0; j < ancestors.length && event.bubbles && !event._stopped; j++) {
+        invokeListeners(ancestors[j], event, false);
+        if (!event._stopped) {
+          runEventHandlerProperty(ancestors[j], event);
+        }
+      }
+      event.eventPhase = 0;
+      event.currentTarget = null;
+      return !event.defaultPrevented;
+    }
+  }
+
+  function invokeListeners(target, event, capture) {
+    var listeners = target._listeners && target._listeners[event.type];
+    if (!listeners) {
+      return;
+    }
+    event.currentTarget = target;
+    listeners.slice().forEach((entry) => {
+      if (event._immediatelyStopped || entry.capture !== capture) {
+        return;
+      }
+      if (entry.once) {
+        target.removeEventListener(event.type, entry.listener, entry.capture);
+      }
+      if (typeof entry.listener === 'function') {
+        entry.listener.call(target, event);
+      } else if (entry.listener && typeof entry.listener.handleEvent === 'function') {
+        entry.listener.handleEvent(event);
+      }
+    });
+  }
+
+  function runEventHandlerProperty(target, event) {
+    var handler = target['on' + event.type];
+    if (typeof handler === 'function') {
+      event.currentTarget = target;
+      if (handler.call(target, event) === false) {
+        event.preventDefault();
+      }
+    }
+  }
+
+  class Node extends EventTarget {
+    constructor(nodeType, nodeName) {
+      super();
+      this.nodeType = nodeType;
+      this.nodeName = nodeName;
+      this.parentNode = null;
+      this.childNodes = [];
+      this.ownerDocument = document;
+    }
+    get firstChild() {
+      return this.childNodes[0] || null;
+    }
+    get lastChild() {
+      return this.childNodes[this.childNodes.length - 1] || null;
+    }
+    get nextSibling() {
+      return this.parentNode ? this.parentNode.childNodes[this.parentNode.childNodes.indexOf(this) + 1] || null : null;
+    }
+    get previousSibling() {
+      return this.parentNode ? this.parentNode.childNodes[this.parentNode.childNodes.indexOf(this) - 1] || null : null;
+    }
+    get parentElement() {
+      return this.parentNode && this.parentNode.nodeType === 1 ? this.parentNode : null;
+    }
+    get isConnected() {
+      var node = this;
+      while (node.parentNode) {
+        node = node.parentNode;
+      }
+      return node === document;
+    }
+    get textContent() {
+      return this.childNodes.map((child) => child.nodeType === 8 ? '' : child.textContent).join('');
+    }
+    set textContent(value) {
+      this.childNodes.slice().forEach((child) => this.removeChild(child));
+      if (value !== null && value !== undefined && value !== '') {
+        this.appendChild(document.createTextNode(String(value)));
+      }
+    }
+    hasChildNodes() {
+      return this.childNodes.length > 0;
+    }
+    getRootNode() {
+      var node = this;
+      while (node.parentNode) {
+        node = node.parentNode;
+      }
+      return node;
+    }
+    appendChild(child) {
+      return this.insertBefore(child, null);
+    }
+    insertBefore(child, referenceNode) {
+      if (child.nodeType === 11) {
+        child.childNodes.slice().forEach((fragmentChild) => this.insertBefore(fragmentChild, referenceNode));
+        return child;
+      }
+      if (child.parentNode) {
+        child.parentNode.removeChild(child);
+      }
+      var index = referenceNode ? this.childNodes.indexOf(referenceNode) : -1;
+      if (referenceNode && index === -1) {
+        throw new Error('NotFoundError: The node before which the new node is to be inserted is not a child of this node.');
+      }
+      this.childNodes.splice(index === -1 ? this.childNodes.length : index, 0, child);
+      child.parentNode = this;
+      return child;
+    }
+    removeChild(child) {
+      var index = this.childNodes.indexOf(child);
+      if (index === -1) {
+        throw new Error('NotFoundError: The node to be removed is not a child of this node.');
+      }
+      this.childNodes.splice(index, 1);
+      child.parentNode = null;
+      if (document.activeElement === child || child.contains(document.activeElement)) {
+        document.activeElement = document.body;
+      }
+      return child;
+    }
+    replaceChild(newChild, oldChild) {
+      this.insertBefore(newChild, oldChild);
+      return this.removeChild(oldChild);
+    }
+    remove() {
+      if (this.parentNode) {
+        this.parentNode.removeChild(this);
+      }
+    }
+    contains(node) {
+      for (; node; node = node.parentNode) {
+        if (node === this) {
+          return true;
+        }
+      }
+      return false;
+    }
+    cloneNode(deep) {
+      var clone = this._cloneShallow();
+      if (deep) {
+        this.childNodes.forEach((child) => clone.appendChild(child.cloneNode(true)));
+      }
+      return clone;
+    }
+    compareDocumentPosition(other) {
+      if (this === other) {
+        return 0;
+      } else if (this.contains(other)) {
+        return 20;
+      } else if (other.contains(this)) {
+        return 10;
+      }
+      var nodes = allNodes(this.getRootNode());
+      return nodes.indexOf(other) > nodes.indexOf(this) ? 4 : 2;
+    }
+  }
+  Object.assign(Node, {
+    ELEMENT_NODE: 1, ATTRIBUTE_NODE: 2, TEXT_NODE: 3, COMMENT_NODE: 8, DOCUMENT_NODE: 9, DOCUMENT_FRAGMENT_NODE: 11,
+    DOCUMENT_POSITION_PRECEDING: 2, DOCUMENT_POSITION_FOLLOWING: 4, DOCUMENT_POSITION_CONTAINS: 8,
+    DOCUMENT_POSITION_CONTAINED_BY: 16
+  });
+
+  class CharacterData extends Node {
+    constructor(nodeType, nodeName, data) {
+      super(nodeType, nodeName);
+      this.data = String(data);
+    }
+    get nodeValue() {
+      return this.data;
+    }
+    set nodeValue(value) {
+      this.data = String(value);
+    }
+    get textContent() {
+      return this.data;
+    }
+    set textContent(value) {
+      this.data = String(value);
+    }
+    get length() {
+      return this.data.length;
+    }
+  }
+
+  class Text extends CharacterData {
+    constructor(data) {
+      super(3, '#text', data);
+    }
+    _cloneShallow() {
+      return document.createTextNode(this.data);
+    }
+  }
+
+  class Comment extends CharacterData {
+    constructor(data) {
+      super(8, '#comment', data);
+    }
+    _cloneShallow() {
+      return document.createComment(this.data);
+    }
+  }
+
+  class ParentNode extends Node {
+    get children() {
+      return this.childNodes.filter((child) => child.nodeType === 1);
+    }
+    get childElementCount() {
+      return this.children.length;
+    }
+    get firstElementChild() {
+      return this.children[0] || null;
+    }
+    get lastElementChild() {
+      var children = this.children;
+      return children[children.length - 1] || null;
+    }
+    append() {
+      Array.from(arguments).forEach((node) => this.appendChild(typeof node === 'string' ? document.createTextNode(node) : node));
+    }
+    prepend() {
+      var firstChild = this.firstChild;
+      Array.from(arguments).forEach((node) => {
+        this.insertBefore(typeof node === 'string' ? document.createTextNode(node) : node, firstChild);
+      });
+    }
+    querySelectorAll(selector) {
+      var selectors = parseSelector(selector);
+      return createNodeList(descendantElements(this).filter((element) => matchesSelectors(element, selectors, this)));
+    }
+    querySelector(selector) {
+      return this.querySelectorAll(selector)[0] || null;
+    }
+    getElementsByTagName(tagName) {
+      return createNodeList(descendantElements(this).filter((element) => {
+        return tagName === '*' || element.localName === tagName.toLowerCase();
+      }));
+    }
+    getElementsByClassName(classNames) {
+      var names = classNames.split(/\s+/).filter(Boolean);
+      return createNodeList(descendantElements(this).filter((element) => {
+        return names.every((name) => element.classList.contains(name));
+      }));
+    }
+  }
+
+  class DocumentFragment extends ParentNode {
+    constructor() {
+      super(11, '#document-fragment');
+    }
+    getElementById(id) {
+      return descendantElements(this).find((element) => element.id === id) || null;
+    }
+    _cloneShallow() {
+      return document.createDocumentFragment();
+    }
+  }
+
+  class DOMTokenList {
+    constructor(element) {
+      this._element = element;
+    }
+    _tokens() {
+      return (this._element.getAttribute('class') || '').split(/\s+/).filter(Boolean);
+    }
+    _update(tokens) {
+      this._element.setAttribute('class', tokens.join(' '));
+    }
+    get length() {
+      return this._tokens().length;
+    }
+    get value() {
+      return this._tokens().join(' ');
+    }
+    item(index) {
+      return this._tokens()[index] || null;
+    }
+    contains(token) {
+      return this._tokens().indexOf(token) !== -1;
+    }
+    add() {
+      var tokens = this._tokens();
+      Array.from(arguments).forEach((token) => tokens.indexOf(token) === -1 ? tokens.push(token) : null);
+      this._update(tokens);
+    }
+    remove() {
+      var tokensToRemove = Array.from(arguments);
+      this._update(this._tokens().filter((token) => tokensToRemove.indexOf(token) === -1));
+    }
+    toggle(token, force) {
+      var shouldAdd = force === undefined ? !this.contains(token) : force;
+      shouldAdd ? this.add(token) : this.remove(token);
+      return shouldAdd;
+    }
+    forEach(callback) {
+      this._tokens().forEach(callback);
+    }
+    toString() {
+      return this.value;
+    }
+  }
+
+  function createStyleDeclaration(element) {
+    var parse = () => (element.getAttribute('style') || '').split(';').reduce((result, declaration) => {
+      var index = declaration.indexOf(':');
+      if (index !== -1) {
+        result[declaration.slice(0, index).trim()] = declaration.slice(index + 1).trim();
+      }
+      return result;
+    }, {});
+    var serialize = (declarations) => {
+      var cssText = Object.keys(declarations).map((name) => `${name}: ${declarations[name]};`).join(' ');
+      cssText ? element.setAttribute('style', cssText) : element.removeAttribute('style');
+    };
+    var toPropertyName = (name) => name.replace(/[A-Z]/g, (character) => '-' + character.toLowerCase());
+    var style = {
+      getPropertyValue: (name) => parse()[name] || '',
+      setProperty: (name, value) => {
+        var declarations = parse();
+        if (value === null || value === undefined || value === '') {
+          delete declarations[name];
+        } else {
+          declarations[name] = String(value);
+        }
+        serialize(declarations);
+      },
+      removeProperty: (name) => {
+        var declarations = parse();
+        var value = declarations[name] || '';
+        delete declarations[name];
+        serialize(declarations);
+        return value;
+      }
+    };
+
+    return new Proxy(style, {
+      get(target, name) {
+        if (name in target || typeof name !== 'string') {
+          return target[name];
+        } else if (name === 'cssText') {
+          return element.getAttribute('style') || '';
+        }
+        return parse()[toPropertyName(name)] || '';
+      },
+      set(target, name, value) {
+        if (name === 'cssText') {
+          value ? element.setAttribute('style', value) : element.removeAttribute('style');
+        } else {
+          target.setProperty(toPropertyName(name), value);
+        }
+        return true;
+      }
+    });
+  }
+
+  class Element extends ParentNode {
+    constructor(localName, namespaceURI) {
+      super(1, namespaceURI === HTML_NAMESPACE ? localName.toUpperCase() : localName);
+      this.localName = namespaceURI === HTML_NAMESPACE ? localName.toLowerCase() : localName;
+      this.namespaceURI = namespaceURI;
+      this._attributes = [];
+      this.classList = new DOMTokenList(this);
+      this.style = createStyleDeclaration(this);
+      this.dataset = createDataset(this);
+    }
+    get tagName() {
+      return this.nodeName;
+    }
+    get attributes() {
+      var attributes = this._attributes.map((attribute) => Object.assign({}, attribute));
+      attributes.getNamedItem = (name) => attributes.find((attribute) => attribute.name === name) || null;
+      attributes.item = (index) => attributes[index] || null;
+      return attributes;
+    }
+    getAttributeNames() {
+      return this._attributes.map((attribute) => attribute.name);
+    }
+    getAttribute(name) {
+      var attribute = this._findAttribute(name);
+      return attribute ? attribute.value : null;
+    }
+    getAttributeNS(namespace, name) {
+      return this.getAttribute(name);
+    }
+    hasAttribute(name) {
+      return !!this._findAttribute(name);
+    }
+    hasAttributes() {
+      return this._attributes.length > 0;
+    }
+    setAttribute(name, value) {
+      var normalizedName = this.namespaceURI === HTML_NAMESPACE ? String(name).toLowerCase() : String(name);
+      var attribute = this._findAttribute(normalizedName);
+      if (attribute) {
+        attribute.value = String(value);
+      } else {
+        this._attributes.push({ name: normalizedName, value: String(value), namespaceURI: null });
+      }
+    }
+    setAttributeNS(namespace, name, value) {
+      this.setAttribute(name, value);
+      this._findAttribute(name).namespaceURI = namespace;
+    }
+    removeAttribute(name) {
+      var normalizedName = this.namespaceURI === HTML_NAMESPACE ? String(name).toLowerCase() : String(name);
+      this._attributes = this._attributes.filter((attribute) => attribute.name !== normalizedName);
+    }
+    removeAttributeNS(namespace, name) {
+      this.removeAttribute(name);
+    }
+    toggleAttribute(name, force) {
+      var shouldAdd = force === undefined ? !this.hasAttribute(name) : force;
+      shouldAdd ? this.setAttribute(name, '') : this.removeAttribute(name);
+      return shouldAdd;
+    }
+    _findAttribute(name) {
+      var normalizedName = this.namespaceURI === HTML_NAMESPACE ? String(name).toLowerCase() : String(name);
+      return this._attributes.find((attribute) => attribute.name === normalizedName);
+    }
+    get innerHTML() {
+      return this.childNodes.map((child) => serialize(child, this)).join('');
+    }
+    set innerHTML(value) {
+      this.childNodes.slice().forEach((child) => this.removeChild(child));
+      this.appendChild(parseHTML(String(value), this.localName));
+    }
+    get outerHTML() {
+      return serialize(this, this.parentNode);
+    }
+    set outerHTML(value) {
+      if (this.parentNode) {
+        this.parentNode.replaceChild(parseHTML(String(value), this.parentNode.localName), this);
+      }
+    }
+    get innerText() {
+      return this.textContent;
+    }
+    set innerText(value) {
+      this.textContent = value;
+    }
+    get nextElementSibling() {
+      for (var node = this.nextSibling; node; node = node.nextSibling) {
+        if (node.nodeType === 1) {
+          return node;
+        }
+      }
+      return null;
+    }
+    get previousElementSibling() {
+      for (var node = this.previousSibling; node; node = node.previousSibling) {
+        if (node.nodeType === 1) {
+          return node;
+        }
+      }
+      return null;
+    }
+    get tabIndex() {
+      var value = parseInt(this.getAttribute('tabindex'), 10);
+      if (!isNaN(value)) {
+        return value;
+      }
+      return ['a', 'button', 'input', 'select', 'textarea'].indexOf(this.localName) !== -1 ? 0 : -1;
+    }
+    set tabIndex(value) {
+      this.setAttribute('tabindex', value);
+    }
+    insertAdjacentHTML(position, html) {
+      var fragment = parseHTML(String(html), ['beforebegin', 'afterend'].indexOf(position.toLowerCase()) !== -1 ?
+        (this.parentNode && this.parentNode.localName) : this.localName);
+      this._insertAdjacent(position, fragment);
+    }
+    insertAdjacentElement(position, element) {
+      this._insertAdjacent(position, element);
+      return element;
+    }
+    insertAdjacentText(position, text) {
+      this._insertAdjacent(position, document.createTextNode(text));
+    }
+    _insertAdjacent(position, node) {
+      switch (position.toLowerCase()) {
+        case 'beforebegin':
+          return this.parentNode.insertBefore(node, this);
+        case 'afterbegin':
+          return this.insertBefore(node, this.firstChild);
+        case 'beforeend':
+          return this.appendChild(node);
+        case 'afterend':
+          return this.parentNode.insertBefore(node, this.nextSibling);
+        default:
+          throw new Error(`SyntaxError: ${position} is not a valid insertAdjacent position`);
+      }
+    }
+    matches(selector) {
+      return matchesSelectors(this, parseSelector(selector), null);
+    }
+    closest(selector) {
+      var selectors = parseSelector(selector);
+      for (var element = this; element && element.nodeType === 1; element = element.parentNode) {
+        if (matchesSelectors(element, selectors, null)) {
+          return element;
+        }
+      }
+      return null;
+    }
+    focus() {
+      if (document.activeElement === this || !this.isConnected) {
+        return;
+      }
+      var previousElement = document.activeElement;
+      if (previousElement && previousElement !== document.body) {
+        previousElement.blur();
+      }
+      document.activeElement = this;
+      this.dispatchEvent(new FocusEvent('focus', { bubbles: false }));
+      this.dispatchEvent(new FocusEvent('focusin', { bubbles: true }));
+    }
+    blur() {
+      if (document.activeElement !== this) {
+        return;
+      }
+      document.activeElement = document.body;
+      this.dispatchEvent(new FocusEvent('blur', { bubbles: false }));
+      this.dispatchEvent(new FocusEvent('focusout', { bubbles: true }));
+    }
+    click() {
+      if (this.disabled) {
+        return;
+      }
+      var event = new MouseEvent('click', { bubbles: true, cancelable: true, view: window });
+      var isCheckable = this.localName === 'input' && ['checkbox', 'radio'].indexOf(this.type) !== -1;
+      var previousChecked = this.checked;
+      if (isCheckable) {
+        this.checked = this.type === 'radio' ? true : !this.checked;
+      }
+      if (!this.dispatchEvent(event) && isCheckable) {
+        this.checked = previousChecked;
+      } else if (isCheckable && previousChecked !== this.checked) {
+        this.dispatchEvent(new Event('input', { bubbles: true }));
+        this.dispatchEvent(new Event('change', { bubbles: true }));
+      } else if (!event.defaultPrevented && this.localName === 'button' && this.type !== 'button' && this.form) {
+        this.form.dispatchEvent(new Event('submit', { bubbles: true, cancelable: true }));
+      }
+    }
+    get form() {
+      return this.closest('form');
+    }
+    getBoundingClientRect() {
+      return { x: 0, y: 0, top: 0, left: 0, bottom: 0, right: 0, width: 0, height: 0 };
+    }
+    getClientRects() {
+      return [];
+    }
+    scrollIntoView() {}
+    _cloneShallow() {
+      var clone = document.createElementNS(this.namespaceURI, this.localName);
+      this._attributes.forEach((attribute) => clone._attributes.push(Object.assign({}, attribute)));
+      return clone;
+    }
+  }
+
+  Object.keys(REFLECTED_ATTRIBUTES).forEach((property) => {
+    Object.defineProperty(Element.prototype, property, {
+      get() {
+        var value = this.getAttribute(REFLECTED_ATTRIBUTES[property]);
+        return value === null ? (property === 'type' && this.localName === 'input' ? 'text' : '') : value;
+      },
+      set(value) {
+        this.setAttribute(REFLECTED_ATTRIBUTES[property], value);
+      },
+      configurable: true
+    });
+  });
+  // NOTE: url decomposition of <a> and <area> elements, libraries like pretender parse urls with anchors
+  var resolveURL = (element) => {
+    try {
+      return new URL(element.getAttribute('href') || '', window.location.href);
+    } catch (error) {
+      return null;
+    }
+  };
+  Object.defineProperty(Element.prototype, 'href', {
+    get() {
+      var value = this.getAttribute('href');
+      var resolvedURL = value !== null && ['a', 'area', 'link', 'base'].indexOf(this.localName) !== -1 && resolveURL(this);
+      return resolvedURL ? resolvedURL.href : (value || '');
+    },
+    set(value) {
+      this.setAttribute('href', value);
+    },
+    configurable: true
+  });
+  ['protocol', 'host', 'hostname', 'port', 'pathname', 'search', 'hash', 'origin'].forEach((property) => {
+    Object.defineProperty(Element.prototype, property, {
+      get() {
+        var resolvedURL = this.hasAttribute('href') && resolveURL(this);
+        return resolvedURL ? resolvedURL[property] : '';
+      },
+      set(value) {
+        var resolvedURL = resolveURL(this);
+        if (resolvedURL && property !== 'origin') {
+          resolvedURL[property] = value;
+          this.setAttribute('href', resolvedURL.href);
+        }
+      },
+      configurable: true
+    });
+  });
+  BOOLEAN_ATTRIBUTES.forEach((property) => {
+    Object.defineProperty(Element.prototype, property, {
+      get() {
+        return this.hasAttribute(property);
+      },
+      set(value) {
+        value ? this.setAttribute(property, '') : this.removeAttribute(property);
+      },
+      configurable: true
+    });
+  });
+  Object.defineProperty(Element.prototype, 'value', {
+    get() {
+      if (this._value !== undefined) {
+        return this._value;
+      } else if (this.localName === 'textarea') {
+        return this.textContent;
+      } else if (this.localName === 'select') {
+        var selectedOption = this.querySelectorAll('option').find((option) => option.selected);
+        return selectedOption ? selectedOption.value : '';
+      } else if (this.localName === 'option') {
+        return this.hasAttribute('value') ? this.getAttribute('value') : this.textContent;
+      }
+      return this.getAttribute('value') || (['checkbox', 'radio'].indexOf(this.type) !== -1 ? 'on' : '');
+    },
+    set(value) {
+      if (this.localName === 'select') {
+        this.querySelectorAll('option').forEach((option) => option.selected = option.value === String(value));
+      } else {
+        this._value = value === null || value === undefined ? '' : String(value);
+      }
+    },
+    configurable: true
+  });
+  ['checked', 'selected'].forEach((property) => {
+    Object.defineProperty(Element.prototype, property, {
+      get() {
+        return this['_' + property] !== undefined ? this['_' + property] : this.hasAttribute(property);
+      },
+      set(value) {
+        if (value && property === 'checked' && this.type === 'radio' && this.name && this.form) {
+          this.form.querySelectorAll(`input[type="radio"][name="${this.name}"]`).forEach((radio) => radio._checked = false);
+        }
+        this['_' + property] = !!value;
+      },
+      configurable: true
+    });
+  });
+
+  function createDataset(element) {
+    var toAttributeName = (name) => 'data-' + name.replace(/[A-Z]/g, (character) => '-' + character.toLowerCase());
+    return new Proxy({}, {
+      get(target, name) {
+        return typeof name === 'string' ? (element.getAttribute(toAttributeName(name)) || undefined) : undefined;
+      },
+      set(target, name, value) {
+        element.setAttribute(toAttributeName(name), value);
+        return true;
+      },
+      deleteProperty(target, name) {
+        element.removeAttribute(toAttributeName(name));
+        return true;
+      }
+    });
+  }
+
+  class Document extends ParentNode {
+    constructor() {
+      super(9, '#document');
+      this.ownerDocument = null;
+      this.readyState = 'loading';
+      this.cookie = '';
+      this.activeElement = null;
+      this.implementation = { createHTMLDocument: () => createDocument('') };
+    }
+    get documentElement() {
+      return this.firstElementChild;
+    }
+    get head() {
+      return this.documentElement.querySelector('head');
+    }
+    get body() {
+      return this.documentElement.querySelector('body');
+    }
+    get title() {
+      var title = this.querySelector('title');
+      return title ? title.textContent : '';
+    }
+    set title(value) {
+      var title = this.querySelector('title') || this.head.appendChild(this.createElement('title'));
+      title.textContent = value;
+    }
+    get defaultView() {
+      return window;
+    }
+    get location() {
+      return window.location;
+    }
+    createElement(tagName) {
+      return this.createElementNS(HTML_NAMESPACE, String(tagName));
+    }
+    createElementNS(namespaceURI, qualifiedName) {
+      var element = new Element(qualifiedName, namespaceURI || HTML_NAMESPACE);
+      element.ownerDocument = this;
+      return element;
+    }
+    createTextNode(data) {
+      var node = new Text(data);
+      node.ownerDocument = this;
+      return node;
+    }
+    createComment(data) {
+      var node = new Comment(data);
+      node.ownerDocument = this;
+      return node;
+    }
+    createDocumentFragment() {
+      var fragment = new DocumentFragment();
+      fragment.ownerDocument = this;
+      return fragment;
+    }
+    createEvent(type) {
+      var eventClasses = {
+        mouseevent: MouseEvent, mouseevents: MouseEvent, keyboardevent: KeyboardEvent, keyevents: KeyboardEvent,
+        uievent: UIEvent, uievents: UIEvent, focusevent: FocusEvent, customevent: CustomEvent
+      };
+      return new (eventClasses[String(type).toLowerCase()] || Event)('');
+    }
+    createRange() {
+      return {
+        setStart() {}, setEnd() {}, setStartBefore() {}, setEndAfter() {}, selectNode() {}, selectNodeContents() {},
+        collapse() {}, getBoundingClientRect: () => Element.prototype.getBoundingClientRect(), getClientRects: () => [],
+        createContextualFragment: (html) => parseHTML(html, 'body')
+      };
+    }
+    getElementById(id) {
+      return descendantElements(this).find((element) => element.id === id) || null;
+    }
+    hasFocus() {
+      return true;
+    }
+    importNode(node, deep) {
+      return node.cloneNode(deep);
+    }
+    adoptNode(node) {
+      node.remove();
+      return node;
+    }
+    execCommand() {
+      return false;
+    }
+    _cloneShallow() {
+      return createDocument('');
+    }
+  }
+
+  function createDocument(source) {
+    var previousDocument = document;
+    var newDocument = new Document();
+    document = newDocument;
+    newDocument.appendChild(parseDocument(source));
+    document = previousDocument || newDocument;
+    return newDocument;
+  }
+
+  function createNodeList(nodes) {
+    nodes.item = (index) => nodes[index] || null;
+    return nodes;
+  }
+
+  function allNodes(root) {
+    return root.childNodes.reduce((result, child) => result.concat([child], allNodes(child)), []);
+  }
+
+  function descendantElements(root) {
+    var result = [];
+    root.childNodes.forEach(function collect(node) {
+      if (node.nodeType === 1) {
+        result.push(node);
+      }
+      node.childNodes.forEach(collect);
+    });
+    return result;
+  }
+
+  // NOTE: selector engine, supports compound selectors, attribute selectors, common pseudo classes and combinators
+  function parseSelector(selector) {
+    var source = String(selector).trim();
+    var index = 0;
+    var groups = [];
+    var sequence = [];
+    var compound = null;
+    var pendingCombinator = ' ';
+
+    function currentCompound() {
+      if (!compound) {
+        compound = { tag: null, id: null, classes: [], attributes: [], pseudos: [] };
+        sequence.push({ combinator: sequence.length === 0 ? null : pendingCombinator, compound: compound });
+        pendingCombinator = ' ';
+      }
+      return compound;
+    }
+    function readIdentifier() {
+      var match = /^(?:\\.|[\w\u00a0-\uffff-])+/.exec(source.slice(index));
+      if (!match) {
+        throw new Error(`SyntaxError: '${selector}' is not a valid selector`);
+      }
+      index += match[0].length;
+      return match[0].replace(/\\(.)/g, '$1');
+    }
+    function readUntilClosing(open, close) {
+      var depth = 1;
+      var start = index;
+      while (index < source.length && depth > 0) {
+        var character = source[index];
+        if (character === '"' || character === '\'') {
+          index = source.indexOf(character, index + 1);
+        } else if (character === open) {
+          depth++;
+        } else if (character === close) {
+          depth--;
+        }
+        index++;
+      }
+      return source.slice(start, index - 1);
+    }
+
+    while (index < source.length) {
+      var character = source[index];
+      if (/\s/.test(character)) {
+        index++;
+        if (compound) {
+          compound = null;
+          pendingCombinator = ' ';
+        }
+      } else if (character === '>' || character === '+' || character === '~') {
+        index++;
+        compound = null;
+        pendingCombinator = character;
+      } else if (character === ',') {
+        index++;
+        groups.push(sequence);
+        sequence = [];
+        compound = null;
+        pendingCombinator = ' ';
+      } else if (character === '#') {
+        index++;
+        currentCompound().id = readIdentifier();
+      } else if (character === '.') {
+        index++;
+        currentCompound().classes.push(readIdentifier());
+      } else if (character === '*') {
+        index++;
+        currentCompound().tag = '*';
+      } else if (character === '[') {
+        index++;
+        var match = /^\s*([^\s~|^$*!=\]]+)\s*(?:([~|^$*]?=)\s*(?:"([^"]*)"|'([^']*)'|([^\]\s]+))\s*(i)?)?\s*$/
+          .exec(readUntilClosing('[', ']'));
+        if (!match) {
+          throw new Error(`SyntaxError: '${selector}' is not a valid selector`);
+        }
+        currentCompound().attributes.push({
+          name: match[1], operator: match[2], value: match[3] !== undefined ? match[3] : (match[4] !== undefined ? match[4] : match[5]),
+          caseInsensitive: !!match[6]
+        });
+      } else if (character === ':') {
+        index += source[index + 1] === ':' ? 2 : 1;
+        var pseudoName = readIdentifier().toLowerCase();
+        var argument = null;
+        if (source[index] === '(') {
+          index++;
+          argument = readUntilClosing('(', ')');
+        }
+        currentCompound().pseudos.push({ name: pseudoName, argument: argument });
+      } else {
+        currentCompound().tag = readIdentifier().toLowerCase();
+      }
+    }
+    groups.push(sequence);
+    if (groups.some((group) => group.length === 0)) {
+      throw new Error(`SyntaxError: '${selector}' is not a valid selector`);
+    }
+    return groups;
+  }
+
+  function matchesSelectors(element, groups, scope) {
+    return groups.some((sequence) => matchesSequence(element, sequence, sequence.length - 1, scope));
+  }
+
+  function matchesSequence(element, sequence, position, scope) {
+    var part = sequence[position];
+    if (!element || element.nodeType !== 1 || !matchesCompound(element, part.compound, scope)) {
+      return false;
+    } else if (position === 0) {
+      return true;
+    }
+    switch (part.combinator) {
+      case '>':
+        return matchesSequence(element.parentNode, sequence, position - 1, scope);
+      case '+':
+        return matchesSequence(element.previousElementSibling, sequence, position - 1, scope);
+      case '~':
+        for (var sibling = element.previousElementSibling; sibling; sibling = sibling.previousElementSibling) {
+          if (matchesSequence(sibling, sequence, position - 1, scope)) {
+            return true;
+          }
+        }
+        return false;
+      default:
+        for (var ancestor = element.parentNode; ancestor && ancestor.nodeType === 1; ancestor = ancestor.parentNode) {
+          if (matchesSequence(ancestor, sequence, position - 1, scope)) {
+            return true;
+          }
+        }
+        return false;
+    }
+  }
+
+  function matchesCompound(element, compound, scope) {
+    if (compound.tag && compound.tag !== '*' && element.localName.toLowerCase() !== compound.tag) {
+      return false;
+    } else if (compound.id !== null && element.getAttribute('id') !== compound.id) {
+      return false;
+    } else if (!compound.classes.every((className) => element.classList.contains(className))) {
+      return false;
+    } else if (!compound.attributes.every((attribute) => matchesAttribute(element, attribute))) {
+      return false;
+    }
+    return compound.pseudos.every((pseudo) => matchesPseudo(element, pseudo, scope));
+  }
+
+  function matchesAttribute(element, attribute) {
+    var value = element.getAttribute(attribute.name);
+    if (value === null) {
+      return false;
+    } else if (!attribute.operator) {
+      return true;
+    }
+    var expected = attribute.caseInsensitive ? attribute.value.toLowerCase() : attribute.value;
+    var actual = attribute.caseInsensitive ? value.toLowerCase() : value;
+    switch (attribute.operator) {
+      case '=': return actual === expected;
+      case '~=': return actual.split(/\s+/).indexOf(expected) !== -1;
+      case '|=': return actual === expected || actual.startsWith(expected + '-');
+      case '^=': return expected !== '' && actual.startsWith(expected);
+      case '$=': return expected !== '' && actual.endsWith(expected);
+      case '*=': return expected !== '' && actual.indexOf(expected) !== -1;
+      default: return false;
+    }
+  }
+
+  function matchesPseudo(element, pseudo, scope) {
+    var siblings = element.parentNode ? element.parentNode.children : [element];
+    var sameTypeSiblings = siblings.filter((sibling) => sibling.localName === element.localName);
+    switch (pseudo.name) {
+      case 'scope': return scope ? element === scope : element === document.documentElement;
+      case 'root': return element === document.documentElement;
+      case 'first-child': return siblings[0] === element;
+      case 'last-child': return siblings[siblings.length - 1] === element;
+      case 'only-child': return siblings.length === 1;
+      case 'first-of-type': return sameTypeSiblings[0] === element;
+      case 'last-of-type': return sameTypeSiblings[sameTypeSiblings.length - 1] === element;
+      case 'nth-child': return matchesNth(siblings.indexOf(element) + 1, pseudo.argument);
+      case 'nth-last-child': return matchesNth(siblings.length - siblings.indexOf(element), pseudo.argument);
+      case 'nth-of-type': return matchesNth(sameTypeSiblings.indexOf(element) + 1, pseudo.argument);
+      case 'empty': return element.childNodes.every((child) => child.nodeType === 8);
+      case 'checked': return !!(element.checked || element.selected);
+      case 'disabled': return !!element.disabled;
+      case 'enabled': return !element.disabled;
+      case 'focus': return document.activeElement === element;
+      case 'not': return !matchesSelectors(element, parseSelector(pseudo.argument), scope);
+      case 'is': case 'matches': return matchesSelectors(element, parseSelector(pseudo.argument), scope);
+      case 'has': return element.querySelector(pseudo.argument) !== null;
+      case 'contains': return element.textContent.indexOf(pseudo.argument.replace(/^["']|["']$/g, '')) !== -1;
+      default: throw new Error(`SyntaxError: :${pseudo.name} pseudo class is not supported`);
+    }
+  }
+
+  function matchesNth(position, argument) {
+    var expression = argument.replace(/\s/g, '').toLowerCase();
+    if (expression === 'odd') {
+      return position % 2 === 1;
+    } else if (expression === 'even') {
+      return position % 2 === 0;
+    }
+    var match = /^([+-]?\d*)n([+-]\d+)?$/.exec(expression);
+    if (!match) {
+      return position === parseInt(expression, 10);
+    }
+    var step = match[1] === '' || match[1] === '+' ? 1 : (match[1] === '-' ? -1 : parseInt(match[1], 10));
+    var offset = parseInt(match[2] || '0', 10);
+    return step === 0 ? position === offset : (position - offset) / step >= 0 && (position - offset) % step === 0;
+  }
+
+  // NOTE: forgiving html parser, enough for templates, fixtures and the test index.html
+  function decodeEntities(text) {
+    var entities = { amp: '&', lt: '<', gt: '>', quot: '"', apos: '\'', nbsp: '\u00a0', copy: '\u00a9' };
+    return text.replace(/&(#x[0-9a-f]+|#\d+|\w+);/gi, (entity, name) => {
+      if (name[0] === '#') {
+        return String.fromCodePoint(name[1].toLowerCase() === 'x' ? parseInt(name.slice(2), 16) : parseInt(name.slice(1), 10));
+      }
+      return entities[name.toLowerCase()] !== undefined ? entities[name.toLowerCase()] : entity;
+    });
+  }
+
+  function parseHTML(source, contextTagName) {
+    var fragment = document.createDocumentFragment();
+    var stack = [fragment];
+    var index = 0;
+    var current = () => stack[stack.length - 1];
+    var namespaceFor = (tagName) => {
+      if (tagName === 'svg') {
+        return 'http://www.w3.org/2000/svg';
+      }
+      var parent = current();
+      return parent.namespaceURI && parent.namespaceURI !== HTML_NAMESPACE && parent.localName !== 'foreignObject' ?
+        parent.namespaceURI : ((contextTagName === 'svg' && stack.length === 1) ? 'http://www.w3.org/2000/svg' : HTML_NAMESPACE);
+    };
+
+    while (index < source.length) {
+      if (source.startsWith('<!--', index)) {
+        var commentEnd = source.indexOf('-->', index + 4);
+        commentEnd = commentEnd === -1 ? source.length : commentEnd;
+        current().appendChild(document.createComment(source.slice(index + 4, commentEnd)));
+        index = commentEnd + 3;
+      } else if (source.startsWith('<!', index) || source.startsWith('<?', index)) {
+        index = source.indexOf('>', index) === -1 ? source.length : source.indexOf('>', index) + 1;
+      } else if (source.startsWith('</', index)) {
+        var closingTagEnd = source.indexOf('>', index);
+        closingTagEnd = closingTagEnd === -1 ? source.length : closingTagEnd;
+        var closingTagName = source.slice(index + 2, closingTagEnd).trim().toLowerCase();
+        var openIndex = stack.map((node) => node.localName && node.localName.toLowerCase()).lastIndexOf(closingTagName);
+        if (openIndex > 0) {
+          stack.length = openIndex;
+        }
+        index = closingTagEnd + 1;
+      } else if (source[index] === '<' && /[a-zA-Z]/.test(source[index + 1] || '')) {
+        var tagMatch = /^<([a-zA-Z][^\s/>]*)/.exec(source.slice(index));
+        var tagName = tagMatch[1];
+        var element = document.createElementNS(namespaceFor(tagName.toLowerCase()), tagName.toLowerCase() === tagName ||
+          namespaceFor(tagName.toLowerCase()) === HTML_NAMESPACE ? tagName.toLowerCase() : tagName);
+        var attributePattern = /\s*([^\s"'>\/=]+)(?:\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s>]+)))?/y;
+        var selfClosing = false;
+        index += tagMatch[0].length;
+        while (index < source.length) {
+          if (source[index] === '>') {
+            index++;
+            break;
+          } else if (source.startsWith('/>', index)) {
+            selfClosing = true;
+            index += 2;
+            break;
+          } else if (/[\s\/]/.test(source[index])) {
+            index++;
+            continue;
+          }
+          attributePattern.lastIndex = index;
+          var attributeMatch = attributePattern.exec(source);
+          if (!attributeMatch) {
+            index++;
+            continue;
+          }
+          var attributeValue = attributeMatch[2] !== undefined ? attributeMatch[2] :
+            (attributeMatch[3] !== undefined ? attributeMatch[3] : (attributeMatch[4] !== undefined ? attributeMatch[4] : ''));
+          if (!element.hasAttribute(attributeMatch[1])) {
+            element.setAttribute(attributeMatch[1], decodeEntities(attributeValue));
+          }
+          index = attributePattern.lastIndex;
+        }
+        current().appendChild(element);
+        var localName = element.localName.toLowerCase();
+        if (RAW_TEXT_ELEMENTS.indexOf(localName) !== -1 && !selfClosing) {
+          var rawTextEnd = source.toLowerCase().indexOf(`</${localName}`, index);
+          rawTextEnd = rawTextEnd === -1 ? source.length : rawTextEnd;
+          var rawText = source.slice(index, rawTextEnd);
+          if (rawText) {
+            element.appendChild(document.createTextNode(localName === 'script' || localName === 'style' ? rawText : decodeEntities(rawText)));
+          }
+          index = source.indexOf('>', rawTextEnd) === -1 ? source.length : source.indexOf('>', rawTextEnd) + 1;
+        } else if (!selfClosing && VOID_ELEMENTS.indexOf(localName) === -1) {
+          stack.push(element);
+        }
+      } else {
+        var textEnd = source.indexOf('<', index + 1);
+        textEnd = textEnd === -1 ? source.length : textEnd;
+        current().appendChild(document.createTextNode(decodeEntities(source.slice(index, textEnd))));
+        index = textEnd;
+      }
+    }
+    return fragment;
+  }
+
+  function parseDocument(source) {
+    var fragment = parseHTML(source, null);
+    var htmlElement = fragment.querySelector('html') || document.createElement('html');
+    if (!htmlElement.parentNode) {
+      fragment.childNodes.slice().forEach((child) => htmlElement.appendChild(child));
+    }
+    var head = htmlElement.querySelector('head');
+    var body = htmlElement.querySelector('body');
+    if (!head) {
+      head = htmlElement.insertBefore(document.createElement('head'), htmlElement.firstChild);
+    }
+    if (!body) {
+      body = htmlElement.appendChild(document.createElement('body'));
+      htmlElement.childNodes.slice().forEach((child) => child !== head && child !== body ? body.appendChild(child) : null);
+    }
+    return htmlElement;
+  }
+
+  function escapeText(text) {
+    return text.replace(/&/g, '&amp;').replace(/</g, '&lt;').replace(/>/g, '&gt;').replace(/\u00a0/g, '&nbsp;');
+  }
+
+  function serialize(node, parent) {
+    switch (node.nodeType) {
+      case 3:
+        return parent && RAW_TEXT_ELEMENTS.indexOf(parent.localName) !== -1 && parent.localName !== 'textarea' &&
+          parent.localName !== 'title' ? node.data : escapeText(node.data);
+      case 8:
+        return `<!--${node.data}-->`;
+      case 1:
+        var attributes = node._attributes.map((attribute) => {
+          return ` ${attribute.name}="${attribute.value.replace(/&/g, '&amp;').replace(/"/g, '&quot;').replace(/\u00a0/g, '&nbsp;')}"`;
+        }).join('');
+        var tagName = node.namespaceURI === HTML_NAMESPACE ? node.localName.toLowerCase() : node.localName;
+        if (node.namespaceURI === HTML_NAMESPACE && VOID_ELEMENTS.indexOf(tagName) !== -1) {
+          return `<${tagName}${attributes}>`;
+        }
+        return `<${tagName}${attributes}>${node.childNodes.map((child) => serialize(child, node)).join('')}</${tagName}>`;
+      default:
+        return node.childNodes.map((child) => serialize(child, node)).join('');
+    }
+  }
+
+  function createStorage() {
+    var items = new Map();
+    return {
+      get length() { return items.size; },
+      key: (index) => Array.from(items.keys())[index] || null,
+      getItem: (key) => items.has(String(key)) ? items.get(String(key)) : null,
+      setItem: (key, value) => items.set(String(key), String(value)),
+      removeItem: (key) => items.delete(String(key)),
+      clear: () => items.clear()
+    };
+  }
+
+  function createLocation(href) {
+    var location = {
+      assign: (url) => setHref(url),
+      replace: (url) => setHref(url),
+      reload: () => {},
+      toString: () => location.href
+    };
+    var setHref = (value) => {
+      var parsedURL = new URL(value, location.href || 'http://localhost/');
+      ['href', 'protocol', 'host', 'hostname', 'port', 'pathname', 'search', 'hash', 'origin'].forEach((key) => {
+        Object.defineProperty(location, key, {
+          get: () => parsedURL[key],
+          set: (newValue) => {
+            if (key === 'href') {
+              setHref(newValue);
+            } else {
+              parsedURL[key] = newValue;
+              setHref(parsedURL.href);
+            }
+          },
+          configurable: true,
+          enumerable: true
+        });
+      });
+    };
+    setHref(href);
+    return location;
+  }
+
+  class XMLHttpRequest extends EventTarget {
+    open(method, url) {
+      this.method = method;
+      this.url = url;
+      this.readyState = 1;
+    }
+    setRequestHeader() {}
+    getResponseHeader() {
+      return null;
+    }
+    getAllResponseHeaders() {
+      return '';
+    }
+    overrideMimeType() {}
+    abort() {}
+    send() {
+      this.readyState = 4;
+      this.status = 0;
+      this.responseText = '';
+      setTimeout(() => {
+        var event = new Event('error');
+        this.dispatchEvent(event);
+        if (typeof this.onreadystatechange === 'function') {
+          this.onreadystatechange(event);
+        }
+      });
+    }
+  }
+  XMLHttpRequest.UNSENT = 0;
+  XMLHttpRequest.OPENED = 1;
+  XMLHttpRequest.HEADERS_RECEIVED = 2;
+  XMLHttpRequest.LOADING = 3;
+  XMLHttpRequest.DONE = 4;
+
+  // NOTE: headless runs have no live reload server, sockets never connect
+  class WebSocket extends EventTarget {
+    constructor(url) {
+      super();
+      this.url = url;
+      this.readyState = 3;
+    }
+    send() {}
+    close() {}
+  }
+
+  var window = globalObject || new EventTarget();
+  var historyEntries = [{ state: null, url: url }];
+  var historyIndex = 0;
+  var changeHistory = (state, title, newURL, shouldReplace) => {
+    if (newURL !== undefined && newURL !== null) {
+      window.location.href = new URL(newURL, window.location.href).href;
+    }
+    var entry = { state: state, url: window.location.href };
+    if (shouldReplace) {
+      historyEntries[historyIndex] = entry;
+    } else {
+      historyEntries = historyEntries.slice(0, historyIndex + 1).concat([entry]);
+      historyIndex++;
+    }
+  };
+  var go = (delta) => {
+    var newIndex = Math.min(Math.max(historyIndex + (delta || 0), 0), historyEntries.length - 1);
+    if (newIndex !== historyIndex) {
+      historyIndex = newIndex;
+      window.location.href = historyEntries[historyIndex].url;
+      setTimeout(() => window.dispatchEvent(Object.assign(new Event('popstate'), { state: historyEntries[historyIndex].state })));
+    }
+  };
+
+  Object.assign(window, {
+    location: createLocation(url),
+    history: {
+      get length() { return historyEntries.length; },
+      get state() { return historyEntries[historyIndex].state; },
+      pushState: (state, title, newURL) => changeHistory(state, title, newURL, false),
+      replaceState: (state, title, newURL) => changeHistory(state, title, newURL, true),
+      back: () => go(-1),
+      forward: () => go(1),
+      go: go
+    },
+    navigator: { userAgent: 'Mozilla/5.0 (mber headless) Node.js', language: 'en-US', languages: ['en-US'], platform: 'node' },
+    localStorage: createStorage(),
+    sessionStorage: createStorage(),
+    innerWidth: 1024,
+    innerHeight: 768,
+    devicePixelRatio: 1,
+    scrollX: 0,
+    scrollY: 0,
+    pageXOffset: 0,
+    pageYOffset: 0,
+    scrollTo: () => {},
+    scroll: () => {},
+    alert: () => {},
+    confirm: () => true,
+    prompt: () => null,
+    open: () => null,
+    focus: () => {},
+    blur: () => {},
+    getComputedStyle: (element) => element.style,
+    matchMedia: (query) => ({
+      matches: false, media: query, addListener: () => {}, removeListener: () => {}, addEventListener: () => {},
+      removeEventListener: () => {}
+    }),
+    requestAnimationFrame: (callback) => setTimeout(() => callback(Date.now()), 16),
+    cancelAnimationFrame: (id) => clearTimeout(id),
+    requestIdleCallback: (callback) => setTimeout(() => callback({ didTimeout: false, timeRemaining: () => 50 })),
+    cancelIdleCallback: (id) => clearTimeout(id),
+    Node: Node, Text: Text, Comment: Comment, CharacterData: CharacterData, Element: Element, HTMLElement: Element,
+    SVGElement: Element, Document: Document, HTMLDocument: Document, DocumentFragment: DocumentFragment,
+    Event: Event, UIEvent: UIEvent, CustomEvent: CustomEvent, MouseEvent: MouseEvent, KeyboardEvent: KeyboardEvent,
+    FocusEvent: FocusEvent, EventTarget: EventTarget, XMLHttpRequest: XMLHttpRequest, WebSocket: WebSocket,
+    DOMTokenList: DOMTokenList
+  });
+
+  ['addEventListener', 'removeEventListener', 'dispatchEvent'].forEach((method) => {
+    window[method] = EventTarget.prototype[method];
+  });
+  window.window = window.self = window.globalThis = window;
+  window.Window = function Window() {};
+  window.constructor = window.Window; // NOTE: ember checks self.constructor === Window for DOM detection
+
+  document = createDocument(html);
+  document.activeElement = document.body;
+  window.document = document;
+
+  return window;
+}
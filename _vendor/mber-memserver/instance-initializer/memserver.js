@@ -0,0 +1,39 @@
+import Ember from 'ember';
+import memServer from 'memserver';
+
+// NOTE: collects <app>/memserver/models/* with their <app>/memserver/fixtures/* and starts MemServer once
+export function initialize(appInstance) {
+  if (typeof FastBoot !== 'undefined' || window.MemServer) {
+    return;
+  }
+
+  const prefix = `${appInstance.application.modulePrefix}/memserver`;
+  const moduleNames = Object.keys(window.requirejs.entries);
+  const requireDefault = (moduleName) => window.require(moduleName).default;
+  const modelFixtureTree = moduleNames
+    .filter((moduleName) => moduleName.startsWith(`${prefix}/models/`))
+    .reduce((tree, moduleName) => {
+      const modelName = moduleName.slice(`${prefix}/models/`.length);
+      const fixturesModuleName = [`${prefix}/fixtures/${modelName}s`, `${prefix}/fixtures/${modelName}`]
+        .find((name) => moduleNames.includes(name));
+
+      tree[Ember.String.classify(modelName)] = {
+        model: requireDefault(moduleName),
+        fixtures: fixturesModuleName ? requireDefault(fixturesModuleName) : []
+      };
+
+      return tree;
+    }, {});
+  const initializerModuleName = `${prefix}/initializer`;
+
+  memServer(
+    modelFixtureTree,
+    requireDefault(`${prefix}/server`),
+    moduleNames.includes(initializerModuleName) ? requireDefault(initializerModuleName) : undefined
+  ).start({ logging: false });
+}
+
+export default {
+  name: 'memserver',
+  initialize
+};
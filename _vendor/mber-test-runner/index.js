@@ -0,0 +1,107 @@
+// Runs the built tests.html of an ember application with QUnit in a headless DOM.
+// Reads { projectRoot, url } from stdin and writes one JSON event per line to stdout:
+// { type: "testDone", ... } for every test, then { type: "done", ... } or { type: "error", message }.
+// Expects mber-dom/context.js to be defined before this file.
+
+function emit(event, callback) {
+  process.stdout.write(JSON.stringify(event) + '\n', callback);
+}
+
+function fail(message) {
+  emit({ type: 'error', message: message }, () => process.exit(1));
+}
+
+function readStdin() {
+  return new Promise((resolve) => {
+    var input = '';
+    process.stdin.setEncoding('utf8');
+    process.stdin.on('data', (chunk) => input += chunk);
+    process.stdin.on('end', () => resolve(JSON.parse(input)));
+  });
+}
+
+function dump(QUnit, value) {
+  return value === undefined ? undefined : QUnit.dump.parse(value);
+}
+
+function setupQUnit(window) {
+  var QUnit = window.QUnit;
+  var failedAssertions = [];
+
+  if (!QUnit) {
+    return fail('QUnit is missing, tests.html needs to include assets/test-support.js');
+  }
+
+  QUnit.config.testTimeout = QUnit.config.testTimeout || 60000;
+  QUnit.log((details) => {
+    if (!details.result) {
+      failedAssertions.push({
+        message: details.message || (details.actual === undefined ? 'failed' : 'failed assertion'),
+        actual: dump(QUnit, details.actual),
+        expected: dump(QUnit, details.expected),
+        stack: details.source || null
+      });
+    }
+  });
+  QUnit.testDone((details) => {
+    var status = 'passed';
+
+    if (details.skipped) {
+      status = 'skipped';
+    } else if (details.todo) {
+      status = 'todo';
+    } else if (details.failed > 0) {
+      status = 'failed';
+    }
+
+    emit({
+      type: 'testDone',
+      module: details.module,
+      name: details.name,
+      status: status,
+      runtime: Math.round(details.runtime || 0),
+      assertionCount: details.total,
+      failedAssertions: status === 'failed' ? failedAssertions : []
+    });
+    failedAssertions = [];
+  });
+  QUnit.done((details) => {
+    emit({
+      type: 'done',
+      total: details.total,
+      passed: details.passed,
+      failed: details.failed,
+      runtime: Math.round(details.runtime || 0)
+    }, () => process.exit(0));
+  });
+}
+
+readStdin().then((options) => {
+  var testsHTMLPath = path.join(options.projectRoot, 'tmp', 'tests.html');
+
+  if (!fs.existsSync(testsHTMLPath)) {
+    return fail(`${testsHTMLPath} does not exist, the tests need to be built first`);
+  }
+
+  var context = createDOMContext(fs.readFileSync(testsHTMLPath, 'utf8'), options.url);
+
+  process.on('uncaughtException', (error) => {
+    if (typeof context.onerror === 'function') {
+      return context.onerror(error.message, error.fileName, error.lineNumber, error.columnNumber, error);
+    }
+    fail(error.stack || String(error));
+  });
+  process.on('unhandledRejection', (reason) => {
+    if (context.QUnit && typeof context.QUnit.onUnhandledRejection === 'function') {
+      return context.QUnit.onUnhandledRejection(reason);
+    }
+    fail(reason && reason.stack ? reason.stack : String(reason));
+  });
+
+  runScripts(context, path.join(options.projectRoot, 'tmp'), testsHTMLPath, (source) => {
+    if (/(^|\/)test-support\.js$/.test(source)) {
+      setupQUnit(context);
+    }
+  });
+  dispatchLoadEvents(context);
+}).catch((error) => fail(error.stack || String(error)));
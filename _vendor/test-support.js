@@ -1,4 +1,92 @@
-define = window.define;require = window.require;(function() {
+define = window.define;require = window.require;define("ember-test-waiters", ["exports"], function (_exports) {
+  "use strict";
+
+  Object.defineProperty(_exports, "__esModule", { value: true });
+
+  var WAITERS = new Map();
+
+  function register(waiter) {
+    WAITERS.set(waiter.name, waiter);
+  }
+
+  function unregister(waiter) {
+    WAITERS.delete(waiter.name);
+  }
+
+  function getWaiters() {
+    var waiters = [];
+    WAITERS.forEach(function(waiter) { waiters.push(waiter); });
+    return waiters;
+  }
+
+  function _reset() {
+    getWaiters().forEach(function(waiter) { waiter.reset && waiter.reset(); });
+    WAITERS.clear();
+  }
+
+  function getPendingWaiterState() {
+    var result = { pending: 0, waiters: {} };
+    WAITERS.forEach(function(waiter) {
+      if (!waiter.waitUntil()) {
+        result.pending++;
+        result.waiters[waiter.name] = waiter.debugInfo ? waiter.debugInfo() : true;
+      }
+    });
+    return result;
+  }
+
+  function hasPendingWaiters() {
+    return getPendingWaiterState().pending > 0;
+  }
+
+  function buildWaiter(name) {
+    var items = new Map();
+    var nextToken = 0;
+    var waiter = {
+      name: name,
+      beginAsync: function(token, label) {
+        token = token === undefined ? nextToken++ : token;
+        items.set(token, { label: label, stack: new Error().stack });
+        register(waiter);
+        return token;
+      },
+      endAsync: function(token) {
+        items.delete(token);
+      },
+      waitUntil: function() {
+        return items.size === 0;
+      },
+      debugInfo: function() {
+        var info = [];
+        items.forEach(function(item) { info.push(item); });
+        return info;
+      },
+      reset: function() {
+        items.clear();
+      }
+    };
+    return waiter;
+  }
+
+  var promiseWaiter = buildWaiter("ember-test-waiters:promise-waiter");
+
+  function waitForPromise(promise, label) {
+    var token = promiseWaiter.beginAsync(promise, label);
+    var end = function() { promiseWaiter.endAsync(token); };
+    promise.then(end, end);
+    return promise;
+  }
+
+  _exports.register = register;
+  _exports.unregister = unregister;
+  _exports.getWaiters = getWaiters;
+  _exports._reset = _reset;
+  _exports.getPendingWaiterState = getPendingWaiterState;
+  _exports.hasPendingWaiters = hasPendingWaiters;
+  _exports.buildWaiter = buildWaiter;
+  _exports.waitForPromise = waitForPromise;
+});
+(function() {
 /*!
  * @overview  Ember - JavaScript Application Framework
  * @copyright Copyright 2011-2019 Tilde Inc. and contributors
@@ -72,6 +72,7 @@ function buildTestVendorJS() {
     const timer = countTime();
 
     return Promise.all([
+      importAddonFolderToAMD("ember-test-waiters", "ember-test-waiters/addon"),
       fs.readFile(`${VENDOR_PATH}/ember-testing.js`),
       fs.readFile(
         `${MODULE_PATH}/@ember/test-helpers/vendor/monkey-patches.js`
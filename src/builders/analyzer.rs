@@ -0,0 +1,313 @@
+use std::fs;
+use std::path::PathBuf;
+use yansi::Paint;
+use regex::Regex;
+use serde_json::{json, Value};
+use super::super::transpilers::minify_js;
+use super::super::utils::{console, file};
+use super::super::utils::source_map::{self, SourceMap};
+use super::super::types::{Config, MberError};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Part {
+    pub segment: String,
+    pub name: String,
+    pub code: String
+}
+
+impl Part {
+    pub fn new(segment: &str, name: &str, code: &str) -> Self {
+        return Part { segment: segment.to_string(), name: name.to_string(), code: code.to_string() };
+    }
+}
+
+// NOTE: --analyze makes the builders write tmp/analysis/<bundle>.json, mber build turns them into dist/analyze.html
+pub fn should_analyze(config: &Config) -> bool {
+    return config.cli_arguments.analyze;
+}
+
+// NOTE: splits prebuilt vendor files like the ember base into their AMD modules. Ember core modules use a minified
+// enifed alias, so single letter define aliases count too. Code before the first module keeps the part name
+pub fn split_modules(parts: Vec<Part>) -> Vec<Part> {
+    let module_regex = Regex::new(r#"\b(?:define|enifed|[a-z])\(\s*["']([^"'\s]+)["']\s*,\s*\["#).unwrap();
+
+    return parts.into_iter().flat_map(|part| {
+        let module_starts = module_regex.captures_iter(&part.code)
+            .map(|captures| (captures.get(0).unwrap().start(), captures[1].to_string()))
+            .collect::<Vec<(usize, String)>>();
+
+        if module_starts.len() < 2 {
+            return vec![part];
+        }
+
+        let mut modules = Vec::new();
+
+        if module_starts[0].0 > 0 {
+            modules.push(Part::new(&part.segment, &part.name, &part.code[..module_starts[0].0]));
+        }
+
+        module_starts.iter().enumerate().for_each(|(index, (start, module_name))| {
+            let end = module_starts.get(index + 1).map_or(part.code.len(), |(next_start, _)| *next_start);
+
+            modules.push(Part::new(&part.segment, module_name, &part.code[*start..end]));
+        });
+
+        return modules;
+    }).collect();
+}
+
+pub fn write_bundle_analysis(config: &Config, bundle_name: &str, parts: Vec<Part>) -> Result<(), MberError> {
+    let analysis_directory = format!("{}/tmp/analysis", config.project_root.display());
+    let analysis_path = format!("{}/{}.json", &analysis_directory, bundle_name);
+
+    fs::create_dir_all(&analysis_directory).map_err(MberError::io("could not create", &analysis_directory))?;
+
+    return fs::write(&analysis_path, format!("{:#}", analyze(bundle_name, &parts)?))
+        .map_err(MberError::io("could not write", &analysis_path));
+}
+
+// NOTE: the parts get minified together like the real bundle, then every minified character is counted for the
+// part its source map mapping points into. Parts do not need to be valid programs on their own
+pub fn analyze(bundle_name: &str, parts: &Vec<Part>) -> Result<Value, MberError> {
+    let code = parts.iter().map(|part| part.code.as_str()).collect::<Vec<&str>>().join("\n");
+    let part_starts = parts.iter().fold((Vec::new(), (0, 0)), |(mut starts, position), part| {
+        starts.push(position);
+
+        return (starts, source_map::advance(position, &format!("{}\n", part.code)));
+    }).0;
+    let (minified_code, map) = minify_js::from_string_with_source_map(&code, bundle_name)?;
+    let minified_sizes = attribute_minified_sizes(&minified_code, &map, &part_starts);
+    let minified_size = minified_sizes.iter().sum::<usize>();
+    let mut segments: Vec<(&str, usize, usize, Vec<Value>)> = Vec::new();
+
+    parts.iter().zip(minified_sizes.iter()).for_each(|(part, part_minified_size)| {
+        let module = json!({
+            "name": part.name,
+            "size": part.code.len(),
+            "minified_size": part_minified_size,
+            "share": share(*part_minified_size, minified_size)
+        });
+
+        match segments.iter_mut().find(|(name, _, _, _)| *name == part.segment) {
+            Some((_, size, segment_minified_size, modules)) => {
+                *size += part.code.len();
+                *segment_minified_size += part_minified_size;
+                modules.push(module);
+            },
+            None => segments.push((&part.segment, part.code.len(), *part_minified_size, vec![module]))
+        };
+    });
+    segments.sort_by_key(|(_, _, segment_minified_size, _)| std::cmp::Reverse(*segment_minified_size));
+
+    let segments = segments.into_iter().map(|(name, size, segment_minified_size, mut modules)| {
+        modules.sort_by_key(|module| std::cmp::Reverse(module["minified_size"].as_u64()));
+
+        return json!({
+            "name": name,
+            "size": size,
+            "minified_size": segment_minified_size,
+            "share": share(segment_minified_size, minified_size),
+            "modules": modules
+        });
+    }).collect::<Vec<Value>>();
+
+    return Ok(json!({
+        "name": bundle_name,
+        "size": code.len(),
+        "minified_size": minified_size,
+        "segments": segments
+    }));
+}
+
+// NOTE: part_starts are the (line, column) positions of the parts in the joined code, characters before the first
+// mapping of a line belong to the part of the previous mapping
+fn attribute_minified_sizes(minified_code: &str, map: &SourceMap, part_starts: &Vec<(usize, usize)>) -> Vec<usize> {
+    let mut sizes = vec![0; part_starts.len()];
+    let mut mappings = map.mappings.iter().collect::<Vec<_>>();
+    let mut current_part = 0;
+
+    mappings.sort_by_key(|mapping| (mapping.generated_line, mapping.generated_column));
+
+    let mut mappings = mappings.into_iter().peekable();
+
+    for (line_index, line) in minified_code.split('\n').enumerate() {
+        let line_length = line.chars().count() + 1;
+        let mut column = 0;
+
+        while let Some(mapping) = mappings.peek().filter(|mapping| mapping.generated_line == line_index) {
+            sizes[current_part] += mapping.generated_column.saturating_sub(column);
+            column = column.max(mapping.generated_column);
+            current_part = part_starts.partition_point(|start| *start <= (mapping.original_line, mapping.original_column))
+                .saturating_sub(1);
+            mappings.next();
+        }
+
+        sizes[current_part] += line_length.saturating_sub(column);
+    }
+
+    if let Some(last_size) = sizes.get_mut(current_part) {
+        *last_size = last_size.saturating_sub(1); // NOTE: the last line has no newline
+    }
+
+    return sizes;
+}
+
+fn share(size: usize, total_size: usize) -> f64 {
+    return match total_size {
+        0 => 0.0,
+        total_size => (size as f64 * 10000.0 / total_size as f64).round() / 100.0
+    };
+}
+
+// NOTE: collects tmp/analysis/*.json into dist/analyze.json and a static treemap in dist/analyze.html
+pub fn write_report(config: &Config) -> Result<PathBuf, MberError> {
+    let project_root = config.project_root.display();
+    let analysis_directory = format!("{}/tmp/analysis", &project_root);
+    let mut analysis_files = fs::read_dir(&analysis_directory)
+        .map_err(|_| MberError::build(&analysis_directory, "no bundle analysis found in tmp/analysis, build with --analyze"))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().map_or(false, |extension| extension == "json"))
+        .collect::<Vec<PathBuf>>();
+
+    analysis_files.sort();
+
+    let bundles = analysis_files.iter()
+        .map(|path| -> Result<Value, MberError> {
+            let content = fs::read_to_string(path).map_err(MberError::io("could not read", path))?;
+
+            return serde_json::from_str(&content).map_err(|error| MberError::build(path, error));
+        })
+        .collect::<Result<Vec<Value>, MberError>>()?;
+    let report = json!({ "environment": config.env["environment"], "bundles": bundles });
+    let (json_path, html_path) = (
+        PathBuf::from(format!("{}/dist/analyze.json", &project_root)), PathBuf::from(format!("{}/dist/analyze.html", &project_root))
+    );
+
+    fs::create_dir_all(format!("{}/dist", &project_root)).map_err(MberError::io("could not create", format!("{}/dist", &project_root)))?;
+    fs::write(&json_path, format!("{:#}", report)).map_err(MberError::io("could not write", &json_path))?;
+    fs::write(&html_path, render_html(&report)).map_err(MberError::io("could not write", &html_path))?;
+
+    print_summary(&bundles);
+
+    return Ok(html_path);
+}
+
+fn print_summary(bundles: &Vec<Value>) {
+    console::log(Paint::green("Bundle composition (minified):"));
+
+    bundles.iter().for_each(|bundle| {
+        println!(
+            "{} {}",
+            Paint::blue(format!(" - {}:", bundle["name"].as_str().unwrap_or(""))),
+            Paint::yellow(file::format_size(bundle["minified_size"].as_u64().unwrap_or(0)))
+        );
+
+        bundle["segments"].as_array().unwrap_or(&Vec::new()).iter().for_each(|segment| {
+            let largest_module = &segment["modules"][0];
+
+            println!(
+                "     {} {} {} largest: {} [{}]",
+                segment["name"].as_str().unwrap_or(""),
+                Paint::yellow(file::format_size(segment["minified_size"].as_u64().unwrap_or(0))),
+                Paint::green(format!("{}%", segment["share"])),
+                largest_module["name"].as_str().unwrap_or(""),
+                file::format_size(largest_module["minified_size"].as_u64().unwrap_or(0))
+            );
+        });
+    });
+}
+
+fn render_html(report: &Value) -> String {
+    let report_json = report.to_string().replace("</", "<\\/");
+
+    return include_str!("../../_vendor/mber-analyzer/index.html").replace("{{REPORT}}", &report_json);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::error::Error;
+    use super::*;
+    use std::collections::HashMap;
+    use super::super::super::types::BuildCache;
+
+    #[test]
+    fn split_modules_splits_prebuilt_files_into_their_modules() {
+        let ember_base = "var loader;(function(){e(\"@ember/a\",[\"exports\"],function(t){t.a=1})\ne(\"@ember/b\",[],function(){})})();\
+            define(\"ember-data/c\", [\"exports\"], function(t){})";
+        let parts = split_modules(vec![
+            Part::new("ember base", "_vendor/full-ember-prod.js", ember_base),
+            Part::new("mber", "glue", "window.a = define('x', ['y'], function() {});")
+        ]);
+
+        assert_eq!(parts.iter().map(|part| part.name.as_str()).collect::<Vec<&str>>(), vec![
+            "_vendor/full-ember-prod.js", "@ember/a", "@ember/b", "ember-data/c", "glue"
+        ]);
+        assert_eq!(parts.iter().map(|part| part.code.as_str()).collect::<String>(), format!(
+            "{}window.a = define('x', ['y'], function() {{}});", ember_base
+        ));
+        assert_eq!(parts[0].code, "var loader;(function(){");
+        assert_eq!(parts[2].code, "e(\"@ember/b\",[],function(){})})();");
+    }
+
+    #[test]
+    fn analyze_attributes_minified_sizes_to_every_part() -> Result<(), Box<dyn Error>> {
+        let parts = vec![
+            Part::new("application", "src/main.js", "define('app/src/main', ['exports'], function (exports) {\n  var longName = 1;\n  exports.default = longName;\n});"),
+            Part::new("application", "src/router.js", "define('app/src/router', [], function () {\n  return   'router';\n});"),
+            Part::new("mber", "boot", "require('app/src/main');")
+        ];
+        let analysis = analyze("application.js", &parts)?;
+        let minified_code = minify_js::from_string(&parts.iter().map(|part| part.code.as_str()).collect::<Vec<&str>>().join("\n"), "application.js")?;
+        let application_segment = &analysis["segments"][0];
+        let module_sizes = application_segment["modules"].as_array().unwrap().iter()
+            .map(|module| (module["name"].as_str().unwrap(), module["minified_size"].as_u64().unwrap()))
+            .collect::<Vec<(&str, u64)>>();
+
+        assert_eq!(analysis["minified_size"], json!(minified_code.len()));
+        assert_eq!(analysis["segments"].as_array().unwrap().len(), 2);
+        assert_eq!(application_segment["name"], json!("application"));
+        assert_eq!(application_segment["modules"][0]["name"], json!("src/main.js"));
+        assert_eq!(application_segment["size"], json!(parts[0].code.len() + parts[1].code.len()));
+        assert_eq!(analysis["segments"][1]["modules"][0]["minified_size"], json!("require('app/src/main');".len()));
+        assert!(module_sizes[0].1 > module_sizes[1].1);
+        assert!(module_sizes[1].1 >= "define('app/src/router',[],function(){return'router'})".len() as u64 - 2);
+        assert_eq!(
+            analysis["segments"].as_array().unwrap().iter().map(|segment| segment["minified_size"].as_u64().unwrap()).sum::<u64>(),
+            minified_code.len() as u64
+        );
+
+        return Ok(());
+    }
+
+    #[test]
+    fn write_report_writes_json_and_html_reports() -> Result<(), Box<dyn Error>> {
+        let current_directory = env::current_dir()?;
+
+        env::set_current_dir(format!("{}/ember-app-boilerplate", current_directory.to_string_lossy()))?;
+        fs::remove_dir_all("tmp").unwrap_or_else(|_| {});
+        fs::remove_dir_all("dist").unwrap_or_else(|_| {});
+
+        let config = Config::build(json!({ "environment": "production" }), HashMap::new(), BuildCache::new())?;
+
+        assert!(write_report(&config).is_err());
+
+        write_bundle_analysis(&config, "application.js", vec![Part::new("application", "src/main.js", "define('a', [], function() {});")])?;
+
+        let html_path = write_report(&config)?;
+        let report: Value = serde_json::from_str(&fs::read_to_string("dist/analyze.json")?)?;
+        let html = fs::read_to_string(&html_path)?;
+
+        assert_eq!(report["environment"], json!("production"));
+        assert_eq!(report["bundles"][0]["name"], json!("application.js"));
+        assert_eq!(report["bundles"][0]["segments"][0]["share"], json!(100.0));
+        assert!(html.contains("\"name\":\"src/main.js\""));
+        assert!(!html.contains("{{REPORT}}"));
+
+        fs::remove_dir_all("tmp")?;
+        fs::remove_dir_all("dist")?;
+        env::set_current_dir(&current_directory)?;
+
+        return Ok(());
+    }
+}
@@ -1,39 +1,44 @@
 use std::time::Instant;
 use std::path::PathBuf;
-use std::str::FromStr;
 use std::result::Result;
-use std::error::Error;
 use std::fs;
 use yansi::Paint;
+use serde_json::Value;
 use super::super::utils::{console, recursive_file_lookup, file};
+use super::super::utils::source_map::Bundle;
 use super::super::transpilers;
-use super::super::types::Config;
+use super::super::linters::{self, LintedFile};
+use super::super::types::{Config, MberError};
+use super::analyzer;
 
-// NOTE: eslint in rust(This one is challenging)
-pub fn build(config: &Config, _lint: bool) -> Result<(String, fs::Metadata), Box<dyn Error>> {
+pub fn build(config: &Config, lint: bool) -> Result<(String, fs::Metadata, Vec<LintedFile>), MberError> {
     console::log(format!("{} application.js...", Paint::yellow("BUILDING:")));
 
     let build_start = Instant::now();
 
     let project_root = &config.project_root.display();
     let environment = config.env["environment"].as_str().unwrap_or("development");
-    let output_path = PathBuf::from_str(format!("{}/tmp/assets/application.js", &project_root).as_str())?;
-    let application_path = PathBuf::from_str(format!("{}/src", &project_root).as_str())?;
+    let output_path = PathBuf::from(format!("{}/tmp/assets/application.js", &project_root));
+    let application_path = PathBuf::from(format!("{}/src", &project_root));
     let should_minify = vec!["production", "demo"].contains(&environment);
-    let contents = recursive_file_lookup::lookup_for_extensions_and_predicate(
+    let application_name = &config.application_name;
+    let (template_files, module_files): (Vec<PathBuf>, Vec<PathBuf>) = recursive_file_lookup::lookup_for_extensions_and_predicate(
         &application_path,
         vec![".js", ".ts", ".hbs"],
-        |entry| { return !entry.file_name().to_str().unwrap().ends_with("-test.js"); }
-    ).into_iter()
-    .map(|file| transpilers::convert_es_module::from_file(&file, should_minify))
-    .collect::<Vec<&str>>()
-    .join("\n");
-    let application_name = &config.application_name;
-    let stringified_env = &config.env.to_string();
-    let code = format!("
+        |entry| { return !entry.file_name().to_string_lossy().ends_with("-test.js"); }
+    )?.into_iter()
+    .partition(|file| file.extension().map_or(false, |extension| extension == "hbs"));
+    let mut modules = transpilers::convert_es_module::from_files(&module_files, &config.project_root, application_name)?;
+
+    modules.append(&mut transpilers::convert_hbs_module::from_files(&template_files, &config.project_root, application_name)?);
+
+    let files = module_files.into_iter().chain(template_files.into_iter()).collect::<Vec<PathBuf>>();
+    let stringified_env = &Value::String(config.env.to_string()).to_string(); // NOTE: JSON.parse needs a string literal
+    let prepend_code = format!("
         {}
         define = window.define;
-        {}
+        ", config.build_cache.application_prepends);
+    let boot_code = format!("
         define('{}/config/environment', ['exports'], function (exports) {{
           'use strict';
 
@@ -76,15 +81,34 @@ pub fn build(config: &Config, _lint: bool) -> Result<(String, fs::Metadata), Box
         }}
 
         {}
-    ", config.build_cache.application_prepends, contents, application_name, stringified_env, stringified_env,
-    application_name, application_name, application_name, application_name, application_name,
-    config.build_cache.application_appends);
-
-    fs::write(&output_path, code)?;
+    ", application_name, stringified_env, stringified_env, application_name, application_name, application_name,
+    application_name, application_name, config.build_cache.application_appends);
+    let linted_files = match lint {
+        true => linters::check_files(&files, config, "application.js")?,
+        false => Vec::new()
+    };
+    let mut bundle = Bundle::new("application.js");
+
+    bundle.push(&prepend_code);
+    super::push_files(&mut bundle, &files, &modules, &config.project_root)?;
+    bundle.push(&boot_code);
+    super::write_bundle(config, bundle, &output_path, should_minify)?;
+
+    if analyzer::should_analyze(config) {
+        let module_parts = files.iter().zip(modules.iter()).map(|(file, module)| {
+            return analyzer::Part::new("application", &file.strip_prefix(&config.project_root).unwrap_or(file).to_string_lossy(), module);
+        });
+        let parts = std::iter::once(analyzer::Part::new("mber", "application.js prepends", &prepend_code))
+            .chain(module_parts)
+            .chain(std::iter::once(analyzer::Part::new("mber", "config/environment and boot", &boot_code)))
+            .collect();
+
+        analyzer::write_bundle_analysis(config, "application.js", parts)?;
+    }
 
     // TODO: in future create a thread global build error to say/stop tts on error
 
-    let output_metadata = fs::metadata(output_path)?;
+    let output_metadata = fs::metadata(&output_path).map_err(MberError::io("could not read", &output_path))?;
     let message = format!(
         "{} application.js in {} [{}] Environment: {}",
         Paint::green("BUILT:"),
@@ -95,13 +119,12 @@ pub fn build(config: &Config, _lint: bool) -> Result<(String, fs::Metadata), Box
 
     console::log(&message);
 
-    // NOTE: then linting
-
-    return Ok((message, output_metadata));
+    return Ok((message, output_metadata, linted_files));
 }
 
 #[cfg(test)]
 mod tests {
+    use std::error::Error;
     use std::env;
     use super::*;
     use std::path::PathBuf;
@@ -109,6 +132,7 @@ mod tests {
     use serde_json::json;
     use std::collections::HashMap;
     use super::super::super::types::BuildCache;
+    use super::super::super::utils::source_map::{self, SourceMap};
 
     const APPLICATION_JS_BUILD_TIME_THRESHOLD: u32 = 2000;
     const APPLICATION_JS_TARGET_BYTE_SIZE: u64 = 1100;
@@ -147,18 +171,21 @@ mod tests {
             json!({ "environment": "development", "moduleprefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
-        let (message, _stats) = build(&config, false)?; // note: config and lint
+        )?;
+        let (message, _stats, _) = build(&config, false)?; // note: config and lint
         let build_time_in_ms = Regex::new(r"application\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
             .replace("application.js in ", "")
             .replace("ms", "")
             .parse::<u32>()?;
+        let application_js_code = fs::read_to_string(&application_js_output_path)?;
 
         assert!(build_time_in_ms < APPLICATION_JS_BUILD_TIME_THRESHOLD);
-
-        // let application_js_code = fs::read_to_string(application_js_output_path).unwrap();
-        // todo: content checks
+        assert!(application_js_code.contains("define(\"frontend/src/router\", [\"exports\""));
+        assert!(application_js_code.contains(
+            "define(\"frontend/src/ui/routes/index/template\", [\"exports\"], function (_exports) {"
+        ));
+        assert!(!application_js_code.contains("define(\"frontend/src/ui/routes/index/unit-test\""));
 
         assert!(fs::metadata(application_js_output_path)?.len() >= APPLICATION_JS_TARGET_BYTE_SIZE - 1000);
         assert!(Regex::new(r"BUILT: application\.js in \d+ms \[\d+.\d+ kB\] Environment: development")?.find(&message).is_some());
@@ -166,6 +193,73 @@ mod tests {
         return finalize_test(current_directory);
     }
 
+    #[test]
+    fn build_writes_a_source_map_to_the_source_files() -> Result<(), Box<dyn Error>> {
+        let (current_directory, application_js_output_path, _) = setup_test()?;
+        let config = Config::build(
+            json!({ "environment": "production", "modulePrefix": "frontend" }),
+            HashMap::new(),
+            BuildCache::new()
+        )?;
+
+        build(&config, false)?;
+
+        let application_js_code = fs::read_to_string(&application_js_output_path)?;
+        let map = SourceMap::from_json(&fs::read_to_string(format!("{}.map", &application_js_output_path))?)?;
+        let index = application_js_code.find("podModulePrefix").unwrap();
+        let position = source_map::advance((0, 0), &application_js_code[..index]);
+        let mapping = map.mappings.iter()
+            .find(|mapping| (mapping.generated_line, mapping.generated_column) == position)
+            .unwrap();
+
+        assert!(application_js_code.ends_with("\n//# sourceMappingURL=application.js.map\n"));
+        assert_eq!(map.file, "application.js");
+        assert!(map.sources.iter().all(|source| source.starts_with("src/")));
+        assert_eq!(map.sources_content.iter().filter(|content| content.is_some()).count(), map.sources.len());
+        assert_eq!(
+            (map.sources[mapping.source].as_str(), mapping.original_line, mapping.original_column),
+            ("src/main.js", 7, 2)
+        );
+
+        return finalize_test(current_directory);
+    }
+
+    #[test]
+    fn build_lints_before_writing_and_fails_on_lint_errors() -> Result<(), Box<dyn Error>> {
+        let (current_directory, application_js_output_path, _) = setup_test()?;
+        let mut config = Config::build(
+            json!({ "environment": "development", "modulePrefix": "frontend" }),
+            HashMap::new(),
+            BuildCache::new()
+        )?;
+
+        fs::write("src/lint-check.js", "export default function check() {\n  debugger;\n}\n")?;
+
+        let (_, _, linted_files) = build(&config, true)?;
+        let warned_application_js_exists = fs::metadata(&application_js_output_path).is_ok();
+
+        fs::remove_file(&application_js_output_path)?;
+        config.cli_arguments.lint = String::from("error");
+
+        let error = build(&config, true).unwrap_err();
+        let failed_application_js_exists = fs::metadata(&application_js_output_path).is_ok();
+
+        fs::remove_file("src/lint-check.js")?;
+
+        let lint_check_file = linted_files.iter().find(|linted_file| linted_file.file_name == "src/lint-check.js").unwrap();
+
+        assert!(warned_application_js_exists);
+        assert_eq!(lint_check_file.diagnostics[0].rule, "no-debugger");
+        assert!(!failed_application_js_exists);
+        assert!(error.to_string().starts_with("application.js has "));
+        assert!(match error {
+            MberError::Lint { diagnostics, .. } => diagnostics.contains("src/lint-check.js:2:3 error"),
+            _ => false
+        });
+
+        return finalize_test(current_directory);
+    }
+
     #[test]
     fn build_works_for_production() -> Result<(), Box<dyn Error>> {
         let (current_directory, application_js_output_path, _) = setup_test()?;
@@ -176,8 +270,8 @@ mod tests {
             json!({ "environment": "production", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
-        let (message, _stats) = build(&config, false)?; // NOTE: config and lint
+        )?;
+        let (message, _stats, _) = build(&config, false)?; // NOTE: config and lint
         let build_time_in_ms = Regex::new(r"application\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
             .replace("application.js in ", "")
@@ -205,8 +299,8 @@ mod tests {
             json!({ "environment": "custom", "modulePrefix": "my-app" }),
             HashMap::new(),
             BuildCache::new()
-        );
-        let (message, _stats) = build(&config, false)?; // NOTE: config and lint
+        )?;
+        let (message, _stats, _) = build(&config, false)?; // NOTE: config and lint
         let build_time_in_ms = Regex::new(r"application\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
             .replace("application.js in ", "")
@@ -234,8 +328,8 @@ mod tests {
             json!({ "environment": "development", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new().insert("application_prepends", CODE_TO_PREPEND)
-        );
-        let (message, _stats) = build(&config, false)?; // NOTE: config and lint
+        )?;
+        let (message, _stats, _) = build(&config, false)?; // NOTE: config and lint
         let build_time_in_ms = Regex::new(r"application\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
             .replace("application.js in ", "")
@@ -262,8 +356,8 @@ mod tests {
             json!({ "environment": "development", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new().insert("application_appends", CODE_TO_APPEND)
-        );
-        let (message, _stats) = build(&config, false)?; // NOTE: config and lint
+        )?;
+        let (message, _stats, _) = build(&config, false)?; // NOTE: config and lint
         let build_time_in_ms = Regex::new(r"application\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
             .replace("application.js in ", "")
@@ -292,8 +386,8 @@ mod tests {
             BuildCache::new()
                 .insert("application_prepends", CODE_TO_PREPEND)
                 .insert("application_appends", CODE_TO_APPEND)
-        );
-        let (message, _stats) = build(&config, false)?; // NOTE: config and lint
+        )?;
+        let (message, _stats, _) = build(&config, false)?; // NOTE: config and lint
         let build_time_in_ms = Regex::new(r"application\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
             .replace("application.js in ", "")
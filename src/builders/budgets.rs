@@ -0,0 +1,255 @@
+use std::fs;
+use yansi::Paint;
+use regex::Regex;
+use serde_json::Value;
+use super::super::utils::file;
+use super::super::types::{Config, MberError};
+
+const BUDGETS_PATH: &str = "config/budgets.json";
+const METRICS: [(&str, &str); 3] = [("size", "size"), ("gzip", "gzip_size"), ("brotli", "brotli_size")];
+
+#[derive(Debug, PartialEq)]
+pub struct BudgetResult {
+    pub asset: String,
+    pub metric: String,
+    pub size: u64,
+    pub budget: u64
+}
+
+impl BudgetResult {
+    pub fn is_exceeded(&self) -> bool {
+        return self.size > self.budget;
+    }
+}
+
+// NOTE: budgets come from ENV.budgets or config/budgets.json: [{ "asset": "vendor.js", "gzip": "250kB" }, { "asset": "*.js", "size": "1MB" }].
+// Each budget limits the combined size of the dist assets matching its pattern
+pub fn check(config: &Config, dist_files: &Vec<Value>) -> Result<Vec<BudgetResult>, MberError> {
+    let budgets = read_budgets(config)?;
+
+    if budgets.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let results = measure(&budgets, dist_files)?;
+
+    print_table(&results);
+
+    let exceeded_budget_count = results.iter().filter(|result| result.is_exceeded()).count();
+
+    if exceeded_budget_count > 0 {
+        return Err(MberError::build(
+            config.project_root.join("dist"), format!("{} of {} bundle size budgets exceeded", exceeded_budget_count, results.len())
+        ));
+    }
+
+    return Ok(results);
+}
+
+fn read_budgets(config: &Config) -> Result<Vec<Value>, MberError> {
+    let budgets_path = format!("{}/config/budgets.json", config.project_root.display());
+    let budgets = match &config.env["budgets"] {
+        Value::Null if fs::metadata(&budgets_path).is_ok() => {
+            let content = fs::read_to_string(&budgets_path).map_err(MberError::io("could not read", &budgets_path))?;
+
+            serde_json::from_str(&content)
+                .map_err(|error| MberError::config(&budgets_path, format!("config/budgets.json is not valid JSON: {}", error)))?
+        },
+        Value::Null => Value::Array(Vec::new()),
+        budgets => budgets.clone()
+    };
+
+    return match budgets {
+        Value::Array(budgets) => Ok(budgets),
+        _ => Err(MberError::config(&budgets_path, "budgets should be a list like [{ \"asset\": \"vendor.js\", \"gzip\": \"250kB\" }]"))
+    };
+}
+
+pub fn measure(budgets: &Vec<Value>, dist_files: &Vec<Value>) -> Result<Vec<BudgetResult>, MberError> {
+    return budgets.iter().try_fold(Vec::new(), |mut result, budget| {
+        let pattern = budget["asset"].as_str().ok_or_else(|| {
+            return MberError::config(BUDGETS_PATH, "every budget needs an \"asset\" pattern like \"vendor.js\" or \"*.js\"");
+        })?;
+        let matching_files = dist_files.iter()
+            .filter(|dist_file| asset_matches(pattern, dist_file["asset"].as_str().unwrap_or("")))
+            .collect::<Vec<&Value>>();
+
+        if matching_files.is_empty() {
+            return Err(MberError::config(BUDGETS_PATH, format!("budget asset pattern {} does not match any asset in dist", pattern)));
+        }
+
+        for (metric, metadata_key) in METRICS.iter() {
+            if budget[metric].is_null() {
+                continue;
+            }
+
+            let limit = parse_size(&budget[metric])?;
+            let size = matching_files.iter().try_fold(0, |total, dist_file| {
+                return dist_file[metadata_key].as_u64().map(|size| total + size)
+                    .ok_or_else(|| MberError::config(
+                        BUDGETS_PATH, format!("{} size of {} is unknown, enable compression.{} to budget it", metric, pattern, metric)
+                    ));
+            })?;
+
+            result.push(BudgetResult { asset: pattern.to_string(), metric: metric.to_string(), size: size, budget: limit });
+        }
+
+        return Ok(result);
+    });
+}
+
+// NOTE: patterns match the end of the unfingerprinted asset path, * matches within a path segment
+pub fn asset_matches(pattern: &str, asset: &str) -> bool {
+    let pattern = regex::escape(pattern.trim_start_matches('/')).replace(r"\*", "[^/]*");
+
+    return Regex::new(&format!("(^|/){}$", pattern)).unwrap().is_match(asset);
+}
+
+// NOTE: sizes are in bytes or strings like "250kB", "1.5 MB", units are decimal like file::format_size
+pub fn parse_size(value: &Value) -> Result<u64, MberError> {
+    if let Some(size) = value.as_u64() {
+        return Ok(size);
+    }
+
+    let size = value.as_str().unwrap_or("").trim().to_lowercase();
+    let unit_index = size.find(|character: char| character.is_alphabetic()).unwrap_or(size.len());
+    let amount = size[..unit_index].trim().parse::<f64>()
+        .map_err(|_| MberError::config(BUDGETS_PATH, format!("{} is not a valid budget size, use bytes or a size like \"250kB\"", value)))?;
+    let multiplier = match &size[unit_index..] {
+        "" | "b" => 1.0,
+        "kb" | "k" => 1000.0,
+        "mb" | "m" => 1000000.0,
+        unit => return Err(MberError::config(BUDGETS_PATH, format!("{} is not a valid budget unit, use B, kB or MB", unit)))
+    };
+
+    return Ok((amount * multiplier).round() as u64);
+}
+
+fn print_table(results: &Vec<BudgetResult>) {
+    let rows = results.iter().map(|result| {
+        return vec![
+            result.asset.clone(),
+            result.metric.clone(),
+            file::format_size(result.size),
+            file::format_size(result.budget)
+        ];
+    }).collect::<Vec<Vec<String>>>();
+    let headers = vec!["Asset", "Metric", "Size", "Budget"];
+    let column_widths = headers.iter().enumerate().map(|(index, header)| {
+        return rows.iter().map(|row| row[index].len()).chain(std::iter::once(header.len())).max().unwrap_or(0);
+    }).collect::<Vec<usize>>();
+    let format_row = |columns: Vec<String>| -> String {
+        return columns.iter().zip(column_widths.iter())
+            .map(|(column, width)| format!("{:width$}", column, width = width))
+            .collect::<Vec<String>>()
+            .join("  ");
+    };
+
+    println!("{}", Paint::green("Bundle size budgets:"));
+    println!("   {}  Status", format_row(headers.iter().map(|header| header.to_string()).collect()));
+
+    results.iter().zip(rows.into_iter()).for_each(|(result, row)| {
+        let status = match result.is_exceeded() {
+            true => Paint::red(format!("exceeded by {}", file::format_size(result.size - result.budget))).to_string(),
+            false => Paint::green("ok").to_string()
+        };
+
+        println!(" - {}  {}", format_row(row), status);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::error::Error;
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+    use super::super::super::types::BuildCache;
+
+    fn dist_files() -> Vec<Value> {
+        return vec![
+            json!({ "file_name": "vendor-abc.js", "asset": "assets/vendor.js", "size": 800000, "gzip_size": 210000, "brotli_size": 170000 }),
+            json!({ "file_name": "application-def.js", "asset": "assets/application.js", "size": 9000, "gzip_size": 2600, "brotli_size": 2200 }),
+            json!({ "file_name": "application-ghi.css", "asset": "assets/application.css", "size": 2000, "gzip_size": 700, "brotli_size": null })
+        ];
+    }
+
+    #[test]
+    fn parse_size_works() {
+        assert_eq!(parse_size(&json!(1200)).unwrap(), 1200);
+        assert_eq!(parse_size(&json!("250kB")).unwrap(), 250000);
+        assert_eq!(parse_size(&json!("1.5 MB")).unwrap(), 1500000);
+        assert_eq!(parse_size(&json!("300B")).unwrap(), 300);
+        assert_eq!(parse_size(&json!("12")).unwrap(), 12);
+        assert_eq!(parse_size(&json!("12 GB")).unwrap_err().to_string(), "gb is not a valid budget unit, use B, kB or MB");
+        assert!(parse_size(&json!("big")).is_err());
+    }
+
+    #[test]
+    fn asset_matches_works() {
+        assert!(asset_matches("vendor.js", "assets/vendor.js"));
+        assert!(asset_matches("/assets/vendor.js", "assets/vendor.js"));
+        assert!(asset_matches("*.js", "assets/application.js"));
+        assert!(asset_matches("assets/*.css", "assets/application.css"));
+        assert!(!asset_matches("vendor.js", "assets/other-vendor.js"));
+        assert!(!asset_matches("*.js", "assets/application.css"));
+    }
+
+    #[test]
+    fn measure_sums_the_matching_assets_for_every_metric() -> Result<(), Box<dyn Error>> {
+        let budgets = vec![json!({ "asset": "vendor.js", "gzip": "200kB" }), json!({ "asset": "*.js", "size": "1MB", "brotli": 200000 })];
+        let results = measure(&budgets, &dist_files())?;
+
+        assert_eq!(results, vec![
+            BudgetResult { asset: "vendor.js".to_string(), metric: "gzip".to_string(), size: 210000, budget: 200000 },
+            BudgetResult { asset: "*.js".to_string(), metric: "size".to_string(), size: 809000, budget: 1000000 },
+            BudgetResult { asset: "*.js".to_string(), metric: "brotli".to_string(), size: 172200, budget: 200000 }
+        ]);
+        assert_eq!(results.iter().map(|result| result.is_exceeded()).collect::<Vec<bool>>(), vec![true, false, false]);
+        assert_eq!(
+            measure(&vec![json!({ "asset": "*.css", "brotli": "1kB" })], &dist_files()).unwrap_err().to_string(),
+            "brotli size of *.css is unknown, enable compression.brotli to budget it"
+        );
+        assert_eq!(
+            measure(&vec![json!({ "asset": "vendr.js", "gzip": "200kB" })], &dist_files()).unwrap_err().to_string(),
+            "budget asset pattern vendr.js does not match any asset in dist"
+        );
+
+        return Ok(());
+    }
+
+    #[test]
+    fn check_fails_when_a_budget_is_exceeded() -> Result<(), Box<dyn Error>> {
+        let current_directory = env::current_dir()?;
+
+        env::set_current_dir(format!("{}/ember-app-boilerplate", current_directory.to_string_lossy()))?;
+
+        let config = Config::build(json!({ "environment": "production" }), HashMap::new(), BuildCache::new())?;
+        let exceeding_config = Config::build(
+            json!({ "environment": "production", "budgets": [{ "asset": "vendor.js", "gzip": "250kB" }, { "asset": "*.css", "size": "1kB" }] }),
+            HashMap::new(),
+            BuildCache::new()
+        )?;
+        let passing_config = Config::build(
+            json!({ "environment": "production", "budgets": [{ "asset": "vendor.js", "gzip": "250kB" }] }),
+            HashMap::new(),
+            BuildCache::new()
+        )?;
+
+        assert_eq!(check(&config, &dist_files())?, Vec::new());
+        assert_eq!(check(&passing_config, &dist_files())?.len(), 1);
+        assert_eq!(check(&exceeding_config, &dist_files()).unwrap_err().to_string(), "1 of 2 bundle size budgets exceeded");
+
+        fs::write("config/budgets.json", r#"[{ "asset": "application.js", "size": "5kB" }]"#)?;
+
+        let file_result = check(&config, &dist_files());
+
+        fs::remove_file("config/budgets.json")?;
+        env::set_current_dir(&current_directory)?;
+
+        assert_eq!(file_result.unwrap_err().to_string(), "1 of 1 bundle size budgets exceeded");
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,121 @@
+use std::fs;
+use std::path::Path;
+use yansi::Paint;
+use serde_json::{json, Value};
+use super::super::utils::file;
+use super::super::types::MberError;
+
+pub const STATS_FILE_NAME: &str = ".mber-stats.json";
+
+// NOTE: stats files look like { "environment": "production", "built_at": 1571000000, "assets": [{ "asset": "assets/vendor.js", "size": 1, "gzip_size": 1 }] }
+pub fn read(stats_path: &Path) -> Result<Option<Vec<Value>>, MberError> {
+    if !stats_path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(stats_path).map_err(MberError::io("could not read", stats_path))?;
+    let stats: Value = serde_json::from_str(&content)
+        .map_err(|error| MberError::config(stats_path, format!("{} is not a valid stats file: {}", stats_path.display(), error)))?;
+
+    return match stats["assets"].as_array() {
+        Some(assets) => Ok(Some(assets.clone())),
+        None => Err(MberError::config(stats_path, format!("{} is not a valid stats file: assets list is missing", stats_path.display())))
+    };
+}
+
+pub fn write(stats_path: &Path, environment: &str, built_at: u64, assets: &Vec<Value>) -> Result<(), MberError> {
+    return fs::write(stats_path, format!("{:#}", json!({
+        "environment": environment,
+        "built_at": built_at,
+        "assets": assets
+    }))).map_err(MberError::io("could not write", stats_path));
+}
+
+pub fn find_asset<'a>(stats: &'a Option<Vec<Value>>, asset: &str) -> Option<&'a Value> {
+    return stats.as_ref()?.iter().find(|previous_asset| previous_asset["asset"].as_str() == Some(asset));
+}
+
+pub fn format_delta(asset: &Value, previous_asset: Option<&Value>) -> String {
+    let previous_asset = match previous_asset {
+        Some(previous_asset) => previous_asset,
+        None => return Paint::yellow("(new)").to_string()
+    };
+    let size_delta = format_size_delta(asset["size"].as_u64().unwrap_or(0), previous_asset["size"].as_u64().unwrap_or(0));
+    let gzip_delta = format_size_delta(asset["gzip_size"].as_u64().unwrap_or(0), previous_asset["gzip_size"].as_u64().unwrap_or(0));
+
+    return format!("({}, {} gzipped)", size_delta, gzip_delta);
+}
+
+// NOTE: assets of the previous build that are not in the current one
+pub fn removed_assets<'a>(stats: &'a Option<Vec<Value>>, assets: &Vec<Value>) -> Vec<&'a Value> {
+    return stats.as_ref().map_or(Vec::new(), |previous_assets| {
+        return previous_assets.iter()
+            .filter(|previous_asset| !assets.iter().any(|asset| asset["asset"] == previous_asset["asset"]))
+            .collect();
+    });
+}
+
+fn format_size_delta(size: u64, previous_size: u64) -> String {
+    if size > previous_size {
+        return Paint::red(format!("+{}", file::format_size(size - previous_size))).to_string();
+    } else if size < previous_size {
+        return Paint::green(format!("-{}", file::format_size(previous_size - size))).to_string();
+    }
+
+    return String::from("±0");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::error::Error;
+    use super::*;
+
+    #[test]
+    fn write_and_read_works() -> Result<(), Box<dyn Error>> {
+        let stats_path = env::temp_dir().join("mber-write-and-read-stats.json");
+        let assets = vec![json!({ "asset": "assets/vendor.js", "file_name": "vendor-abc.js", "size": 1000, "gzip_size": 300 })];
+
+        fs::remove_file(&stats_path).unwrap_or_else(|_| {});
+
+        assert_eq!(read(&stats_path)?, None);
+
+        write(&stats_path, "production", 1571000000, &assets)?;
+
+        let stats: Value = serde_json::from_str(&fs::read_to_string(&stats_path)?)?;
+
+        assert_eq!(stats["built_at"], json!(1571000000));
+        assert_eq!(read(&stats_path)?, Some(assets));
+
+        fs::write(&stats_path, "{}")?;
+
+        assert_eq!(
+            read(&stats_path).unwrap_err().to_string(),
+            format!("{} is not a valid stats file: assets list is missing", stats_path.display())
+        );
+
+        return Ok(fs::remove_file(&stats_path)?);
+    }
+
+    #[test]
+    fn format_delta_and_removed_assets_work() {
+        Paint::disable();
+
+        let stats = Some(vec![
+            json!({ "asset": "assets/vendor.js", "size": 1000, "gzip_size": 300 }),
+            json!({ "asset": "assets/memserver.js", "size": 500, "gzip_size": 100 })
+        ]);
+        let assets = vec![
+            json!({ "asset": "assets/vendor.js", "size": 1500, "gzip_size": 250 }),
+            json!({ "asset": "assets/application.js", "size": 200, "gzip_size": 90 })
+        ];
+
+        assert_eq!(format_delta(&assets[0], find_asset(&stats, "assets/vendor.js")), "(+0.50 kB, -0.05 kB gzipped)");
+        assert_eq!(format_delta(&assets[0], Some(&assets[0])), "(±0, ±0 gzipped)");
+        assert_eq!(format_delta(&assets[1], find_asset(&stats, "assets/application.js")), "(new)");
+        assert_eq!(find_asset(&None, "assets/vendor.js"), None);
+        assert_eq!(removed_assets(&stats, &assets), vec![&json!({ "asset": "assets/memserver.js", "size": 500, "gzip_size": 100 })]);
+
+        Paint::enable();
+    }
+}
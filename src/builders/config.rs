@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use serde_json::{json, Value};
+use super::super::transpilers::{convert_es_module, import_addon_folder_to_amd};
+use super::super::types::{BuildCache, CLIArguments, Config, MberError, TranspileError};
+
+// NOTE: both files run through convert_es_module, so ES modules and CommonJS index.js files work the same way.
+// index.js receives a recording stand-in for the mber-rust package, rust resolves the recorded imports afterwards.
+// stdout is reserved for the result, console.log of the project files goes to stderr
+const EVALUATE_PROJECT_SCRIPT: &str = "
+  const { createRequire } = require('module');
+  const path = require('path');
+  let input = '';
+
+  console.log = console.info = console.error;
+  process.stdin.setEncoding('utf8');
+  process.stdin.on('data', (chunk) => { input += chunk; });
+  process.stdin.on('end', async () => {
+    const payload = JSON.parse(input);
+    const projectRequire = createRequire(path.join(payload.projectRoot, 'package.json'));
+    const recorded = { imports: [], injections: {} };
+    const app = {
+      import(filePath, options = {}) {
+        recorded.imports.push(Object.assign({ kind: 'file', path: filePath }, assetOptions(options)));
+      },
+      importAddon(addonName, options = {}) {
+        recorded.imports.push(Object.assign({ kind: 'addon', name: addonName }, assetOptions(options)));
+      },
+      importAsAMDModule(moduleName, filePath, options = {}) {
+        if (typeof filePath === 'object' && filePath !== null) {
+          options = filePath;
+          filePath = null;
+        }
+
+        recorded.imports.push(Object.assign({ kind: 'amd', name: moduleName, path: filePath || null }, assetOptions(options)));
+      },
+      injectInlineContent(key, value) {
+        recorded.injections[key] = String(value);
+      },
+      build(environment) {
+        return recorded;
+      }
+    };
+
+    try {
+      const ENV = evaluate(payload.environmentModule, app, projectRequire)(payload.environment);
+      const indexFunction = evaluate(payload.indexModule, app, projectRequire);
+
+      await indexFunction(ENV);
+
+      process.stdout.write(JSON.stringify({ ENV: ENV, imports: recorded.imports, injections: recorded.injections }, serialize));
+    } catch (error) {
+      process.stdout.write(JSON.stringify({ error: error.stack || String(error) }));
+    }
+  });
+
+  function assetOptions(options) {
+    return { type: options.type || 'vendor', prepend: !!options.prepend };
+  }
+
+  function evaluate(moduleCode, app, projectRequire) {
+    const module = { exports: {} };
+    let definition;
+
+    new Function('define', 'require', 'module', 'exports', moduleCode)(
+      (name, dependencies, factory) => { definition = { dependencies, factory }; },
+      (dependency) => dependency === 'mber-rust' || dependency === 'mber' ? app : projectRequire(dependency),
+      module,
+      module.exports
+    );
+
+    const dependencies = definition.dependencies.map((dependency) => {
+      if (dependency === 'exports') {
+        return module.exports;
+      } else if (dependency === 'mber-rust' || dependency === 'mber') {
+        return { default: app };
+      }
+
+      const importedModule = projectRequire(dependency);
+
+      return importedModule && importedModule.__esModule ? importedModule : Object.assign({ default: importedModule }, importedModule);
+    });
+
+    definition.factory.apply(null, dependencies);
+
+    return typeof module.exports.default === 'function' ? module.exports.default : module.exports;
+  }
+
+  function serialize(key, value) {
+    return value instanceof RegExp ? value.toString() : value;
+  }
+";
+
+pub fn build(project_root: &Path, cli_arguments: CLIArguments) -> Result<Config, MberError> {
+    let environment_module = transpile_project_file(project_root, "config/environment.js")?;
+    let index_module = transpile_project_file(project_root, "index.js")?;
+    let evaluation = evaluate(project_root, cli_arguments.env.as_deref().unwrap_or("development"), &environment_module, &index_module)?;
+    let index_html_injections = evaluation["injections"].as_object()
+        .map(|injections| {
+            return injections.iter()
+                .map(|(key, value)| (key.to_string(), value.as_str().unwrap_or("").to_string()))
+                .collect::<HashMap<String, String>>();
+        })
+        .unwrap_or_default();
+    let mut config = Config::new(evaluation["ENV"].clone(), index_html_injections, BuildCache::new(), project_root.to_path_buf());
+
+    config.cli_arguments = Box::new(cli_arguments);
+    config.build_cache = Box::new(build_cache_from_imports(
+        evaluation["imports"].as_array().unwrap_or(&Vec::new()),
+        &config
+    )?);
+
+    return Ok(config);
+}
+
+fn transpile_project_file(project_root: &Path, relative_path: &str) -> Result<String, MberError> {
+    let code = fs::read_to_string(project_root.join(relative_path)).map_err(MberError::io("could not read", relative_path))?;
+
+    return Ok(convert_es_module::from_string(&code, relative_path.trim_end_matches(".js"))
+        .map_err(|error| TranspileError { file_name: relative_path.to_string(), ..error })?);
+}
+
+fn evaluate(project_root: &Path, environment: &str, environment_module: &str, index_module: &str) -> Result<Value, MberError> {
+    let payload = json!({
+        "projectRoot": project_root.to_string_lossy(),
+        "environment": environment,
+        "environmentModule": environment_module,
+        "indexModule": index_module
+    });
+    let mut child = Command::new("node")
+        .args(&["-e", EVALUATE_PROJECT_SCRIPT])
+        .current_dir(project_root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(node_spawn_error)?;
+
+    child.stdin.take()
+        .ok_or_else(|| MberError::build("index.js", "node stdin is unavailable"))?
+        .write_all(payload.to_string().as_bytes())
+        .map_err(MberError::io("could not send the project to node for", "index.js"))?;
+
+    let output = child.wait_with_output().map_err(MberError::io("node failed to evaluate", "index.js"))?;
+    let evaluation: Value = serde_json::from_slice(&output.stdout)
+        .map_err(|_| MberError::build("index.js", format!("index.js evaluation exited without a result [{}]", output.status)))?;
+
+    if let Some(error) = evaluation["error"].as_str() {
+        return Err(MberError::config("index.js", format!("index.js or config/environment.js failed: {}", error)));
+    }
+
+    return Ok(evaluation);
+}
+
+// NOTE: index.js and config/environment.js may require any node package of the project, so they need node.js itself
+fn node_spawn_error(error: io::Error) -> MberError {
+    return match error.kind() {
+        io::ErrorKind::NotFound => MberError::config(
+            "index.js", "node.js is required to evaluate index.js and config/environment.js, install it and make sure node is in your PATH"
+        ),
+        _ => MberError::io("node could not be spawned to evaluate", "index.js")(error)
+    };
+}
+
+fn build_cache_from_imports(imports: &Vec<Value>, config: &Config) -> Result<BuildCache, MberError> {
+    let mut cache_parts: HashMap<String, (Vec<String>, Vec<String>)> = HashMap::new();
+
+    for import in imports {
+        let asset_type = import["type"].as_str().unwrap_or("vendor");
+
+        if !vec!["vendor", "application", "test"].contains(&asset_type) {
+            return Err(MberError::config(
+                "index.js", format!("{} is not a valid import type, use vendor, application or test", asset_type)
+            ));
+        }
+
+        let name = import["name"].as_str().unwrap_or("");
+        let code = match import["kind"].as_str().unwrap_or("") {
+            "file" => {
+                let file_path = import["path"].as_str().unwrap_or("");
+
+                fs::read_to_string(config.project_root.join(file_path)).map_err(MberError::io("app.import could not read", file_path))?
+            },
+            "addon" => import_addon_folder_to_amd::to_string(&format!("{}/addon", name), config)?,
+            _ => import_as_amd_module(name, import["path"].as_str(), &config.project_root)?
+        };
+        let (prepends, appends) = cache_parts.entry(asset_type.to_string()).or_insert((Vec::new(), Vec::new()));
+
+        match import["prepend"].as_bool().unwrap_or(false) {
+            true => prepends.push(code),
+            false => appends.push(code)
+        };
+    }
+
+    return Ok(cache_parts.iter().fold(BuildCache::new(), |build_cache, (asset_type, (prepends, appends))| {
+        return build_cache
+            .insert(&format!("{}_prepends", asset_type), &prepends.join("\n"))
+            .insert(&format!("{}_appends", asset_type), &appends.join("\n"));
+    }));
+}
+
+// NOTE: wraps a CommonJS file as an AMD module, loader.js adds the default export
+fn import_as_amd_module(module_name: &str, file_path: Option<&str>, project_root: &Path) -> Result<String, MberError> {
+    let entrypoint = match file_path {
+        Some(file_path) => project_root.join(file_path),
+        None => {
+            let package_path = project_root.join("node_modules").join(module_name);
+            let package_json: Value = fs::read_to_string(package_path.join("package.json"))
+                .map(|content| serde_json::from_str(&content).unwrap_or(Value::Null))
+                .map_err(|_| MberError::config(
+                    &package_path, format!("{} package is missing in {}, did you run npm install?", module_name, package_path.display())
+                ))?;
+
+            package_path.join(package_json["main"].as_str().unwrap_or("index.js"))
+        }
+    };
+    let code = fs::read_to_string(&entrypoint).map_err(MberError::io("importAsAMDModule could not read", &entrypoint))?;
+
+    return Ok(format!(
+        ";(function() {{\n  function vendorModule() {{\n    var module = {{ exports: {{}} }}, exports = module.exports;\n{}\n    return module.exports;\n  }}\n\n  define('{}', [], vendorModule);\n}})();",
+        code, module_name
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::error::Error;
+    use super::*;
+
+    fn arguments_for(environment: &str) -> CLIArguments {
+        return CLIArguments { env: Some(environment.to_string()), ..CLIArguments::new() };
+    }
+
+    fn write_project(project_root: &Path, index_js: &str) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(project_root.join("config"))?;
+        fs::create_dir_all(project_root.join("vendor"))?;
+        fs::create_dir_all(project_root.join("node_modules/left-pad"))?;
+        fs::write(project_root.join("package.json"), "{ \"name\": \"config-test\" }")?;
+        fs::write(project_root.join("config/environment.js"), "export default function(environment) {
+  return { modulePrefix: 'my-app', environment, fastboot: { hostWhitelist: [/^localhost:\\d+$/] } };
+}")?;
+        fs::write(project_root.join("index.js"), index_js)?;
+        fs::write(project_root.join("vendor/first.js"), "window.first = true;")?;
+        fs::write(project_root.join("vendor/second.js"), "window.second = true;")?;
+        fs::write(project_root.join("node_modules/left-pad/package.json"), "{ \"main\": \"lib/index.js\" }")?;
+        fs::create_dir_all(project_root.join("node_modules/left-pad/lib"))?;
+        fs::write(project_root.join("node_modules/left-pad/lib/index.js"), "module.exports = function leftPad() {};")?;
+
+        return Ok(());
+    }
+
+    #[test]
+    fn build_evaluates_the_boilerplate_project() -> Result<(), Box<dyn Error>> {
+        let project_root = import_addon_folder_to_amd::test_project()?;
+
+        let config = build(&project_root, arguments_for("test"))?;
+
+        assert_eq!(config.application_name, "{{applicationName}}");
+        assert_eq!(config.env["environment"], "test");
+        assert_eq!(config.env["APP"]["autoboot"], false);
+        assert_eq!(config.env["memserver"]["enabled"], true);
+        assert_eq!(config.env["fastboot"]["hostWhitelist"], json!(["localhost:1234", "localhost:3000", "/^localhost:\\d+$/"]));
+        assert!(config.build_cache.vendor_appends.contains("define(\"mber-head/"));
+        assert_eq!(config.build_cache.application_appends, "");
+        assert_eq!(config.index_html_injections.len(), 0);
+        assert_eq!(config.project_root, project_root);
+
+        return Ok(());
+    }
+
+    #[test]
+    fn build_records_index_js_imports_and_injections() -> Result<(), Box<dyn Error>> {
+        let project_root = env::current_dir()?.join("config-test");
+
+        write_project(&project_root, "const app = require('mber-rust');
+
+module.exports = function(ENV) {
+  app.import('vendor/second.js', { type: 'application' });
+  app.import('vendor/first.js', { type: 'application', prepend: true });
+  app.import('vendor/first.js', { type: 'test' });
+  app.importAsAMDModule('left-pad');
+  app.injectInlineContent('analytics', `<script>ga('${ENV.modulePrefix}');</script>`);
+
+  return app.build(ENV.environment);
+};")?;
+
+        let config = build(&project_root, arguments_for("production"));
+
+        fs::remove_dir_all(&project_root)?;
+
+        let config = config?;
+
+        assert_eq!(config.application_name, "my-app");
+        assert_eq!(config.env["environment"], "production");
+        assert_eq!(config.env["fastboot"]["hostWhitelist"][0], "/^localhost:\\d+$/");
+        assert_eq!(config.build_cache.application_prepends, "window.first = true;");
+        assert_eq!(config.build_cache.application_appends, "window.second = true;");
+        assert_eq!(config.build_cache.test_appends, "window.first = true;");
+        assert!(config.build_cache.vendor_appends.contains("module.exports = function leftPad() {};"));
+        assert!(config.build_cache.vendor_appends.contains("define('left-pad', [], vendorModule);"));
+        assert_eq!(config.index_html_injections.get("analytics").unwrap(), "<script>ga('my-app');</script>");
+
+        return Ok(());
+    }
+
+    #[test]
+    fn build_reports_errors_of_the_project_files() -> Result<(), Box<dyn Error>> {
+        let project_root = env::current_dir()?.join("config-error-test");
+
+        write_project(&project_root, "import app from 'mber-rust';\n\nexport default function(ENV) {\n  app.import('vendor/missing.js');\n}")?;
+
+        let missing_file_error = build(&project_root, arguments_for("development")).unwrap_err().to_string();
+
+        fs::write(project_root.join("index.js"), "export default function(ENV) {\n  throw new Error('broken index.js');\n}")?;
+
+        let thrown_error = build(&project_root, arguments_for("development")).unwrap_err().to_string();
+
+        fs::write(project_root.join("index.js"), "export default function(ENV) {")?;
+
+        let syntax_error = build(&project_root, arguments_for("development")).unwrap_err().to_string();
+
+        fs::remove_dir_all(&project_root)?;
+
+        assert!(missing_file_error.starts_with("app.import could not read vendor/missing.js"));
+        assert!(thrown_error.starts_with("index.js or config/environment.js failed: Error: broken index.js"));
+        assert_eq!(syntax_error, "index.js:1:31 Unexpected end of input");
+
+        return Ok(());
+    }
+
+    #[test]
+    fn node_spawn_error_explains_that_node_is_missing() {
+        let missing_node_error = node_spawn_error(io::Error::new(io::ErrorKind::NotFound, "No such file or directory"));
+        let spawn_error = node_spawn_error(io::Error::new(io::ErrorKind::PermissionDenied, "Permission denied"));
+
+        assert!(match missing_node_error { MberError::Config { .. } => true, _ => false });
+        assert_eq!(
+            missing_node_error.to_string(),
+            "node.js is required to evaluate index.js and config/environment.js, install it and make sure node is in your PATH"
+        );
+        assert_eq!(spawn_error.to_string(), "node could not be spawned to evaluate index.js: Permission denied");
+    }
+}
@@ -1,45 +1,44 @@
 use std::time::Instant;
-use std::path::PathBuf;
-use std::str::FromStr;
+use std::path::{Path, PathBuf};
 use std::result::Result;
-use std::error::Error;
 use std::fs;
 use yansi::Paint;
+use regex::Regex;
 use sass_rs;
 use super::super::utils::{console, recursive_file_lookup, file};
-use super::super::types::Config;
+use super::super::utils::source_map::{self, Mapping, SourceMap};
+use super::super::types::{Config, MberError};
 
-pub fn build(config: &Config) -> Result<(String, fs::Metadata), Box<dyn Error>> {
+const SOURCE_MARKER: &str = "/*! mber-source:";
+
+pub fn build(config: &Config) -> Result<(String, fs::Metadata), MberError> {
     console::log(format!("{} application.css...", Paint::yellow("BUILDING:")));
 
     let build_start = Instant::now();
     let environment = config.env["environment"].as_str().unwrap_or("development");
     let project_root = &config.project_root.display();
-    let output_path = PathBuf::from_str(format!("{}/tmp/assets/application.css", &project_root).as_str())?;
-    let application_path = PathBuf::from_str(format!("{}/src", &project_root).as_str())?;
-    let mut all_styles = vec![fs::read_to_string(format!("{}/src/ui/styles/application.scss", project_root))?];
-    let mut component_styles = recursive_file_lookup::lookup_for_extensions_and_predicate(
+    let output_path = PathBuf::from(format!("{}/tmp/assets/application.css", &project_root));
+    let application_path = PathBuf::from(format!("{}/src", &project_root));
+    let mut style_files = vec![PathBuf::from(format!("{}/src/ui/styles/application.scss", project_root))];
+    let mut component_style_files = recursive_file_lookup::lookup_for_extensions_and_predicate(
         &application_path,
         vec![".scss"],
-        |entry| { return !entry.file_name().to_str().unwrap().contains("/src/ui/styles"); }
-    ).into_iter()
-    .map(|file_name| fs::read_to_string(file_name).unwrap())
-    .collect::<Vec<String>>();
+        |entry| { return !entry.file_name().to_string_lossy().contains("/src/ui/styles"); }
+    )?;
     let output_style = match vec!["production", "demo"].contains(&environment) {
         true => sass_rs::OutputStyle::Compressed,
         false => sass_rs::OutputStyle::Expanded
     };
 
-    all_styles.append(&mut component_styles);
+    style_files.append(&mut component_style_files);
+
+    let styles = read_styles(&style_files, &config.project_root)?;
 
-    fs::write(&output_path, sass_rs::compile_string(&all_styles.join("\n"), sass_rs::Options {
-        output_style: output_style, precision: 5, indented_syntax: false,
-        include_paths: vec![format!("{}/src/ui/styles", project_root)]
-    })?)?;
+    write_styles(config, &output_path, &styles, output_style, format!("{}/src/ui/styles", project_root))?;
 
     // TODO: in future create a thread global build error to say/stop tts on error
 
-    let output_metadata = fs::metadata(output_path)?;
+    let output_metadata = fs::metadata(&output_path).map_err(MberError::io("could not read", &output_path))?;
     let message = format!(
         "{} application.css in {} [{}] Environment: {}",
         Paint::green("BUILT:"),
@@ -55,8 +54,148 @@ pub fn build(config: &Config) -> Result<(String, fs::Metadata), Box<dyn Error>>
     return Ok((message, output_metadata));
 }
 
+// NOTE: returns (relative_path, content) pairs in the order they get compiled
+pub fn read_styles(files: &[PathBuf], project_root: &Path) -> Result<Vec<(String, String)>, MberError> {
+    return files.iter()
+        .map(|file| -> Result<(String, String), MberError> {
+            let source = file.strip_prefix(project_root).unwrap_or(file).to_string_lossy().replace('\\', "/");
+
+            return Ok((source, fs::read_to_string(file).map_err(MberError::io("could not read", file))?));
+        })
+        .collect();
+}
+
+pub fn write_styles(
+    config: &Config, output_path: &Path, styles: &[(String, String)], output_style: sass_rs::OutputStyle, include_path: String
+) -> Result<(), MberError> {
+    let file_name = output_path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    let (code, map) = compile(styles, output_style, include_path, &file_name)?;
+
+    if !super::should_write_source_maps(config) {
+        fs::remove_file(format!("{}.map", output_path.display())).unwrap_or_else(|_| {});
+
+        return fs::write(output_path, code).map_err(MberError::io("could not write", output_path));
+    }
+
+    let map_path = format!("{}.map", output_path.display());
+
+    fs::write(&map_path, map.to_json()).map_err(MberError::io("could not write", &map_path))?;
+
+    return fs::write(output_path, format!("{}\n/*# sourceMappingURL={}.map */\n", code.trim_end(), file_name))
+        .map_err(MberError::io("could not write", output_path));
+}
+
+// NOTE: sass_rs has no source map option. Each file gets a loud marker comment that survives every output style,
+// the rules after a marker are mapped to the line of their selector in that file, then the markers are removed
+pub fn compile(
+    styles: &[(String, String)], output_style: sass_rs::OutputStyle, include_path: String, file_name: &str
+) -> Result<(String, SourceMap), MberError> {
+    let input = styles.iter().enumerate()
+        .map(|(index, (_, content))| format!("{}{} */\n{}", SOURCE_MARKER, index, content))
+        .collect::<Vec<String>>()
+        .join("\n");
+    let output = sass_rs::compile_string(&input, sass_rs::Options {
+        output_style: output_style, precision: 5, indented_syntax: false, include_paths: vec![include_path]
+    }).map_err(|message| MberError::Sass { path: PathBuf::from(find_failing_style(styles, &message)), message: message })?;
+    let mut regions = output.split(SOURCE_MARKER);
+    let mut code = regions.next().unwrap_or("").to_string();
+    let mut map = SourceMap::new(file_name);
+    let mut position = source_map::advance((0, 0), &code);
+
+    for region in regions {
+        let marker_end = region.find(" */")
+            .ok_or_else(|| MberError::build(file_name, "compiled css has an unterminated source marker"))?;
+        let (source, content) = region[..marker_end].parse::<usize>().ok().and_then(|index| styles.get(index))
+            .ok_or_else(|| MberError::build(file_name, "compiled css has an invalid source marker"))?;
+        let region = region[marker_end + 3..].strip_prefix('\n').unwrap_or(&region[marker_end + 3..]);
+        let source_index = map.add_source(source, Some(content));
+        let (mut cursor, mut original_position, mut mapped_until) = (0, (0, 0), 0);
+        let mut is_rule_start = true;
+
+        for (index, character) in region.char_indices() {
+            if character == '{' || character == '}' {
+                is_rule_start = true;
+            } else if is_rule_start && !character.is_whitespace() {
+                is_rule_start = false;
+
+                let rule_end = region[index..].find(|character| character == '{' || character == '}' || character == ';');
+
+                if let Some(rule_end) = rule_end.filter(|rule_end| region[index + rule_end..].starts_with('{')) {
+                    original_position = find_selector(content, &region[index..index + rule_end], &mut cursor)
+                        .unwrap_or(original_position);
+                    position = source_map::advance(position, &region[mapped_until..index]);
+                    mapped_until = index;
+                    map.mappings.push(Mapping {
+                        generated_line: position.0,
+                        generated_column: position.1,
+                        source: source_index,
+                        original_line: original_position.0,
+                        original_column: original_position.1,
+                        name: None
+                    });
+                }
+            }
+        }
+
+        position = source_map::advance(position, &region[mapped_until..]);
+        code.push_str(region);
+    }
+
+    return Ok((code, map));
+}
+
+// NOTE: sass reports lines of the joined input, every style takes its marker line, its content and the separator
+fn find_failing_style<'a>(styles: &'a [(String, String)], message: &str) -> &'a str {
+    let line = Regex::new(r"line (\d+)").unwrap().captures(message)
+        .and_then(|captures| captures[1].parse::<usize>().ok())
+        .unwrap_or(1);
+    let mut last_line = 0;
+
+    for (source, content) in styles {
+        last_line += content.lines().count() + 1;
+
+        if line <= last_line {
+            return source;
+        }
+    }
+
+    return styles.last().map_or("", |(source, _)| source.as_str());
+}
+
+// NOTE: nested scss rules compile to longer selectors, so the search falls back to the last compound selector
+// and then to its leading part. Returns the 0 based (line, column) of the match
+fn find_selector(content: &str, selector: &str, cursor: &mut usize) -> Option<(usize, usize)> {
+    let selector = selector.trim();
+    let candidates = match selector.starts_with('@') {
+        true => vec![selector.split(|character: char| character.is_whitespace() || character == '(').next().unwrap_or(selector)],
+        false => {
+            let first_selector = selector.split(',').next().unwrap_or(selector).trim();
+            let compound = first_selector.rsplit(|character: char| character.is_whitespace() || ">+~".contains(character))
+                .find(|part| !part.is_empty())
+                .unwrap_or(first_selector);
+
+            vec![first_selector, compound, compound.split(|character| character == ':' || character == '[').next().unwrap_or(compound)]
+        }
+    };
+
+    for candidate in candidates.into_iter().filter(|candidate| !candidate.is_empty()) {
+        let found = content[*cursor..].find(candidate).map(|index| index + *cursor).or_else(|| content.find(candidate));
+
+        if let Some(index) = found {
+            let line_start = content[..index].rfind('\n').map_or(0, |line_end| line_end + 1);
+
+            *cursor = index;
+
+            return Some((content[..index].matches('\n').count(), content[line_start..index].chars().count()));
+        }
+    }
+
+    return None;
+}
+
 #[cfg(test)]
 mod tests {
+    use std::error::Error;
     use std::env;
     use super::*;
     use std::path::PathBuf;
@@ -100,7 +239,7 @@ mod tests {
             json!({ "environment": "development", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
         let (message, _stats) = build(&config)?;
         let build_time_in_ms = Regex::new(r"application\.css in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
@@ -125,7 +264,7 @@ mod tests {
             json!({ "environment": "production", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
         let (message, _stats) = build(&config)?;
         let build_time_in_ms = Regex::new(r"application\.css in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
@@ -150,7 +289,7 @@ mod tests {
             json!({ "environment": "custom", "modulePrefix": "my-app" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
         let (message, _stats) = build(&config)?;
         let build_time_in_ms = Regex::new(r"application\.css in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
@@ -175,7 +314,7 @@ mod tests {
             json!({ "environment": "development", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
 
         build(&config)?;
 
@@ -186,4 +325,32 @@ mod tests {
 
         return finalize_test(current_directory);
     }
+
+    #[test]
+    fn compile_maps_rules_to_their_scss_files() -> Result<(), Box<dyn Error>> {
+        let styles = vec![
+            (String::from("src/ui/styles/application.scss"), String::from("$color: red;\n\nbody {\n  color: $color;\n  .title:hover { color: blue; }\n}\n")),
+            (String::from("src/ui/components/x-button/styles.scss"), String::from("// button\n.x-button,\n.other > .button { margin: 0; }\n"))
+        ];
+
+        for output_style in vec![sass_rs::OutputStyle::Expanded, sass_rs::OutputStyle::Compressed] {
+            let (code, map) = compile(&styles, output_style, String::from("."), "application.css")?;
+            let original_position_of = |selector: &str| {
+                let index = code.find(selector).unwrap();
+                let position = source_map::advance((0, 0), &code[..index]);
+
+                return map.mappings.iter()
+                    .find(|mapping| (mapping.generated_line, mapping.generated_column) == position)
+                    .map(|mapping| (map.sources[mapping.source].as_str(), mapping.original_line, mapping.original_column));
+            };
+
+            assert!(!code.contains("mber-source"));
+            assert_eq!(map.sources, vec!["src/ui/styles/application.scss", "src/ui/components/x-button/styles.scss"]);
+            assert_eq!(original_position_of("body"), Some(("src/ui/styles/application.scss", 2, 0)));
+            assert_eq!(original_position_of("body .title"), Some(("src/ui/styles/application.scss", 4, 2)));
+            assert_eq!(original_position_of(".x-button"), Some(("src/ui/components/x-button/styles.scss", 1, 0)));
+        }
+
+        return Ok(());
+    }
 }
@@ -1,32 +1,92 @@
 use std::time::Instant;
 use std::fs;
-use std::collections::HashMap;
-use std::error::Error;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::{Path, PathBuf};
 use yansi::Paint;
-use md5;
+use rayon::prelude::*;
+use regex::Regex;
+use walkdir::WalkDir;
 use serde_json;
 use serde_json::{Map, json, Value};
 use select::document::Document;
-use super::{fastboot_package_json};
+use super::{budgets, build_stats, fastboot_package_json};
 use super::super::utils::{console, file, html_file, project};
-use super::super::types::Config;
+use super::super::utils::digest::{self, Algorithm};
+use super::super::types::{Config, MberError};
+
+const DEFAULT_DIGEST_LENGTH: usize = 32;
+const ENVIRONMENT_CONFIG_PATH: &str = "config/environment.js";
+const STAGING_FOLDER_NAME: &str = ".dist-tmp";
+const REPLACED_FOLDER_NAME: &str = ".dist-replaced";
+const PREVIOUS_BUILDS_FOLDER_NAME: &str = "dist-previous";
+const COMPRESSED_EXTENSIONS: [&str; 4] = ["js", "css", "html", "svg"];
+
+struct FingerprintOptions {
+    enabled: bool,
+    algorithm: Algorithm,
+    digest_length: usize,
+    exclude: Vec<Regex>
+}
+
+struct IntegrityOptions {
+    enabled: bool,
+    algorithm: Algorithm,
+    crossorigin: String
+}
+
+struct CompressionOptions {
+    gzip_level: Option<u32>,
+    brotli_level: Option<u32>
+}
 
 // TODO: where is the documentation?? Add it here? Or at least test it
-pub fn build(config: &Config) -> Result<(String, Vec<Value>), Box<dyn Error>> {
+// NOTE: the build is written to a staging folder next to dist and only replaces dist when every step and budget succeeds
+pub fn build(config: &Config) -> Result<(String, Vec<Value>), MberError> {
+    let output_directory = format!("{}/dist", &config.project_root.display());
+    let staging_directory = format!("{}/{}", &config.project_root.display(), STAGING_FOLDER_NAME);
+    let previous_stats = match &config.cli_arguments.compare {
+        Some(compared_stats_path) => Some(build_stats::read(&PathBuf::from(compared_stats_path))?.ok_or_else(|| {
+            return MberError::config(compared_stats_path, format!("--compare file {} does not exist", compared_stats_path));
+        })?),
+        None => build_stats::read(&PathBuf::from(format!("{}/{}", &output_directory, build_stats::STATS_FILE_NAME)))
+            .unwrap_or(None)
+    };
+
+    reset_output_folder(&staging_directory.as_str())?;
+
+    let result = build_in_folder(config, STAGING_FOLDER_NAME, previous_stats).and_then(|result| {
+        budgets::check(config, &result.1)?;
+        swap_output_folder(&config.project_root, &PathBuf::from(&staging_directory), config.cli_arguments.keep_previous)?;
+
+        return Ok(result);
+    });
+
+    if result.is_err() {
+        fs::remove_dir_all(&staging_directory).unwrap_or_else(|_| {});
+    }
+
+    return result;
+}
+
+fn build_in_folder(
+    config: &Config, output_folder_name: &str, previous_stats: Option<Vec<Value>>
+) -> Result<(String, Vec<Value>), MberError> {
     console::log(format!("{} {}...", Paint::yellow("BUNDLING:"), config.application_name));
 
     let bundle_start = Instant::now();
     let environment = config.env["environment"].as_str().unwrap_or("development");
     let project_root = &config.project_root.display();
-    let output_directory = format!("{}/dist", &project_root);
+    let output_directory = format!("{}/{}", &project_root, output_folder_name);
     let should_build_tests = (environment != "production") && config.cli_arguments.testing;
     let should_build_documentation = config.env["documentation"]["enabled"].as_bool().unwrap_or(false);
-
-    reset_output_folder(&output_directory.as_str())?;
+    let fingerprint_options = read_fingerprint_options(&config.env["fingerprint"])?;
+    let integrity_options = read_integrity_options(&config.env["SRI"], environment)?;
+    let compression_options = read_compression_options(&config.env["compression"], environment)?;
+    let stats_path = PathBuf::from(format!("{}/{}", &output_directory, build_stats::STATS_FILE_NAME));
 
     let mut build_files: Vec<String> = Vec::new();
-    let index_html_path_tuple = (format!("{}/tmp/index.html", &project_root), format!("{}/dist/index.html", &project_root));
+    let index_html_path_tuple = (format!("{}/tmp/index.html", &project_root), format!("{}/index.html", &output_directory));
 
     build_files.extend(get_build_files_from_html(&index_html_path_tuple.0.as_str())?);
 
@@ -34,7 +94,7 @@ pub fn build(config: &Config) -> Result<(String, Vec<Value>), Box<dyn Error>> {
 
     if should_build_tests {
         let tests_html_path_tuple = (
-            format!("{}/tmp/tests.html", &project_root), format!("{}/dist/tests.html", &project_root)
+            format!("{}/tmp/tests.html", &project_root), format!("{}/tests.html", &output_directory)
         );
 
         build_files.extend(get_build_files_from_html(&tests_html_path_tuple.0.as_str())?);
@@ -45,7 +105,7 @@ pub fn build(config: &Config) -> Result<(String, Vec<Value>), Box<dyn Error>> {
         let documentation_path_in_config = config.env["documentation"]["path"].as_str().unwrap_or("/styleguide");
         let documentation_html_path_tuple = (
             format!("{}/tmp{}.html", &config.project_root.display(), documentation_path_in_config),
-            format!("{}/dist{}.html", &project_root, documentation_path_in_config)
+            format!("{}{}.html", &output_directory, documentation_path_in_config)
         );
 
         build_files.extend(get_build_files_from_html(&documentation_html_path_tuple.0.as_str())?);
@@ -55,26 +115,59 @@ pub fn build(config: &Config) -> Result<(String, Vec<Value>), Box<dyn Error>> {
     build_files.sort();
     build_files.dedup();
 
-    let target_asset_map = build_files.iter().fold(HashMap::new(), |mut result, file_name| {
-        let content = fs::read_to_string(format!("{}/tmp{}", &project_root, &file_name)).unwrap();
+    let should_include_source_maps = super::should_write_source_maps(config);
+    let source_maps = build_files.iter().try_fold(BTreeMap::new(), |mut result, file_name| -> Result<_, MberError> {
+        let source_map_path = format!("{}/tmp{}.map", &project_root, &file_name);
 
-        result.insert(file_name, content);
+        if should_include_source_maps && fs::metadata(&source_map_path).is_ok() {
+            result.insert(file_name, fs::read_to_string(&source_map_path).map_err(MberError::io("could not read", &source_map_path))?);
+        }
 
-        return result;
-    });
-    let hashed_file_name_map = build_hashed_filename_map(&target_asset_map);
+        return Ok(result);
+    })?;
+    let target_asset_map = build_files.iter().try_fold(BTreeMap::new(), |mut result, file_name| -> Result<_, MberError> {
+        let asset_path = format!("{}/tmp{}", &project_root, &file_name);
+        let content = fs::read_to_string(&asset_path).map_err(MberError::io("could not read", &asset_path))?;
+
+        result.insert(file_name, strip_source_mapping_url(&content, &file_name).to_string());
+
+        return Ok(result);
+    })?;
+    let hashed_file_name_map = build_hashed_filename_map(&target_asset_map.iter().map(|(file_name, content)| {
+        return (*file_name, match source_maps.get(file_name) {
+            Some(source_map) => format!("{}{}", content, source_map),
+            None => content.clone()
+        });
+    }).collect(), &fingerprint_options);
+    let target_asset_map = add_source_mapping_urls(target_asset_map, &source_maps, &hashed_file_name_map);
+    let integrities = match integrity_options.enabled {
+        true => build_integrity_map(&target_asset_map, &hashed_file_name_map, integrity_options.algorithm),
+        false => HashMap::new()
+    };
 
     project::recursively_copy_folder(format!("{}/public", &project_root), &output_directory)?;
-    safe_write_html_and_assets(&output_directory, build_html_paths, &hashed_file_name_map, &target_asset_map)?;
+    safe_write_html_and_assets(
+        &output_directory, build_html_paths, &hashed_file_name_map, &target_asset_map, &integrities, &integrity_options.crossorigin
+    )?;
+    source_maps.iter().try_for_each(|(file_name, source_map)| {
+        let source_map_path = format!("{}/{}.map", &output_directory, hashed_file_name_map[file_name]);
 
-    let target_map_json = build_file_map_with_asset_map(hashed_file_name_map);
+        return fs::write(&source_map_path, source_map).map_err(MberError::io("could not write", &source_map_path));
+    })?;
 
-    write_asset_map(&config.project_root, &target_map_json)?;
+    let asset_names: HashMap<String, String> = hashed_file_name_map.iter()
+        .map(|(file_name, hashed_file_name)| (hashed_file_name[1..].to_string(), file_name[1..].to_string()))
+        .collect();
+    let target_map_json = build_file_map_with_asset_map(hashed_file_name_map, &source_maps);
+
+    write_asset_map(&output_directory, &target_map_json)?;
 
     if config.cli_arguments.fastboot {
-        fastboot_package_json::build(target_map_json, config, Some("dist"))?;
+        fastboot_package_json::build(target_map_json, config, Some(output_folder_name))?;
     }
 
+    let compressed_file_sizes = write_compressed_files(&output_directory, &compression_options)?;
+
     // TODO: in future create a thread global build error to say/stop tts on error
 
     let build_message = format!(
@@ -87,123 +180,397 @@ pub fn build(config: &Config) -> Result<(String, Vec<Value>), Box<dyn Error>> {
     console::log(&build_message);
     console::log(Paint::green("Built project successfully. Stored in \"./dist\":"));
 
-    let dist_assets = fs::read_dir(format!("{}/assets", output_directory))?;
-    let output_metadata = dist_assets.fold(Vec::new(), |mut result, entry| {
-        let target_entry = entry.unwrap();
-        let file_name = String::from(target_entry.file_name().to_str().unwrap());
+    let dist_assets_path = format!("{}/assets", output_directory);
+    let mut dist_assets = fs::read_dir(&dist_assets_path)
+        .and_then(|entries| entries.collect::<io::Result<Vec<fs::DirEntry>>>())
+        .map_err(MberError::io("could not read", &dist_assets_path))?;
+
+    dist_assets.sort_by_key(|entry| entry.file_name());
+
+    let output_metadata = dist_assets.iter().try_fold(Vec::new(), |mut result, target_entry| -> Result<_, MberError> {
+        let file_name = target_entry.file_name().to_string_lossy().to_string();
 
         if file_name.ends_with(".js") || file_name.ends_with(".css") {
-            let file_size = target_entry.metadata().unwrap().len();
-            let gzip_size = file::gzip_metadata(&target_entry.path()).unwrap();
+            let file_size = target_entry.metadata().map_err(MberError::io("could not read", target_entry.path()))?.len();
+            let (gzip_size, brotli_size) = match compressed_file_sizes.get(&target_entry.path()) {
+                Some((Some(gzip_size), brotli_size)) => (*gzip_size, *brotli_size),
+                Some((None, brotli_size)) => (file::gzip_metadata(&target_entry.path())?, *brotli_size),
+                None => (file::gzip_metadata(&target_entry.path())?, None)
+            };
+            let asset_path = format!("assets/{}", &file_name);
+            let asset_name = asset_names.get(&asset_path).unwrap_or(&asset_path);
             let file_metadata = json!({
                 "file_name": file_name,
+                "asset": asset_name,
                 "size": file_size,
-                "gzip_size": gzip_size
+                "gzip_size": gzip_size,
+                "brotli_size": brotli_size
             });
+            let brotli_report = brotli_size.map_or(String::new(), |size| {
+                return format!(" {}", Paint::green(format!("[{} brotli]", file::format_size(size as u64))));
+            });
+
+            let delta_report = match previous_stats.is_some() {
+                true => format!(" {}", build_stats::format_delta(
+                    &file_metadata, build_stats::find_asset(&previous_stats, asset_name)
+                )),
+                false => String::new()
+            };
 
             println!(
-                "{} {} {}",
-                Paint::blue(format!(" - {}:", file_metadata["file_name"].as_str().unwrap())),
+                "{} {} {}{}{}",
+                Paint::blue(format!(" - {}:", file_name)),
                 Paint::yellow(file::format_size(file_size)),
-                Paint::green(format!("[{} gzipped]", file::format_size(gzip_size as u64)))
+                Paint::green(format!("[{} gzipped]", file::format_size(gzip_size as u64))),
+                brotli_report,
+                delta_report
             );
 
             result.push(file_metadata);
         }
 
-        return result;
+        return Ok(result);
+    })?;
+
+    build_stats::removed_assets(&previous_stats, &output_metadata).iter().for_each(|removed_asset| {
+        println!(
+            "{} {}",
+            Paint::blue(format!(" - {}:", removed_asset["asset"].as_str().unwrap_or(""))),
+            Paint::green(format!("removed (-{})", file::format_size(removed_asset["size"].as_u64().unwrap_or(0))))
+        );
     });
+    build_stats::write(&stats_path, environment, config.build_timestamp, &output_metadata)?;
 
     return Ok((build_message, output_metadata));
 }
 
-fn reset_output_folder(output_directory: &str) -> Result<(), Box<dyn Error>> {
+fn reset_output_folder(output_directory: &str) -> Result<(), MberError> {
+    let assets_directory = format!("{}/assets", output_directory);
+
     fs::remove_dir_all(output_directory).unwrap_or_else(|_| {});
 
-    return Ok(fs::create_dir_all(format!("{}/assets", output_directory).as_str())?);
+    return fs::create_dir_all(&assets_directory).map_err(MberError::io("could not create", &assets_directory));
+}
+
+// NOTE: the staged build replaces dist with two renames, the current dist is moved aside first. With --keep-previous=<count>
+// it is moved to dist-previous/<n> and the newest <count> of them are kept, otherwise it is removed after the swap and
+// older previous builds are left untouched
+fn swap_output_folder(project_root: &Path, staging_directory: &Path, keep_previous: Option<usize>) -> Result<(), MberError> {
+    let output_directory = project_root.join("dist");
+    let previous_builds_directory = project_root.join(PREVIOUS_BUILDS_FOLDER_NAME);
+    let replaced_directory = match keep_previous {
+        Some(_) => previous_builds_directory.join(
+            list_previous_builds(&previous_builds_directory)?.last().map_or(1, |number| number + 1).to_string()
+        ),
+        None => project_root.join(REPLACED_FOLDER_NAME)
+    };
+    let has_output = output_directory.exists();
+
+    if has_output {
+        if keep_previous.is_some() {
+            fs::create_dir_all(&previous_builds_directory).map_err(MberError::io("could not create", &previous_builds_directory))?;
+        }
+
+        fs::remove_dir_all(&replaced_directory).unwrap_or_else(|_| {}); // NOTE: left over from an interrupted swap
+        fs::rename(&output_directory, &replaced_directory).map_err(MberError::io("could not move away", &output_directory))?;
+    }
+
+    fs::rename(staging_directory, &output_directory).map_err(|error| {
+        if has_output {
+            fs::rename(&replaced_directory, &output_directory).unwrap_or_else(|_| {});
+        }
+
+        return MberError::io("could not move the build to", &output_directory)(error);
+    })?;
+
+    if has_output && keep_previous.is_none() {
+        fs::remove_dir_all(&replaced_directory).map_err(MberError::io("could not remove", &replaced_directory))?;
+    }
+
+    if let Some(count) = keep_previous {
+        let previous_builds = list_previous_builds(&previous_builds_directory)?;
+
+        previous_builds[..previous_builds.len().saturating_sub(count)].iter()
+            .map(|number| previous_builds_directory.join(number.to_string()))
+            .try_for_each(|directory| fs::remove_dir_all(&directory).map_err(MberError::io("could not remove", &directory)))?;
+
+        if list_previous_builds(&previous_builds_directory)?.is_empty() {
+            fs::remove_dir(&previous_builds_directory).unwrap_or_else(|_| {});
+        }
+    }
+
+    return Ok(());
 }
 
-fn get_build_files_from_html(html_path: &str) -> Result<Vec<String>, Box<dyn Error>> {
-    let html = fs::read_to_string(&html_path)?;
+fn list_previous_builds(previous_builds_directory: &Path) -> Result<Vec<usize>, MberError> {
+    if !previous_builds_directory.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut build_numbers = fs::read_dir(previous_builds_directory)
+        .map_err(MberError::io("could not read", previous_builds_directory))?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.file_name().to_str().and_then(|name| name.parse::<usize>().ok()))
+        .collect::<Vec<usize>>();
+
+    build_numbers.sort();
+
+    return Ok(build_numbers);
+}
+
+fn get_build_files_from_html(html_path: &str) -> Result<Vec<String>, MberError> {
+    let html = fs::read_to_string(&html_path).map_err(MberError::io("could not read", html_path))?;
     let html_document = Document::from(html.as_str());
     let (html_js_files, html_css_files) = html_file::find_internal_assets_from_html(&html_document);
 
     return Ok(html_js_files.into_iter().chain(html_css_files.into_iter()).collect());
 }
 
-fn build_hashed_filename_map<'a>(asset_map: &'a HashMap<&String, String>) -> HashMap<&'a String, String> {
-    return asset_map.iter().fold(HashMap::new(), |mut result, (file_name, content)| {
-        let hash = format!("{:?}", md5::compute(content));
+// NOTE: builders end bundles with a sourceMappingURL comment to <bundle>.map, other comments like the ones in
+// vendor files are kept as they are
+fn strip_source_mapping_url<'a>(content: &'a str, file_name: &str) -> &'a str {
+    let trimmed_content = content.trim_end();
+    let line_start = trimmed_content.rfind('\n').map_or(0, |line_end| line_end + 1);
+    let last_line = &trimmed_content[line_start..];
+    let source_map_name = format!("sourceMappingURL={}.map", file_name.rsplit('/').next().unwrap_or(file_name));
+
+    if (last_line.starts_with("//# ") || last_line.starts_with("/*# ")) && last_line.contains(&source_map_name) {
+        return &trimmed_content[..line_start.saturating_sub(1)];
+    }
+
+    return content;
+}
+
+// NOTE: assets with a source map get fingerprinted together with their map, so the comment points to <hashed asset>.map
+fn add_source_mapping_urls<'a>(
+    asset_map: BTreeMap<&'a String, String>, source_maps: &BTreeMap<&String, String>, hashed_file_names: &BTreeMap<&String, String>
+) -> BTreeMap<&'a String, String> {
+    return asset_map.into_iter().map(|(file_name, content)| {
+        if !source_maps.contains_key(file_name) {
+            return (file_name, content);
+        }
+
+        let source_map_name = format!("{}.map", hashed_file_names[file_name].rsplit('/').next().unwrap_or(""));
+        let content = match file_name.ends_with(".css") {
+            true => format!("{}\n/*# sourceMappingURL={} */\n", content, source_map_name),
+            false => format!("{}\n//# sourceMappingURL={}\n", content, source_map_name)
+        };
+
+        return (file_name, content);
+    }).collect();
+}
+
+// NOTE: fingerprint: { enabled, algorithm: md5|sha256|sha384, digestLength, exclude: ["vendor", "assets/*.css"] } in the env config
+fn read_fingerprint_options(options: &Value) -> Result<FingerprintOptions, MberError> {
+    let algorithm = options["algorithm"].as_str().unwrap_or("md5");
+    let algorithm = Algorithm::parse(algorithm).ok_or_else(|| {
+        return MberError::config(ENVIRONMENT_CONFIG_PATH, format!("fingerprint.algorithm should be md5, sha256 or sha384, got: {}", algorithm));
+    })?;
+    let digest_length = options["digestLength"].as_u64().map_or(DEFAULT_DIGEST_LENGTH, |length| length as usize);
+    let exclude = options["exclude"].as_array().map_or(Vec::new(), |patterns| {
+        return patterns.iter().filter_map(|pattern| pattern.as_str()).map(|pattern| {
+            return Regex::new(&regex::escape(pattern).replace(r"\*", "[^/]*")).unwrap();
+        }).collect();
+    });
+
+    if digest_length == 0 {
+        return Err(MberError::config(ENVIRONMENT_CONFIG_PATH, "fingerprint.digestLength should be greater than 0"));
+    }
+
+    return Ok(FingerprintOptions {
+        enabled: options["enabled"].as_bool().unwrap_or(true),
+        algorithm: algorithm,
+        digest_length: digest_length,
+        exclude: exclude
+    });
+}
+
+// NOTE: SRI: { enabled, algorithm: sha256|sha384, crossorigin } in the env config, enabled by default in production
+fn read_integrity_options(options: &Value, environment: &str) -> Result<IntegrityOptions, MberError> {
+    let algorithm = options["algorithm"].as_str().unwrap_or("sha384");
+    let algorithm = match Algorithm::parse(algorithm) {
+        Some(Algorithm::Md5) | None => return Err(MberError::config(
+            ENVIRONMENT_CONFIG_PATH, format!("SRI.algorithm should be sha256 or sha384, got: {}", algorithm)
+        )),
+        Some(algorithm) => algorithm
+    };
+
+    return Ok(IntegrityOptions {
+        enabled: options["enabled"].as_bool().unwrap_or(environment == "production"),
+        algorithm: algorithm,
+        crossorigin: options["crossorigin"].as_str().unwrap_or("anonymous").to_string()
+    });
+}
+
+// NOTE: compression: { enabled, gzip: { enabled, level: 0-9 }, brotli: { enabled, level: 0-11 } } in the env config,
+// enabled by default for the minified environments
+fn read_compression_options(options: &Value, environment: &str) -> Result<CompressionOptions, MberError> {
+    let is_enabled = options["enabled"].as_bool().unwrap_or(vec!["production", "demo"].contains(&environment));
+
+    return Ok(CompressionOptions {
+        gzip_level: read_compression_level(&options["gzip"], "gzip", file::DEFAULT_GZIP_LEVEL, 9)?.filter(|_| is_enabled),
+        brotli_level: read_compression_level(&options["brotli"], "brotli", file::DEFAULT_BROTLI_LEVEL, 11)?.filter(|_| is_enabled)
+    });
+}
+
+fn read_compression_level(options: &Value, name: &str, default_level: u32, max_level: u64) -> Result<Option<u32>, MberError> {
+    let level = options["level"].as_u64().unwrap_or(default_level as u64);
+
+    if level > max_level {
+        return Err(MberError::config(
+            ENVIRONMENT_CONFIG_PATH, format!("compression.{}.level should be between 0 and {}, got: {}", name, max_level, level)
+        ));
+    } else if !options["enabled"].as_bool().unwrap_or(true) {
+        return Ok(None);
+    }
+
+    return Ok(Some(level as u32));
+}
+
+// NOTE: writes <file>.gz and <file>.br next to every js, css, html and svg file in dist. Returns the compressed sizes
+fn write_compressed_files(
+    output_directory: &str, options: &CompressionOptions
+) -> Result<HashMap<PathBuf, (Option<usize>, Option<usize>)>, MberError> {
+    if options.gzip_level.is_none() && options.brotli_level.is_none() {
+        return Ok(HashMap::new());
+    }
+
+    let files = WalkDir::new(output_directory).into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            return entry.file_type().is_file() && entry.path().extension()
+                .map_or(false, |extension| COMPRESSED_EXTENSIONS.contains(&extension.to_str().unwrap_or("")));
+        })
+        .map(|entry| entry.into_path())
+        .collect::<Vec<PathBuf>>();
+
+    return files.par_iter().map(|path| -> Result<(PathBuf, (Option<usize>, Option<usize>)), MberError> {
+        let content = fs::read(path).map_err(MberError::io("could not read", path))?;
+        let write_compressed_file = |extension: &str, compressed_content: io::Result<Vec<u8>>| -> Result<usize, MberError> {
+            let compressed_path = format!("{}.{}", path.display(), extension);
+            let compressed_content = compressed_content.map_err(MberError::io("could not compress", path))?;
+
+            fs::write(&compressed_path, &compressed_content).map_err(MberError::io("could not write", &compressed_path))?;
+
+            return Ok(compressed_content.len());
+        };
+        let gzip_size = options.gzip_level
+            .map(|level| write_compressed_file("gz", file::gzip(&content, level)))
+            .transpose()?;
+        let brotli_size = options.brotli_level
+            .map(|level| write_compressed_file("br", file::brotli(&content, level)))
+            .transpose()?;
+
+        return Ok((path.clone(), (gzip_size, brotli_size)));
+    }).collect::<Result<HashMap<PathBuf, (Option<usize>, Option<usize>)>, MberError>>();
+}
+
+fn build_hashed_filename_map<'a>(
+    asset_map: &BTreeMap<&'a String, String>, options: &FingerprintOptions
+) -> BTreeMap<&'a String, String> {
+    return asset_map.iter().fold(BTreeMap::new(), |mut result, (file_name, content)| {
+        if !options.enabled || options.exclude.iter().any(|pattern| pattern.is_match(file_name)) {
+            result.insert(file_name, file_name.to_string());
+
+            return result;
+        }
+
+        let hash = digest::to_hex(&options.algorithm.digest(content.as_bytes()));
+        let hash = &hash[..options.digest_length.min(hash.len())];
         let file = PathBuf::from(file_name);
+        let extension = file.extension().map_or(String::new(), |extension| format!(".{}", extension.to_string_lossy()));
         let file_reference = file.iter().fold(String::new(), |mut result, path_component| {
-            if path_component == file.file_name().unwrap() {
-                result.push_str(format!("/{}", file.file_stem().unwrap().to_str().unwrap()).as_str());
+            if Some(path_component) == file.file_name() {
+                result.push_str(format!("/{}", file.file_stem().unwrap_or(path_component).to_string_lossy()).as_str());
             } else {
-                result.push_str(path_component.to_str().unwrap());
+                result.push_str(&path_component.to_string_lossy());
             }
 
             return result;
         });
 
-        result.insert(file_name, format!("{}-{}.{}", file_reference, hash, file.extension().unwrap().to_str().unwrap()));
+        result.insert(file_name, format!("{}-{}{}", file_reference, hash, extension));
 
         return result;
     });
 }
 
+// NOTE: integrities are computed from the final asset content, keyed by the hashed file name referenced in html
+fn build_integrity_map(
+    target_asset_map: &BTreeMap<&String, String>, hashed_file_names: &BTreeMap<&String, String>, algorithm: Algorithm
+) -> HashMap<String, String> {
+    return target_asset_map.iter().map(|(file_name, content)| {
+        let integrity = format!("{}-{}", algorithm.name(), digest::to_base64(&algorithm.digest(content.as_bytes())));
+
+        return (hashed_file_names[file_name].clone(), integrity);
+    }).collect();
+}
+
 fn safe_write_html_and_assets(
     output_directory: &String,
     html_path_tuples: Vec<(String, String)>,
-    hashed_file_names: &HashMap<&String, String>,
-    target_asset_map: &HashMap<&String, String>
-) -> Result<(), Box<dyn Error>> {
-    html_path_tuples.iter().for_each(|(html_path, target_dist_html_path)| {
-        let html_content = fs::read_to_string(&html_path).unwrap();
+    hashed_file_names: &BTreeMap<&String, String>,
+    target_asset_map: &BTreeMap<&String, String>,
+    integrities: &HashMap<String, String>,
+    crossorigin: &str
+) -> Result<(), MberError> {
+    html_path_tuples.iter().try_for_each(|(html_path, target_dist_html_path)| -> Result<(), MberError> {
+        let html_content = fs::read_to_string(&html_path).map_err(MberError::io("could not read", html_path))?;
         let target_content = hashed_file_names.iter().fold(html_content, |result, (file_name, hashed_file_name)| {
             return result.replace(file_name.as_str(), hashed_file_name.as_str());
         });
+        let target_content = match integrities.is_empty() {
+            true => target_content,
+            false => html_file::add_integrity_attributes(&target_content, integrities, crossorigin)
+        };
 
-        fs::write(target_dist_html_path, target_content).unwrap();
-    });
-    target_asset_map.iter().for_each(|(file_name, content)| {
-        let hashed_file_name = hashed_file_names.get(file_name).unwrap();
+        return fs::write(target_dist_html_path, target_content).map_err(MberError::io("could not write", target_dist_html_path));
+    })?;
 
-        fs::write(format!("{}/{}", &output_directory, hashed_file_name), content).unwrap();
-    });
+    return target_asset_map.iter().try_for_each(|(file_name, content)| {
+        let asset_path = format!("{}/{}", &output_directory, hashed_file_names[file_name]);
 
-    return Ok(());
+        return fs::write(&asset_path, content).map_err(MberError::io("could not write", &asset_path));
+    });
 }
 
-fn build_file_map_with_asset_map<'a>(hashed_file_name_map: HashMap<&'a String, String>) -> Value {
+fn build_file_map_with_asset_map<'a>(hashed_file_name_map: BTreeMap<&'a String, String>, source_maps: &BTreeMap<&String, String>) -> Value {
     let mut map = Map::new();
 
     hashed_file_name_map.iter().for_each(|(key, value)| {
         map.insert(key[1..].to_string(), Value::String(value[1..].to_string()));
+
+        if source_maps.contains_key(key) {
+            map.insert(format!("{}.map", &key[1..]), Value::String(format!("{}.map", &value[1..])));
+        }
     });
     map.insert("assets/assetMap.json".to_string(), Value::String("assets/assetMap.json".to_string()));
 
     return Value::Object(map);
 }
 
-fn write_asset_map(project_root: &PathBuf, hashed_file_names: &Value)
-    -> Result<(), Box<dyn Error>> {
-    fs::write(format!("{}/dist/assets/assetMap.json", project_root.display()), serde_json::to_string_pretty(&json!({
+fn write_asset_map(output_directory: &str, hashed_file_names: &Value)
+    -> Result<(), MberError> {
+    let asset_map_path = format!("{}/assets/assetMap.json", output_directory);
+
+    return fs::write(&asset_map_path, format!("{:#}", json!({
         "assets": hashed_file_names,
         "prepend": ""
-    })).unwrap())?;
-
-    return Ok(());
+    }))).map_err(MberError::io("could not write", &asset_map_path));
 }
 
 #[cfg(test)]
 mod tests {
     use std::env;
+    use std::error::Error;
     use super::*;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
     use serde_json::json;
     use std::collections::HashMap;
     use super::super::{build_all_assets};
     use super::super::super::types::BuildCache;
+    use super::super::super::transpilers::import_addon_folder_to_amd;
+    use regex::Regex;
 
     const TIME_TO_BUILD_DIST_THRESHOLD: u128 = 4000;
 
@@ -214,9 +581,16 @@ mod tests {
         }
     }
 
+    fn read_without_source_mapping_url(path: &str) -> String {
+        let content = fs::read_to_string(path).unwrap();
+        let file_name = PathBuf::from(path).file_name().unwrap().to_string_lossy().to_string();
+
+        return strip_source_mapping_url(&content, &file_name).to_string();
+    }
+
     fn setup_test() -> Result<(PathBuf, String, String), Box<dyn Error>> {
         let current_directory = env::current_dir()?;
-        let project_directory = format!("{}/ember-app-boilerplate", current_directory.to_string_lossy());
+        let project_directory = import_addon_folder_to_amd::test_project()?.to_string_lossy().to_string();
 
         env::set_current_dir(&project_directory)?;
 
@@ -231,6 +605,7 @@ mod tests {
     fn finalize_test(actual_current_directory: PathBuf) -> Result<(), Box<dyn Error>> {
         fs::remove_dir_all("tmp")?;
         fs::remove_dir_all("dist")?;
+        fs::remove_dir_all(STAGING_FOLDER_NAME).unwrap_or_else(|_| {});
         env::set_current_dir(&actual_current_directory)?;
 
         return Ok(());
@@ -242,11 +617,13 @@ mod tests {
 
         assert_eq!(fs::metadata(&output_directory).is_ok(), false);
 
-        let config = Config::build(
+        let mut config = Config::build(
             json!({ "environment": "development", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        ); // NOTE: testing: true must be there
+        )?;
+
+        config.cli_arguments.testing = true;
 
         build_all_assets(&config)?;
 
@@ -264,12 +641,12 @@ mod tests {
         });
 
         assert!(time_passed < TIME_TO_BUILD_DIST_THRESHOLD);
-        assert!(file_names.len() == 7);
+        assert_eq!(file_names.len(), 12);
 
         let target_index_html_assets = file_names.iter().filter(|file_name| {
             let target_file_name = file_name.to_str().unwrap().to_string();
 
-            return !target_file_name.contains("tests") && !target_file_name.contains("test-support");
+            return !target_file_name.contains("tests") && !target_file_name.contains("test-support") && !target_file_name.ends_with(".map");
         });
         let output_html = fs::read_to_string("dist/index.html")?;
         let output_test_html = fs::read_to_string("dist/tests.html")?;
@@ -287,16 +664,16 @@ mod tests {
             return result;
         });
         let file_contents = [
-            fs::read_to_string("tmp/assets/vendor.js")?,
-            fs::read_to_string("tmp/assets/application.css")?,
-            fs::read_to_string("tmp/assets/application.js")?,
-            fs::read_to_string("tmp/assets/test-support.css")?,
-            fs::read_to_string("tmp/assets/test-support.js")?,
-            fs::read_to_string("tmp/assets/tests.js")?
+            read_without_source_mapping_url("tmp/assets/vendor.js"),
+            read_without_source_mapping_url("tmp/assets/application.css"),
+            read_without_source_mapping_url("tmp/assets/application.js"),
+            read_without_source_mapping_url("tmp/assets/test-support.css"),
+            read_without_source_mapping_url("tmp/assets/test-support.js"),
+            read_without_source_mapping_url("tmp/assets/tests.js")
         ];
 
         dist_file_assets.iter().for_each(|dist_file| {
-            assert!(file_contents.contains(&fs::read_to_string(dist_file).unwrap()));
+            assert!(file_contents.contains(&read_without_source_mapping_url(dist_file)));
         });
         build_metadata_output.iter().for_each(|file| {
             let file_size = file["size"].as_u64().unwrap();
@@ -313,7 +690,7 @@ mod tests {
         let asset_map: Value = serde_json::from_str(fs::read_to_string("dist/assets/assetMap.json")?.as_str())?;
 
         assert_eq!(asset_map["prepend"], Value::String("".to_string()));
-        assert_eq!(asset_map["assets"].as_object().unwrap().len(), 7);
+        assert_eq!(asset_map["assets"].as_object().unwrap().len(), 12);
         assert_eq!(asset_map["assets"]["assets/assetMap.json"], Value::String("assets/assetMap.json".to_string()));
 
         let dist_files: Vec<String> = file_names.iter()
@@ -338,7 +715,7 @@ mod tests {
 
         assert_eq!(fs::metadata(&output_directory).is_ok(), false);
 
-        let config = Config::build(
+        let mut config = Config::build(
             json!({
                 "environment": "development",
                 "modulePrefix": "my-app",
@@ -347,7 +724,9 @@ mod tests {
             }),
             HashMap::new(),
             BuildCache::new()
-        ); // NOTE: testing: true must be there
+        )?;
+
+        config.cli_arguments.testing = true;
 
         build_all_assets(&config)?;
 
@@ -365,12 +744,12 @@ mod tests {
         });
 
         assert!(time_passed < TIME_TO_BUILD_DIST_THRESHOLD);
-        assert!(file_names.len() == 10);
+        assert_eq!(file_names.len(), 18);
 
         let target_index_html_assets = file_names.iter().filter(|file_name| {
             let target_file_name = file_name.to_str().unwrap().to_string();
 
-            return vec!["tests", "test-support", "documentation"].iter_mut()
+            return vec!["tests", "test-support", "documentation", ".map"].iter_mut()
                 .all(|name| !target_file_name.contains(&name.to_string()));
         });
         let output_html = fs::read_to_string("dist/index.html")?;
@@ -389,19 +768,19 @@ mod tests {
             return result;
         });
         let file_contents = [
-            fs::read_to_string("tmp/assets/application.css")?,
-            fs::read_to_string("tmp/assets/application.js")?,
-            fs::read_to_string("tmp/assets/documentation.css")?,
-            fs::read_to_string("tmp/assets/documentation.js")?,
-            fs::read_to_string("tmp/assets/memserver.js")?,
-            fs::read_to_string("tmp/assets/test-support.css")?,
-            fs::read_to_string("tmp/assets/test-support.js")?,
-            fs::read_to_string("tmp/assets/tests.js")?,
-            fs::read_to_string("tmp/assets/vendor.js")?
+            read_without_source_mapping_url("tmp/assets/application.css"),
+            read_without_source_mapping_url("tmp/assets/application.js"),
+            read_without_source_mapping_url("tmp/assets/documentation.css"),
+            read_without_source_mapping_url("tmp/assets/documentation.js"),
+            read_without_source_mapping_url("tmp/assets/memserver.js"),
+            read_without_source_mapping_url("tmp/assets/test-support.css"),
+            read_without_source_mapping_url("tmp/assets/test-support.js"),
+            read_without_source_mapping_url("tmp/assets/tests.js"),
+            read_without_source_mapping_url("tmp/assets/vendor.js")
         ];
 
         dist_file_assets.iter().for_each(|dist_file| {
-            assert!(file_contents.contains(&fs::read_to_string(dist_file).unwrap()));
+            assert!(file_contents.contains(&read_without_source_mapping_url(dist_file)));
         });
         build_metadata_output.iter().for_each(|file| {
             let file_size = file["size"].as_u64().unwrap();
@@ -418,7 +797,7 @@ mod tests {
         let asset_map: Value = serde_json::from_str(fs::read_to_string("dist/assets/assetMap.json")?.as_str())?;
 
         assert_eq!(asset_map["prepend"], Value::String("".to_string()));
-        assert_eq!(asset_map["assets"].as_object().unwrap().len(), 10);
+        assert_eq!(asset_map["assets"].as_object().unwrap().len(), 18);
         assert_eq!(asset_map["assets"]["assets/assetMap.json"], Value::String("assets/assetMap.json".to_string()));
 
         let dist_files: Vec<String> = file_names.iter()
@@ -447,7 +826,7 @@ mod tests {
             json!({ "environment": "production", "modulePrefix": "my-app" }),
             HashMap::new(),
             BuildCache::new()
-        ); // NOTE: testing: true must be there
+        )?;
 
         build_all_assets(&config)?;
 
@@ -465,13 +844,14 @@ mod tests {
         });
 
         assert!(time_passed < TIME_TO_BUILD_DIST_THRESHOLD);
-        assert!(file_names.len() == 4);
+        assert_eq!(file_names.len(), 13);
         assert_eq!(fs::metadata("dist/tests.html").is_ok(), false);
 
         let target_index_html_assets = file_names.iter().filter(|file_name| {
             let target_file_name = file_name.to_str().unwrap().to_string();
 
-            return !target_file_name.contains("tests") && !target_file_name.contains("test-support");
+            return !target_file_name.contains("tests") && !target_file_name.contains("test-support") &&
+                !target_file_name.ends_with(".map") && !target_file_name.ends_with(".gz") && !target_file_name.ends_with(".br");
         });
         let output_html = fs::read_to_string("dist/index.html")?;
 
@@ -488,30 +868,37 @@ mod tests {
             return result;
         });
         let file_contents = [
-            fs::read_to_string("tmp/assets/application.css")?,
-            fs::read_to_string("tmp/assets/application.js")?,
-            fs::read_to_string("tmp/assets/vendor.js")?
+            read_without_source_mapping_url("tmp/assets/application.css"),
+            read_without_source_mapping_url("tmp/assets/application.js"),
+            read_without_source_mapping_url("tmp/assets/vendor.js")
         ];
 
         dist_file_assets.iter().for_each(|dist_file| {
-            assert!(file_contents.contains(&fs::read_to_string(dist_file).unwrap()));
+            assert!(file_contents.contains(&read_without_source_mapping_url(dist_file)));
         });
         build_metadata_output.iter().for_each(|file| {
             let file_size = file["size"].as_u64().unwrap();
+            let file_path = format!("dist/assets/{}", file["file_name"].as_str().unwrap());
 
             assert!(file_size > 0);
+            assert_eq!(file["gzip_size"].as_u64().unwrap(), fs::metadata(format!("{}.gz", &file_path)).unwrap().len());
+            assert_eq!(file["brotli_size"].as_u64().unwrap(), fs::metadata(format!("{}.br", &file_path)).unwrap().len());
 
             if file_size > 1000 {
                 assert!(file["gzip_size"].as_u64().unwrap() < file_size);
+                assert!(file["brotli_size"].as_u64().unwrap() < file["gzip_size"].as_u64().unwrap());
             }
         });
 
         assert!(fs::metadata("dist/package.json").is_ok());
+        assert!(fs::metadata("dist/index.html.gz").is_ok());
+        assert!(build_metadata_output.iter().any(|file| file["asset"] == json!("assets/vendor.js")));
+        assert!(fs::metadata("dist/index.html.br").is_ok());
 
         let asset_map: Value = serde_json::from_str(fs::read_to_string("dist/assets/assetMap.json")?.as_str())?;
 
         assert_eq!(asset_map["prepend"], Value::String("".to_string()));
-        assert_eq!(asset_map["assets"].as_object().unwrap().len(), 4);
+        assert_eq!(asset_map["assets"].as_object().unwrap().len(), 7);
         assert_eq!(asset_map["assets"]["assets/assetMap.json"], Value::String("assets/assetMap.json".to_string()));
 
         let dist_files: Vec<String> = file_names.iter()
@@ -527,9 +914,204 @@ mod tests {
         assert!(!&dist_files.contains(&get_file_key(target_assets, "assets/test-support.css")));
         assert!(!&dist_files.contains(&get_file_key(target_assets, "assets/tests.js")));
 
+        let application_js_path = get_file_key(target_assets, "assets/application.js");
+        let application_css_path = get_file_key(target_assets, "assets/application.css");
+        let application_js_map: Value = serde_json::from_str(&fs::read_to_string(format!("dist{}.map", &application_js_path))?)?;
+
+        assert_eq!(get_file_key(target_assets, "assets/application.js.map"), format!("{}.map", &application_js_path));
+        assert_eq!(get_file_key(target_assets, "assets/application.css.map"), format!("{}.map", &application_css_path));
+        assert!(fs::read_to_string(format!("dist{}", &application_js_path))?.ends_with(&format!(
+            "\n//# sourceMappingURL={}.map\n", &application_js_path.replace("/assets/", "")
+        )));
+        assert!(fs::read_to_string(format!("dist{}", &application_css_path))?.ends_with(&format!(
+            "\n/*# sourceMappingURL={}.map */\n", &application_css_path.replace("/assets/", "")
+        )));
+        assert!(application_js_map["sources"].as_array().unwrap().contains(&json!("src/main.js")));
+
+        let application_js_integrity = format!(
+            "sha384-{}", digest::to_base64(&digest::sha384(&fs::read(format!("dist{}", &application_js_path))?))
+        );
+
+        assert!(output_html.contains(&format!(
+            "src=\"{}\" integrity=\"{}\" crossorigin=\"anonymous\"", &application_js_path, &application_js_integrity
+        )));
+        assert!(Regex::new(r#"href="/assets/application-[0-9a-f]{32}\.css" integrity="sha384-[A-Za-z0-9+/]{64}" crossorigin="anonymous""#)?
+            .is_match(&output_html));
+
         return finalize_test(actual_current_directory);
     }
 
+    #[test]
+    fn build_uses_fingerprint_and_sri_options_from_the_config() -> Result<(), Box<dyn Error>> {
+        let (actual_current_directory, _output_directory, _project_directory) = setup_test()?;
+        let config = Config::build(
+            json!({
+                "environment": "development",
+                "modulePrefix": "my-app",
+                "fingerprint": { "algorithm": "sha256", "digestLength": 12, "exclude": ["vendor*"] },
+                "SRI": { "enabled": true, "algorithm": "sha256", "crossorigin": "use-credentials" }
+            }),
+            HashMap::new(),
+            BuildCache::new()
+        )?;
+
+        build_all_assets(&config)?;
+        build(&config)?;
+
+        let asset_map: Value = serde_json::from_str(fs::read_to_string("dist/assets/assetMap.json")?.as_str())?;
+        let output_html = fs::read_to_string("dist/index.html")?;
+        let vendor_js_integrity = format!("sha256-{}", digest::to_base64(&digest::sha256(&fs::read("dist/assets/vendor.js")?)));
+
+        assert_eq!(get_file_key(&asset_map["assets"], "assets/vendor.js"), "/assets/vendor.js");
+        assert!(Regex::new(r"^/assets/application-[0-9a-f]{12}\.js$")?
+            .is_match(&get_file_key(&asset_map["assets"], "assets/application.js")));
+        assert!(output_html.contains(&format!(
+            "src=\"/assets/vendor.js\" integrity=\"{}\" crossorigin=\"use-credentials\"", vendor_js_integrity
+        )));
+
+        fs::remove_dir_all("dist")?;
+
+        let config = Config::build(
+            json!({ "environment": "production", "modulePrefix": "my-app", "fingerprint": { "enabled": false }, "SRI": { "enabled": false } }),
+            HashMap::new(),
+            BuildCache::new()
+        )?;
+
+        build(&config)?;
+
+        let output_html = fs::read_to_string("dist/index.html")?;
+
+        assert!(fs::metadata("dist/assets/application.js").is_ok());
+        assert!(output_html.contains("src=\"/assets/application.js\""));
+        assert!(!output_html.contains("integrity="));
+
+        let invalid_config = Config::build(
+            json!({ "environment": "production", "modulePrefix": "my-app", "SRI": { "algorithm": "md5" } }),
+            HashMap::new(),
+            BuildCache::new()
+        )?;
+
+        assert_eq!(build(&invalid_config).unwrap_err().to_string(), "SRI.algorithm should be sha256 or sha384, got: md5");
+
+        return finalize_test(actual_current_directory);
+    }
+
+    #[test]
+    fn build_leaves_source_maps_out_when_they_are_disabled() -> Result<(), Box<dyn Error>> {
+        let (actual_current_directory, _output_directory, _project_directory) = setup_test()?;
+        let mut config = Config::build(
+            json!({ "environment": "production", "modulePrefix": "my-app" }),
+            HashMap::new(),
+            BuildCache::new()
+        )?;
+        config.cli_arguments.source_maps = false;
+
+        build_all_assets(&config)?;
+        build(&config)?;
+
+        let dist_files = fs::read_dir("dist/assets")?
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect::<Vec<String>>();
+        let asset_map: Value = serde_json::from_str(fs::read_to_string("dist/assets/assetMap.json")?.as_str())?;
+
+        assert_eq!(dist_files.len(), 10);
+        assert!(dist_files.iter().all(|file_name| !file_name.ends_with(".map")));
+        assert!(!fs::metadata("tmp/assets/application.js.map").is_ok());
+        assert!(!fs::read_to_string(format!("dist{}", get_file_key(&asset_map["assets"], "assets/application.js")))?
+            .contains("sourceMappingURL"));
+        assert_eq!(asset_map["assets"].as_object().unwrap().len(), 4);
+
+        return finalize_test(actual_current_directory);
+    }
+
+    #[test]
+    fn build_writes_compressed_files_with_the_configured_levels() -> Result<(), Box<dyn Error>> {
+        use std::io::Read;
+
+        let (actual_current_directory, _output_directory, _project_directory) = setup_test()?;
+        let config = Config::build(
+            json!({
+                "environment": "development",
+                "modulePrefix": "my-app",
+                "compression": { "enabled": true, "gzip": { "level": 1 }, "brotli": { "enabled": false } }
+            }),
+            HashMap::new(),
+            BuildCache::new()
+        )?;
+
+        build_all_assets(&config)?;
+
+        let (_message, build_metadata_output) = build(&config)?;
+        let asset_map: Value = serde_json::from_str(fs::read_to_string("dist/assets/assetMap.json")?.as_str())?;
+        let application_js_path = format!("dist{}", get_file_key(&asset_map["assets"], "assets/application.js"));
+        let application_js = fs::read(&application_js_path)?;
+        let mut decompressed_application_js = Vec::new();
+
+        flate2::read::GzDecoder::new(&fs::read(format!("{}.gz", &application_js_path))?[..])
+            .read_to_end(&mut decompressed_application_js)?;
+
+        assert_eq!(decompressed_application_js, application_js);
+        assert_eq!(fs::read(format!("{}.gz", &application_js_path))?, file::gzip(&application_js, 1)?);
+        assert!(fs::metadata("dist/index.html.gz").is_ok());
+        assert!(!fs::metadata(format!("{}.br", &application_js_path)).is_ok());
+        assert!(!fs::metadata(format!("{}.map.gz", &application_js_path)).is_ok());
+        assert!(!fs::metadata("dist/assets/assetMap.json.gz").is_ok());
+        assert!(build_metadata_output.iter().all(|file| file["brotli_size"].is_null()));
+
+        let invalid_config = Config::build(
+            json!({ "environment": "production", "modulePrefix": "my-app", "compression": { "brotli": { "level": 12 } } }),
+            HashMap::new(),
+            BuildCache::new()
+        )?;
+
+        assert_eq!(
+            build(&invalid_config).unwrap_err().to_string(), "compression.brotli.level should be between 0 and 11, got: 12"
+        );
+
+        return finalize_test(actual_current_directory);
+    }
+
+    #[test]
+    fn build_saves_stats_and_compares_them_with_the_previous_build() -> Result<(), Box<dyn Error>> {
+        let (actual_current_directory, _output_directory, _project_directory) = setup_test()?;
+        let mut config = Config::build(
+            json!({ "environment": "development", "modulePrefix": "my-app" }),
+            HashMap::new(),
+            BuildCache::new()
+        )?;
+
+        build_all_assets(&config)?;
+
+        let (_message, build_metadata_output) = build(&config)?;
+        let stats: Value = serde_json::from_str(&fs::read_to_string("dist/.mber-stats.json")?)?;
+
+        assert_eq!(stats["environment"], json!("development"));
+        assert_eq!(stats["assets"], Value::Array(build_metadata_output.clone()));
+
+        fs::write("tmp/main-stats.json", serde_json::to_string(&json!({ "assets": [
+            { "asset": "assets/vendor.js", "size": 1, "gzip_size": 1 }
+        ] }))?)?;
+        build(&config)?;
+
+        assert_eq!(build_stats::read(Path::new("dist/.mber-stats.json"))?, Some(build_metadata_output));
+
+        config.cli_arguments.compare = Some("tmp/main-stats.json".to_string());
+        build(&config)?;
+        config.cli_arguments.compare = Some("tmp/missing-stats.json".to_string());
+
+        assert_eq!(build(&config).unwrap_err().to_string(), "--compare file tmp/missing-stats.json does not exist");
+
+        return finalize_test(actual_current_directory);
+    }
+
+    #[test]
+    fn strip_source_mapping_url_only_strips_the_comment_of_the_bundle() {
+        assert_eq!(strip_source_mapping_url("a();\n//# sourceMappingURL=application.js.map\n", "/assets/application.js"), "a();");
+        assert_eq!(strip_source_mapping_url("a{}\n/*# sourceMappingURL=application.css.map */\n", "/assets/application.css"), "a{}");
+        assert_eq!(strip_source_mapping_url("a();\n//# sourceMappingURL=ember.map\n", "/assets/vendor.js"), "a();\n//# sourceMappingURL=ember.map\n");
+        assert_eq!(strip_source_mapping_url("a();", "/assets/vendor.js"), "a();");
+    }
+
     #[test]
     fn build_works_for_different_application_with_memserver_mode_and_fastboot_false() -> Result<(), Box<dyn Error>> {
         let (actual_current_directory, output_directory, _project_directory) = setup_test()?;
@@ -545,8 +1127,9 @@ mod tests {
             }),
             HashMap::new(),
             BuildCache::new()
-        ); // NOTE: testing: true must be there
+        )?;
         config.cli_arguments.fastboot = false;
+        config.cli_arguments.testing = true;
 
         build_all_assets(&config)?;
 
@@ -564,12 +1147,12 @@ mod tests {
         });
 
         assert!(time_passed < TIME_TO_BUILD_DIST_THRESHOLD);
-        assert!(file_names.len() == 10);
+        assert_eq!(file_names.len(), 18);
 
         let target_index_html_assets = file_names.iter().filter(|file_name| {
             let target_file_name = file_name.to_str().unwrap().to_string();
 
-            return vec!["tests", "test-support", "documentation"].iter_mut()
+            return vec!["tests", "test-support", "documentation", ".map"].iter_mut()
                 .all(|name| !target_file_name.contains(&name.to_string()));
         });
         let output_html = fs::read_to_string("dist/index.html")?;
@@ -588,19 +1171,19 @@ mod tests {
             return result;
         });
         let file_contents = [
-            fs::read_to_string("tmp/assets/application.css")?,
-            fs::read_to_string("tmp/assets/application.js")?,
-            fs::read_to_string("tmp/assets/documentation.css")?,
-            fs::read_to_string("tmp/assets/documentation.js")?,
-            fs::read_to_string("tmp/assets/memserver.js")?,
-            fs::read_to_string("tmp/assets/test-support.css")?,
-            fs::read_to_string("tmp/assets/test-support.js")?,
-            fs::read_to_string("tmp/assets/tests.js")?,
-            fs::read_to_string("tmp/assets/vendor.js")?
+            read_without_source_mapping_url("tmp/assets/application.css"),
+            read_without_source_mapping_url("tmp/assets/application.js"),
+            read_without_source_mapping_url("tmp/assets/documentation.css"),
+            read_without_source_mapping_url("tmp/assets/documentation.js"),
+            read_without_source_mapping_url("tmp/assets/memserver.js"),
+            read_without_source_mapping_url("tmp/assets/test-support.css"),
+            read_without_source_mapping_url("tmp/assets/test-support.js"),
+            read_without_source_mapping_url("tmp/assets/tests.js"),
+            read_without_source_mapping_url("tmp/assets/vendor.js")
         ];
 
         dist_file_assets.iter().for_each(|dist_file| {
-            assert!(file_contents.contains(&fs::read_to_string(dist_file).unwrap()));
+            assert!(file_contents.contains(&read_without_source_mapping_url(dist_file)));
         });
         build_metadata_output.iter().for_each(|file| {
             let file_size = file["size"].as_u64().unwrap();
@@ -617,7 +1200,7 @@ mod tests {
         let asset_map: Value = serde_json::from_str(fs::read_to_string("dist/assets/assetMap.json")?.as_str())?;
 
         assert_eq!(asset_map["prepend"], Value::String("".to_string()));
-        assert_eq!(asset_map["assets"].as_object().unwrap().len(), 10);
+        assert_eq!(asset_map["assets"].as_object().unwrap().len(), 18);
         assert_eq!(asset_map["assets"]["assets/assetMap.json"], Value::String("assets/assetMap.json".to_string()));
 
         let dist_files: Vec<String> = file_names.iter()
@@ -640,11 +1223,14 @@ mod tests {
     fn build_resets_dist() -> Result<(), Box<dyn Error>> {
         let (actual_current_directory, output_directory, _project_directory) = setup_test()?;
 
-        let config = Config::build(
+        let mut config = Config::build(
             json!({ "environment": "development", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
+
+        config.cli_arguments.testing = true;
+
         let temp_file = format!("{}/dist/assets/izel.js", &config.project_root.display());
 
         fs::create_dir_all(format!("{}/assets", &output_directory))?;
@@ -668,12 +1254,12 @@ mod tests {
         });
 
         assert!(time_passed < TIME_TO_BUILD_DIST_THRESHOLD);
-        assert!(file_names.len() == 7);
+        assert_eq!(file_names.len(), 12);
 
         let target_index_html_assets = file_names.iter().filter(|file_name| {
             let target_file_name = file_name.to_str().unwrap().to_string();
 
-            return !target_file_name.contains("tests") && !target_file_name.contains("test-support");
+            return !target_file_name.contains("tests") && !target_file_name.contains("test-support") && !target_file_name.ends_with(".map");
         });
         let output_html = fs::read_to_string("dist/index.html")?;
         let output_test_html = fs::read_to_string("dist/tests.html")?;
@@ -691,16 +1277,16 @@ mod tests {
             return result;
         });
         let file_contents = [
-            fs::read_to_string("tmp/assets/vendor.js")?,
-            fs::read_to_string("tmp/assets/application.css")?,
-            fs::read_to_string("tmp/assets/application.js")?,
-            fs::read_to_string("tmp/assets/test-support.css")?,
-            fs::read_to_string("tmp/assets/test-support.js")?,
-            fs::read_to_string("tmp/assets/tests.js")?
+            read_without_source_mapping_url("tmp/assets/vendor.js"),
+            read_without_source_mapping_url("tmp/assets/application.css"),
+            read_without_source_mapping_url("tmp/assets/application.js"),
+            read_without_source_mapping_url("tmp/assets/test-support.css"),
+            read_without_source_mapping_url("tmp/assets/test-support.js"),
+            read_without_source_mapping_url("tmp/assets/tests.js")
         ];
 
         dist_file_assets.iter().for_each(|dist_file| {
-            assert!(file_contents.contains(&fs::read_to_string(dist_file).unwrap()));
+            assert!(file_contents.contains(&read_without_source_mapping_url(dist_file)));
         });
         build_metadata_output.iter().for_each(|file| {
             let file_size = file["size"].as_u64().unwrap();
@@ -717,7 +1303,7 @@ mod tests {
         let asset_map: Value = serde_json::from_str(fs::read_to_string("dist/assets/assetMap.json")?.as_str())?;
 
         assert_eq!(asset_map["prepend"], Value::String("".to_string()));
-        assert_eq!(asset_map["assets"].as_object().unwrap().len(), 7);
+        assert_eq!(asset_map["assets"].as_object().unwrap().len(), 12);
         assert_eq!(asset_map["assets"]["assets/assetMap.json"], Value::String("assets/assetMap.json".to_string()));
 
         let dist_files: Vec<String> = file_names.iter()
@@ -735,4 +1321,101 @@ mod tests {
 
         return finalize_test(actual_current_directory);
     }
+
+    #[test]
+    fn build_leaves_the_current_dist_untouched_when_it_fails() -> Result<(), Box<dyn Error>> {
+        let (actual_current_directory, output_directory, project_directory) = setup_test()?;
+        let config = Config::build(
+            json!({ "environment": "development", "modulePrefix": "frontend" }),
+            HashMap::new(),
+            BuildCache::new()
+        )?;
+        let previous_file = format!("{}/assets/izel.js", &output_directory);
+
+        fs::create_dir_all(format!("{}/assets", &output_directory))?;
+        fs::write(&previous_file, "console.log('hello');")?;
+        build_all_assets(&config)?;
+        fs::remove_file("tmp/assets/application.js")?;
+
+        let error = build(&config).unwrap_err().to_string();
+        let leftover_folders = fs::read_dir(&project_directory)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_name().to_string_lossy().starts_with(".dist-"))
+            .count();
+
+        assert!(error.starts_with(&format!("could not read {}/tmp/assets/application.js", &project_directory)));
+        assert_eq!(fs::read_to_string(&previous_file)?, "console.log('hello');");
+        assert_eq!(fs::read_dir(&output_directory)?.count(), 1);
+        assert_eq!(leftover_folders, 0);
+
+        let budget_config = Config::build(
+            json!({ "environment": "development", "modulePrefix": "frontend", "budgets": [{ "asset": "vendor.js", "size": "1kB" }] }),
+            HashMap::new(),
+            BuildCache::new()
+        )?;
+
+        build_all_assets(&budget_config)?;
+
+        assert_eq!(build(&budget_config).unwrap_err().to_string(), "1 of 1 bundle size budgets exceeded");
+        assert_eq!(fs::read_to_string(&previous_file)?, "console.log('hello');");
+        assert_eq!(fs::read_dir(&output_directory)?.count(), 1);
+        assert_eq!(Path::new(STAGING_FOLDER_NAME).exists(), false);
+
+        return finalize_test(actual_current_directory);
+    }
+
+    #[test]
+    fn swap_output_folder_keeps_the_given_number_of_previous_builds() -> Result<(), Box<dyn Error>> {
+        let project_root = env::temp_dir().join("mber-swap-output-folder");
+        let staged_build = |name: &str| -> Result<PathBuf, Box<dyn Error>> {
+            let staging_directory = project_root.join(STAGING_FOLDER_NAME);
+
+            fs::create_dir_all(&staging_directory)?;
+            fs::write(staging_directory.join("index.html"), name)?;
+
+            return Ok(staging_directory);
+        };
+        let read_build = |path: &str| fs::read_to_string(project_root.join(path).join("index.html")).unwrap();
+
+        let is_directory = |path: &str| fs::symlink_metadata(project_root.join(path)).map_or(false, |metadata| metadata.is_dir());
+
+        fs::remove_dir_all(&project_root).unwrap_or_else(|_| {});
+        fs::create_dir_all(project_root.join("dist"))?;
+        fs::write(project_root.join("dist/index.html"), "old")?;
+
+        swap_output_folder(&project_root, &staged_build("first")?, None)?;
+
+        assert_eq!(read_build("dist"), "first");
+        assert!(is_directory("dist"));
+        assert_eq!(project_root.join("dist-previous").exists(), false);
+
+        swap_output_folder(&project_root, &staged_build("second")?, Some(2))?;
+        swap_output_folder(&project_root, &staged_build("third")?, Some(2))?;
+        swap_output_folder(&project_root, &staged_build("fourth")?, Some(2))?;
+
+        assert_eq!(read_build("dist"), "fourth");
+        assert_eq!(list_previous_builds(&project_root.join("dist-previous"))?, vec![2, 3]);
+        assert_eq!((read_build("dist-previous/2"), read_build("dist-previous/3")), ("second".to_string(), "third".to_string()));
+        assert!(is_directory("dist") && is_directory("dist-previous/2"));
+
+        swap_output_folder(&project_root, &staged_build("fifth")?, None)?;
+
+        assert_eq!(read_build("dist"), "fifth");
+        assert_eq!(list_previous_builds(&project_root.join("dist-previous"))?, vec![2, 3]);
+
+        swap_output_folder(&project_root, &staged_build("sixth")?, Some(0))?;
+
+        assert_eq!(read_build("dist"), "sixth");
+        assert_eq!(project_root.join("dist-previous").exists(), false);
+        assert_eq!(project_root.join(STAGING_FOLDER_NAME).exists(), false);
+        assert_eq!(project_root.join(REPLACED_FOLDER_NAME).exists(), false);
+
+        fs::remove_dir_all(project_root.join("dist"))?;
+        swap_output_folder(&project_root, &staged_build("seventh")?, Some(1))?;
+
+        assert_eq!(read_build("dist"), "seventh");
+        assert_eq!(project_root.join("dist-previous").exists(), false);
+
+        return Ok(fs::remove_dir_all(&project_root)?);
+    }
 }
@@ -1,45 +1,41 @@
 use std::time::Instant;
 use std::path::PathBuf;
-use std::str::FromStr;
 use std::result::Result;
-use std::error::Error;
 use std::fs;
 use yansi::Paint;
 use sass_rs;
+use super::css;
 use super::super::utils::{console, recursive_file_lookup, file};
-use super::super::types::Config;
+use super::super::types::{Config, MberError};
 
-pub fn build(config: &Config) -> Result<(String, fs::Metadata), Box<dyn Error>> {
+pub fn build(config: &Config) -> Result<(String, fs::Metadata), MberError> {
     console::log(format!("{} documentation.css...", Paint::yellow("BUILDING:")));
 
     let build_start = Instant::now();
     let environment = config.env["environment"].as_str().unwrap_or("development");
     let project_root = &config.project_root.display();
-    let output_path = PathBuf::from_str(format!("{}/tmp/assets/documentation.css", &project_root).as_str())?;
-    let documentation_path = PathBuf::from_str(format!("{}/documentation/ui", &project_root).as_str())?;
-    let mut all_styles = vec![fs::read_to_string(format!("{}/documentation/ui/styles/application.scss", project_root))?];
-    let mut component_styles = recursive_file_lookup::lookup_for_extensions_and_predicate(
+    let output_path = PathBuf::from(format!("{}/tmp/assets/documentation.css", &project_root));
+    let documentation_path = PathBuf::from(format!("{}/documentation/ui", &project_root));
+    let mut style_files = vec![PathBuf::from(format!("{}/documentation/ui/styles/application.scss", project_root))];
+    let mut component_style_files = recursive_file_lookup::lookup_for_extensions_and_predicate(
         &documentation_path,
         vec![".scss"],
-        |entry| { return !entry.file_name().to_str().unwrap().contains("/src/ui/styles"); }
-    ).into_iter()
-    .map(|file_name| fs::read_to_string(file_name).unwrap())
-    .collect::<Vec<String>>();
+        |entry| { return !entry.file_name().to_string_lossy().contains("/src/ui/styles"); }
+    )?;
     let output_style = match vec!["production", "demo"].contains(&environment) {
         true => sass_rs::OutputStyle::Compressed,
         false => sass_rs::OutputStyle::Expanded
     };
 
-    all_styles.append(&mut component_styles);
+    style_files.append(&mut component_style_files);
 
-    fs::write(&output_path, sass_rs::compile_string(&all_styles.join("\n"), sass_rs::Options {
-        output_style: output_style, precision: 5, indented_syntax: false,
-        include_paths: vec![format!("{}/documentation/ui/styles", project_root)]
-    })?)?;
+    let styles = css::read_styles(&style_files, &config.project_root)?;
+
+    css::write_styles(config, &output_path, &styles, output_style, format!("{}/documentation/ui/styles", project_root))?;
 
     // TODO: in future create a thread global build error to say/stop tts on error
 
-    let output_metadata = fs::metadata(output_path)?;
+    let output_metadata = fs::metadata(&output_path).map_err(MberError::io("could not read", &output_path))?;
     let message = format!(
         "{} documentation.css in {} [{}] Environment: {}",
         Paint::green("BUILT:"),
@@ -57,6 +53,7 @@ pub fn build(config: &Config) -> Result<(String, fs::Metadata), Box<dyn Error>>
 
 #[cfg(test)]
 mod tests {
+    use std::error::Error;
     use std::env;
     use super::*;
     use std::path::PathBuf;
@@ -100,7 +97,7 @@ mod tests {
             json!({ "environment": "development", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
         let (message, _stats) = build(&config)?;
         let build_time_in_ms = Regex::new(r"documentation\.css in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
@@ -125,7 +122,7 @@ mod tests {
             json!({ "environment": "production", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
         let (message, _stats) = build(&config)?;
         let build_time_in_ms = Regex::new(r"documentation\.css in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
@@ -150,7 +147,7 @@ mod tests {
             json!({ "environment": "custom", "modulePrefix": "my-app" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
         let (message, _stats) = build(&config)?;
         let build_time_in_ms = Regex::new(r"documentation\.css in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
@@ -1,41 +1,52 @@
 use std::time::Instant;
 use std::path::PathBuf;
-use std::str::FromStr;
-use std::error::Error;
 use std::fs;
 use yansi::Paint;
+use rayon::prelude::*;
 use super::super::utils::{console, recursive_file_lookup, file, walk_injection};
+use super::super::utils::source_map::Bundle;
 use super::super::transpilers;
+use super::super::linters::{self, LintedFile};
 use super::super::injections::documentation;
-use super::super::types::Config;
+use super::super::types::{Config, MberError, TranspileError};
 
-// NOTE: eslint in rust(This one is challenging)
-pub fn build(config: &Config, _lint: bool) -> Result<(String, fs::Metadata), Box<dyn Error>> {
+pub fn build(config: &Config, lint: bool) -> Result<(String, fs::Metadata, Vec<LintedFile>), MberError> {
     console::log(format!("{} documentation.js...", Paint::yellow("BUILDING:")));
 
     let build_start = Instant::now();
     let environment = config.env["environment"].as_str().unwrap_or("development");
     let project_root = &config.project_root.display();
-    let output_path = PathBuf::from_str(format!("{}/tmp/assets/documentation.js", &project_root).as_str())?;
-    let documentation_path = PathBuf::from_str(format!("{}/documentation", &project_root).as_str())?;
+    let output_path = PathBuf::from(format!("{}/tmp/assets/documentation.js", &project_root));
+    let documentation_path = PathBuf::from(format!("{}/documentation", &project_root));
     let should_minify = vec!["production", "demo"].contains(&environment);
-    let documentation_addon_code = import_documentation_code(&config.project_root, &config.application_name, should_minify);
-    let contents = recursive_file_lookup::lookup_for_extensions(
+    let documentation_addon_code = import_documentation_code(&config.project_root, &config.application_name)?;
+    let documentation_files = recursive_file_lookup::lookup_for_extensions(
         &documentation_path,
         vec![".js", ".ts", ".hbs"]
-    ).into_iter()
-    .map(|file| transpilers::convert_es_module::from_file(&file, should_minify))
-    .collect::<Vec<&str>>()
-    .join("\n");
-
-    fs::write(&output_path, format!(
-        "define = window.define; {}",
-        vec![documentation_addon_code, contents].join("\n")
-    ))?; // TODO: maybe minify here on demand
+    )?.into_iter()
+    .map(|file| -> Result<(String, String), MberError> {
+        let content = fs::read_to_string(&file).map_err(MberError::io("could not read", &file))?;
+
+        return Ok((file.strip_prefix(&config.project_root).unwrap_or(&file).to_string_lossy().to_string(), content));
+    })
+    .collect::<Result<Vec<(String, String)>, MberError>>()?;
+    let documentation_paths = documentation_files.iter()
+        .map(|(path, _)| config.project_root.join(path))
+        .collect::<Vec<PathBuf>>();
+    let sources = transpile_documentation_files(documentation_files, &config.application_name)?;
+    let linted_files = match lint {
+        true => linters::check_files(&documentation_paths, config, "documentation.js")?,
+        false => Vec::new()
+    };
+    let mut bundle = Bundle::new("documentation.js");
+
+    bundle.push(&format!("define = window.define; {}\n", documentation_addon_code));
+    super::push_sources(&mut bundle, &sources);
+    super::write_bundle(config, bundle, &output_path, should_minify)?;
 
     // TODO: in future create a thread global build error to say/stop tts on error
 
-    let output_metadata = fs::metadata(output_path)?;
+    let output_metadata = fs::metadata(&output_path).map_err(MberError::io("could not read", &output_path))?;
     let message = format!(
         "{} documentation.js in {} [{}] Environment: {}",
         Paint::green("BUILT:"),
@@ -46,32 +57,83 @@ pub fn build(config: &Config, _lint: bool) -> Result<(String, fs::Metadata), Box
 
     console::log(&message);
 
-    // NOTE: then linting
-
-    return Ok((message, output_metadata));
+    return Ok((message, output_metadata, linted_files));
 }
 
-fn import_documentation_code(_project_root: &PathBuf, application_name: &String, should_minify: bool) -> String {
-    let documentation_hashmap = serde_json::from_str(documentation::as_str()).unwrap(); // TODO: always keep it flat
+fn import_documentation_code(_project_root: &PathBuf, application_name: &String) -> Result<String, MberError> {
+    let documentation_hashmap = serde_json::from_str(documentation::as_str()) // TODO: always keep it flat
+        .map_err(|error| MberError::build("_vendor/mber-documentation", error))?;
     let flat_documentation_hashmap = walk_injection::flatten_fs_hashmap(documentation_hashmap, vec![]);
+    let vendor_file = |path: &str| -> Result<String, MberError> {
+        return flat_documentation_hashmap.get(path).cloned()
+            .ok_or_else(|| MberError::build(path, format!("{} is missing in the mber binary", path)));
+    };
 
-    return format!(
+    return Ok(format!(
         "{} {} {}",
-        flat_documentation_hashmap.get("_vendor/mber-documentation/vendor/copee.umd.js").unwrap().clone(),
-        flat_documentation_hashmap.get("_vendor/mber-documentation/vendor/highlight.pack.js").unwrap().clone(),
-        walk_injection::lookup_for_extensions_with_predicate(flat_documentation_hashmap, vec![".js", ".ts", ".hbs"], |filename| {
-            return filename.starts_with("_vendor/mber-documentation/src");
-        }).iter()
-        .map(|(_path, content)| {
-            return transpilers::convert_es_module::from_string(content, application_name, should_minify);
-        }) // NOTE: build the right module path and hbs transpiler if it needs it
-        .collect::<Vec<&str>>()
-        .join("\n")
-    );
+        vendor_file("_vendor/mber-documentation/vendor/copee.umd.js")?,
+        vendor_file("_vendor/mber-documentation/vendor/highlight.pack.js")?,
+        transpile_documentation_files(
+            walk_injection::lookup_for_extensions_with_predicate(flat_documentation_hashmap, vec![".js", ".ts", ".hbs"], |filename| {
+                return filename.starts_with("_vendor/mber-documentation/src");
+            }).into_iter()
+            .map(|(path, content)| (path.replace("_vendor/mber-documentation/", ""), content))
+            .collect(),
+            application_name
+        )?.into_iter().map(|(_, _, output)| output).collect::<Vec<String>>().join("\n")
+    ));
+}
+
+// NOTE: files are (relative_path, content) pairs, templates are precompiled in one batch. Returns
+// (relative_path, content, output) triples with the templates last
+fn transpile_documentation_files(
+    files: Vec<(String, String)>, application_name: &str
+) -> Result<Vec<(String, String, String)>, TranspileError> {
+    let (template_files, module_files): (Vec<(String, String)>, Vec<(String, String)>) = files.into_iter()
+        .partition(|(path, _)| path.ends_with(".hbs"));
+    let mut modules = module_files.par_iter()
+        .map(|(path, content)| {
+            let module_name = documentation_module_name(path, application_name);
+
+            return transpilers::convert_es_module::from_string(content, &module_name)
+                .map_err(|error| TranspileError { file_name: path.to_string(), ..error });
+        })
+        .collect::<Result<Vec<String>, TranspileError>>()?;
+    let templates = template_files.iter()
+        .map(|(path, content)| (documentation_module_name(path, application_name), content.to_string()))
+        .collect::<Vec<(String, String)>>();
+    let mut compiled_templates = transpilers::convert_hbs_module::from_strings(&templates).map_err(|error| {
+        let index = templates.iter().position(|(module_name, _)| module_name == &error.file_name).unwrap_or(0);
+
+        return TranspileError { file_name: template_files[index].0.clone(), ..error };
+    })?;
+
+    modules.append(&mut compiled_templates);
+
+    return Ok(module_files.into_iter().chain(template_files.into_iter()).zip(modules.into_iter())
+        .map(|((path, content), output)| (path, content, output))
+        .collect());
+}
+
+// NOTE: documentation/router.js is looked up by mber-documentation/index.js, routes nest under the documentation route
+fn documentation_module_name(relative_path: &str, application_name: &str) -> String {
+    let path = relative_path.trim_end_matches(".js").trim_end_matches(".ts").trim_end_matches(".hbs");
+    let target_path = if path.starts_with("src/") {
+        path.to_string()
+    } else if path.starts_with("documentation/ui/routes/") {
+        path.replacen("documentation/ui/routes/", "src/ui/routes/documentation/", 1)
+    } else if path.starts_with("documentation/ui/") {
+        path.replacen("documentation/ui/", "src/ui/", 1)
+    } else {
+        path.replacen("documentation/", "documentation/src/", 1)
+    };
+
+    return format!("{}/{}", application_name, target_path);
 }
 
 #[cfg(test)]
 mod tests {
+    use std::error::Error;
     use std::env;
     use super::*;
     use std::path::PathBuf;
@@ -113,8 +175,8 @@ mod tests {
             json!({ "environment": "development", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
-        let (development_build_message, _stats) = build(&config, false)?;
+        )?;
+        let (development_build_message, _stats, _) = build(&config, false)?;
         let development_build_time_in_ms = Regex::new(r"documentation\.js in \d+ms")?
             .find(development_build_message.as_str()).unwrap().as_str()
             .replace("documentation.js in ", "")
@@ -136,8 +198,8 @@ mod tests {
             json!({ "environment": "production", "modulePrefix": "customapp" }),
             HashMap::new(),
             BuildCache::new()
-        );
-        let (production_build_message, _stats) = build(&production_config, false)?;
+        )?;
+        let (production_build_message, _stats, _) = build(&production_config, false)?;
         let production_build_time_in_ms = Regex::new(r"documentation\.js in \d+ms")?
             .find(production_build_message.as_str()).unwrap().as_str()
             .replace("documentation.js in ", "")
@@ -147,7 +209,7 @@ mod tests {
 
         assert!(production_build_time_in_ms < BUILD_TIME_THRESHOLD);
         assert!(production_build_output_size >= 100);
-        // assert!(development_build_output_size > production_build_output_size);
+        assert!(development_build_output_size > production_build_output_size);
         assert!(
             Regex::new(r"BUILT: documentation\.js in \d+ms \[\d+.\d+ kB\] Environment: production")?
                 .find(&production_build_message).is_some()
@@ -1,32 +1,28 @@
 use std::path::PathBuf;
-use std::str::FromStr;
 use std::fs;
 use std::result::Result;
-use std::error::Error;
 use serde_json;
-use serde_json::{json, Value, Map};
-use super::super::types::Config;
+use serde_json::{json, Value};
+use super::super::types::{Config, MberError};
 
-pub fn build(asset_map: Value, config: &Config, dist_folder: Option<&str>) -> Result<(String), Box<dyn Error>> {
+pub fn build(asset_map: Value, config: &Config, dist_folder: Option<&str>) -> Result<String, MberError> {
     let target_dist_folder = dist_folder.unwrap_or("dist");
-    let target_dist_path = PathBuf::from_str(format!("{}/{}/package.json", &config.project_root.display(), target_dist_folder).as_str())?;
+    let target_dist_path = PathBuf::from(format!("{}/{}/package.json", &config.project_root.display(), target_dist_folder));
     let application_path = &asset_map["assets/application.js"];
     let application_name = &config.application_name;
 
-    let mut original_env = config.env.clone();
-    let env = original_env.as_object_mut().unwrap();
-    let (mut default_app_value, default_fastboot_whitelist) = (Value::Object(Map::new()), Vec::new());
-    let target_app = env.get_mut("APP").unwrap_or(&mut default_app_value).as_object_mut().unwrap();
+    let mut env = config.env.as_object().cloned()
+        .ok_or_else(|| MberError::config("config/environment.js", "config/environment.js should return an object"))?;
+    let mut target_app = env.get("APP").and_then(|app| app.as_object()).cloned().unwrap_or_default();
+    let default_fastboot_whitelist = Vec::new();
 
-    target_app.insert(String::from_str("autoboot")?, Value::Bool(false));
-    target_app.insert(String::from_str("name")?, Value::String(config.env["modulePrefix"].as_str().unwrap_or("frontend").to_string()));
-    target_app.insert(String::from_str("version")?, Value::String("0.0.0+b5f80b0d".to_string()));
+    target_app.insert(String::from("autoboot"), Value::Bool(false));
+    target_app.insert(String::from("name"), Value::String(config.env["modulePrefix"].as_str().unwrap_or("frontend").to_string()));
+    target_app.insert(String::from("version"), Value::String("0.0.0+b5f80b0d".to_string()));
 
-    let final_app = serde_json::to_value(target_app)?;
-
-    env.insert(String::from_str("APP")?, final_app);
-    env.insert(String::from_str("exportApplicationGlobal")?, Value::Bool(true));
-    env.insert(String::from_str("isModuleUnification")?, Value::Bool(true));
+    env.insert(String::from("APP"), Value::Object(target_app));
+    env.insert(String::from("exportApplicationGlobal"), Value::Bool(true));
+    env.insert(String::from("isModuleUnification"), Value::Bool(true));
 
     let host_whitelist = config.env["fastboot"]["hostWhitelist"].as_array().unwrap_or(&default_fastboot_whitelist);
     let json = json!({
@@ -48,15 +44,15 @@ pub fn build(asset_map: Value, config: &Config, dist_folder: Option<&str>) -> Re
                 vec![application_path]
             },
             "htmlFile": "index.html",
-            "vendorFiles": [asset_map["assets/vendor.js"].as_str().unwrap()]
+            "vendorFiles": [asset_map["assets/vendor.js"]]
           },
           "moduleWhitelist": ["node-fetch", "abortcontroller-polyfill"],
           "schemaVersion": 3
         }
     });
-    let json_string = serde_json::to_string_pretty(&json)?;
+    let json_string = format!("{:#}", json);
 
-    fs::write(target_dist_path, &json_string)?;
+    fs::write(&target_dist_path, &json_string).map_err(MberError::io("could not write", &target_dist_path))?;
 
     return Ok(json_string);
 }
@@ -64,6 +60,7 @@ pub fn build(asset_map: Value, config: &Config, dist_folder: Option<&str>) -> Re
 #[cfg(test)]
 mod tests {
     use std::env;
+    use std::error::Error;
     use super::*;
     use std::path::PathBuf;
     use serde_json::json;
@@ -191,7 +188,7 @@ mod tests {
             development_env,
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
 
         build(example_asset_map, &config, Some("tmp"))?;
 
@@ -236,7 +233,7 @@ mod tests {
             production_env,
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
 
         build(second_example_asset_map, &config, Some("dist"))?;
 
@@ -314,7 +311,7 @@ mod tests {
             development_env,
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
 
         build(example_asset_map, &config, Some("tmp"))?;
 
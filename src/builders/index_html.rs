@@ -1,12 +1,11 @@
 use std::result::Result;
-use std::error::{Error};
 use std::fs;
 use std::collections::HashMap;
 use mustache;
 use mustache::MapBuilder;
-use super::super::types::Config;
+use super::super::types::{Config, MberError};
 
-pub fn build(html_path: &str, config: &Config) -> Result<String, Box<dyn Error>> {
+pub fn build(html_path: &str, config: &Config) -> Result<String, MberError> {
     let output_path = match html_path.ends_with("tests/index.html") {
         true => format!("{}/tmp/tests.html", &config.project_root.display()),
         false => format!("{}/tmp/index.html", &config.project_root.display())
@@ -21,12 +20,12 @@ pub fn build(html_path: &str, config: &Config) -> Result<String, Box<dyn Error>>
         );
     }
 
-    fs::write(output_path, &content)?;
+    fs::write(&output_path, &content).map_err(MberError::io("could not write", &output_path))?;
 
     return Ok(content);
 }
 
-pub fn build_documentation_html(html_path: &str, config: &Config) -> Result<String, Box<dyn Error>> {
+pub fn build_documentation_html(html_path: &str, config: &Config) -> Result<String, MberError> {
     let documentation_path_in_config = &config.env["documentation"]["path"].as_str().unwrap_or("/styleguide");
     let output_path = format!("{}/tmp{}.html", &config.project_root.display(), documentation_path_in_config);
     let mut content = transpile_mustache_template(html_path, &config.index_html_injections)?;
@@ -47,24 +46,28 @@ pub fn build_documentation_html(html_path: &str, config: &Config) -> Result<Stri
         );
     }
 
-    fs::write(output_path, &content)?;
+    fs::write(&output_path, &content).map_err(MberError::io("could not write", &output_path))?;
 
     return Ok(content);
 }
 
-fn transpile_mustache_template(template_path: &str, index_html_injections: &HashMap<String, String>) -> Result<String, Box<dyn Error>> {
+fn transpile_mustache_template(template_path: &str, index_html_injections: &HashMap<String, String>) -> Result<String, MberError> {
     let dynamic_data = &index_html_injections.into_iter()
         .fold(MapBuilder::new(), |result, (injection_key, injection_value)| {
             return result.insert_str(injection_key, injection_value);
         }).build();
-    let template = mustache::compile_path(&template_path)?;
+    let template = mustache::compile_path(&template_path).map_err(|error| match error {
+        mustache::Error::Io(error) => MberError::io("could not read", template_path)(error),
+        error => MberError::build(template_path, error)
+    })?;
 
-    return Ok(template.render_data_to_string(&dynamic_data)?);
+    return template.render_data_to_string(&dynamic_data).map_err(|error| MberError::build(template_path, error));
 }
 
 #[cfg(test)]
 mod tests {
     use std::env;
+    use std::error::Error;
     use std::path::PathBuf;
     use std::collections::HashMap;
     use serde_json::json;
@@ -105,7 +108,7 @@ mod tests {
             json!({ "environment": "development", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
         let output_html = build(&html_input_path.as_str(), &config)?;
         let content = fs::read_to_string(html_output_path)?;
 
@@ -138,7 +141,7 @@ mod tests {
             json!({ "environment": "development", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
         let output_html = build(&html_input_path.as_str(), &config)?;
         let content = fs::read_to_string(html_output_path)?;
 
@@ -178,7 +181,7 @@ mod tests {
             json!({ "environment": "memserver", "modulePrefix": "izelapp", "memserver": { "enabled": true } }),
             index_html_injections,
             BuildCache::new()
-        );
+        )?;
         let output_html = build(&html_input_path.as_str(), &config)?;
         let content = fs::read_to_string(html_output_path)?;
 
@@ -218,7 +221,7 @@ mod tests {
         let config = Config::build(json!({
             "environment": "development", "modulePrefix": "frontend",
             "documentation": { "path": documentation_path }
-        }), index_html_injections, BuildCache::new());
+        }), index_html_injections, BuildCache::new())?;
         let output_html = build_documentation_html(&html_input_path.as_str(), &config)?;
         let content = fs::read_to_string(html_output_path)?;
 
@@ -258,7 +261,7 @@ mod tests {
         let config = Config::build(json!({
             "environment": "memserver", "modulePrefix": "custom-app",
             "memserver": { "enabled": true }, "documentation": { "path": documentation_path }
-        }), index_html_injections, BuildCache::new());
+        }), index_html_injections, BuildCache::new())?;
         let output_html = build_documentation_html(&html_input_path.as_str(), &config)?;
         let content = fs::read_to_string(html_output_path)?;
 
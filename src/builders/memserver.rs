@@ -1,47 +1,49 @@
 use std::time::Instant;
 use std::path::PathBuf;
-use std::str::FromStr;
 use std::result::Result;
-use std::error::Error;
 use std::fs;
 use yansi::Paint;
 use super::super::utils::{console, recursive_file_lookup, file};
+use super::super::utils::source_map::Bundle;
 use super::super::transpilers;
-use super::super::types::Config;
+use super::super::linters::{self, LintedFile};
+use super::super::types::{Config, MberError};
 
-// NOTE: eslint in rust(This one is challenging)
-pub fn build(config: &Config, _lint: bool) -> Result<(String, fs::Metadata), Box<dyn Error>> {
+pub fn build(config: &Config, lint: bool) -> Result<(String, fs::Metadata, Vec<LintedFile>), MberError> {
     console::log(format!("{} memserver.js...", Paint::yellow("BUILDING:")));
 
     let build_start = Instant::now();
     let environment = config.env["environment"].as_str().unwrap_or("development");
     let project_root = &config.project_root.display();
-    let output_path = PathBuf::from_str(format!("{}/tmp/assets/memserver.js", &project_root).as_str())?;
-    let memserver_path = PathBuf::from_str(format!("{}/memserver", &project_root).as_str())?;
+    let output_path = PathBuf::from(format!("{}/tmp/assets/memserver.js", &project_root));
+    let memserver_path = PathBuf::from(format!("{}/memserver", &project_root));
     let should_minify = vec!["production", "demo"].contains(&environment);
-    let user_memserver_code = recursive_file_lookup::lookup_for_extensions_and_predicate(
+    let user_memserver_files = recursive_file_lookup::lookup_for_extensions_and_predicate(
         &memserver_path,
-        vec![".js", ".ts", ".hbs"],
-        |entry| { return !entry.file_name().to_str().unwrap().ends_with("-test.js"); }
-    ).into_iter()
-    .map(|file| transpilers::convert_es_module::from_file(&file, should_minify))
-    .collect::<Vec<&str>>()
-    .join("\n");
-    let memserver_vendor_code = String::from_utf8(include_bytes!("../../_vendor/memserver.js").to_vec())?;
-    let memserver_instance_initializer_code = String::from(transpilers::convert_es_module::from_string(
-        String::from_utf8(include_bytes!("../../_vendor/mber-memserver/instance-initializer/memserver.js").to_vec())?.as_str(),
-        &project_root.to_string(),
-        should_minify
-    ));
-
-    fs::write(&output_path, format!(
-        "define = window.define; {}",
-        vec![memserver_vendor_code, memserver_instance_initializer_code, user_memserver_code].join("\n")
-    ))?; // TODO: maybe minify here on demand
+        vec![".js", ".ts"],
+        |entry| { return !entry.file_name().to_string_lossy().ends_with("-test.js"); }
+    )?;
+    let user_memserver_code = transpilers::convert_es_module::from_files(
+        &user_memserver_files, &config.project_root, &config.application_name
+    )?;
+    let memserver_vendor_code = include_str!("../../_vendor/memserver.js").to_string();
+    let memserver_instance_initializer_code = transpilers::convert_es_module::from_string(
+        include_str!("../../_vendor/mber-memserver/instance-initializer/memserver.js").to_string().as_str(),
+        format!("{}/src/init/instance-initializers/memserver", &config.application_name).as_str()
+    )?;
+    let linted_files = match lint {
+        true => linters::check_files(&user_memserver_files, config, "memserver.js")?,
+        false => Vec::new()
+    };
+    let mut bundle = Bundle::new("memserver.js");
+
+    bundle.push(&format!("define = window.define; {}\n{}\n", memserver_vendor_code, memserver_instance_initializer_code));
+    super::push_files(&mut bundle, &user_memserver_files, &user_memserver_code, &config.project_root)?;
+    super::write_bundle(config, bundle, &output_path, should_minify)?;
 
     // TODO: in future create a thread global build error to say/stop tts on error
 
-    let output_metadata = fs::metadata(output_path)?;
+    let output_metadata = fs::metadata(&output_path).map_err(MberError::io("could not read", &output_path))?;
     let message = format!(
         "{} memserver.js in {} [{}] Environment: {}",
         Paint::green("BUILT:"),
@@ -52,13 +54,12 @@ pub fn build(config: &Config, _lint: bool) -> Result<(String, fs::Metadata), Box
 
     console::log(&message);
 
-    // NOTE: then linting
-
-    return Ok((message, output_metadata));
+    return Ok((message, output_metadata, linted_files));
 }
 
 #[cfg(test)]
 mod tests {
+    use std::error::Error;
     use std::env;
     use super::*;
     use std::path::PathBuf;
@@ -102,8 +103,8 @@ mod tests {
             json!({ "environment": "development", "moduleprefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
-        let (message, _stats) = build(&config, false)?; // NOTE: config and lint
+        )?;
+        let (message, _stats, _) = build(&config, false)?; // NOTE: config and lint
         let build_time_in_ms = Regex::new(r"memserver\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
             .replace("memserver.js in ", "")
@@ -131,8 +132,8 @@ mod tests {
             json!({ "environment": "production", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
-        let (message, _stats) = build(&config, false)?; // NOTE: config and lint
+        )?;
+        let (message, _stats, _) = build(&config, false)?; // NOTE: config and lint
         let build_time_in_ms = Regex::new(r"memserver\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
             .replace("memserver.js in ", "")
@@ -160,8 +161,8 @@ mod tests {
             json!({ "environment": "custom", "modulePrefix": "my-app" }),
             HashMap::new(),
             BuildCache::new()
-        );
-        let (message, _stats) = build(&config, false)?; // NOTE: config and lint
+        )?;
+        let (message, _stats, _) = build(&config, false)?; // NOTE: config and lint
         let build_time_in_ms = Regex::new(r"memserver\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
             .replace("memserver.js in ", "")
@@ -1,4 +1,8 @@
+pub mod analyzer;
 pub mod application;
+pub mod budgets;
+pub mod build_stats;
+pub mod config;
 pub mod css;
 pub mod dist_folder;
 pub mod documentation_css;
@@ -6,15 +10,26 @@ pub mod documentation_js;
 pub mod fastboot_package_json;
 pub mod index_html;
 pub mod memserver;
+pub mod reproducibility;
 pub mod test_files;
 pub mod vendor;
 
 use std::fs;
-use super::types::Config;
-use std::error::Error;
+use std::path::{Path, PathBuf};
+use yansi::Paint;
+use super::transpilers::minify_js;
+use super::linters::LintedFile;
+use super::types::{Config, MberError};
+use super::utils::{console, file, transpile_cache};
+use super::utils::source_map::Bundle;
 use serde_json::{json, Value};
+use rayon::prelude::*;
 
-pub fn build_all_assets(config: &Config) -> Result<(), Box<dyn Error>> {
+type BuildStep<'a> = Box<dyn Fn() -> Result<Vec<LintedFile>, MberError> + Send + Sync + 'a>;
+
+// NOTE: builders write independent files so they all run at the same time, each one still logs its own timing.
+// Returns the linted files with problems for the command to print
+pub fn build_all_assets(config: &Config) -> Result<Vec<LintedFile>, MberError> {
     let project_root = config.project_root.display();
 
     if !config.cli_arguments.testing {
@@ -22,6 +37,8 @@ pub fn build_all_assets(config: &Config) -> Result<(), Box<dyn Error>> {
     }
 
     fs::create_dir_all(format!("{}/tmp/assets", &project_root)).unwrap_or_else(|_| {});
+    transpile_cache::set_directory(&config.cache_directory);
+    transpile_cache::take_session_counts();
 
     let mut default_asset_map = json!({
       "assets/application.css": "assets/application.css",
@@ -31,54 +48,192 @@ pub fn build_all_assets(config: &Config) -> Result<(), Box<dyn Error>> {
     let memserver_is_enabled = config.env["memserver"]["enabled"].as_bool().unwrap_or(false);
     let documentation_is_enabled = config.env["documentation"]["enabled"].as_bool().unwrap_or(false);
     let index_html_path = format!("{}/index.html", &project_root);
-
-    index_html::build(index_html_path.as_str(), &config)?;
-    css::build(&config)?;
-    vendor::build(&config)?;
-    application::build(&config, false)?; // NOTE: enable linting in future
+    let test_index_path = format!("{}/tests/index.html", &project_root);
+    let should_minify = vec!["production", "demo"].contains(&config.env["environment"].as_str().unwrap_or("development"));
+    let should_lint = config.cli_arguments.lint != "off";
 
     if memserver_is_enabled {
-        memserver::build(&config, false)?; // NOTE: enable linting in future
         default_asset_map.as_object_mut().unwrap().insert(
             "assets/memserver.js".to_string(),
             Value::String("assets/memserver.js".to_string())
         );
     }
 
-    fastboot_package_json::build(default_asset_map, &config, Some("tmp"))?;
+    let mut build_steps: Vec<BuildStep> = vec![
+        Box::new(|| { index_html::build(index_html_path.as_str(), &config)?; return Ok(Vec::new()); }),
+        Box::new(|| { css::build(&config)?; return Ok(Vec::new()); }),
+        Box::new(|| { vendor::build(&config)?; return Ok(Vec::new()); }),
+        Box::new(|| { return Ok(application::build(&config, should_lint)?.2); }),
+        Box::new(|| { fastboot_package_json::build(default_asset_map.clone(), &config, Some("tmp"))?; return Ok(Vec::new()); })
+    ];
 
-    if documentation_is_enabled {
-        documentation_js::build(&config, false)?; // NOTE: enable linting in future
-        documentation_css::build(&config)?;
+    if memserver_is_enabled {
+        build_steps.push(Box::new(|| { return Ok(memserver::build(&config, should_lint)?.2); }));
+    }
 
-        index_html::build_documentation_html(index_html_path.as_str(), &config)?;
+    if documentation_is_enabled {
+        build_steps.push(Box::new(|| { return Ok(documentation_js::build(&config, should_lint)?.2); }));
+        build_steps.push(Box::new(|| { documentation_css::build(&config)?; return Ok(Vec::new()); }));
+        build_steps.push(Box::new(|| { index_html::build_documentation_html(index_html_path.as_str(), &config)?; return Ok(Vec::new()); }));
     }
 
     if config.cli_arguments.testing {
-        let test_index_path = format!("{}/tests/index.html", &project_root);
+        build_steps.push(Box::new(|| { index_html::build(test_index_path.as_str(), &config)?; return Ok(Vec::new()); }));
+        build_steps.push(Box::new(|| { return Ok(test_files::build(&config, should_lint)?.2); }));
+        build_steps.push(Box::new(|| {
+            let (css_path, js_path) = (
+                format!("{}/tmp/assets/test-support.css", &project_root), format!("{}/tmp/assets/test-support.js", &project_root)
+            );
+
+            let mut bundle = Bundle::new("test-support.js");
+
+            fs::write(&css_path, include_str!("../../_vendor/test-support.css")).map_err(MberError::io("could not write", &css_path))?;
+            push_sources(&mut bundle, &[
+                ("_vendor/test-support.js", include_str!("../../_vendor/test-support.js")),
+                ("_vendor/ember-template-compiler.js", include_str!("../../_vendor/ember-template-compiler.js")),
+                ("_vendor/htmlbars-inline-precompile.js", include_str!("../../_vendor/htmlbars-inline-precompile.js"))
+            ].map(|(source, code)| (source.to_string(), code.to_string(), code.to_string())));
+
+            write_bundle(&config, bundle, Path::new(&js_path), should_minify)?;
+
+            return Ok(Vec::new());
+        }));
+    }
+
+    let linted_files = run_in_parallel(build_steps)?;
+
+    let (cache_hits, cache_misses) = transpile_cache::take_session_counts();
+
+    transpile_cache::prune().unwrap_or_default();
 
-        index_html::build(test_index_path.as_str(), &config)?;
+    if cache_hits + cache_misses > 0 {
+        console::log(format!(
+            "{} {} of {} transpiled modules reused from {}",
+            Paint::green("CACHE:"), cache_hits, cache_hits + cache_misses, transpile_cache::directory().display()
+        ));
+    }
 
-        test_files::build(&config, false)?; // NOTE: enable linting in future
-        fs::write(format!("{}/tmp/assets/test-support.css", &project_root), include_str!("../../_vendor/test-support.css"))?;
-        fs::write(format!("{}/tmp/assets/test-support.js", &project_root), include_str!("../../_vendor/test-support.js"))?;
+    return Ok(linted_files);
+}
+
+// NOTE: production and demo bundles get minified, the size comparison is logged next to the BUILT message
+fn minify_bundle(code: String, file_name: &str, should_minify: bool) -> Result<String, MberError> {
+    if !should_minify {
+        return Ok(code);
     }
 
+    let minified_code = minify_js::from_string(&code, file_name)?;
+
+    log_minified_size(file_name, code.len(), minified_code.len());
+
+    return Ok(minified_code);
+}
+
+fn log_minified_size(file_name: &str, size: usize, minified_size: usize) {
+    let saved_percentage = match size {
+        0 => 0,
+        size => 100usize.saturating_sub(minified_size * 100 / size)
+    };
+
+    console::log(format!(
+        "{} {} {} -> {} ({}% smaller)",
+        Paint::green("MINIFIED:"),
+        file_name,
+        file::format_size(size as u64),
+        Paint::yellow(file::format_size(minified_size as u64)),
+        saved_percentage
+    ));
+}
+
+// NOTE: --no-source-maps or sourceMaps: false in config/environment.js leaves the .map files out of the build
+fn should_write_source_maps(config: &Config) -> bool {
+    return config.cli_arguments.source_maps && config.env["sourceMaps"].as_bool().unwrap_or(true);
+}
+
+fn push_files(bundle: &mut Bundle, files: &[PathBuf], outputs: &[String], project_root: &Path) -> Result<(), MberError> {
+    let sources = files.iter().zip(outputs.iter())
+        .map(|(file, output)| -> Result<(String, String, String), MberError> {
+            let source = file.strip_prefix(project_root).unwrap_or(file).to_string_lossy().replace('\\', "/");
+            let content = fs::read_to_string(file).map_err(MberError::io("could not read", file))?;
+
+            return Ok((source, content, output.clone()));
+        })
+        .collect::<Result<Vec<(String, String, String)>, MberError>>()?;
+
+    push_sources(bundle, &sources);
+
     return Ok(());
 }
 
+// NOTE: sources are (relative_path, content, output) triples joined with newlines, compiled templates map to the
+// start of their .hbs file
+fn push_sources(bundle: &mut Bundle, sources: &[(String, String, String)]) {
+    for (index, (source, content, output)) in sources.iter().enumerate() {
+        if index > 0 {
+            bundle.push("\n");
+        }
+
+        match source.ends_with(".hbs") {
+            true => bundle.push_compiled(output, source, content),
+            false => bundle.push_source(output, source, content)
+        };
+    }
+}
+
+// NOTE: the map goes next to the bundle as <bundle>.map, minification is composed into it so it still points
+// to the original files
+fn write_bundle(config: &Config, bundle: Bundle, output_path: &Path, should_minify: bool) -> Result<(), MberError> {
+    let file_name = output_path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+
+    if !should_write_source_maps(config) {
+        fs::remove_file(format!("{}.map", output_path.display())).unwrap_or_else(|_| {});
+
+        return fs::write(output_path, minify_bundle(bundle.code, &file_name, should_minify)?)
+            .map_err(MberError::io("could not write", output_path));
+    }
+
+    let (code, map) = match should_minify {
+        true => {
+            let (minified_code, minified_map) = minify_js::from_string_with_source_map(&bundle.code, &file_name)?;
+
+            log_minified_size(&file_name, bundle.code.len(), minified_code.len());
+
+            (minified_code, minified_map.compose(&bundle.map))
+        },
+        false => (bundle.code, bundle.map)
+    };
+
+    let map_path = format!("{}.map", output_path.display());
+
+    fs::write(&map_path, map.to_json()).map_err(MberError::io("could not write", &map_path))?;
+
+    return fs::write(output_path, format!("{}\n//# sourceMappingURL={}.map\n", code, file_name))
+        .map_err(MberError::io("could not write", output_path));
+}
+
+// NOTE: every step runs to the end, the first failing step in order is reported
+fn run_in_parallel(build_steps: Vec<BuildStep>) -> Result<Vec<LintedFile>, MberError> {
+    let results = build_steps.par_iter()
+        .map(|build_step| build_step())
+        .collect::<Vec<Result<Vec<LintedFile>, MberError>>>();
+
+    return results.into_iter().collect::<Result<Vec<Vec<LintedFile>>, MberError>>().map(|linted_files| linted_files.concat());
+}
+
 #[cfg(test)]
 mod tests {
     use std::env;
+    use std::error::Error;
     use super::*;
-    use std::path::PathBuf;
+    use std::path::{Path, PathBuf};
+    use super::super::transpilers::import_addon_folder_to_amd;
     use serde_json::json;
     use std::collections::HashMap;
     use super::super::types::BuildCache;
 
     fn setup_test() -> Result<(PathBuf, String, String, String, String, String, String, String, String, String, String), Box<dyn Error>> {
         let current_directory = env::current_dir()?;
-        let project_directory = format!("{}/ember-app-boilerplate", current_directory.to_string_lossy());
+        let project_directory = import_addon_folder_to_amd::test_project()?.to_string_lossy().to_string();
 
         env::set_current_dir(&project_directory)?;
 
@@ -116,12 +271,13 @@ mod tests {
             application_css_output_path, index_html_output_path, memserver_output_path, tests_output_path,
             tests_support_js_path, tests_support_css_path, package_json_path
         ) = setup_test()?;
-        let config = Config::build(
+        let mut config = Config::build(
             json!({ "environment": "production", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
 
+        config.cache_directory = env::temp_dir().join("mber-cache-test");
         fs::create_dir_all(&output_directory)?;
 
         assert!(!fs::metadata(&application_js_output_path).is_ok());
@@ -137,10 +293,12 @@ mod tests {
         assert!(fs::metadata(application_css_output_path).is_ok());
         assert!(fs::metadata(index_html_output_path).is_ok());
         assert!(!fs::metadata(memserver_output_path).is_ok());
-        assert!(fs::metadata(tests_output_path).is_ok());
-        assert!(fs::metadata(tests_support_js_path).is_ok());
-        assert!(fs::metadata(tests_support_css_path).is_ok());
+        assert!(!fs::metadata(tests_output_path).is_ok());
+        assert!(!fs::metadata(tests_support_js_path).is_ok());
+        assert!(!fs::metadata(tests_support_css_path).is_ok());
         assert!(fs::metadata(package_json_path).is_ok());
+        assert_eq!(transpile_cache::directory(), config.cache_directory);
+        assert!(transpile_cache::stats()?.entries > 0);
 
         return finalize_test(current_directory);
     }
@@ -167,7 +325,7 @@ mod tests {
             }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
 
         fs::remove_dir_all(&output_directory).unwrap_or_else(|_| {});
 
@@ -184,9 +342,9 @@ mod tests {
         assert!(fs::metadata(application_css_output_path).is_ok());
         assert!(fs::metadata(index_html_output_path).is_ok());
         assert!(!fs::metadata(memserver_output_path).is_ok());
-        assert!(fs::metadata(tests_output_path).is_ok());
-        assert!(fs::metadata(tests_support_js_path).is_ok());
-        assert!(fs::metadata(tests_support_css_path).is_ok());
+        assert!(!fs::metadata(tests_output_path).is_ok());
+        assert!(!fs::metadata(tests_support_js_path).is_ok());
+        assert!(!fs::metadata(tests_support_css_path).is_ok());
         assert!(fs::metadata(package_json_path).is_ok());
 
         return finalize_test(current_directory);
@@ -217,7 +375,7 @@ mod tests {
             }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
 
         fs::remove_dir_all(&output_directory).unwrap_or_else(|_| {});
 
@@ -236,9 +394,9 @@ mod tests {
         assert!(fs::metadata(application_css_output_path).is_ok());
         assert!(fs::metadata(index_html_output_path).is_ok());
         assert!(fs::metadata(memserver_output_path).is_ok());
-        assert!(fs::metadata(tests_output_path).is_ok());
-        assert!(fs::metadata(tests_support_js_path).is_ok());
-        assert!(fs::metadata(tests_support_css_path).is_ok());
+        assert!(!fs::metadata(tests_output_path).is_ok());
+        assert!(!fs::metadata(tests_support_js_path).is_ok());
+        assert!(!fs::metadata(tests_support_css_path).is_ok());
         assert!(fs::metadata(package_json_path).is_ok());
 
         return finalize_test(current_directory);
@@ -272,7 +430,7 @@ mod tests {
             }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
         config.cli_arguments.testing = true;
 
         fs::remove_dir_all(&output_directory).unwrap_or_else(|_| {});
@@ -296,10 +454,81 @@ mod tests {
         assert!(fs::metadata(index_html_output_path).is_ok());
         assert!(fs::metadata(memserver_output_path).is_ok());
         assert!(fs::metadata(tests_output_path).is_ok());
-        assert!(fs::metadata(tests_support_js_path).is_ok());
         assert!(fs::metadata(tests_support_css_path).is_ok());
         assert!(fs::metadata(package_json_path).is_ok());
 
+        let test_support_js_map: Value = serde_json::from_str(&fs::read_to_string(format!("{}.map", &tests_support_js_path))?)?;
+
+        assert!(fs::read_to_string(tests_support_js_path)?.ends_with("\n//# sourceMappingURL=test-support.js.map\n"));
+        assert_eq!(test_support_js_map["sources"], json!([
+            "_vendor/test-support.js", "_vendor/ember-template-compiler.js", "_vendor/htmlbars-inline-precompile.js"
+        ]));
+
+        return finalize_test(current_directory);
+    }
+
+    #[test]
+    fn build_all_assets_and_dist_folder_are_reproducible() -> Result<(), Box<dyn Error>> {
+        let (current_directory, output_directory, ..) = setup_test()?;
+        let dist_directory = PathBuf::from(&output_directory).with_file_name("dist");
+        let mut config = Config::build(
+            json!({
+                "environment": "production",
+                "modulePrefix": "frontend",
+                "memserver": { "enabled": true },
+                "documentation": { "enabled": true, "path": "/styleguide" }
+            }),
+            HashMap::new(),
+            BuildCache::new()
+        )?;
+
+        config.build_timestamp = 1571000000;
+        fs::remove_dir_all(&dist_directory).unwrap_or_else(|_| {});
+        build_all_assets(&config)?;
+        dist_folder::build(&config)?;
+
+        let first_assets = reproducibility::snapshot(Path::new(&output_directory))?;
+        let first_dist = reproducibility::snapshot(&dist_directory)?;
+
+        build_all_assets(&config)?;
+        dist_folder::build(&config)?;
+
+        let asset_differences = reproducibility::differences(&first_assets, &reproducibility::snapshot(Path::new(&output_directory))?);
+        let dist_differences = reproducibility::differences(&first_dist, &reproducibility::snapshot(&dist_directory)?);
+
+        fs::remove_dir_all(&dist_directory)?;
+
+        assert!(first_dist.keys().any(|file_name| file_name.starts_with("assets/documentation-")));
+        assert_eq!(asset_differences, Vec::<String>::new());
+        assert_eq!(dist_differences, Vec::<String>::new());
+
         return finalize_test(current_directory);
     }
+
+    #[test]
+    fn run_in_parallel_runs_every_step_and_reports_the_first_error() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let finished_steps = AtomicUsize::new(0);
+        let step = |error: Option<&'static str>| -> BuildStep {
+            let finished_steps = &finished_steps;
+
+            return Box::new(move || {
+                finished_steps.fetch_add(1, Ordering::SeqCst);
+
+                return match error {
+                    Some(message) => Err(MberError::build("tmp/assets", message)),
+                    None => Ok(Vec::new())
+                };
+            });
+        };
+
+        assert!(run_in_parallel(vec![step(None), step(None)]).is_ok());
+        assert_eq!(
+            run_in_parallel(vec![step(None), step(Some("css failed")), step(None), step(Some("vendor failed"))])
+                .unwrap_err().to_string(),
+            "css failed"
+        );
+        assert_eq!(finished_steps.load(Ordering::SeqCst), 6);
+    }
 }
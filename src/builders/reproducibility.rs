@@ -0,0 +1,90 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use walkdir::WalkDir;
+use super::super::types::MberError;
+use super::super::utils::digest;
+
+// NOTE: maps every file in the directory to the sha256 of its content, keyed by its path relative to the directory
+pub fn snapshot(directory: &Path) -> Result<BTreeMap<String, String>, MberError> {
+    return WalkDir::new(directory).sort_by(|a, b| a.file_name().cmp(b.file_name())).into_iter()
+        .try_fold(BTreeMap::new(), |mut result, entry| {
+            let entry = entry.map_err(|error| MberError::io("could not read", directory)(io::Error::from(error)))?;
+
+            if entry.file_type().is_file() {
+                let content = fs::read(entry.path()).map_err(MberError::io("could not read", entry.path()))?;
+                let relative_path = entry.path().strip_prefix(directory).unwrap_or(entry.path()).to_string_lossy().to_string();
+
+                result.insert(relative_path, digest::to_hex(&digest::sha256(&content)));
+            }
+
+            return Ok(result);
+        });
+}
+
+pub fn differences(first_build: &BTreeMap<String, String>, second_build: &BTreeMap<String, String>) -> Vec<String> {
+    let changed_files = first_build.iter().filter_map(|(file_name, hash)| {
+        return match second_build.get(file_name) {
+            Some(second_hash) if second_hash == hash => None,
+            Some(_) => Some(format!("{} differs", file_name)),
+            None => Some(format!("{} is missing in the second build", file_name))
+        };
+    });
+    let added_files = second_build.keys()
+        .filter(|file_name| !first_build.contains_key(*file_name))
+        .map(|file_name| format!("{} is missing in the first build", file_name));
+
+    return changed_files.chain(added_files).collect();
+}
+
+pub fn verify(directory: &Path, first_build: &BTreeMap<String, String>) -> Result<(), MberError> {
+    let differences = differences(first_build, &snapshot(directory)?);
+
+    if differences.is_empty() {
+        return Ok(());
+    }
+
+    return Err(MberError::build(directory, format!(
+        "build is not reproducible, {} files changed between two builds:\n{}", differences.len(), differences.join("\n")
+    )));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::error::Error;
+    use super::*;
+
+    #[test]
+    fn verify_reports_the_files_that_changed_between_builds() -> Result<(), Box<dyn Error>> {
+        let directory = env::temp_dir().join("mber-reproducibility-test");
+
+        fs::remove_dir_all(&directory).unwrap_or_else(|_| {});
+        fs::create_dir_all(directory.join("assets"))?;
+        fs::write(directory.join("index.html"), "<html></html>")?;
+        fs::write(directory.join("assets/vendor.js"), "window.vendor = true;")?;
+
+        let first_build = snapshot(&directory)?;
+
+        assert_eq!(first_build.keys().collect::<Vec<&String>>(), vec!["assets/vendor.js", "index.html"]);
+        assert!(verify(&directory, &first_build).is_ok());
+
+        fs::write(directory.join("assets/vendor.js"), "window.vendor = false;")?;
+        fs::write(directory.join("assets/application.js"), "window.application = true;")?;
+        fs::remove_file(directory.join("index.html"))?;
+
+        let error = verify(&directory, &first_build).unwrap_err();
+
+        fs::remove_dir_all(&directory)?;
+
+        assert_eq!(error.to_string(), vec![
+            "build is not reproducible, 3 files changed between two builds:",
+            "assets/vendor.js differs",
+            "index.html is missing in the second build",
+            "assets/application.js is missing in the first build"
+        ].join("\n"));
+
+        return Ok(());
+    }
+}
@@ -1,56 +1,56 @@
 use std::time::Instant;
 use std::path::PathBuf;
-use std::str::FromStr;
 use std::result::Result;
-use std::error::Error;
 use std::fs;
 use yansi::Paint;
 use super::super::utils::{console, recursive_file_lookup, file};
+use super::super::utils::source_map::Bundle;
 use super::super::transpilers;
-use super::super::types::Config;
+use super::super::linters::{self, LintedFile};
+use super::super::types::{Config, MberError};
 
-// NOTE: eslint in rust(This one is challenging)
-pub fn build(config: &Config, _lint: bool) -> Result<(String, fs::Metadata), Box<dyn Error>> {
+pub fn build(config: &Config, lint: bool) -> Result<(String, fs::Metadata, Vec<LintedFile>), MberError> {
     console::log(format!("{} tests.js...", Paint::yellow("BUILDING:")));
 
     let build_start = Instant::now();
     let environment = config.env["environment"].as_str().unwrap_or("development");
     let project_root = &config.project_root.display();
-    let output_path = PathBuf::from_str(format!("{}/tmp/assets/tests.js", &project_root).as_str())?;
+    let output_path = PathBuf::from(format!("{}/tmp/assets/tests.js", &project_root));
     let should_minify = vec!["production", "demo"].contains(&environment);
-    let tests_folder_code = recursive_file_lookup::lookup_for_extensions(
-        &PathBuf::from_str(format!("{}/tests", &project_root).as_str())?,
+    let tests_folder_files = recursive_file_lookup::lookup_for_extensions(
+        &PathBuf::from(format!("{}/tests", &project_root)),
         vec![".js", ".ts"]
-    ).into_iter()
-    .map(|file| transpilers::convert_es_module::from_file(&file, should_minify))
-    .collect::<Vec<&str>>()
-    .join("\n");
-    let app_folder_test_code = recursive_file_lookup::lookup_for_extensions_and_predicate(
-        &PathBuf::from_str(format!("{}/src", &project_root).as_str())?,
+    )?;
+    let app_folder_test_files = recursive_file_lookup::lookup_for_extensions_and_predicate(
+        &PathBuf::from(format!("{}/src", &project_root)),
         vec!["js", "ts"],
         |entry| {
-            let file_name = entry.file_name().to_str().unwrap();
+            let file_name = entry.file_name().to_string_lossy();
 
             return file_name.ends_with("-test.js") || file_name.ends_with("-test.ts");
         }
-    ).into_iter()
-    .map(|file| transpilers::convert_es_module::from_file(&file, should_minify))
-    .collect::<Vec<&str>>()
-    .join("\n");
-    let code = format!(
-        "define = window.define; {}
-        {}
+    )?;
+    let files = tests_folder_files.into_iter().chain(app_folder_test_files.into_iter()).collect::<Vec<PathBuf>>();
+    let modules = transpilers::convert_es_module::from_files(&files, &config.project_root, &config.application_name)?;
+    let linted_files = match lint {
+        true => linters::check_files(&files, config, "tests.js")?,
+        false => Vec::new()
+    };
+    let mut bundle = Bundle::new("tests.js");
+
+    bundle.push(&format!("define = window.define; {}
+        ", config.build_cache.test_prepends));
+    super::push_files(&mut bundle, &files, &modules, &config.project_root)?;
+    bundle.push(&format!("
         window.require('{}/tests/test-helper');
         EmberENV.TESTS_FILE_LOADED = true;
         {}
-        ", config.build_cache.test_prepends, vec![tests_folder_code, app_folder_test_code].join("\n"),
-        config.application_name, config.build_cache.test_appends);
-
-    fs::write(&output_path, code)?;
+        ", config.application_name, config.build_cache.test_appends));
+    super::write_bundle(config, bundle, &output_path, should_minify)?;
 
     // TODO: in future create a thread global build error to say/stop tts on error
 
-    let output_metadata = fs::metadata(output_path)?;
+    let output_metadata = fs::metadata(&output_path).map_err(MberError::io("could not read", &output_path))?;
     let message = format!(
         "{} tests.js in {} [{}] Environment: {}",
         Paint::green("BUILT:"),
@@ -61,13 +61,12 @@ pub fn build(config: &Config, _lint: bool) -> Result<(String, fs::Metadata), Box
 
     console::log(&message);
 
-    // NOTE: then linting
-
-    return Ok((message, output_metadata));
+    return Ok((message, output_metadata, linted_files));
 }
 
 #[cfg(test)]
 mod tests {
+    use std::error::Error;
     use std::env;
     use super::*;
     use std::path::PathBuf;
@@ -113,8 +112,8 @@ mod tests {
             json!({ "environment": "development", "moduleprefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
-        let (message, _stats) = build(&config, false)?; // NOTE: config and lint
+        )?;
+        let (message, _stats, _) = build(&config, false)?; // NOTE: config and lint
         let build_time_in_ms = Regex::new(r"tests\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
             .replace("tests.js in ", "")
@@ -142,8 +141,8 @@ mod tests {
             json!({ "environment": "production", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
-        let (message, _stats) = build(&config, false)?; // NOTE: config and lint
+        )?;
+        let (message, _stats, _) = build(&config, false)?; // NOTE: config and lint
         let build_time_in_ms = Regex::new(r"tests\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
             .replace("tests.js in ", "")
@@ -171,8 +170,8 @@ mod tests {
             json!({ "environment": "custom", "modulePrefix": "my-app" }),
             HashMap::new(),
             BuildCache::new()
-        );
-        let (message, _stats) = build(&config, false)?; // NOTE: config and lint
+        )?;
+        let (message, _stats, _) = build(&config, false)?; // NOTE: config and lint
         let build_time_in_ms = Regex::new(r"tests\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
             .replace("tests.js in ", "")
@@ -200,8 +199,8 @@ mod tests {
             json!({ "environment": "development", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new().insert("test_prepends", CODE_TO_PREPEND)
-        );
-        let (message, _stats) = build(&config, false)?; // NOTE: config and lint
+        )?;
+        let (message, _stats, _) = build(&config, false)?; // NOTE: config and lint
         let build_time_in_ms = Regex::new(r"tests\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
             .replace("tests.js in ", "")
@@ -228,8 +227,8 @@ mod tests {
             json!({ "environment": "development", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new().insert("test_appends", CODE_TO_APPEND)
-        );
-        let (message, _stats) = build(&config, false)?; // NOTE: config and lint
+        )?;
+        let (message, _stats, _) = build(&config, false)?; // NOTE: config and lint
         let build_time_in_ms = Regex::new(r"tests\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
             .replace("tests.js in ", "")
@@ -258,8 +257,8 @@ mod tests {
             BuildCache::new()
                 .insert("test_prepends", CODE_TO_PREPEND)
                 .insert("test_appends", CODE_TO_APPEND)
-        );
-        let (message, _stats) = build(&config, false)?; // NOTE: config and lint
+        )?;
+        let (message, _stats, _) = build(&config, false)?; // NOTE: config and lint
         let build_time_in_ms = Regex::new(r"tests\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
             .replace("tests.js in ", "")
@@ -1,62 +1,107 @@
 use std::time::Instant;
 use std::path::PathBuf;
-use std::str::FromStr;
 use std::result::Result;
-use std::error::Error;
 use std::fs;
 use yansi::Paint;
+use regex::Regex;
 use serde_json::{value::Value};
 use super::super::utils::{console, file};
-use super::super::transpilers::{import_addon_folder_to_amd}; // also convert_es_module
-use super::super::types::Config;
+use super::super::utils::source_map::Bundle;
+use super::super::transpilers::{convert_es_module, import_addon_folder_to_amd};
+use super::super::types::{Config, MberError};
+use super::analyzer::{self, Part};
 
 // NOTE: has hard dependency on ember-data(when needed) and ember-cli-fastboot
 // TODO: content/module check tests
-pub fn build(config: &Config) -> Result<(String, fs::Metadata), Box<dyn Error>> {
+pub fn build(config: &Config) -> Result<(String, fs::Metadata), MberError> {
     console::log(format!("{} vendor.js...", Paint::yellow("BUILDING:")));
 
     let build_start = Instant::now();
     let environment = config.env["environment"].as_str().unwrap_or("development");
     let project_root = &config.project_root.display();
-    let output_path = PathBuf::from_str(format!("{}/tmp/assets/vendor.js", &project_root).as_str())?;
-    let _should_minify = vec!["production", "demo"].contains(&environment);
+    let output_path = PathBuf::from(format!("{}/tmp/assets/vendor.js", &project_root));
+    let should_minify = vec!["production", "demo"].contains(&environment);
     let should_exclude_ember_data = &config.env["excludeEmberData"].as_bool().unwrap_or(false);
 
-    let mut content = vec![
-        get_right_ember_base_string(&config.env, &should_exclude_ember_data),
-        if should_exclude_ember_data == &true {
+    let (ember_base_name, ember_base) = get_right_ember_base_string(&config.env, &should_exclude_ember_data);
+    let mut parts = vec![
+        Part::new("ember base", ember_base_name, &ember_base),
+        Part::new("ember base", "_vendor/ember-modules-api.js", include_str!("../../_vendor/ember-modules-api.js")),
+        Part::new("ember-data", "ember-data/app", &if should_exclude_ember_data == &true {
             String::from("")
         } else {
-            import_addon_folder_to_amd::to_string("ember-data/app", &config)
-        },
-        String::from_utf8(include_bytes!("../../_vendor/mber-documentation/index.js").to_vec())?
-    ].join("\n");
-
+            import_addon_folder_to_amd::to_string("ember-data/app", &config)?
+        }),
+        Part::new(
+            "mber-documentation",
+            "_vendor/mber-documentation/index.js",
+            &include_str!("../../_vendor/mber-documentation/index.js").to_string()
+        )
+    ];
     if config.cli_arguments.fastboot {
-        let fastboot_initializer_code = match &config.env["memserver"]["enabled"].as_bool() { // TODO: this needs to be converted with convert_es_module::to_amd as initializer
-            Some(_) => String::from_utf8(include_bytes!("../../_vendor/memserver/fastboot/initializers/ajax.js").to_vec())?,
-            None => String::from_utf8(include_bytes!("../../_vendor/fastboot/initializers/ajax.js").to_vec())?
-        };
-        content.push_str(vec![
-            String::from_utf8(include_bytes!("../../_vendor/fastboot/fastboot-addon-modules.js").to_vec())?,
-            String::from_utf8(include_bytes!("../../_vendor/fetch/fetch-fastboot-shim.js").to_vec())?,
-            fastboot_initializer_code,
-            import_addon_folder_to_amd::to_string("ember-cli-fastboot/app", &config)
-        ].join("\n").as_str());
+        let fastboot_initializer_code = convert_es_module::from_string(
+            match &config.env["memserver"]["enabled"].as_bool() {
+                Some(_) => include_str!("../../_vendor/memserver/fastboot/initializers/ajax.js"),
+                None => include_str!("../../_vendor/fastboot/initializers/ajax.js")
+            },
+            format!("{}/src/init/initializers/ajax", &config.application_name).as_str()
+        )?;
+        let fastboot_parts = vec![
+            Part::new(
+                "fastboot",
+                "_vendor/fastboot/fastboot-addon-modules.js",
+                &include_str!("../../_vendor/fastboot/fastboot-addon-modules.js").to_string()
+            ),
+            Part::new(
+                "fastboot",
+                "_vendor/fetch/fetch-fastboot-shim.js",
+                &include_str!("../../_vendor/fetch/fetch-fastboot-shim.js").to_string()
+            ),
+            Part::new("fastboot", "src/init/initializers/ajax", &fastboot_initializer_code),
+            Part::new("fastboot", "ember-cli-fastboot/app", &import_addon_folder_to_amd::to_string_skipping(
+                "ember-cli-fastboot/app", &config, &defined_module_names(include_str!("../../_vendor/fastboot/fastboot-addon-modules.js"))
+            )?)
+        ];
+
+        parts.extend(fastboot_parts);
     }
 
-    fs::write(&output_path, format!("{}
+    let prepend_code = format!("{}
         window.EmberENV = JSON.parse({});
         window.runningTests = !!(window.location && (window.location.pathname === '/tests') && (EmberENV.environment !== 'production'));
+        ", &config.build_cache.vendor_prepends, Value::String(config.env.to_string()));
+    let append_code = format!("
         {}
         {}
-        {}
-    ", &config.build_cache.vendor_prepends, &config.env.to_string(), content,
-    add_socket_watch_code(&config.cli_arguments.port), &config.build_cache.vendor_appends))?; // TODO: maybe minify here on demand
+    ", match config.cli_arguments.live_reload {
+        true => add_socket_watch_code(&config.cli_arguments.socket_port),
+        false => String::from("")
+    }, &config.build_cache.vendor_appends);
+
+    let mut bundle = Bundle::new("vendor.js");
+    let sources = parts.iter()
+        .filter(|part| !part.code.is_empty())
+        .map(|part| (part.name.clone(), part.code.clone(), part.code.clone()))
+        .collect::<Vec<(String, String, String)>>();
+
+    bundle.push(&prepend_code);
+    super::push_sources(&mut bundle, &sources);
+    bundle.push(&append_code);
+    super::write_bundle(config, bundle, &output_path, should_minify)?;
+
+    if analyzer::should_analyze(config) {
+        let parts = std::iter::once(Part::new("mber", "vendor.js prepends and EmberENV", &prepend_code))
+            .chain(analyzer::split_modules(parts).into_iter().map(|part| Part { segment: vendor_segment(&part), ..part }))
+            .chain(std::iter::once(Part::new("mber", "vendor.js appends", &append_code)))
+            .filter(|part| !part.code.is_empty())
+            .collect();
+
+        analyzer::write_bundle_analysis(config, "vendor.js", parts)?;
+    }
 
     // TODO: in future create a thread global build error to say/stop tts on error
 
-    let output_metadata = fs::metadata(output_path)?;
+    let output_metadata = fs::metadata(&output_path).map_err(MberError::io("could not read", &output_path))?;
     let message = format!(
         "{} vendor.js in {} [{}] Environment: {}",
         Paint::green("BUILT:"),
@@ -70,21 +115,52 @@ pub fn build(config: &Config) -> Result<(String, fs::Metadata), Box<dyn Error>>
     return Ok((message, output_metadata));
 }
 
-fn get_right_ember_base_string(env: &Value, should_exclude_ember_data: &bool) -> String {
-    match (should_exclude_ember_data, vec!["production", "demo"].contains(&env["environment"].as_str().unwrap())) {
-        (true, true) => String::from_utf8(include_bytes!("../../_vendor/no-ember-data-ember-prod.js").to_vec()).unwrap(),
-        (true, false) => String::from_utf8(include_bytes!("../../_vendor/no-ember-data-ember-debug.js").to_vec()).unwrap(),
-        (false, true) => String::from_utf8(include_bytes!("../../_vendor/full-ember-prod.js").to_vec()).unwrap(),
-        (false, false) => String::from_utf8(include_bytes!("../../_vendor/full-ember-debug.js").to_vec()).unwrap()
+fn get_right_ember_base_string(env: &Value, should_exclude_ember_data: &bool) -> (&'static str, String) {
+    match (should_exclude_ember_data, vec!["production", "demo"].contains(&env["environment"].as_str().unwrap_or("development"))) {
+        (true, true) => ("_vendor/no-ember-data-ember-prod.js", include_str!("../../_vendor/no-ember-data-ember-prod.js").to_string()),
+        (true, false) => ("_vendor/no-ember-data-ember-debug.js", include_str!("../../_vendor/no-ember-data-ember-debug.js").to_string()),
+        (false, true) => ("_vendor/full-ember-prod.js", include_str!("../../_vendor/full-ember-prod.js").to_string()),
+        (false, false) => ("_vendor/full-ember-debug.js", include_str!("../../_vendor/full-ember-debug.js").to_string())
     }
 }
 
+// NOTE: names of the modules a prebuilt vendor file defines, addon folders skip them to not define a module twice
+fn defined_module_names(code: &str) -> Vec<String> {
+    let define_regex = Regex::new(r#"\bdefine\(\s*["']([^"'\s]+)["']"#).unwrap();
+
+    return define_regex.captures_iter(code).map(|captures| captures[1].to_string()).collect();
+}
+
+// NOTE: the ember base bundles ember-data, its modules are reported as their own segment
+fn vendor_segment(part: &Part) -> String {
+    let is_ember_data_module = vec!["ember-data", "@ember-data/", "ember-inflector"].iter()
+        .any(|prefix| part.name.starts_with(prefix));
+
+    return match is_ember_data_module && part.segment == "ember base" {
+        true => String::from("ember-data"),
+        false => part.segment.clone()
+    };
+}
+
+// NOTE: css messages swap the matching stylesheets in place, any other message reloads the page
 fn add_socket_watch_code(socket_port: &u16) -> String {
   return format!("
     if (typeof FastBoot === 'undefined') {{
-      window.socket = new WebSocket('ws://localhost:{}');
+      window.socket = new WebSocket('ws://' + (window.location.hostname || 'localhost') + ':{}');
 
       window.socket.addEventListener('message', function(event) {{
+        var message = JSON.parse(event.data);
+
+        if (message.type === 'css') {{
+          return document.querySelectorAll('link[rel=\"stylesheet\"]').forEach(function(link) {{
+            var path = link.getAttribute('href').split('?')[0];
+
+            if (message.files.indexOf(path) !== -1) {{
+              link.setAttribute('href', path + '?' + Date.now());
+            }}
+          }});
+        }}
+
         document.querySelectorAll('.ember-view').forEach((e) => e.remove());
         window.location.reload(true);
       }});
@@ -94,6 +170,7 @@ fn add_socket_watch_code(socket_port: &u16) -> String {
 
 #[cfg(test)]
 mod tests {
+    use std::error::Error;
     use std::env;
     use super::*;
     use std::path::PathBuf;
@@ -110,7 +187,7 @@ mod tests {
 
     fn setup_test() -> Result<(PathBuf, String, String), Box<dyn Error>> {
         let current_directory = env::current_dir()?;
-        let project_directory = format!("{}/ember-app-boilerplate", current_directory.to_string_lossy());
+        let project_directory = import_addon_folder_to_amd::test_project()?.to_string_lossy().to_string();
         let vendor_js_output_path = format!("{}/tmp/assets/vendor.js", &project_directory);
 
         Paint::disable();
@@ -135,11 +212,11 @@ mod tests {
 
         assert_eq!(fs::metadata(&vendor_js_output_path).is_ok(), false);
 
-        let config = Config::build(
+        let mut config = Config::build(
             json!({ "environment": "development", "moduleprefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
         let (message, _stats) = build(&config)?;
         let build_time_in_ms = Regex::new(r"vendor\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
@@ -147,14 +224,23 @@ mod tests {
             .replace("ms", "")
             .parse::<u32>()?;
 
-        assert!(build_time_in_ms < VENDOR_JS_BUILD_TIME_THRESHOLD);
+        let vendor_js_code = fs::read_to_string(&vendor_js_output_path)?;
 
-        // let vendor_js_code = fs::read_to_string(vendor_js_output_path).unwrap();
-        // todo: content checks
+        assert!(build_time_in_ms < VENDOR_JS_BUILD_TIME_THRESHOLD);
+        assert!(vendor_js_code.contains("define(\"ember-data/initializers/ember-data\", [\"exports\", \"ember-data/setup-container\", \"ember-data\"]"));
+        assert_eq!(vendor_js_code.matches("define(\"ember-cli-fastboot/instance-initializers/clear-double-boot\"").count(), 1);
+        assert!(!vendor_js_code.contains("window.socket = new WebSocket"));
 
-        assert!(fs::metadata(vendor_js_output_path)?.len() >= VENDOR_JS_TARGET_BYTE_SIZE - 1000);
+        assert!(fs::metadata(&vendor_js_output_path)?.len() >= VENDOR_JS_TARGET_BYTE_SIZE - 1000);
         assert!(Regex::new(r"BUILT: vendor\.js in \d+ms \[\d+.\d+ MB\] Environment: development")?.find(&message).is_some());
 
+        config.cli_arguments.live_reload = true;
+        build(&config)?;
+
+        assert!(fs::read_to_string(&vendor_js_output_path)?.contains(
+            "window.socket = new WebSocket('ws://' + (window.location.hostname || 'localhost') + ':65511');"
+        ));
+
         return finalize_test(current_directory);
     }
 
@@ -168,7 +254,7 @@ mod tests {
             json!({ "environment": "production", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
         let (message, _stats) = build(&config)?;
         let build_time_in_ms = Regex::new(r"vendor\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
@@ -176,10 +262,15 @@ mod tests {
             .replace("ms", "")
             .parse::<u32>()?;
 
-        assert!(build_time_in_ms < VENDOR_JS_BUILD_TIME_THRESHOLD);
+        let vendor_js_code = fs::read_to_string(&vendor_js_output_path)?;
+        let vendor_js_map: Value = serde_json::from_str(&fs::read_to_string(format!("{}.map", &vendor_js_output_path))?)?;
+        let sources = vendor_js_map["sources"].as_array().unwrap();
 
-        // let vendor_js_code = fs::read_to_string(vendor_js_output_path).unwrap();
-        // TODO: content checks
+        assert!(build_time_in_ms < VENDOR_JS_BUILD_TIME_THRESHOLD);
+        assert!(!vendor_js_code.contains("window.socket = new WebSocket"));
+        assert!(vendor_js_code.ends_with("\n//# sourceMappingURL=vendor.js.map\n"));
+        assert_eq!(sources[0..2].to_vec(), vec![json!("_vendor/full-ember-prod.js"), json!("_vendor/ember-modules-api.js")]);
+        assert!(sources.contains(&json!("_vendor/fastboot/fastboot-addon-modules.js")));
 
         assert!(fs::metadata(vendor_js_output_path)?.len() >= VENDOR_JS_COMPRESSED_TARGET_BYTE_SIZE - 1000);
         assert!(Regex::new(r"BUILT: vendor\.js in \d+ms \[\d+.\d+ kB\] Environment: production")?.find(&message).is_some());
@@ -187,6 +278,35 @@ mod tests {
         return finalize_test(current_directory);
     }
 
+    #[test]
+    fn build_writes_the_bundle_analysis_with_separate_vendor_segments() -> Result<(), Box<dyn Error>> {
+        let (current_directory, vendor_js_output_path, _) = setup_test()?;
+        let mut config = Config::build(
+            json!({ "environment": "production", "modulePrefix": "frontend" }),
+            HashMap::new(),
+            BuildCache::new()
+        )?;
+        config.cli_arguments.analyze = true;
+
+        build(&config)?;
+
+        let analysis: Value = serde_json::from_str(&fs::read_to_string("tmp/analysis/vendor.js.json")?)?;
+        let segments = analysis["segments"].as_array().unwrap();
+        let segment_names = segments.iter().map(|segment| segment["name"].as_str().unwrap()).collect::<Vec<&str>>();
+        let ember_data_modules = &segments[segment_names.iter().position(|name| *name == "ember-data").unwrap()]["modules"];
+
+        assert_eq!(segment_names[0..2].to_vec(), vec!["ember base", "ember-data"]);
+        assert!(segment_names.contains(&"mber-documentation") && segment_names.contains(&"fastboot"));
+        assert!(segments[0]["modules"].as_array().unwrap().iter().any(|module| module["name"] == json!("@ember/-internals/glimmer/index")));
+        assert!(ember_data_modules.as_array().unwrap().iter().any(|module| module["name"] == json!("@ember-data/store/-private/system/store")));
+        let vendor_js_code = fs::read_to_string(&vendor_js_output_path)?;
+        let vendor_js_size = vendor_js_code.trim_end_matches("\n//# sourceMappingURL=vendor.js.map\n").len();
+
+        assert!((analysis["minified_size"].as_u64().unwrap() as i64 - vendor_js_size as i64).abs() < 16);
+
+        return finalize_test(current_directory);
+    }
+
     #[test]
     fn build_works_for_development_without_ember_data() -> Result<(), Box<dyn Error>> {
         let (current_directory, vendor_js_output_path, _) = setup_test()?;
@@ -197,7 +317,7 @@ mod tests {
             json!({ "excludeEmberData": true, "environment": "development", "moduleprefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
         let (message, _stats) = build(&config)?;
         let build_time_in_ms = Regex::new(r"vendor\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
@@ -226,7 +346,7 @@ mod tests {
             json!({ "excludeEmberData": true, "environment": "production", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
         let (message, _stats) = build(&config)?;
         let build_time_in_ms = Regex::new(r"vendor\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
@@ -257,7 +377,7 @@ mod tests {
             json!({ "environment": "development", "moduleprefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
 
         config.cli_arguments.fastboot = false;
 
@@ -267,11 +387,11 @@ mod tests {
             .replace("vendor.js in ", "")
             .replace("ms", "")
             .parse::<u32>()?;
+        let vendor_js_code = fs::read_to_string(&vendor_js_output_path)?;
 
         assert!(build_time_in_ms < VENDOR_JS_BUILD_TIME_THRESHOLD);
-
-        // let vendor_js_code = fs::read_to_string(vendor_js_output_path).unwrap();
-        // todo: content checks
+        assert!(vendor_js_code.contains("define(\"ember-data/initializers/ember-data\""));
+        assert!(!vendor_js_code.contains("define(\"ember-cli-fastboot/instance-initializers/clear-double-boot\""));
 
         assert!(fs::metadata(vendor_js_output_path)?.len() >= VENDOR_JS_TARGET_BYTE_SIZE - 1000);
         assert!(Regex::new(r"BUILT: vendor\.js in \d+ms \[\d+.\d+ MB\] Environment: development")?.find(&message).is_some());
@@ -289,7 +409,7 @@ mod tests {
             json!({ "environment": "production", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
 
         config.cli_arguments.fastboot = false;
 
@@ -321,7 +441,7 @@ mod tests {
             json!({ "environment": "custom", "moduleprefix": "my-app" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
         let (message, _stats) = build(&config)?;
         let build_time_in_ms = Regex::new(r"vendor\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
@@ -350,7 +470,7 @@ mod tests {
             json!({ "environment": "custom", "moduleprefix": "my-app" }),
             HashMap::new(),
             BuildCache::new()
-        );
+        )?;
 
         config.cli_arguments.fastboot = false;
 
@@ -382,7 +502,7 @@ mod tests {
             json!({ "environment": "development", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new().insert("vendor_prepends", CODE_TO_PREPEND)
-        );
+        )?;
         let (message, _stats) = build(&config)?;
         let build_time_in_ms = Regex::new(r"vendor\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
@@ -410,7 +530,7 @@ mod tests {
             json!({ "environment": "development", "modulePrefix": "frontend" }),
             HashMap::new(),
             BuildCache::new().insert("vendor_appends", CODE_TO_APPEND)
-        );
+        )?;
         let (message, _stats) = build(&config)?;
         let build_time_in_ms = Regex::new(r"vendor\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
@@ -440,7 +560,7 @@ mod tests {
             BuildCache::new()
                 .insert("vendor_prepends", CODE_TO_PREPEND)
                 .insert("vendor_appends", CODE_TO_APPEND)
-        );
+        )?;
         let (message, _stats) = build(&config)?;
         let build_time_in_ms = Regex::new(r"vendor\.js in \d+ms")?
             .find(message.as_str()).unwrap().as_str()
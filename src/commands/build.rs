@@ -1,44 +1,45 @@
-use std::process::Command;
-// use super::super::types::cli_arguments::CLIArguments;
-use mber::builders::{build_all_assets, dist_folder};
-use super::super::utils;
-use super::super::utils::{console};
-use mber::types::{Config, BuildCache};
-use std::collections::HashMap;
-use serde_json::json;
-
-pub fn run() -> std::io::Result<()> {
+use std::io::Error;
+use mber::builders::{analyzer, build_all_assets, config, dist_folder, reproducibility};
+use mber::types::CLIArguments;
+use super::super::utils::console;
+
+pub fn run(cli_arguments: CLIArguments) -> std::io::Result<()> {
     console::log("Building the application...");
 
-    // TODO: add ENV and config
-    let project_root = utils::find_project_root();
-    let project_root_path = project_root.to_string_lossy();
-    let _output = Command::new("node")
-        .args(&["-e", format!("
-            require('{}/index.js')({{ }});
-        ", project_root_path).as_str()])
-        .current_dir(project_root)
-        .spawn()
-        .expect("couldnt spawn node index.js on the project")
-        .wait_with_output()
-        .expect("couldnt run node index.js on the project");
-
-    // let cli_arguments = CLIArguments::new();
-    // TODO: get ENV
-
-    // println!("cli_arguments are {:?}", cli_arguments);
-
-    let config = Config::build(
-        json!({ "environment": "development", "modulePrefix": "frontend" }),
-        HashMap::new(),
-        BuildCache::new()
-    ); // NOTE: testing: true must be there
-
-    build_all_assets(&config).unwrap();
-    dist_folder::build(&config).unwrap();
-    // TODO: run {project_root}/index.js)(ENV) and then.. it returns buildConfig
-    // cast buildConfig JS Value to my rust types
-    // use that buildConfig to buildDistFolder(which triggers build functions and more)
+    let project_root = super::find_project_root();
+
+    let config = config::build(&project_root, cli_arguments)?;
+    let build_result = build_all_assets(&config);
+
+    super::print_lint_diagnostics(build_result.as_ref());
+    build_result?;
+    dist_folder::build(&config).map_err(|error| {
+        console::error(&error.to_string());
+
+        return Error::from(error);
+    })?;
+
+    // NOTE: both builds share the build_timestamp of the config so they embed the same time
+    if config.cli_arguments.verify_reproducible {
+        let output_directory = project_root.join("dist");
+        let first_build = reproducibility::snapshot(&output_directory)?;
+
+        console::log("Building the application again to verify that the build is reproducible...");
+        build_all_assets(&config)?;
+        dist_folder::build(&config)?;
+        reproducibility::verify(&output_directory, &first_build).map_err(|error| {
+            console::error(&error.to_string());
+
+            return Error::from(error);
+        })?;
+        console::log(format!("Build is reproducible, {} files are identical in both builds", first_build.len()));
+    }
+
+    if analyzer::should_analyze(&config) {
+        let report_path = analyzer::write_report(&config)?;
+
+        console::log(format!("Bundle analysis written to {}", report_path.display()));
+    }
 
     Ok(())
 }
@@ -0,0 +1,32 @@
+use std::io::{Error, ErrorKind};
+use yansi::Paint;
+use super::super::utils::{console, file, transpile_cache};
+
+pub fn run() -> std::io::Result<()> {
+    let directory = transpile_cache::directory();
+
+    match std::env::args().nth(2).as_ref().map(|subcommand| subcommand.as_str()) {
+        Some("clean") => {
+            let stats = transpile_cache::clean()?;
+
+            console::log(format!(
+                "{} {} cached modules [{}] from {}",
+                Paint::green("removed"), stats.entries, file::format_size(stats.size), directory.display()
+            ));
+        },
+        Some("stats") | None => {
+            let stats = transpile_cache::stats()?;
+
+            console::log(format!("{} {}", Paint::yellow("cache directory:"), directory.display()));
+            console::log(format!("{} {}", Paint::yellow("cached modules:"), stats.entries));
+            console::log(format!("{} {}", Paint::yellow("cache size:"), file::format_size(stats.size)));
+        },
+        Some(subcommand) => {
+            console::error(format!("unknown cache command {}, use mber cache stats or mber cache clean", subcommand));
+
+            return Err(Error::new(ErrorKind::InvalidInput, "Exiting with error"));
+        }
+    };
+
+    Ok(())
+}
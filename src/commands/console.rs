@@ -1,4 +1,35 @@
-pub fn run() -> std::io::Result<()> {
-    println!("run console command here");
+use std::env;
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+use yansi::Paint;
+use mber::builders::{build_all_assets, config};
+use mber::runners::console::{self as repl, ConsoleExit};
+use mber::types::CLIArguments;
+use super::super::utils::{console};
+
+pub fn run(cli_arguments: CLIArguments) -> std::io::Result<()> {
+    let project_root = super::find_project_root();
+    let history_path = env::var("HOME").ok().map(|home| PathBuf::from(home).join(".mber_console_history"));
+    let url = format!("http://localhost:{}/", cli_arguments.port);
+
+    loop {
+        console::log("Building the application for the console...");
+
+        let config = config::build(&project_root, cli_arguments.clone())
+            .map_err(|error| Error::new(ErrorKind::Other, error.to_string()))?;
+
+        let build_result = build_all_assets(&config);
+
+        super::print_lint_diagnostics(build_result.as_ref());
+        build_result.map_err(|error| Error::new(ErrorKind::Other, error.to_string()))?;
+        console::log(format!("{} {}", Paint::green("Application booted, it is available as"), Paint::yellow("window.App")));
+
+        match repl::start(&project_root, &url, history_path.as_ref().map(|path| path.as_path()))
+            .map_err(|error| Error::new(ErrorKind::Other, error.to_string()))? {
+            ConsoleExit::Reload => continue,
+            ConsoleExit::Quit => break
+        };
+    }
+
     Ok(())
 }
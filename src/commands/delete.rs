@@ -5,7 +5,6 @@ use std::io;
 use inflector::cases::snakecase::to_snake_case;
 use inflector::string::singularize::to_singular;
 use yansi::Paint;
-use super::super::utils;
 use super::super::utils::console;
 
 pub fn run() -> std::io::Result<()> {
@@ -36,7 +35,7 @@ pub fn run() -> std::io::Result<()> {
     }
 
     let name = remaining_args.join(" ");
-    let project_root = utils::find_project_root().to_string_lossy().to_string();
+    let project_root = super::find_project_root().to_string_lossy().to_string();
 
     match abstraction.as_str() {
         "component" => {
@@ -1,7 +1,6 @@
 use std::env;
 use std::fs::File;
 use std::process;
-use super::super::utils;
 use super::super::utils::console;
 use super::super::generators::{component, helper, initializer, instance_initializer, mixin, model, route, service, util};
 use serde_json;
@@ -35,7 +34,7 @@ pub fn run() -> std::io::Result<()> {
     }
 
     let name = remaining_args.join(" ");
-    let project_root = utils::find_project_root();
+    let project_root = super::find_project_root();
     let package_json: Value = serde_json::from_reader(
         File::open(format!("{}/package.json", project_root.to_string_lossy()))?
     )?;
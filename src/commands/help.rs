@@ -10,12 +10,40 @@ mber serve | server                # Starts your ember development server {}
 mber build | b                     # Builds your ember application and outputs to /dist folder
 mber console | c                   # Boots your ember application with DOM in a node.js repl
 mber test | t                      # Runs your ember tests {}
+mber cache [stats | clean]         # Shows or removes the cached transpiled modules
+mber lint [paths]                  # Lints the javascript, templates and styles of the project or of the given paths
 mber generate | g [type] [name]    # Generate ember files for certain abstraction type
-mber delete | d [type] [name]      # Remove ember files for certain abstraction type",
+mber delete | d [type] [name]      # Remove ember files for certain abstraction type
+
+{}
+--env=<environment>                # Environment to build for (Default: development)
+--port=<port>                      # Port of the development server (Default: 1234)
+--socket-port=<port>               # Port of the live-reload socket server (Default: 65511)
+--proxy=<url>                      # Proxies unknown requests to the given url
+--server | --no-server             # Runs tests and commands with or without a server
+--no-fastboot                      # Disables fastboot
+--no-watch                         # Disables file watching
+--debug                            # Logs extra debug information
+--talk | --no-talk                 # Turns build notifications on or off
+--no-source-maps                   # Leaves the .map files out of the build
+--compare=<stats.json>             # Shows the asset size changes against the stats file of another build
+--analyze                          # Writes the bundle composition to dist/analyze.html and dist/analyze.json
+--keep-previous=<count>            # Keeps the last <count> builds in dist-previous/ for rollbacks
+--verify-reproducible              # Builds twice and fails when the outputs differ
+--lint=<level> | --no-lint         # Lints javascript while building, error fails the build (Default: warn)
+--filter=<text>                    # Runs only the tests with names containing the text
+--module=<name>                    # Runs only the tests of the given module
+--reporter=<name>                  # Test output format: console, tap, junit or json (Default: console)
+--output=<path>                    # Writes the test or lint report to the given file
+--templates                        # Lints only the templates
+--fix                              # Fixes the lint problems that can be fixed automatically
+--format=<name>                    # Lint output format: stylish, json, checkstyle or sarif (Default: stylish)
+--changed=<git ref>                # Lints only the files changed since the git ref (Default: HEAD)",
         Paint::red("[mber CLI ".to_owned() + &version + &"] Usage:").bold(),
         Paint::yellow("<command (Default: help)>"),
         Paint::green("[alias: \"mber s\"]"),
-        Paint::green("(--server to run them in browser)")
+        Paint::green("(--server to run them in browser)"),
+        Paint::yellow("Options:")
     );
 
     Ok(())
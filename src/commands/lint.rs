@@ -0,0 +1,79 @@
+use std::env;
+use std::fs;
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+use yansi::Paint;
+use mber::builders::config;
+use mber::linters::{self, Severity};
+use mber::linters::reporter::Format;
+use mber::types::CLIArguments;
+use super::super::utils::{console, recursive_file_lookup};
+
+const LINTED_FOLDERS: [&str; 4] = ["src", "tests", "memserver", "documentation"];
+
+pub fn run(cli_arguments: CLIArguments) -> std::io::Result<()> {
+    let project_root = super::find_project_root().canonicalize()?;
+    let format = Format::parse(&cli_arguments.format).map_err(|error| Error::new(ErrorKind::InvalidInput, error.to_string()))?;
+    let config = config::build(&project_root, cli_arguments)?;
+    let extensions = match config.cli_arguments.templates {
+        true => vec![".hbs"],
+        false => vec![".js", ".hbs", ".scss"]
+    };
+    let paths = match config.cli_arguments.positional_arguments.is_empty() {
+        true => LINTED_FOLDERS.iter().map(|folder| project_root.join(folder)).filter(|directory| directory.is_dir()).collect(),
+        false => config.cli_arguments.positional_arguments.iter()
+            .map(|path| env::current_dir()?.join(path).canonicalize().map_err(|_| {
+                return Error::new(ErrorKind::NotFound, format!("{} does not exist", path));
+            }))
+            .collect::<std::io::Result<Vec<PathBuf>>>()?
+    };
+    let mut files = paths.iter().map(|path| match path.is_dir() {
+        true => recursive_file_lookup::lookup_for_extensions(path, extensions.clone()),
+        false => Ok(vec![path.to_path_buf()].into_iter()
+            .filter(|file| extensions.iter().any(|extension| file.to_string_lossy().ends_with(extension)))
+            .collect())
+    }).collect::<Result<Vec<Vec<PathBuf>>, _>>()?.concat();
+
+    files.sort();
+    files.dedup();
+
+    if let Some(git_ref) = &config.cli_arguments.changed {
+        let changed_files = linters::changed_files(&project_root, git_ref)?.into_iter()
+            .filter_map(|file| file.canonicalize().ok())
+            .collect::<Vec<PathBuf>>();
+
+        files.retain(|file| changed_files.contains(file));
+    }
+
+    // NOTE: only the stylish format logs, so json, checkstyle and sarif output can be piped to other tools
+    let should_log = format == Format::Stylish;
+
+    if config.cli_arguments.fix {
+        let fixed_problem_count = linters::fix_files(&files, &config)?;
+
+        if should_log && fixed_problem_count > 0 {
+            console::log(format!("{} {} lint problems", Paint::green("FIXED:"), fixed_problem_count));
+        }
+    }
+
+    let linted_files = linters::lint_files(&files, &config)?;
+    let diagnostics = linted_files.iter().flat_map(|linted_file| linted_file.diagnostics.iter()).collect::<Vec<_>>();
+    let error_count = diagnostics.iter().filter(|diagnostic| diagnostic.severity == Severity::Error).count();
+    let report = format.report(&linted_files, true);
+
+    if !report.is_empty() {
+        println!("{}", report);
+    }
+
+    if let Some(output_path) = &config.cli_arguments.output {
+        fs::write(output_path, format.report(&linted_files, false))?;
+    }
+
+    if error_count > 0 {
+        return Err(Error::new(ErrorKind::Other, format!("{} lint errors", error_count)));
+    } else if should_log && diagnostics.is_empty() {
+        console::log(format!("{} {} files have no lint problems", Paint::green("LINTED:"), files.len()));
+    }
+
+    return Ok(());
+}
@@ -1,8 +1,45 @@
 pub mod build;
+pub mod cache;
 pub mod console;
 pub mod delete;
 pub mod generate;
 pub mod help;
+pub mod lint;
 pub mod new;
 pub mod server;
 pub mod test;
+
+use std::path::PathBuf;
+use std::process;
+use yansi::Paint;
+use mber::linters::{self, LintedFile, Severity};
+use mber::types::MberError;
+use super::utils;
+
+// NOTE: the library returns MberError::MissingProject, commands stop the process with its message
+pub fn find_project_root() -> PathBuf {
+    return utils::find_project_root().unwrap_or_else(|error| {
+        utils::console::error(error);
+
+        process::exit(1);
+    });
+}
+
+// NOTE: builders return lint problems instead of printing them. A failed lint carries the diagnostics of the bundle
+// that was not written, other errors are printed by the caller
+pub fn print_lint_diagnostics(result: Result<&Vec<LintedFile>, &MberError>) {
+    match result {
+        Ok(linted_files) if !linted_files.is_empty() => {
+            let diagnostics = linted_files.iter().flat_map(|linted_file| linted_file.diagnostics.iter()).collect::<Vec<_>>();
+            let error_count = diagnostics.iter().filter(|diagnostic| diagnostic.severity == Severity::Error).count();
+
+            println!("{}", linters::format_diagnostics(linted_files));
+            utils::console::log(format!(
+                "{} {} problems ({} errors, {} warnings)",
+                Paint::yellow("LINTED:"), diagnostics.len(), error_count, diagnostics.len() - error_count
+            ));
+        },
+        Err(MberError::Lint { diagnostics, .. }) => println!("{}", diagnostics),
+        _ => {}
+    }
+}
@@ -129,8 +129,8 @@ fn add_application_name_to_boilerplate_files(
     fs::write(
         format!("{}/.gitignore", &application_directory),
         format!(
-            "{}\n{}\n{}\n{}\n{}\n{}",
-            ".cache", "dist", "node_modules", "npm-debug.log*", "yarn-error.log", "tmp"
+            "{}\n{}\n{}\n{}\n{}\n{}\n{}\n{}",
+            ".cache", ".dist-tmp", "dist", "dist-previous", "node_modules", "npm-debug.log*", "yarn-error.log", "tmp"
         ),
     )?;
 
@@ -1,4 +1,46 @@
-pub fn run() -> std::io::Result<()> {
-    println!("run server command here");
+use std::io::{Error, ErrorKind};
+use mber::builders::{build_all_assets, config};
+use mber::runners::{file_watcher, http_server, websocket_server};
+use mber::runners::http_server::ServerOptions;
+use mber::types::CLIArguments;
+use super::super::utils::{console};
+
+pub fn run(mut cli_arguments: CLIArguments) -> std::io::Result<()> {
+    console::log("Starting the development server...");
+
+    let project_root = super::find_project_root();
+
+    cli_arguments.testing = true;
+    cli_arguments.live_reload = cli_arguments.watch;
+
+    let config = config::build(&project_root, cli_arguments)
+        .map_err(|error| Error::new(ErrorKind::Other, error.to_string()))?;
+
+    let build_result = build_all_assets(&config);
+
+    super::print_lint_diagnostics(build_result.as_ref());
+    build_result.map_err(|error| Error::new(ErrorKind::Other, error.to_string()))?;
+
+    let port = config.cli_arguments.port;
+    let server_options = ServerOptions::from_config(&config);
+
+    if config.cli_arguments.watch {
+        let broadcaster = websocket_server::start(config.cli_arguments.socket_port)
+            .map_err(|error| Error::new(ErrorKind::Other, error.to_string()))?;
+
+        file_watcher::start(config, move |builders, result| {
+            super::print_lint_diagnostics(result);
+
+            match result {
+                Ok(_) => if let Err(error) = broadcaster.broadcast(&file_watcher::reload_message(builders)) {
+                    console::error(format!("Socket error: {}", error));
+                },
+                Err(error) => console::error(format!("Build error: {}", error))
+            };
+        }).map_err(|error| Error::new(ErrorKind::Other, error.to_string()))?;
+    }
+
+    http_server::start(port, server_options).map_err(|error| Error::new(ErrorKind::Other, error.to_string()))?;
+
     Ok(())
 }
@@ -1,4 +1,96 @@
-pub fn run() -> std::io::Result<()> {
-    println!("run test command here");
+use std::fs;
+use std::io::{Error, ErrorKind};
+use yansi::Paint;
+use mber::builders::{build_all_assets, config};
+use mber::runners::{file_watcher, http_server, test_runner, websocket_server};
+use mber::runners::http_server::ServerOptions;
+use mber::runners::test_reporter::Reporter;
+use mber::runners::test_runner::{TestRun, TestRunOptions};
+use mber::types::CLIArguments;
+use super::super::utils::{console};
+
+pub fn run(mut cli_arguments: CLIArguments) -> std::io::Result<()> {
+    let project_root = super::find_project_root();
+    let reporter = Reporter::parse(&cli_arguments.reporter)
+        .map_err(|error| Error::new(ErrorKind::InvalidInput, error.to_string()))?;
+
+    if cli_arguments.env.is_none() {
+        cli_arguments.env = Some(String::from("test"));
+    }
+
+    cli_arguments.testing = true;
+    cli_arguments.live_reload = cli_arguments.server && cli_arguments.watch;
+
+    if reporter == Reporter::Console || cli_arguments.server {
+        console::log("Building the application for tests...");
+    }
+
+    let config = config::build(&project_root, cli_arguments)
+        .map_err(|error| Error::new(ErrorKind::Other, error.to_string()))?;
+
+    let build_result = build_all_assets(&config);
+
+    super::print_lint_diagnostics(build_result.as_ref());
+    build_result.map_err(|error| Error::new(ErrorKind::Other, error.to_string()))?;
+
+    let port = config.cli_arguments.port;
+    let test_run_options = TestRunOptions {
+        filter: config.cli_arguments.filter.clone(),
+        module: config.cli_arguments.module.clone()
+    };
+
+    if config.cli_arguments.server {
+        let server_options = ServerOptions::from_config(&config);
+
+        console::log(format!("{} {}", Paint::green("Tests are served on"), test_run_options.url(port)));
+
+        if config.cli_arguments.watch {
+            let broadcaster = websocket_server::start(config.cli_arguments.socket_port)
+                .map_err(|error| Error::new(ErrorKind::Other, error.to_string()))?;
+
+            file_watcher::start(config, move |builders, result| {
+                super::print_lint_diagnostics(result);
+
+                match result {
+                    Ok(_) => if let Err(error) = broadcaster.broadcast(&file_watcher::reload_message(builders)) {
+                        console::error(format!("Socket error: {}", error));
+                    },
+                    Err(error) => console::error(format!("Build error: {}", error))
+                };
+            }).map_err(|error| Error::new(ErrorKind::Other, error.to_string()))?;
+        }
+
+        return http_server::start(port, server_options).map_err(|error| Error::new(ErrorKind::Other, error.to_string()));
+    }
+
+    if reporter == Reporter::Tap {
+        println!("TAP version 13");
+    }
+
+    let mut index = 0;
+    let test_run = test_runner::run(&project_root, &test_run_options.url(port), |result| {
+        index += 1;
+
+        if let Some(line) = reporter.stream_result(index, result) {
+            println!("{}", line);
+        }
+    }).map_err(|error| Error::new(ErrorKind::Other, error.to_string()))?;
+
+    println!("{}", reporter.stream_summary(&test_run));
+
+    if let Some(output_path) = &config.cli_arguments.output {
+        fs::write(output_path, reporter.report(&test_run))?;
+    }
+
+    return finish(&test_run);
+}
+
+fn finish(test_run: &TestRun) -> std::io::Result<()> {
+    if test_run.results.is_empty() {
+        return Err(Error::new(ErrorKind::Other, "no tests were found to run"));
+    } else if !test_run.is_successful() {
+        return Err(Error::new(ErrorKind::Other, "some tests have failed"));
+    }
+
     Ok(())
 }
@@ -1,5 +1,7 @@
 pub mod builders;
+pub mod runners;
 pub mod transpilers;
 pub mod utils;
 pub mod types;
+pub mod linters;
 mod injections;
@@ -0,0 +1,608 @@
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::OnceLock;
+use walkdir::WalkDir;
+use super::{location_finder, Diagnostic, Directives, LintConfig, Severity};
+use super::super::injections::documentation;
+use super::super::utils::walk_injection;
+
+pub const RULES: [(&str, Severity); 7] = [
+    ("no-unbalanced-blocks", Severity::Error),
+    ("no-bare-strings", Severity::Off),
+    ("no-triple-curlies", Severity::Error),
+    ("no-action", Severity::Warn),
+    ("require-valid-alt-text", Severity::Error),
+    ("no-invalid-interactive", Severity::Error),
+    ("no-unknown-components", Severity::Warn)
+];
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source", "track", "wbr"
+];
+const RAW_TEXT_ELEMENTS: [&str; 2] = ["script", "style"];
+const INTERACTIVE_ELEMENTS: [&str; 11] = [
+    "a", "button", "details", "embed", "iframe", "input", "label", "option", "select", "summary", "textarea"
+];
+const INTERACTIVE_EVENTS: [&str; 7] = ["click", "dblclick", "keydown", "keypress", "keyup", "mousedown", "mouseup"];
+const BUILT_IN_COMPONENTS: [&str; 11] = [
+    "Input", "Textarea", "LinkTo", "link-to", "each-in", "has-block", "has-block-params", "query-params", "in-element",
+    "unique-id", "head-layout"
+];
+
+static DOCUMENTATION_COMPONENTS: OnceLock<Vec<String>> = OnceLock::new();
+
+struct Mustache<'a> {
+    start: usize,
+    content: &'a str,
+    triple: bool,
+    is_comment: bool
+}
+
+struct Open<'a> {
+    is_block: bool,
+    name: &'a str,
+    start: usize,
+    block_params: Vec<&'a str>
+}
+
+struct Scanner<'a, 'b> {
+    source: &'a str,
+    bytes: &'a [u8],
+    position: usize,
+    stack: Vec<Open<'a>>,
+    comments: Vec<(usize, &'a str)>,
+    problems: Vec<(&'static str, usize, String)>,
+    known_components: &'b HashSet<String>,
+    globals: &'b Vec<String>
+}
+
+// NOTE: known_components are the paths under src/ui/components and documentation/ui/components like welcome-page or
+// forms/text-field, config globals count as known components and helpers too
+pub fn lint(source: &str, file_name: &str, config: &LintConfig, known_components: &HashSet<String>) -> Vec<Diagnostic> {
+    let mut scanner = Scanner {
+        source: source,
+        bytes: source.as_bytes(),
+        position: 0,
+        stack: Vec::new(),
+        comments: Vec::new(),
+        problems: Vec::new(),
+        known_components: known_components,
+        globals: &config.globals
+    };
+
+    scanner.scan();
+
+    let location = location_finder(source);
+    let directives = read_directives(&scanner.comments, &location);
+    let mut diagnostics = scanner.problems.into_iter()
+        .filter_map(|(rule, offset, message)| {
+            let default_severity = RULES.iter().find(|(name, _)| *name == rule).map_or(Severity::Off, |(_, severity)| *severity);
+            let severity = config.severity(rule, default_severity);
+            let (line, column) = location(offset);
+
+            if severity == Severity::Off || directives.disables(rule, line) {
+                return None;
+            }
+
+            return Some(Diagnostic {
+                file_name: file_name.to_string(),
+                line: line,
+                column: column,
+                rule: rule.to_string(),
+                severity: severity,
+                message: message,
+                fix: None
+            });
+        })
+        .collect::<Vec<Diagnostic>>();
+
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+
+    return diagnostics;
+}
+
+pub fn known_components(project_root: &Path) -> HashSet<String> {
+    let documentation_components = DOCUMENTATION_COMPONENTS.get_or_init(|| {
+        let documentation_hashmap = serde_json::from_str(documentation::as_str()).unwrap_or_default();
+
+        return walk_injection::flatten_fs_hashmap(documentation_hashmap, vec![]).keys()
+            .filter_map(|path| path.split("/src/ui/components/").nth(1))
+            .flat_map(|path| {
+                let segments = path.rsplitn(2, '.').last().unwrap_or("").split('/').collect::<Vec<&str>>();
+
+                return (1..=segments.len()).map(move |length| segments[..length].join("/"));
+            })
+            .collect();
+    });
+    let project_components = ["src/ui/components", "documentation/ui/components"].iter().flat_map(|folder| {
+        let directory = project_root.join(folder);
+
+        return WalkDir::new(&directory).min_depth(1).into_iter().filter_map(Result::ok).map(move |entry| {
+            let relative_path = entry.path().strip_prefix(&directory).unwrap_or(entry.path()).to_string_lossy().replace('\\', "/");
+
+            return match entry.file_type().is_dir() {
+                true => relative_path,
+                false => relative_path.rsplitn(2, '.').last().unwrap_or("").to_string()
+            };
+        });
+    });
+
+    return project_components.chain(documentation_components.iter().cloned()).collect();
+}
+
+impl<'a, 'b> Scanner<'a, 'b> {
+    fn scan(&mut self) {
+        let mut text_start = 0;
+
+        while self.position < self.bytes.len() {
+            let rest = &self.bytes[self.position..];
+
+            if rest.starts_with(b"\\{{") {
+                self.position += 3;
+            } else if rest.starts_with(b"{{") {
+                self.text(text_start);
+                self.statement();
+                text_start = self.position;
+            } else if rest[0] == b'<' && rest.get(1).map_or(false, |next| next.is_ascii_alphabetic() || b"/!:@".contains(next)) {
+                self.text(text_start);
+                self.tag();
+                text_start = self.position;
+            } else {
+                self.position += 1;
+            }
+        }
+
+        self.text(text_start);
+
+        for open in std::mem::replace(&mut self.stack, Vec::new()) {
+            self.report_unclosed(&open);
+        }
+    }
+
+    fn report(&mut self, rule: &'static str, offset: usize, message: String) {
+        self.problems.push((rule, offset, message));
+    }
+
+    fn text(&mut self, start: usize) {
+        let text = &self.source[start..self.position];
+
+        if let Some((offset, _)) = text.char_indices().find(|(_, character)| character.is_alphabetic()) {
+            let first_character = text[..offset].rfind(char::is_whitespace).map_or(0, |index| index + 1);
+
+            self.report("no-bare-strings", start + first_character, String::from("Non-translated string used"));
+        }
+    }
+
+    // NOTE: strings inside mustaches can hold whole templates like code='{{#if a}}', so they are skipped as one piece
+    fn read_mustache(&mut self) -> Mustache<'a> {
+        let start = self.position;
+        let rest = &self.source[start..];
+        let (open_length, close) = match (rest.starts_with("{{!--"), rest.starts_with("{{!"), rest.starts_with("{{{")) {
+            (true, _, _) => (5, "--}}"),
+            (_, true, _) => (3, "}}"),
+            (_, _, true) => (3, "}}}"),
+            _ => (2, "}}")
+        };
+        let mut cursor = start + open_length;
+        let mut quote = None;
+
+        while cursor < self.bytes.len() && !(quote.is_none() && self.bytes[cursor..].starts_with(close.as_bytes())) {
+            match (quote, self.bytes[cursor]) {
+                (Some(_), b'\\') => { cursor += 1; },
+                (Some(quote_byte), byte) if byte == quote_byte => { quote = None; },
+                (None, byte) if open_length == 2 && (byte == b'"' || byte == b'\'') => { quote = Some(byte); },
+                _ => {}
+            }
+
+            cursor += 1;
+        }
+
+        if cursor >= self.bytes.len() {
+            self.report("no-unbalanced-blocks", start, String::from("Unclosed mustache, expected }}"));
+        }
+
+        self.position = (cursor + close.len()).min(self.bytes.len());
+
+        return Mustache {
+            start: start,
+            content: self.source[start + open_length..cursor.min(self.bytes.len())].trim_matches('~').trim(),
+            triple: open_length == 3 && close == "}}}",
+            is_comment: close == "--}}" || rest.starts_with("{{!")
+        };
+    }
+
+    fn statement(&mut self) {
+        let mustache = self.read_mustache();
+
+        if mustache.is_comment {
+            self.comments.push((self.position, mustache.content));
+
+            return;
+        }
+
+        self.check_mustache(&mustache);
+
+        if mustache.content.starts_with('#') {
+            let name = first_word(&mustache.content[1..]);
+
+            self.stack.push(Open {
+                is_block: true,
+                name: name,
+                start: mustache.start,
+                block_params: block_params(mustache.content)
+            });
+        } else if mustache.content.starts_with('/') {
+            self.close(true, mustache.content[1..].trim(), mustache.start);
+        }
+    }
+
+    fn check_mustache(&mut self, mustache: &Mustache<'a>) {
+        let code = blank_strings(mustache.content);
+        let name = first_word(code.trim_start_matches(|character| character == '#' || character == '/'));
+
+        if mustache.triple {
+            self.report("no-triple-curlies", mustache.start, String::from("Usage of triple curly brackets is unsafe"));
+        }
+
+        if name == "action" || code.contains("(action ") || code.contains("(action)") {
+            self.report(
+                "no-action", mustache.start,
+                String::from("Do not use `action` as {{action ...}}. Instead, use the `on` modifier and `fn` helper.")
+            );
+        }
+
+        if name.contains('-') && !mustache.content.starts_with('/') && !name.contains('.') && !name.starts_with('@') {
+            self.check_component(name, name.to_string(), mustache.start);
+        }
+    }
+
+    fn check_component(&mut self, name: &str, path: String, start: usize) {
+        let is_block_param = self.stack.iter().any(|open| open.block_params.contains(&name));
+        let is_global = self.globals.iter().any(|global| global == name);
+
+        if !is_block_param && !is_global && !BUILT_IN_COMPONENTS.contains(&name) && !self.known_components.contains(&path) {
+            self.report("no-unknown-components", start, format!(
+                "Unknown component or helper `{}`, there is no src/ui/components/{}", name, path
+            ));
+        }
+    }
+
+    fn tag(&mut self) {
+        let start = self.position;
+        let rest = &self.source[start..];
+
+        if rest.starts_with("<!--") {
+            self.position = rest.find("-->").map_or(self.bytes.len(), |index| start + index + 3);
+
+            return;
+        } else if rest.starts_with("<!") || rest.starts_with("</") {
+            let end = rest.find('>').map_or(self.bytes.len(), |index| start + index);
+
+            self.position = (end + 1).min(self.bytes.len());
+
+            if rest.starts_with("</") {
+                self.close(false, self.source[start + 2..end].trim(), start);
+            }
+
+            return;
+        }
+
+        self.position += 1;
+
+        let name = self.read_while(|byte| !byte.is_ascii_whitespace() && byte != b'/' && byte != b'>');
+        let mut attributes: Vec<(&str, Option<&str>)> = Vec::new();
+        let mut modifiers = Vec::new();
+        let mut block_params = Vec::new();
+        let mut self_closing = false;
+
+        loop {
+            self.read_while(|byte| byte.is_ascii_whitespace());
+
+            let rest = &self.bytes[self.position..];
+
+            if rest.is_empty() {
+                self.report("no-unbalanced-blocks", start, format!("Unclosed tag <{}, expected >", name));
+
+                return;
+            } else if rest.starts_with(b"/>") {
+                self.position += 2;
+                self_closing = true;
+
+                break;
+            } else if rest[0] == b'>' {
+                self.position += 1;
+
+                break;
+            } else if rest.starts_with(b"{{") {
+                let modifier = self.read_mustache();
+
+                if !modifier.is_comment {
+                    self.check_mustache(&modifier);
+                    modifiers.push(modifier);
+                }
+
+                continue;
+            }
+
+            let attribute_name = self.read_while(|byte| !byte.is_ascii_whitespace() && !b"=>/".contains(&byte));
+
+            if attribute_name.is_empty() {
+                self.position += 1;
+            } else if attribute_name == "as" || attribute_name.starts_with('|') {
+                let params_end = self.source[self.position..].find('>').map_or(self.bytes.len(), |index| self.position + index);
+                let params = &self.source[self.position..params_end];
+
+                block_params.extend(params.split(|character: char| character == '|' || character.is_whitespace())
+                    .chain(attribute_name.split('|'))
+                    .filter(|param| !param.is_empty() && *param != "as" && *param != "/"));
+                self.position = params_end;
+            } else if self.bytes.get(self.position) == Some(&b'=') {
+                self.position += 1;
+                attributes.push((attribute_name, Some(self.attribute_value())));
+            } else {
+                attributes.push((attribute_name, None));
+            }
+        }
+
+        self.check_element(name, start, &attributes, &modifiers);
+
+        if RAW_TEXT_ELEMENTS.contains(&name) && !self_closing {
+            let closing_tag = format!("</{}", name);
+
+            self.position = self.source[self.position..].find(&closing_tag).map_or(self.bytes.len(), |index| self.position + index);
+        }
+
+        if !self_closing && !VOID_ELEMENTS.contains(&name) {
+            self.stack.push(Open { is_block: false, name: name, start: start, block_params: block_params });
+        }
+    }
+
+    fn attribute_value(&mut self) -> &'a str {
+        let start = self.position;
+
+        match self.bytes.get(start) {
+            Some(&quote) if quote == b'"' || quote == b'\'' => {
+                self.position += 1;
+
+                while self.position < self.bytes.len() && self.bytes[self.position] != quote {
+                    if self.bytes[self.position..].starts_with(b"{{") {
+                        let mustache = self.read_mustache();
+
+                        self.check_mustache(&mustache);
+                    } else {
+                        self.position += 1;
+                    }
+                }
+
+                self.position = (self.position + 1).min(self.bytes.len());
+
+                return &self.source[start + 1..self.position.saturating_sub(1).max(start + 1)];
+            },
+            Some(b'{') => {
+                let mustache = self.read_mustache();
+
+                self.check_mustache(&mustache);
+
+                return mustache.content;
+            },
+            _ => return self.read_while(|byte| !byte.is_ascii_whitespace() && byte != b'>')
+        };
+    }
+
+    fn read_while<F: Fn(u8) -> bool>(&mut self, predicate: F) -> &'a str {
+        let start = self.position;
+
+        while self.position < self.bytes.len() && predicate(self.bytes[self.position]) {
+            self.position += 1;
+        }
+
+        return &self.source[start..self.position];
+    }
+
+    fn check_element(&mut self, name: &str, start: usize, attributes: &Vec<(&str, Option<&str>)>, modifiers: &Vec<Mustache>) {
+        let has = |attribute: &str| attributes.iter().any(|(attribute_name, _)| *attribute_name == attribute);
+        let value = |attribute: &str| attributes.iter().find(|(attribute_name, _)| *attribute_name == attribute).and_then(|(_, value)| *value);
+        let needs_alt = match name {
+            "img" | "area" => true,
+            "input" => value("type") == Some("image"),
+            _ => false
+        };
+
+        if needs_alt && !has("alt") && !has("...attributes") && !has("aria-label") && !has("aria-labelledby") {
+            self.report("require-valid-alt-text", start, format!("All `<{}>` tags must have an alt attribute", name));
+        }
+
+        let is_native_element = name.chars().next().map_or(false, |character| character.is_ascii_lowercase()) &&
+            !name.contains('-') && !name.contains('.');
+        let has_interaction = modifiers.iter().any(|modifier| {
+            let words = modifier.content.split_whitespace().collect::<Vec<&str>>();
+            let event = words.get(1).unwrap_or(&"").trim_matches(|character| character == '"' || character == '\'');
+
+            return match words.get(0) {
+                Some(&"action") => !modifier.content.contains("on="),
+                Some(&"on") => INTERACTIVE_EVENTS.contains(&event),
+                _ => false
+            };
+        }) || attributes.iter().any(|(attribute_name, value)| {
+            return value.is_some() && attribute_name.starts_with("on") && INTERACTIVE_EVENTS.contains(&&attribute_name[2..]);
+        });
+
+        if is_native_element && has_interaction && !INTERACTIVE_ELEMENTS.contains(&name) && !has("role") && !has("tabindex") {
+            self.report("no-invalid-interactive", start, format!("Interaction added to non-interactive element <{}>", name));
+        }
+
+        let is_component = name.contains("::") || name.chars().next().map_or(false, |character| character.is_ascii_uppercase());
+
+        if is_component && !name.contains('.') {
+            let path = name.split("::").map(dasherize).collect::<Vec<String>>().join("/");
+
+            self.check_component(name, path, start);
+        }
+    }
+
+    fn close(&mut self, is_block: bool, name: &str, start: usize) {
+        match self.stack.iter().rposition(|open| open.is_block == is_block && open.name == name) {
+            Some(index) => {
+                for open in self.stack.split_off(index + 1) {
+                    self.report_unclosed(&open);
+                }
+
+                self.stack.pop();
+            },
+            None => {
+                let message = match is_block {
+                    true => format!("Closing {{{{/{}}}}} has no matching opening block", name),
+                    false => format!("Closing </{}> has no matching opening element", name)
+                };
+
+                self.report("no-unbalanced-blocks", start, message);
+            }
+        }
+    }
+
+    fn report_unclosed(&mut self, open: &Open) {
+        let message = match open.is_block {
+            true => format!("Unclosed block {{{{#{}}}}}", open.name),
+            false => format!("Unclosed element <{}>", open.name)
+        };
+
+        self.report("no-unbalanced-blocks", open.start, message);
+    }
+}
+
+// NOTE: {{! template-lint-disable [rules] }} turns rules off for the file, template-lint-disable-next-line for the next line
+fn read_directives<F: Fn(usize) -> (usize, usize)>(comments: &Vec<(usize, &str)>, location: &F) -> Directives {
+    return comments.iter().fold(Directives::default(), |mut directives, (end, content)| {
+        let mut words = content.trim_start_matches('!').trim_start_matches("--").trim_end_matches("--")
+            .split(|character: char| character.is_whitespace() || character == ',')
+            .filter(|word| !word.is_empty());
+        let directive = words.next().unwrap_or("");
+        let rules = words.map(String::from).collect::<Vec<String>>();
+
+        match directive {
+            "template-lint-disable" => { directives.file_rules = Some(rules); },
+            "template-lint-disable-next-line" => { directives.line_rules.insert(location(*end).0 + 1, rules); },
+            _ => {}
+        }
+
+        return directives;
+    });
+}
+
+fn first_word(code: &str) -> &str {
+    return code.split(|character: char| character.is_whitespace() || character == ')').next().unwrap_or("");
+}
+
+fn block_params(content: &str) -> Vec<&str> {
+    return match content.rfind(" as |") {
+        Some(index) => content[index + 4..].split(|character: char| character == '|' || character.is_whitespace())
+            .filter(|param| !param.is_empty())
+            .collect(),
+        None => Vec::new()
+    };
+}
+
+// NOTE: keeps the offsets of the code while hiding string contents, so code inside them is not checked
+fn blank_strings(content: &str) -> String {
+    let mut quote = None;
+    let mut is_escaped = false;
+
+    return content.chars().map(|character| {
+        let was_escaped = is_escaped;
+
+        is_escaped = quote.is_some() && character == '\\' && !was_escaped;
+
+
+        return match (quote, character) {
+            (Some(_), _) if was_escaped => ' ',
+            (Some(quote_character), _) if quote_character == character => { quote = None; character },
+            (Some(_), _) => ' ',
+            (None, '"') | (None, '\'') => { quote = Some(character); character },
+            _ => character
+        };
+    }).collect();
+}
+
+fn dasherize(name: &str) -> String {
+    return name.chars().enumerate().fold(String::new(), |mut result, (index, character)| {
+        if character.is_ascii_uppercase() && index > 0 {
+            result.push('-');
+        }
+
+        result.push(character.to_ascii_lowercase());
+
+        return result;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+
+    fn problems(source: &str) -> Vec<(usize, usize, String, String)> {
+        let known_components = vec!["welcome-page", "forms/text-field"].into_iter().map(String::from).collect();
+
+        return lint(source, "src/ui/routes/index/template.hbs", &LintConfig::default(), &known_components).into_iter()
+            .map(|diagnostic| (diagnostic.line, diagnostic.column, diagnostic.rule, diagnostic.message))
+            .collect();
+    }
+
+    #[test]
+    fn lint_reports_unbalanced_blocks_and_elements() {
+        let source = "{{#if isOpen}}\n  <div class=\"modal\">\n    {{#each items as |item|}}\n      <p>{{item.name}}</span>\n    \
+            {{/each}}\n{{/if}}\n</section>\n{{#unless isOpen}}\n";
+
+        assert_eq!(problems(source), vec![
+            (2, 3, String::from("no-unbalanced-blocks"), String::from("Unclosed element <div>")),
+            (4, 7, String::from("no-unbalanced-blocks"), String::from("Unclosed element <p>")),
+            (4, 23, String::from("no-unbalanced-blocks"), String::from("Closing </span> has no matching opening element")),
+            (7, 1, String::from("no-unbalanced-blocks"), String::from("Closing </section> has no matching opening element")),
+            (8, 1, String::from("no-unbalanced-blocks"), String::from("Unclosed block {{#unless}}"))
+        ]);
+    }
+
+    #[test]
+    fn lint_reports_unsafe_and_inaccessible_markup() {
+        let source = "<WelcomePage @title={{{this.title}}} />\n<img src=\"/logo.png\">\n<img src=\"/logo.png\" alt=\"\">\n\
+            <div {{action \"open\"}}>Open</div>\n<div role=\"button\" {{on \"click\" this.open}}></div>\n\
+            <button onclick={{action (action \"open\")}}></button>\n<form {{on \"submit\" this.save}}></form>\n";
+
+        assert_eq!(problems(source), vec![
+            (1, 21, String::from("no-triple-curlies"), String::from("Usage of triple curly brackets is unsafe")),
+            (2, 1, String::from("require-valid-alt-text"), String::from("All `<img>` tags must have an alt attribute")),
+            (4, 1, String::from("no-invalid-interactive"), String::from("Interaction added to non-interactive element <div>")),
+            (4, 6, String::from("no-action"), String::from("Do not use `action` as {{action ...}}. Instead, use the `on` modifier and `fn` helper.")),
+            (6, 17, String::from("no-action"), String::from("Do not use `action` as {{action ...}}. Instead, use the `on` modifier and `fn` helper."))
+        ]);
+    }
+
+    #[test]
+    fn lint_reports_unknown_components_outside_strings_and_block_params() {
+        let source = "<Forms::TextField @value={{this.name}} />\n<Forms::Select as |select|>\n  <select.Option />\n</Forms::Select>\n\
+            {{#docs-demo as |demo|}}{{demo.snippet}}{{/docs-demo}}\n{{welcome-page}}\n{{snippet-viewer code='{{missing-component}} \\'{{other-component}}\\''}}\n\
+            \\{{escaped-component}}\n<LinkTo @route=\"index\">Home</LinkTo>\n";
+
+        assert_eq!(problems(source), vec![
+            (2, 1, String::from("no-unknown-components"), String::from("Unknown component or helper `Forms::Select`, there is no src/ui/components/forms/select")),
+            (5, 1, String::from("no-unknown-components"), String::from("Unknown component or helper `docs-demo`, there is no src/ui/components/docs-demo")),
+            (7, 1, String::from("no-unknown-components"), String::from("Unknown component or helper `snippet-viewer`, there is no src/ui/components/snippet-viewer"))
+        ]);
+    }
+
+    #[test]
+    fn lint_follows_the_project_config_and_comment_directives() {
+        let config = LintConfig::from_value(&json!({
+            "rules": { "no-bare-strings": "warn", "no-triple-curlies": "off" },
+            "globals": ["t-label"]
+        }), "config/lint.json").unwrap();
+        let source = "<h1>{{t-label \"title\"}}</h1>\n<p>Hello {{{name}}}</p>\n{{! template-lint-disable-next-line }}\n<img>\n\
+            {{!-- template-lint-disable no-unknown-components --}}\n<UnknownThing />\n";
+        let diagnostics = lint(source, "src/ui/routes/index/template.hbs", &config, &HashSet::new());
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            (diagnostics[0].rule.as_str(), diagnostics[0].severity, diagnostics[0].line, diagnostics[0].column),
+            ("no-bare-strings", Severity::Warn, 2, 4)
+        );
+    }
+}
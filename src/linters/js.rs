@@ -0,0 +1,551 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use super::{Diagnostic, Directives, Fix, LintConfig, Severity};
+use super::super::transpilers::minify_js::{self, Analysis, Piece, Role};
+use super::super::transpilers::tokenizer::{self, TokenKind};
+use super::super::types::TranspileError;
+
+pub const RULES: [(&str, Severity); 6] = [
+    ("no-undef", Severity::Error),
+    ("no-unused-vars", Severity::Warn),
+    ("no-debugger", Severity::Error),
+    ("ember/no-observers", Severity::Warn),
+    ("ember/no-jquery", Severity::Warn),
+    ("ember/no-new-mixins", Severity::Warn)
+];
+const GLOBALS: [&str; 118] = [
+    "Array", "ArrayBuffer", "Atomics", "BigInt", "BigInt64Array", "BigUint64Array", "Boolean", "DataView", "Date",
+    "decodeURI", "decodeURIComponent", "encodeURI", "encodeURIComponent", "Error", "escape", "eval", "EvalError",
+    "FinalizationRegistry", "Float32Array", "Float64Array", "Function", "globalThis", "Infinity", "Int16Array",
+    "Int32Array", "Int8Array", "Intl", "isFinite", "isNaN", "JSON", "Map", "Math", "NaN", "Number", "Object",
+    "parseFloat", "parseInt", "Promise", "Proxy", "RangeError", "ReferenceError", "Reflect", "RegExp", "Set",
+    "SharedArrayBuffer", "String", "Symbol", "SyntaxError", "TypeError", "Uint16Array", "Uint32Array", "Uint8Array",
+    "Uint8ClampedArray", "undefined", "unescape", "URIError", "WeakMap", "WeakRef", "WeakSet", "AggregateError",
+    "AbortController", "alert", "atob", "Blob", "btoa", "cancelAnimationFrame", "clearInterval", "clearTimeout",
+    "confirm", "console", "crypto", "CustomEvent", "document", "DOMParser", "Element", "Event", "fetch", "File",
+    "FileReader", "FormData", "getComputedStyle", "Headers", "history", "HTMLElement", "Image", "indexedDB",
+    "IntersectionObserver", "localStorage", "location", "matchMedia", "MutationObserver", "navigator", "Node",
+    "Notification", "performance", "prompt", "queueMicrotask", "Request", "requestAnimationFrame", "ResizeObserver",
+    "Response", "screen", "self", "sessionStorage", "setInterval", "setTimeout", "structuredClone", "TextDecoder",
+    "TextEncoder", "URL", "URLSearchParams", "WebSocket", "window", "Worker", "XMLHttpRequest", "define", "require",
+    "requirejs"
+];
+const CONTEXTUAL_NAMES: [&str; 6] = ["arguments", "async", "of", "get", "set", "from"];
+const EXPRESSION_STARTS: [&str; 12] = ["=", "(", "[", ",", ":", "?", "=>", "&&", "||", "??", "return", "!"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum DeclarationKind {
+    Variable,
+    Import,
+    Function,
+    Class,
+    Parameter
+}
+
+struct Declaration<'a> {
+    name: &'a str,
+    index: usize,
+    kind: DeclarationKind,
+    references: usize,
+    exported: bool
+}
+
+// NOTE: every import statement is recorded once with an empty imported name, then once per imported binding
+struct Import<'a> {
+    imported: &'a str,
+    local: Option<usize>,
+    source: &'a str,
+    index: usize
+}
+
+struct Linter<'a, 'b> {
+    pieces: &'b [Piece<'a>],
+    closer: &'b [usize],
+    analysis: &'b Analysis,
+    parents: Vec<usize>,
+    scope_of: Vec<usize>,
+    bindings: Vec<HashMap<&'a str, usize>>,
+    declarations: Vec<Declaration<'a>>,
+    declaration_positions: HashSet<usize>,
+    skipped_positions: HashSet<usize>,
+    export_references: Vec<usize>,
+    imports: Vec<Import<'a>>
+}
+
+// NOTE: scopes follow functions, let and const inside blocks belong to their function. Unused parameters are not reported
+pub fn lint(source: &str, file_name: &str, config: &LintConfig) -> Result<Vec<Diagnostic>, TranspileError> {
+    let (pieces, closer, analysis) = minify_js::analyze_code(source, file_name)?;
+    let directives = read_directives(source, file_name)?;
+    let mut linter = Linter::new(&pieces, &closer, &analysis);
+
+    linter.collect_declarations();
+
+    let undefined_references = linter.resolve_references(config, &directives);
+    let mut diagnostics = Vec::new();
+    let mut report = |rule: &str, index: usize, message: String| {
+        let severity = config.severity(rule, RULES.iter().find(|(name, _)| *name == rule).map_or(Severity::Off, |(_, severity)| *severity));
+        let piece = &pieces[index];
+
+        if severity != Severity::Off && !directives.disables(rule, piece.line) {
+            diagnostics.push(Diagnostic {
+                file_name: file_name.to_string(),
+                line: piece.line,
+                column: piece.column,
+                rule: rule.to_string(),
+                severity: severity,
+                message: message,
+                fix: match rule {
+                    "no-debugger" => debugger_fix(source, piece.line, piece.column),
+                    _ => None
+                }
+            });
+        }
+    };
+
+    undefined_references.into_iter()
+        .for_each(|index| report("no-undef", index, format!("'{}' is not defined.", pieces[index].text)));
+    linter.declarations.iter()
+        .filter(|declaration| declaration.kind != DeclarationKind::Parameter && declaration.references == 0 && !declaration.exported)
+        .for_each(|declaration| report("no-unused-vars", declaration.index, format!("'{}' is defined but never used.", declaration.name)));
+    pieces.iter().enumerate()
+        .filter(|(index, piece)| piece.text == "debugger" && analysis.roles[*index] == Role::Keyword)
+        .for_each(|(index, _)| report("no-debugger", index, String::from("Unexpected 'debugger' statement.")));
+    linter.imports.iter().for_each(|import| {
+        match (import.source, import.imported) {
+            ("@ember/object", "observer") | ("@ember/object/observers", _) if import.local.is_some() => {
+                report("ember/no-observers", import.local.unwrap_or(import.index), String::from("Don't use observers if possible."));
+            },
+            ("jquery", "") | ("@ember/jquery", "") => report("ember/no-jquery", import.index, String::from("Do not use jQuery.")),
+            _ => {}
+        }
+    });
+    pieces.iter().enumerate().filter(|(index, _)| analysis.roles[*index] == Role::Property && text_at(&pieces, index + 1) == "(")
+        .for_each(|(index, piece)| match (piece.text, text_at(&pieces, index.wrapping_sub(2))) {
+            ("observes", _) | ("addObserver", _) => report("ember/no-observers", index, String::from("Don't use observers if possible.")),
+            ("$", "this") => report("ember/no-jquery", index, String::from("Do not use jQuery.")),
+            _ => {}
+        });
+
+    let mixin_names = linter.imports.iter()
+        .filter(|import| import.source == "@ember/object/mixin" && import.imported == "default")
+        .filter_map(|import| import.local.map(|local| pieces[local].text))
+        .collect::<Vec<&str>>();
+
+    pieces.iter().enumerate()
+        .filter(|(index, piece)| {
+            return analysis.roles[*index] == Role::Reference && mixin_names.contains(&piece.text) &&
+                text_at(&pieces, index + 1) == "." && text_at(&pieces, index + 2) == "create";
+        })
+        .for_each(|(index, _)| report("ember/no-new-mixins", index, String::from("Don't create new mixins.")));
+
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+
+    return Ok(diagnostics);
+}
+
+// NOTE: removes the statement with its semicolon, and the whole line when nothing else is on it
+fn debugger_fix(source: &str, line: usize, column: usize) -> Option<Fix> {
+    let line_start = source.split_inclusive('\n').take(line - 1).map(|text| text.len()).sum::<usize>();
+    let line_end = source[line_start..].find('\n').map_or(source.len(), |index| line_start + index);
+    let start = line_start + source[line_start..line_end].chars().take(column - 1).map(|character| character.len_utf8()).sum::<usize>();
+
+    if !source[start..].starts_with("debugger") {
+        return None;
+    }
+
+    let end = start + "debugger".len() + if source[start + "debugger".len()..].starts_with(';') { 1 } else { 0 };
+
+    if source[line_start..start].trim().is_empty() && source[end..line_end].trim().is_empty() {
+        return Some(Fix { start: line_start, end: (line_end + 1).min(source.len()), replacement: String::new() });
+    }
+
+    return Some(Fix { start: start, end: end, replacement: String::new() });
+}
+
+impl<'a, 'b> Linter<'a, 'b> {
+    // NOTE: function ranges that do not nest leave every name in the module scope
+    fn new(pieces: &'b [Piece<'a>], closer: &'b [usize], analysis: &'b Analysis) -> Self {
+        let mut functions = analysis.functions.iter().enumerate().collect::<Vec<_>>();
+        let mut parents = vec![0];
+        let mut ends = vec![usize::MAX];
+        let mut stack = vec![0];
+        let mut scope_of = vec![0; pieces.len()];
+
+        functions.sort_by_key(|(_, function)| (function.start, Reverse(function.end)));
+
+        for (_, function) in functions.iter() {
+            while ends[*stack.last().unwrap_or(&0)] < function.start {
+                stack.pop();
+            }
+
+            let parent = *stack.last().unwrap_or(&0);
+
+            if ends[parent] < function.end {
+                parents.truncate(1);
+                scope_of = vec![0; pieces.len()];
+
+                break;
+            }
+
+            parents.push(parent);
+            ends.push(function.end);
+            stack.push(parents.len() - 1);
+
+            for index in function.start..=function.end.min(pieces.len().saturating_sub(1)) {
+                scope_of[index] = parents.len() - 1;
+            }
+        }
+
+        let mut linter = Linter {
+            pieces: pieces,
+            closer: closer,
+            analysis: analysis,
+            bindings: vec![HashMap::new(); parents.len()],
+            parents: parents,
+            scope_of: scope_of,
+            declarations: Vec::new(),
+            declaration_positions: HashSet::new(),
+            skipped_positions: HashSet::new(),
+            export_references: Vec::new(),
+            imports: Vec::new()
+        };
+
+        for (position, (_, function)) in functions.iter().enumerate() {
+            let scope = match linter.bindings.len() > 1 { true => position + 1, false => 0 };
+
+            for param in function.params.iter().chain(function.own_name.iter()) {
+                linter.declare(scope, *param, DeclarationKind::Parameter, false);
+            }
+        }
+
+        return linter;
+    }
+
+    fn declare(&mut self, scope: usize, index: usize, kind: DeclarationKind, exported: bool) {
+        let name = self.pieces[index].text;
+
+        self.declaration_positions.insert(index);
+
+        if !self.bindings[scope].contains_key(name) {
+            self.bindings[scope].insert(name, self.declarations.len());
+            self.declarations.push(Declaration { name: name, index: index, kind: kind, references: 0, exported: exported });
+        }
+    }
+
+    fn is_keyword(&self, index: usize, text: &str) -> bool {
+        return index < self.pieces.len() && self.pieces[index].text == text && self.analysis.roles[index] == Role::Keyword;
+    }
+
+    fn is_exported(&self, index: usize) -> bool {
+        let index = match index > 0 && self.pieces[index - 1].text == "async" { true => index - 1, false => index };
+
+        return (index > 0 && self.is_keyword(index - 1, "export")) ||
+            (index > 1 && self.is_keyword(index - 1, "default") && self.is_keyword(index - 2, "export"));
+    }
+
+    fn collect_declarations(&mut self) {
+        let own_names = self.analysis.functions.iter().filter_map(|function| function.own_name).collect::<HashSet<usize>>();
+
+        for index in 0..self.pieces.len() {
+            if self.analysis.roles[index] != Role::Keyword {
+                continue;
+            }
+
+            let scope = self.scope_of[index];
+            let next_text = text_at(self.pieces, index + 1);
+
+            match self.pieces[index].text {
+                "var" | "let" | "const" => self.collect_variables(index),
+                "function" => {
+                    let name = match next_text { "*" => index + 2, _ => index + 1 };
+
+                    let exported = self.is_exported(index);
+
+                    // NOTE: the minifier runs after the module transform, so it reads export function a() {} as an expression
+                    let is_named = self.pieces.get(name).map_or(false, |piece| piece.kind == TokenKind::Identifier);
+
+                    if is_named && (exported || !own_names.contains(&name)) {
+                        self.declare(scope, name, DeclarationKind::Function, exported);
+                    }
+                },
+                "class" if self.pieces.get(index + 1).map_or(false, |piece| piece.kind == TokenKind::Identifier) && next_text != "extends" => {
+                    let is_expression = index > 0 && !self.is_exported(index) &&
+                        EXPRESSION_STARTS.contains(&self.pieces[index - 1].text);
+                    let kind = match is_expression { true => DeclarationKind::Parameter, false => DeclarationKind::Class };
+                    let exported = self.is_exported(index);
+
+                    self.declare(scope, index + 1, kind, exported);
+                },
+                "catch" if next_text == "(" => {
+                    let mut names = Vec::new();
+
+                    minify_js::pattern_element(self.pieces, self.closer, index + 2, &mut names);
+                    names.into_iter().for_each(|name| self.declare(scope, name, DeclarationKind::Parameter, false));
+                },
+                "import" if next_text != "(" && next_text != "." => self.collect_import(index),
+                "export" if next_text == "{" || next_text == "*" => self.collect_export(index),
+                _ => {}
+            }
+        }
+    }
+
+    fn collect_variables(&mut self, index: usize) {
+        let exported = self.is_exported(index);
+        let mut cursor = index + 1;
+
+        loop {
+            let mut names = Vec::new();
+
+            cursor = match minify_js::pattern_element(self.pieces, self.closer, cursor, &mut names) {
+                Some(next) => next,
+                None => return
+            };
+            names.into_iter().for_each(|name| self.declare(self.scope_of[name], name, DeclarationKind::Variable, exported));
+
+            if text_at(self.pieces, cursor) == "=" {
+                cursor = minify_js::expression_end(self.pieces, self.closer, cursor + 1);
+            }
+
+            if text_at(self.pieces, cursor) != "," {
+                return;
+            }
+
+            cursor += 1;
+        }
+    }
+
+    // NOTE: import a, { b as c, d } from 'e', import * as f from 'g' and import 'h'
+    fn collect_import(&mut self, index: usize) {
+        let end = (index + 1..self.pieces.len())
+            .find(|cursor| self.pieces[*cursor].kind == TokenKind::String)
+            .unwrap_or(self.pieces.len() - 1);
+        let source = unquote(self.pieces[end].text);
+        let mut cursor = index + 1;
+
+        (index..=end).for_each(|position| { self.skipped_positions.insert(position); });
+        self.imports.push(Import { imported: "", local: None, source: source, index: index });
+
+        while cursor < end {
+            let piece = &self.pieces[cursor];
+
+            match piece.text {
+                "*" if text_at(self.pieces, cursor + 1) == "as" => {
+                    self.add_import("*", cursor + 2, source, index);
+                    cursor += 3;
+                },
+                "{" => {
+                    let brace_end = self.closer[cursor];
+
+                    cursor += 1;
+
+                    while cursor < brace_end {
+                        let local = match text_at(self.pieces, cursor + 1) { "as" => cursor + 2, _ => cursor };
+
+                        self.add_import(self.pieces[cursor].text, local, source, index);
+                        cursor = local + 2;
+                    }
+
+                    cursor = brace_end + 1;
+                },
+                "," | "from" => { cursor += 1; },
+                _ if piece.kind == TokenKind::Identifier => {
+                    self.add_import("default", cursor, source, index);
+                    cursor += 1;
+                },
+                _ => { cursor += 1; }
+            }
+        }
+    }
+
+    fn add_import(&mut self, imported: &'a str, local: usize, source: &'a str, index: usize) {
+        if self.pieces.get(local).map_or(false, |piece| piece.kind == TokenKind::Identifier) {
+            self.declare(0, local, DeclarationKind::Import, false);
+            self.imports.push(Import { imported: imported, local: Some(local), source: source, index: index });
+        }
+    }
+
+    // NOTE: export { a, b as c } uses a, export { a } from 'b' and export * from 'c' use nothing of this module
+    fn collect_export(&mut self, index: usize) {
+        let end = match text_at(self.pieces, index + 1) {
+            "{" => self.closer[index + 1],
+            _ => index + 1
+        };
+        let is_reexport = text_at(self.pieces, end + 1) == "from" || text_at(self.pieces, index + 1) == "*";
+
+        (index..=end).for_each(|position| { self.skipped_positions.insert(position); });
+
+        if is_reexport {
+            (end..(end + 3).min(self.pieces.len())).for_each(|position| { self.skipped_positions.insert(position); });
+
+            return;
+        }
+
+        let mut cursor = index + 2;
+
+        while cursor < end {
+            if self.pieces[cursor].kind == TokenKind::Identifier {
+                self.export_references.push(cursor);
+            }
+
+            cursor = match text_at(self.pieces, cursor + 1) { "as" => cursor + 4, _ => cursor + 2 };
+        }
+    }
+
+    // NOTE: returns the positions of the names that resolve to no declaration and no global
+    fn resolve_references(&mut self, config: &LintConfig, directives: &Directives) -> Vec<usize> {
+        let references = (0..self.pieces.len())
+            .filter(|index| {
+                let role = self.analysis.roles[*index];
+                let piece = &self.pieces[*index];
+
+                return (role == Role::Reference || role == Role::Shorthand) && piece.kind == TokenKind::Identifier &&
+                    !piece.text.starts_with('#') && !self.declaration_positions.contains(index) &&
+                    !self.skipped_positions.contains(index) && !CONTEXTUAL_NAMES.contains(&piece.text);
+            })
+            .chain(self.export_references.clone().into_iter())
+            .collect::<Vec<usize>>();
+        let mut undefined_references = Vec::new();
+
+        for index in references {
+            let name = self.pieces[index].text;
+            let mut scope = self.scope_of[index];
+            let declaration = loop {
+                if let Some(declaration) = self.bindings[scope].get(name) {
+                    break Some(*declaration);
+                } else if scope == 0 {
+                    break None;
+                }
+
+                scope = self.parents[scope];
+            };
+
+            match declaration {
+                Some(declaration) => { self.declarations[declaration].references += 1; },
+                None => {
+                    let is_global = GLOBALS.contains(&name) || config.globals.iter().any(|global| global == name) ||
+                        directives.globals.iter().any(|global| global == name);
+                    let is_typeof_check = index > 0 && self.is_keyword(index - 1, "typeof");
+
+                    if !is_global && !is_typeof_check {
+                        undefined_references.push(index);
+                    }
+                }
+            }
+        }
+
+        return undefined_references;
+    }
+}
+
+// NOTE: /* global a, b */, /* eslint-disable [rules] */, // eslint-disable-line [rules] and // eslint-disable-next-line [rules]
+fn read_directives(source: &str, file_name: &str) -> Result<Directives, TranspileError> {
+    let mut directives = Directives::default();
+
+    for token in tokenizer::tokenize(source, file_name)? {
+        let text = match token.kind {
+            TokenKind::LineComment => token.text(source).trim_start_matches("//").trim(),
+            TokenKind::BlockComment => token.text(source).trim_start_matches("/*").trim_end_matches("*/").trim(),
+            _ => continue
+        };
+        let (directive, value) = text.split_at(text.find(char::is_whitespace).unwrap_or(text.len()));
+        let names = value.split(',')
+            .map(|name| name.split(':').next().unwrap_or("").trim().to_string())
+            .filter(|name| !name.is_empty())
+            .collect::<Vec<String>>();
+
+        match directive {
+            "global" | "globals" => directives.globals.extend(names),
+            "eslint-disable" => { directives.file_rules = Some(names); },
+            "eslint-disable-line" => { directives.line_rules.insert(token.line, names); },
+            "eslint-disable-next-line" => { directives.line_rules.insert(token.line + 1, names); },
+            _ => {}
+        }
+    }
+
+    return Ok(directives);
+}
+
+fn text_at<'a>(pieces: &[Piece<'a>], index: usize) -> &'a str {
+    return pieces.get(index).map_or("", |piece| piece.text);
+}
+
+fn unquote(string_literal: &str) -> &str {
+    return string_literal.get(1..string_literal.len().saturating_sub(1)).unwrap_or("");
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+
+    fn problems(source: &str) -> Vec<(usize, usize, String, String)> {
+        return lint(source, "src/test.js", &LintConfig::default()).unwrap().into_iter()
+            .map(|diagnostic| (diagnostic.line, diagnostic.column, diagnostic.rule, diagnostic.message))
+            .collect();
+    }
+
+    #[test]
+    fn lint_reports_undefined_and_unused_names() {
+        let source = "import Route from '@ember/routing/route';\nimport { inject as service } from '@ember/service';\n\n\
+            export default Route.extend({\n  model(params) {\n    const { id, ...rest } = params;\n    let unused = 1;\n\n    \
+            return fetch(`/users/${id}`).then((response) => { try { return response.json(); } catch (error) { return missing; } });\n  }\n});\n";
+
+        assert_eq!(problems(source), vec![
+            (2, 20, String::from("no-unused-vars"), String::from("'service' is defined but never used.")),
+            (6, 20, String::from("no-unused-vars"), String::from("'rest' is defined but never used.")),
+            (7, 9, String::from("no-unused-vars"), String::from("'unused' is defined but never used.")),
+            (9, 110, String::from("no-undef"), String::from("'missing' is not defined."))
+        ]);
+    }
+
+    #[test]
+    fn lint_understands_hoisting_exports_and_typeof_checks() {
+        let source = "export function format(value) {\n  return helper(value);\n}\n\nfunction helper(value) {\n  \
+            return typeof FastBoot === 'undefined' ? value : String(value);\n}\n\nexport function initialize() {}\n\n\
+            export default { initialize };\nconst a = 1, b = 2;\n\nexport { a, b as c };\n\
+            export { default as Button } from './button';\nclass Local {}\nexport class Exported extends Local {}\n";
+
+        assert_eq!(problems(source), Vec::new());
+    }
+
+    #[test]
+    fn lint_reports_debugger_and_ember_rules() {
+        let source = "import $ from 'jquery';\nimport Mixin from '@ember/object/mixin';\nimport { observer } from '@ember/object';\n\n\
+            export default Mixin.create({\n  changed: observer('value', function() {\n    debugger;\n    this.$('.item').hide();\n    \
+            return $;\n  })\n});\n";
+
+        assert_eq!(problems(source), vec![
+            (1, 1, String::from("ember/no-jquery"), String::from("Do not use jQuery.")),
+            (3, 10, String::from("ember/no-observers"), String::from("Don't use observers if possible.")),
+            (5, 16, String::from("ember/no-new-mixins"), String::from("Don't create new mixins.")),
+            (7, 5, String::from("no-debugger"), String::from("Unexpected 'debugger' statement.")),
+            (8, 10, String::from("ember/no-jquery"), String::from("Do not use jQuery."))
+        ]);
+    }
+
+    #[test]
+    fn lint_follows_the_project_config_and_inline_directives() {
+        let config = LintConfig::from_value(&json!({
+            "rules": { "no-debugger": "warn", "no-unused-vars": "off" },
+            "globals": ["moment"]
+        }), "config/lint.json").unwrap();
+        let source = "/* global QUnit */\nlet unused = moment();\nQUnit.start();\ndebugger;\n\
+            missing(); // eslint-disable-line no-undef\n// eslint-disable-next-line\nalsoMissing();\n";
+        let diagnostics = lint(source, "tests/test-helper.js", &config).unwrap();
+
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].rule.as_str(), diagnostics[0].severity, diagnostics[0].line), ("no-debugger", Severity::Warn, 4));
+        assert_eq!(problems("/* eslint-disable */\nmissing();\n"), Vec::new());
+    }
+
+    #[test]
+    fn lint_fixes_debugger_statements() {
+        let source = "function run() {\n  debugger;\n  if (window.DEBUG) { debugger; }\n  return 1;\n}\nrun();\n";
+        let diagnostics = lint(source, "src/test.js", &LintConfig::default()).unwrap();
+
+        assert_eq!(diagnostics[0].fix, Some(Fix { start: 17, end: 29, replacement: String::new() }));
+        assert_eq!(super::super::apply_fixes(source, &diagnostics), Some((
+            String::from("function run() {\n  if (window.DEBUG) {  }\n  return 1;\n}\nrun();\n"), 2
+        )));
+    }
+}
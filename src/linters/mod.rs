@@ -0,0 +1,360 @@
+pub mod hbs;
+pub mod js;
+pub mod reporter;
+pub mod scss;
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use yansi::Paint;
+use rayon::prelude::*;
+use serde_json::Value;
+use super::types::{Config, MberError};
+
+const LINT_CONFIG_PATH: &str = "config/lint.json";
+const LINTED_EXTENSIONS: [&str; 3] = ["js", "hbs", "scss"];
+const MAXIMUM_FIX_PASSES: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Severity {
+    Off,
+    Warn,
+    Error
+}
+
+impl Severity {
+    // NOTE: accepts eslint style values, "off", "warn", "error" or 0, 1, 2
+    pub fn parse(value: &Value) -> Option<Severity> {
+        return match value {
+            Value::String(severity) if severity == "off" => Some(Severity::Off),
+            Value::String(severity) if severity == "warn" => Some(Severity::Warn),
+            Value::String(severity) if severity == "error" => Some(Severity::Error),
+            Value::Number(severity) => match severity.as_u64() {
+                Some(0) => Some(Severity::Off),
+                Some(1) => Some(Severity::Warn),
+                Some(2) => Some(Severity::Error),
+                _ => None
+            },
+            _ => None
+        };
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        return match self {
+            Severity::Off => "off",
+            Severity::Warn => "warning",
+            Severity::Error => "error"
+        };
+    }
+}
+
+// NOTE: replaces the start..end byte range of the linted source
+#[derive(Debug, Clone, PartialEq)]
+pub struct Fix {
+    pub start: usize,
+    pub end: usize,
+    pub replacement: String
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub file_name: String,
+    pub line: usize,
+    pub column: usize,
+    pub rule: String,
+    pub severity: Severity,
+    pub message: String,
+    pub fix: Option<Fix>
+}
+
+#[derive(Debug, Clone)]
+pub struct LintedFile {
+    pub path: PathBuf,
+    pub file_name: String,
+    pub source: String,
+    pub diagnostics: Vec<Diagnostic>
+}
+
+// NOTE: comment directives of a file, an empty rule list turns off every rule
+#[derive(Default)]
+pub(crate) struct Directives {
+    pub(crate) globals: Vec<String>,
+    pub(crate) file_rules: Option<Vec<String>>,
+    pub(crate) line_rules: HashMap<usize, Vec<String>>
+}
+
+impl Directives {
+    pub(crate) fn disables(&self, rule: &str, line: usize) -> bool {
+        let matches = |rules: &Vec<String>| rules.is_empty() || rules.iter().any(|disabled_rule| disabled_rule == rule);
+
+        return self.file_rules.as_ref().map_or(false, matches) || self.line_rules.get(&line).map_or(false, matches);
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LintConfig {
+    pub rules: HashMap<String, Severity>,
+    pub globals: Vec<String>
+}
+
+impl LintConfig {
+    // NOTE: lint config comes from ENV.lint or config/lint.json: { "rules": { "no-debugger": "warn" }, "globals": ["moment"] }
+    pub fn read(config: &Config) -> Result<LintConfig, MberError> {
+        let lint_config_path = format!("{}/{}", config.project_root.display(), LINT_CONFIG_PATH);
+        let options = match &config.env["lint"] {
+            Value::Null if fs::metadata(&lint_config_path).is_ok() => {
+                let content = fs::read_to_string(&lint_config_path).map_err(MberError::io("could not read", &lint_config_path))?;
+
+                serde_json::from_str(&content)
+                    .map_err(|error| MberError::config(&lint_config_path, format!("config/lint.json is not valid JSON: {}", error)))?
+            },
+            options => options.clone()
+        };
+
+        return LintConfig::from_value(&options, &lint_config_path);
+    }
+
+    pub fn from_value(options: &Value, path: &str) -> Result<LintConfig, MberError> {
+        let rules = options["rules"].as_object().map_or(Ok(HashMap::new()), |rules| {
+            return rules.iter().map(|(rule, value)| {
+                if !js::RULES.iter().chain(hbs::RULES.iter()).chain(scss::RULES.iter()).any(|(name, _)| name == rule) {
+                    return Err(MberError::config(path, format!("{} is not a known lint rule", rule)));
+                }
+
+                return Severity::parse(value).map(|severity| (rule.to_string(), severity)).ok_or_else(|| {
+                    return MberError::config(path, format!("lint rule {} should be off, warn or error, got: {}", rule, value));
+                });
+            }).collect::<Result<HashMap<String, Severity>, MberError>>();
+        })?;
+        let globals = match &options["globals"] {
+            Value::Array(globals) => globals.iter().filter_map(|global| global.as_str().map(String::from)).collect(),
+            Value::Object(globals) => globals.keys().cloned().collect(),
+            _ => Vec::new()
+        };
+
+        return Ok(LintConfig { rules: rules, globals: globals });
+    }
+
+    pub fn severity(&self, rule: &str, default_severity: Severity) -> Severity {
+        return *self.rules.get(rule).unwrap_or(&default_severity);
+    }
+}
+
+// NOTE: lints the javascript and template files of a bundle before it is written, the files with problems are returned
+// for the command to print. Fails only when --lint=error and a rule with error severity reports something, so warnings
+// never break a build. The error carries the formatted diagnostics since the bundle is not written
+pub fn check_files(files: &Vec<PathBuf>, config: &Config, bundle_name: &str) -> Result<Vec<LintedFile>, MberError> {
+    let linted_files = lint_files(files, config)?.into_iter()
+        .filter(|linted_file| !linted_file.diagnostics.is_empty())
+        .collect::<Vec<LintedFile>>();
+    let errors = linted_files.iter()
+        .flat_map(|linted_file| linted_file.diagnostics.iter())
+        .filter(|diagnostic| diagnostic.severity == Severity::Error)
+        .collect::<Vec<&Diagnostic>>();
+
+    return match errors.first() {
+        Some(first_error) if config.cli_arguments.lint == "error" => Err(MberError::Lint {
+            path: config.project_root.join(&first_error.file_name),
+            message: format!("{} has {} lint errors", bundle_name, errors.len()),
+            diagnostics: format_diagnostics(&linted_files)
+        }),
+        _ => Ok(linted_files)
+    };
+}
+
+// NOTE: files with other extensions are skipped, results keep the order of the files
+pub fn lint_files(files: &Vec<PathBuf>, config: &Config) -> Result<Vec<LintedFile>, MberError> {
+    let lint_config = LintConfig::read(config)?;
+    let has_templates = files.iter().any(|file| file.extension().map_or(false, |extension| extension == "hbs"));
+    let known_components = match has_templates {
+        true => hbs::known_components(&config.project_root),
+        false => HashSet::new()
+    };
+
+    return files.par_iter()
+        .filter(|file| file.extension().map_or(false, |extension| LINTED_EXTENSIONS.iter().any(|linted| extension == *linted)))
+        .map(|file| -> Result<LintedFile, MberError> {
+            let source = fs::read_to_string(file).map_err(MberError::io("could not read", file))?;
+            let file_name = file.strip_prefix(&config.project_root).unwrap_or(file).to_string_lossy().to_string();
+            let diagnostics = match file.extension().and_then(|extension| extension.to_str()) {
+                Some("hbs") => hbs::lint(&source, &file_name, &lint_config, &known_components),
+                Some("scss") => scss::lint(&source, &file_name, &lint_config),
+                _ => js::lint(&source, &file_name, &lint_config)?
+            };
+
+            return Ok(LintedFile { path: file.to_path_buf(), file_name: file_name, source: source, diagnostics: diagnostics });
+        })
+        .collect();
+}
+
+// NOTE: a fix can uncover or unblock another one, so fixing repeats until nothing changes like eslint --fix does.
+// Returns the number of applied fixes
+pub fn fix_files(files: &Vec<PathBuf>, config: &Config) -> Result<usize, MberError> {
+    let mut fixed_problem_count = 0;
+
+    for _ in 0..MAXIMUM_FIX_PASSES {
+        let fixed_files = lint_files(files, config)?.into_iter()
+            .filter_map(|linted_file| {
+                return apply_fixes(&linted_file.source, &linted_file.diagnostics)
+                    .map(|(fixed_source, fix_count)| (linted_file.path, fixed_source, fix_count));
+            })
+            .collect::<Vec<(PathBuf, String, usize)>>();
+
+        if fixed_files.is_empty() {
+            break;
+        }
+
+        for (path, fixed_source, fix_count) in fixed_files {
+            fs::write(&path, fixed_source).map_err(MberError::io("could not write", &path))?;
+            fixed_problem_count += fix_count;
+        }
+    }
+
+    return Ok(fixed_problem_count);
+}
+
+// NOTE: overlapping fixes are left for the next pass. Returns None when there is nothing to fix
+pub fn apply_fixes(source: &str, diagnostics: &Vec<Diagnostic>) -> Option<(String, usize)> {
+    let mut fixes = diagnostics.iter().filter_map(|diagnostic| diagnostic.fix.as_ref()).collect::<Vec<&Fix>>();
+    let mut fixed_source = String::new();
+    let mut cursor = 0;
+    let mut fix_count = 0;
+
+    fixes.sort_by_key(|fix| (fix.start, fix.end));
+
+    for fix in fixes {
+        if fix.start < cursor || fix.end > source.len() {
+            continue;
+        }
+
+        fixed_source.push_str(&source[cursor..fix.start]);
+        fixed_source.push_str(&fix.replacement);
+        cursor = fix.end;
+        fix_count += 1;
+    }
+
+    if fix_count == 0 {
+        return None;
+    }
+
+    fixed_source.push_str(&source[cursor..]);
+
+    return Some((fixed_source, fix_count));
+}
+
+// NOTE: files changed since the git ref and the untracked ones, missing files are deleted ones so they are left out
+pub fn changed_files(project_root: &Path, git_ref: &str) -> Result<Vec<PathBuf>, MberError> {
+    let git = |arguments: &[&str]| -> Result<Vec<PathBuf>, MberError> {
+        let output = Command::new("git").args(arguments).current_dir(project_root).output()
+            .map_err(MberError::io("could not run git in", project_root))?;
+
+        if !output.status.success() {
+            return Err(MberError::build(project_root, format!(
+                "could not list the files changed since {}: {}", git_ref, String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+
+        return Ok(String::from_utf8_lossy(&output.stdout).lines()
+            .map(|line| project_root.join(line.trim()))
+            .filter(|path| path.is_file())
+            .collect());
+    };
+    let mut files = git(&["diff", "--name-only", "--relative", git_ref])?;
+
+    files.extend(git(&["ls-files", "--others", "--exclude-standard"])?);
+    files.sort();
+    files.dedup();
+
+    return Ok(files);
+}
+
+pub fn format_diagnostics(linted_files: &Vec<LintedFile>) -> String {
+    return linted_files.iter()
+        .flat_map(|linted_file| {
+            return linted_file.diagnostics.iter().map(move |diagnostic| format!("{}\n", format_diagnostic(diagnostic, &linted_file.source)));
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+}
+
+pub fn format_diagnostic(diagnostic: &Diagnostic, source: &str) -> String {
+    let severity = match diagnostic.severity {
+        Severity::Error => Paint::red(diagnostic.severity.as_str()),
+        _ => Paint::yellow(diagnostic.severity.as_str())
+    };
+
+    return format!(
+        "{}:{}:{} {} {} ({})\n{}",
+        diagnostic.file_name, diagnostic.line, diagnostic.column, severity, diagnostic.message,
+        Paint::cyan(&diagnostic.rule), code_frame(source, diagnostic.line, diagnostic.column)
+    );
+}
+
+// NOTE: maps byte offsets of a source to 1 based line and column pairs, columns count characters
+pub(crate) fn location_finder(source: &str) -> impl Fn(usize) -> (usize, usize) + '_ {
+    let line_starts = std::iter::once(0).chain(source.match_indices('\n').map(|(index, _)| index + 1)).collect::<Vec<usize>>();
+
+    return move |offset: usize| {
+        let line = match line_starts.binary_search(&offset) { Ok(line) => line, Err(line) => line - 1 };
+
+        return (line + 1, source[line_starts[line]..offset].chars().count() + 1);
+    };
+}
+
+// NOTE: babel style frame with two lines of context around the reported line and a caret under the reported column
+pub fn code_frame(source: &str, line: usize, column: usize) -> String {
+    let lines = source.lines().collect::<Vec<&str>>();
+    let first_line = line.saturating_sub(2).max(1);
+    let last_line = (line + 2).min(lines.len());
+    let number_width = last_line.to_string().len();
+
+    return (first_line..=last_line).map(|number| {
+        let text = lines[number - 1];
+        let gutter = format!("{:>width$} |", number, width = number_width);
+
+        if number != line {
+            return format!("  {} {}", gutter, text).trim_end().to_string();
+        }
+
+        let caret_padding = text.chars().take(column.saturating_sub(1))
+            .map(|character| match character { '\t' => '\t', _ => ' ' })
+            .collect::<String>();
+
+        return format!("> {} {}\n  {:>width$} | {}^", gutter, text, "", caret_padding, width = number_width);
+    }).collect::<Vec<String>>().join("\n");
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use super::*;
+
+    #[test]
+    fn code_frame_points_to_the_reported_column() {
+        let source = "import Route from '@ember/routing/route';\n\nexport default Route.extend({\n  model() {\n    debugger;\n  }\n});\n";
+
+        assert_eq!(code_frame(source, 5, 5), vec![
+            "  3 | export default Route.extend({",
+            "  4 |   model() {",
+            "> 5 |     debugger;",
+            "    |     ^",
+            "  6 |   }",
+            "  7 | });"
+        ].join("\n"));
+        assert_eq!(code_frame("missing();", 1, 1), "> 1 | missing();\n    | ^");
+    }
+
+    #[test]
+    fn lint_config_rejects_unknown_rules_and_severities() {
+        let error = |options: Value| LintConfig::from_value(&options, "config/lint.json").unwrap_err().to_string();
+        let config = LintConfig::from_value(&json!({ "rules": { "no-undef": 1 }, "globals": { "moment": "readonly" } }), "")
+            .unwrap();
+
+        assert_eq!(config.severity("no-undef", Severity::Error), Severity::Warn);
+        assert_eq!(config.severity("no-debugger", Severity::Error), Severity::Error);
+        assert_eq!(config.globals, vec!["moment"]);
+        assert_eq!(error(json!({ "rules": { "no-console": "error" } })), "no-console is not a known lint rule");
+        assert_eq!(error(json!({ "rules": { "no-undef": "fatal" } })), "lint rule no-undef should be off, warn or error, got: \"fatal\"");
+    }
+}
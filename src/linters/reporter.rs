@@ -0,0 +1,291 @@
+use std::error::Error;
+use yansi::Paint;
+use serde_json::{json, Value};
+use super::{js, hbs, scss, Diagnostic, LintedFile, Severity};
+use super::super::runners::test_reporter::escape_xml;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Stylish,
+    Json,
+    Checkstyle,
+    Sarif
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Result<Self, Box<dyn Error>> {
+        return match name {
+            "stylish" => Ok(Format::Stylish),
+            "json" => Ok(Format::Json),
+            "checkstyle" => Ok(Format::Checkstyle),
+            "sarif" => Ok(Format::Sarif),
+            _ => Err(format!("--format value {} is not valid, use stylish, json, checkstyle or sarif", name).into())
+        };
+    }
+
+    // NOTE: only stylish is colored, the others are read by tools. Files without problems are only listed in json
+    pub fn report(&self, files: &Vec<LintedFile>, colored: bool) -> String {
+        return match self {
+            Format::Stylish => stylish_report(files, colored),
+            Format::Json => serde_json::to_string_pretty(&json_report(files)).unwrap_or_default(),
+            Format::Checkstyle => checkstyle_report(files),
+            Format::Sarif => serde_json::to_string_pretty(&sarif_report(files)).unwrap_or_default()
+        };
+    }
+}
+
+fn count(diagnostics: &Vec<&Diagnostic>, severity: Severity, only_fixable: bool) -> usize {
+    return diagnostics.iter()
+        .filter(|diagnostic| diagnostic.severity == severity && (!only_fixable || diagnostic.fix.is_some()))
+        .count();
+}
+
+fn pluralize(count: usize, word: &str) -> String {
+    return format!("{} {}{}", count, word, if count == 1 { "" } else { "s" });
+}
+
+// NOTE: eslint stylish layout, problems are grouped by file with aligned columns and a summary at the end
+fn stylish_report(files: &Vec<LintedFile>, colored: bool) -> String {
+    let paint = |text: String, painter: fn(String) -> Paint<String>| match colored {
+        true => painter(text).to_string(),
+        false => text
+    };
+    let diagnostics = files.iter().flat_map(|file| file.diagnostics.iter()).collect::<Vec<&Diagnostic>>();
+
+    if diagnostics.is_empty() {
+        return String::new();
+    }
+
+    let file_reports = files.iter().filter(|file| !file.diagnostics.is_empty()).map(|file| {
+        let position_width = file.diagnostics.iter().map(|diagnostic| format!("{}:{}", diagnostic.line, diagnostic.column).len())
+            .max().unwrap_or(0);
+        let message_width = file.diagnostics.iter().map(|diagnostic| diagnostic.message.chars().count()).max().unwrap_or(0);
+        let lines = file.diagnostics.iter().map(|diagnostic| {
+            let position = format!("{:<width$}", format!("{}:{}", diagnostic.line, diagnostic.column), width = position_width);
+            let severity = match diagnostic.severity {
+                Severity::Error => paint(format!("{:<7}", "error"), Paint::red),
+                _ => paint(format!("{:<7}", "warning"), Paint::yellow)
+            };
+            let message = format!("{:<width$}", diagnostic.message, width = message_width);
+
+            let rule = paint(diagnostic.rule.clone(), |text| Paint::new(text).dimmed());
+
+            return format!("  {}  {}  {}  {}", paint(position, |text| Paint::new(text).dimmed()), severity, message, rule);
+        }).collect::<Vec<String>>();
+
+        return format!("{}\n{}", paint(file.file_name.clone(), |text| Paint::new(text).underline()), lines.join("\n"));
+    }).collect::<Vec<String>>();
+    let error_count = count(&diagnostics, Severity::Error, false);
+    let warning_count = count(&diagnostics, Severity::Warn, false);
+    let fixable_error_count = count(&diagnostics, Severity::Error, true);
+    let fixable_warning_count = count(&diagnostics, Severity::Warn, true);
+    let summary = format!(
+        "✖ {} ({}, {})", pluralize(diagnostics.len(), "problem"), pluralize(error_count, "error"),
+        pluralize(warning_count, "warning")
+    );
+    let summary = match error_count {
+        0 => paint(summary, Paint::yellow),
+        _ => paint(summary, Paint::red)
+    };
+    let fixable_summary = match fixable_error_count + fixable_warning_count {
+        0 => String::new(),
+        _ => format!(
+            "\n  {} and {} potentially fixable with the `--fix` option.", pluralize(fixable_error_count, "error"),
+            pluralize(fixable_warning_count, "warning")
+        )
+    };
+
+    return format!("\n{}\n\n{}{}\n", file_reports.join("\n\n"), summary, fixable_summary);
+}
+
+fn json_report(files: &Vec<LintedFile>) -> Value {
+    return Value::Array(files.iter().map(|file| {
+        let diagnostics = file.diagnostics.iter().collect::<Vec<&Diagnostic>>();
+
+        return json!({
+            "filePath": file.path.display().to_string(),
+            "messages": diagnostics.iter().map(|diagnostic| {
+                let mut message = json!({
+                    "ruleId": diagnostic.rule,
+                    "severity": match diagnostic.severity { Severity::Error => 2, _ => 1 },
+                    "message": diagnostic.message,
+                    "line": diagnostic.line,
+                    "column": diagnostic.column
+                });
+
+                if let Some(fix) = &diagnostic.fix {
+                    message["fix"] = json!({ "range": [fix.start, fix.end], "text": fix.replacement });
+                }
+
+                return message;
+            }).collect::<Vec<Value>>(),
+            "errorCount": count(&diagnostics, Severity::Error, false),
+            "warningCount": count(&diagnostics, Severity::Warn, false),
+            "fixableErrorCount": count(&diagnostics, Severity::Error, true),
+            "fixableWarningCount": count(&diagnostics, Severity::Warn, true)
+        });
+    }).collect());
+}
+
+fn checkstyle_report(files: &Vec<LintedFile>) -> String {
+    let file_elements = files.iter().map(|file| {
+        let errors = file.diagnostics.iter().map(|diagnostic| format!(
+            "    <error line=\"{}\" column=\"{}\" severity=\"{}\" message=\"{}\" source=\"mber.rules.{}\"/>\n",
+            diagnostic.line, diagnostic.column, diagnostic.severity.as_str(), escape_xml(&diagnostic.message),
+            escape_xml(&diagnostic.rule)
+        )).collect::<String>();
+
+        return format!("  <file name=\"{}\">\n{}  </file>\n", escape_xml(&file.path.display().to_string()), errors);
+    }).collect::<String>();
+
+    return format!("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n{}</checkstyle>", file_elements);
+}
+
+// NOTE: SARIF 2.1.0 for code review bots, uris are relative to the project root which is the %SRCROOT% base
+fn sarif_report(files: &Vec<LintedFile>) -> Value {
+    let rules = js::RULES.iter().chain(hbs::RULES.iter()).chain(scss::RULES.iter()).collect::<Vec<_>>();
+    let results = files.iter().flat_map(|file| file.diagnostics.iter()).map(|diagnostic| json!({
+        "ruleId": diagnostic.rule,
+        "ruleIndex": rules.iter().position(|(name, _)| *name == diagnostic.rule),
+        "level": match diagnostic.severity { Severity::Error => "error", _ => "warning" },
+        "message": { "text": diagnostic.message },
+        "locations": [{
+            "physicalLocation": {
+                "artifactLocation": { "uri": diagnostic.file_name, "uriBaseId": "%SRCROOT%" },
+                "region": { "startLine": diagnostic.line, "startColumn": diagnostic.column }
+            }
+        }]
+    })).collect::<Vec<Value>>();
+
+    return json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "mber",
+                    "informationUri": "https://github.com/izelnakri/mber-rust",
+                    "rules": rules.iter().map(|(name, severity)| json!({
+                        "id": name,
+                        "defaultConfiguration": {
+                            "level": match severity { Severity::Error => "error", Severity::Warn => "warning", Severity::Off => "none" }
+                        }
+                    })).collect::<Vec<Value>>()
+                }
+            },
+            "results": results
+        }]
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use super::*;
+    use super::super::Fix;
+
+    fn linted_files() -> Vec<LintedFile> {
+        let diagnostic = |line, column, rule: &str, severity, message: &str, fix| Diagnostic {
+            file_name: String::from("src/ui/routes/index/route.js"),
+            line: line,
+            column: column,
+            rule: rule.to_string(),
+            severity: severity,
+            message: message.to_string(),
+            fix: fix
+        };
+
+        return vec![
+            LintedFile {
+                path: PathBuf::from("/project/src/ui/routes/index/route.js"),
+                file_name: String::from("src/ui/routes/index/route.js"),
+                source: String::from("debugger;\nmissing(\"<a>\");\n"),
+                diagnostics: vec![
+                    diagnostic(1, 1, "no-debugger", Severity::Error, "Unexpected 'debugger' statement.", Some(Fix {
+                        start: 0, end: 10, replacement: String::new()
+                    })),
+                    diagnostic(12, 1, "no-undef", Severity::Warn, "'missing' & \"<a>\" is not defined.", None)
+                ]
+            },
+            LintedFile {
+                path: PathBuf::from("/project/src/app.js"),
+                file_name: String::from("src/app.js"),
+                source: String::new(),
+                diagnostics: Vec::new()
+            }
+        ];
+    }
+
+    #[test]
+    fn parse_works() {
+        assert_eq!(Format::parse("stylish").unwrap(), Format::Stylish);
+        assert_eq!(Format::parse("json").unwrap(), Format::Json);
+        assert_eq!(Format::parse("checkstyle").unwrap(), Format::Checkstyle);
+        assert_eq!(Format::parse("sarif").unwrap(), Format::Sarif);
+        assert_eq!(
+            Format::parse("junit").unwrap_err().to_string(),
+            "--format value junit is not valid, use stylish, json, checkstyle or sarif"
+        );
+    }
+
+    #[test]
+    fn report_works_for_stylish_and_checkstyle() {
+        assert_eq!(Format::Stylish.report(&linted_files(), false), vec![
+            "",
+            "src/ui/routes/index/route.js",
+            "  1:1   error    Unexpected 'debugger' statement.   no-debugger",
+            "  12:1  warning  'missing' & \"<a>\" is not defined.  no-undef",
+            "",
+            "✖ 2 problems (1 error, 1 warning)",
+            "  1 error and 0 warnings potentially fixable with the `--fix` option.",
+            ""
+        ].join("\n"));
+        assert_eq!(Format::Stylish.report(&vec![], false), "");
+        assert_eq!(Format::Checkstyle.report(&linted_files(), false), vec![
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>",
+            "<checkstyle version=\"4.3\">",
+            "  <file name=\"/project/src/ui/routes/index/route.js\">",
+            "    <error line=\"1\" column=\"1\" severity=\"error\" message=\"Unexpected &apos;debugger&apos; statement.\" \
+                source=\"mber.rules.no-debugger\"/>",
+            "    <error line=\"12\" column=\"1\" severity=\"warning\" \
+                message=\"&apos;missing&apos; &amp; &quot;&lt;a&gt;&quot; is not defined.\" source=\"mber.rules.no-undef\"/>",
+            "  </file>",
+            "  <file name=\"/project/src/app.js\">",
+            "  </file>",
+            "</checkstyle>"
+        ].join("\n"));
+    }
+
+    #[test]
+    fn report_works_for_json_and_sarif() -> Result<(), Box<dyn Error>> {
+        let json_report: Value = serde_json::from_str(&Format::Json.report(&linted_files(), false))?;
+        let sarif_report: Value = serde_json::from_str(&Format::Sarif.report(&linted_files(), false))?;
+        let result = &sarif_report["runs"][0]["results"][1];
+
+        assert_eq!(json_report[0]["messages"][0], json!({
+            "ruleId": "no-debugger",
+            "severity": 2,
+            "message": "Unexpected 'debugger' statement.",
+            "line": 1,
+            "column": 1,
+            "fix": { "range": [0, 10], "text": "" }
+        }));
+        assert_eq!(
+            (&json_report[0]["errorCount"], &json_report[0]["warningCount"], &json_report[0]["fixableErrorCount"]),
+            (&json!(1), &json!(1), &json!(1))
+        );
+        assert_eq!(json_report[1]["messages"], json!([]));
+        assert_eq!(sarif_report["version"], "2.1.0");
+        assert_eq!(sarif_report["runs"][0]["tool"]["driver"]["name"], "mber");
+        assert_eq!(sarif_report["runs"][0]["results"].as_array().map(|results| results.len()), Some(2));
+        assert_eq!(result["ruleId"], "no-undef");
+        assert_eq!(result["level"], "warning");
+        assert_eq!(result["ruleIndex"], 0);
+        assert_eq!(result["locations"][0]["physicalLocation"], json!({
+            "artifactLocation": { "uri": "src/ui/routes/index/route.js", "uriBaseId": "%SRCROOT%" },
+            "region": { "startLine": 12, "startColumn": 1 }
+        }));
+
+        return Ok(());
+    }
+}
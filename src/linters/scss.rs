@@ -0,0 +1,313 @@
+use std::collections::HashSet;
+use super::{location_finder, Diagnostic, Directives, Fix, LintConfig, Severity};
+
+pub const RULES: [(&str, Severity); 5] = [
+    ("no-unbalanced-braces", Severity::Error),
+    ("block-no-empty", Severity::Warn),
+    ("color-no-invalid-hex", Severity::Error),
+    ("declaration-block-no-duplicate-properties", Severity::Warn),
+    ("color-hex-case", Severity::Warn)
+];
+const VALUELESS_AT_RULES: [&str; 4] = ["extend", "import", "use", "forward"];
+
+struct Comment<'a> {
+    start: usize,
+    end: usize,
+    content: &'a str
+}
+
+struct Scanner<'a> {
+    source: &'a str,
+    code: &'a str,
+    stack: Vec<(u8, usize)>,
+    blocks: Vec<(usize, HashSet<String>)>,
+    problems: Vec<(&'static str, usize, String, Option<Fix>)>
+}
+
+// NOTE: stylelint style checks, scss is not parsed. Statements are split on braces and semicolons of the source where
+// comments, strings and unquoted url() contents are blanked first
+pub fn lint(source: &str, file_name: &str, config: &LintConfig) -> Vec<Diagnostic> {
+    let (code, comments) = blank_comments_and_strings(source);
+    let mut scanner = Scanner {
+        source: source,
+        code: &code,
+        stack: Vec::new(),
+        blocks: vec![(0, HashSet::new())],
+        problems: Vec::new()
+    };
+
+    scanner.scan();
+
+    let location = location_finder(source);
+    let directives = read_directives(&comments, &location);
+    let mut diagnostics = scanner.problems.into_iter()
+        .filter_map(|(rule, offset, message, fix)| {
+            let default_severity = RULES.iter().find(|(name, _)| *name == rule).map_or(Severity::Off, |(_, severity)| *severity);
+            let severity = config.severity(rule, default_severity);
+            let (line, column) = location(offset);
+
+            if severity == Severity::Off || directives.disables(rule, line) {
+                return None;
+            }
+
+            return Some(Diagnostic {
+                file_name: file_name.to_string(),
+                line: line,
+                column: column,
+                rule: rule.to_string(),
+                severity: severity,
+                message: message,
+                fix: fix
+            });
+        })
+        .collect::<Vec<Diagnostic>>();
+
+    diagnostics.sort_by_key(|diagnostic| (diagnostic.line, diagnostic.column));
+
+    return diagnostics;
+}
+
+impl<'a> Scanner<'a> {
+    fn scan(&mut self) {
+        let bytes = self.code.as_bytes();
+        let mut segment_start = 0;
+
+        for (position, byte) in bytes.iter().enumerate() {
+            match byte {
+                b'{' if position > 0 && bytes[position - 1] == b'#' => self.stack.push((b'#', position)),
+                b'{' => {
+                    let selector_start = segment_start + (self.code[segment_start..position].len() -
+                        self.code[segment_start..position].trim_start().len());
+
+                    self.stack.push((b'{', position));
+                    self.blocks.push((selector_start, HashSet::new()));
+                    segment_start = position + 1;
+                },
+                b'(' | b'[' => self.stack.push((*byte, position)),
+                b')' | b']' => {
+                    let opener = if *byte == b')' { b'(' } else { b'[' };
+
+                    match self.stack.last() {
+                        Some((last_opener, _)) if *last_opener == opener => { self.stack.pop(); },
+                        _ => self.report_unexpected(position)
+                    }
+                },
+                b'}' => match self.stack.last().cloned() {
+                    Some((b'#', _)) => { self.stack.pop(); },
+                    Some((b'{', start)) => {
+                        self.check_statement(segment_start, position);
+
+                        let (selector_start, _) = self.blocks.pop().unwrap_or_default();
+
+                        if self.source[start + 1..position].trim().is_empty() {
+                            self.problems.push(("block-no-empty", selector_start, String::from("Unexpected empty block"), None));
+                        }
+
+                        self.stack.pop();
+                        segment_start = position + 1;
+                    },
+                    _ => self.report_unexpected(position)
+                },
+                b';' if self.stack.last().map_or(true, |(opener, _)| *opener == b'{') => {
+                    self.check_statement(segment_start, position);
+                    segment_start = position + 1;
+                },
+                _ => {}
+            }
+        }
+
+        for (opener, position) in self.stack.drain(..) {
+            self.problems.push((
+                "no-unbalanced-braces", position, format!("Unclosed \"{}\"", opener as char), None
+            ));
+        }
+    }
+
+    fn report_unexpected(&mut self, position: usize) {
+        let character = self.code[position..].chars().next().unwrap_or(' ');
+
+        self.problems.push(("no-unbalanced-braces", position, format!("Unexpected \"{}\"", character), None));
+    }
+
+    // NOTE: declarations and at-rules that end with a semicolon or with the closing brace of their block
+    fn check_statement(&mut self, start: usize, end: usize) {
+        let text = self.code[start..end].trim_start();
+        let start = end - text.len();
+        let text = text.trim_end();
+
+        if text.starts_with('@') {
+            let name = text[1..].split(|character: char| !character.is_alphanumeric() && character != '-').next().unwrap_or("");
+
+            if !VALUELESS_AT_RULES.contains(&name) {
+                self.check_colors(start + 1 + name.len(), start + text.len());
+            }
+
+            return;
+        }
+
+        let colon = match text.find(':') {
+            Some(colon) => colon,
+            None => return
+        };
+        let property = text[..colon].trim_end();
+
+        self.check_colors(start + colon + 1, start + text.len());
+
+        if property.is_empty() || property.starts_with('$') || property.starts_with('%') || property.contains("#{") ||
+            self.blocks.len() < 2 {
+            return;
+        }
+
+        let is_new_property = self.blocks.last_mut().map_or(true, |(_, properties)| properties.insert(property.to_lowercase()));
+
+        if !is_new_property {
+            self.problems.push((
+                "declaration-block-no-duplicate-properties", start, format!("Unexpected duplicate \"{}\"", property), None
+            ));
+        }
+    }
+
+    fn check_colors(&mut self, start: usize, end: usize) {
+        let bytes = self.code.as_bytes();
+
+        for position in (start..end).filter(|position| bytes[*position] == b'#' && bytes.get(position + 1) != Some(&b'{')) {
+            let hex = self.code[position + 1..end]
+                .split(|character: char| !character.is_ascii_alphanumeric() && character != '_' && character != '-')
+                .next()
+                .unwrap_or("");
+
+            if hex.is_empty() {
+                continue;
+            } else if !hex.chars().all(|character| character.is_ascii_hexdigit()) || ![3, 4, 6, 8].contains(&hex.len()) {
+                self.problems.push(("color-no-invalid-hex", position, format!("Unexpected invalid hex color \"#{}\"", hex), None));
+            } else if hex.chars().any(|character| character.is_ascii_uppercase()) {
+                let fix = Fix { start: position + 1, end: position + 1 + hex.len(), replacement: hex.to_lowercase() };
+
+                self.problems.push((
+                    "color-hex-case", position, format!("Expected \"#{}\" to be \"#{}\"", hex, hex.to_lowercase()), Some(fix)
+                ));
+            }
+        }
+    }
+}
+
+// NOTE: blanked parts become spaces so the offsets and line breaks of the code stay the same. Line comments only start
+// outside of parentheses since unquoted urls have // in them
+fn blank_comments_and_strings(source: &str) -> (String, Vec<Comment<'_>>) {
+    let bytes = source.as_bytes();
+    let mut code = bytes.to_vec();
+    let mut comments = Vec::new();
+    let mut paren_depth: usize = 0;
+    let mut position = 0;
+    let blank = |code: &mut Vec<u8>, start: usize, end: usize| {
+        code[start..end].iter_mut().filter(|byte| **byte != b'\n').for_each(|byte| *byte = b' ');
+    };
+
+    while position < bytes.len() {
+        let rest = &source[position..];
+
+        if rest.starts_with("/*") || (rest.starts_with("//") && paren_depth == 0) {
+            let (content_end, end) = match rest.starts_with("/*") {
+                true => rest[2..].find("*/").map_or((bytes.len(), bytes.len()), |index| (position + 2 + index, position + 4 + index)),
+                false => rest.find('\n').map_or((bytes.len(), bytes.len()), |index| (position + index, position + index))
+            };
+
+            comments.push(Comment { start: position, end: end, content: &source[position + 2..content_end] });
+            blank(&mut code, position, end);
+            position = end;
+        } else if bytes[position] == b'"' || bytes[position] == b'\'' {
+            let mut end = position + 1;
+
+            while end < bytes.len() && bytes[end] != bytes[position] && bytes[end] != b'\n' {
+                end += if bytes[end] == b'\\' { 2 } else { 1 };
+            }
+
+            blank(&mut code, position + 1, end.min(bytes.len()));
+            position = end + 1;
+        } else if rest.get(..4).map_or(false, |word| word.eq_ignore_ascii_case("url(")) &&
+            (position == 0 || !(bytes[position - 1].is_ascii_alphanumeric() || bytes[position - 1] == b'-')) &&
+            !rest[4..].trim_start().starts_with(|character| character == '"' || character == '\'') {
+            let end = rest.find(')').map_or(bytes.len(), |index| position + index);
+
+            blank(&mut code, position + 4, end);
+            position = end;
+        } else {
+            match bytes[position] {
+                b'(' => paren_depth += 1,
+                b')' => paren_depth = paren_depth.saturating_sub(1),
+                _ => {}
+            }
+
+            position += 1;
+        }
+    }
+
+    return (String::from_utf8(code).unwrap_or_default(), comments);
+}
+
+fn read_directives<F: Fn(usize) -> (usize, usize)>(comments: &Vec<Comment>, location: &F) -> Directives {
+    return comments.iter().fold(Directives::default(), |mut directives, comment| {
+        let mut words = comment.content.split(|character: char| character.is_whitespace() || character == ',')
+            .filter(|word| !word.is_empty());
+        let directive = words.next().unwrap_or("");
+        let rules = words.map(String::from).collect::<Vec<String>>();
+
+        match directive {
+            "stylelint-disable" => { directives.file_rules = Some(rules); },
+            "stylelint-disable-line" => { directives.line_rules.insert(location(comment.start).0, rules); },
+            "stylelint-disable-next-line" => { directives.line_rules.insert(location(comment.end).0 + 1, rules); },
+            _ => {}
+        }
+
+        return directives;
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn problems(source: &str) -> Vec<(usize, usize, String, String)> {
+        return lint(source, "src/ui/styles/application.scss", &LintConfig::default()).into_iter()
+            .map(|diagnostic| (diagnostic.line, diagnostic.column, diagnostic.rule, diagnostic.message))
+            .collect();
+    }
+
+    #[test]
+    fn lint_reports_braces_empty_blocks_and_duplicate_properties() {
+        let source = "$primary: #FF0000;\n.header {\n  color: $primary;\n  .title {}\n  color: red;\n  \
+            &:hover { width: calc(100% - #{$gap}) }\n}\n.footer { margin: 0;\n";
+
+        assert_eq!(problems(source), vec![
+            (1, 11, String::from("color-hex-case"), String::from("Expected \"#FF0000\" to be \"#ff0000\"")),
+            (4, 3, String::from("block-no-empty"), String::from("Unexpected empty block")),
+            (5, 3, String::from("declaration-block-no-duplicate-properties"), String::from("Unexpected duplicate \"color\"")),
+            (8, 9, String::from("no-unbalanced-braces"), String::from("Unclosed \"{\""))
+        ]);
+        assert_eq!(problems(".a { color: red; }\n}\n"), vec![
+            (2, 1, String::from("no-unbalanced-braces"), String::from("Unexpected \"}\""))
+        ]);
+    }
+
+    #[test]
+    fn lint_skips_comments_strings_urls_selectors_and_disabled_lines() {
+        let source = "// #zzz { color: #GGG }\n@import 'theme';\n#main .item {\n  /* color: #bad1; */\n  \
+            background: url(http://example.com/image.png#xyz);\n  content: \"#nope {\";\n  \
+            border-color: #12; // stylelint-disable-line color-no-invalid-hex\n  \
+            /* stylelint-disable-next-line */\n  color: #ABCDEFG;\n  background-color: #abc;\n  outline-color: #xyz;\n}\n";
+
+        assert_eq!(problems(source), vec![
+            (11, 18, String::from("color-no-invalid-hex"), String::from("Unexpected invalid hex color \"#xyz\""))
+        ]);
+    }
+
+    #[test]
+    fn lint_fixes_uppercase_hex_colors() {
+        let source = ".a { color: #ABC; border: 1px solid #FfFfFf; }\n";
+        let diagnostics = lint(source, "app.scss", &LintConfig::default());
+
+        assert_eq!(super::super::apply_fixes(source, &diagnostics), Some((
+            String::from(".a { color: #abc; border: 1px solid #ffffff; }\n"), 2
+        )));
+    }
+}
@@ -6,20 +6,30 @@ mod injections;
 
 use std::io::{Error, ErrorKind, Result};
 use yansi::Paint;
+use mber::types::CLIArguments;
 
 fn main() -> Result<()> {
     if let Ok(true) = std::env::var("FORCE_COLOR").map(|v| v == "0") {
         Paint::disable();
     }
 
+    // NOTE: exits instead of returning the error, main would print it a second time
+    let cli_arguments = CLIArguments::parse().unwrap_or_else(|error| {
+        println!("{} {}", Paint::red("invalid arguments:"), error);
+
+        std::process::exit(1);
+    });
+
     match std::env::args().nth(1) {
         None => commands::help::run(),
         Some(command) => match command.as_str() {
-            "server" | "serve" | "s" => commands::server::run(),
-            "test" | "t" => commands::test::run(),
-            "build" | "b" => commands::build::run(),
-            "console" | "c" => commands::console::run(),
+            "server" | "serve" | "s" => commands::server::run(cli_arguments),
+            "test" | "t" => commands::test::run(cli_arguments),
+            "build" | "b" => commands::build::run(cli_arguments),
+            "cache" => commands::cache::run(),
+            "console" | "c" => commands::console::run(cli_arguments),
             "help" | "h" => commands::help::run(),
+            "lint" => commands::lint::run(cli_arguments),
             "init" | "new" => commands::new::run(),
             "generate" | "g" | "create" => commands::generate::run(),
             "delete" | "d" | "destroy" => commands::delete::run(),
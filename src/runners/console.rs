@@ -0,0 +1,110 @@
+use std::error::Error;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use serde_json::json;
+use super::dom;
+
+const CONSOLE_SCRIPT: &str = include_str!("../../_vendor/mber-console/index.js");
+const RELOAD_EXIT_CODE: i32 = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConsoleExit {
+    Quit,
+    Reload
+}
+
+fn command(project_root: &Path, url: &str, history_path: Option<&Path>) -> Command {
+    let options = json!({
+        "projectRoot": project_root.to_string_lossy(),
+        "url": url,
+        "historyPath": history_path.map(|path| path.to_string_lossy()),
+        "reloadExitCode": RELOAD_EXIT_CODE
+    });
+    let mut command = Command::new("node");
+
+    command.args(&["-e", &dom::script_with_dom(CONSOLE_SCRIPT), &options.to_string()]).current_dir(project_root);
+
+    return command;
+}
+
+fn exit_from_code(code: Option<i32>) -> Result<ConsoleExit, Box<dyn Error>> {
+    return match code {
+        Some(0) => Ok(ConsoleExit::Quit),
+        Some(RELOAD_EXIT_CODE) => Ok(ConsoleExit::Reload),
+        Some(code) => Err(format!("console exited with code {}", code).into()),
+        None => Ok(ConsoleExit::Quit) // NOTE: killed by a signal like ctrl+c
+    };
+}
+
+// NOTE: boots the already built tmp/index.html and hands the terminal to the node.js repl until it exits
+pub fn start(project_root: &Path, url: &str, history_path: Option<&Path>) -> Result<ConsoleExit, Box<dyn Error>> {
+    let status = command(project_root, url, history_path)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .status()
+        .map_err(|error| format!("node could not be spawned to start the console: {}", error))?;
+
+    return exit_from_code(status.code());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::io::Write;
+    use super::*;
+    use super::super::super::builders::{build_all_assets, config};
+    use super::super::super::transpilers::import_addon_folder_to_amd;
+    use super::super::super::types::CLIArguments;
+
+    fn run_console(project_root: &Path, input: &str, history_path: &Path) -> Result<(ConsoleExit, String), Box<dyn Error>> {
+        let mut child = command(project_root, "http://localhost:1234/", Some(history_path))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        child.stdin.take().ok_or("node stdin is unavailable")?.write_all(input.as_bytes())?;
+
+        let output = child.wait_with_output()?;
+
+        return Ok((exit_from_code(output.status.code())?, String::from_utf8_lossy(&output.stdout).to_string()));
+    }
+
+    #[test]
+    fn start_boots_the_application_in_a_repl() -> Result<(), Box<dyn Error>> {
+        let current_directory = env::current_dir()?;
+        let project_root = import_addon_folder_to_amd::test_project()?;
+        let history_path = env::temp_dir().join("mber-console-test-history");
+
+        env::set_current_dir(&project_root)?;
+        fs::remove_file(&history_path).unwrap_or_else(|_| {});
+
+        build_all_assets(&config::build(&project_root, CLIArguments::new())?)?;
+
+        let (exit, output) = run_console(&project_root, vec![
+            "App instanceof Ember.Application",
+            "[1, 2,",
+            "3].length",
+            "{ title: document.querySelector('#ember-welcome-page-id-selector') !== null }",
+            ".exit\n"
+        ].join("\n").as_str(), &history_path)?;
+
+        assert_eq!(exit, ConsoleExit::Quit);
+        assert_eq!(output, vec![
+            "{{applicationName}}> true", "{{applicationName}}> ... 3", "{{applicationName}}> { title: true }",
+            "{{applicationName}}> "
+        ].join("\n"));
+
+        let (exit, _) = run_console(&project_root, ".reload\n", &history_path)?;
+
+        assert_eq!(exit, ConsoleExit::Reload);
+
+        fs::remove_file(&history_path).unwrap_or_else(|_| {}); // NOTE: node.js only writes history on a tty
+        fs::remove_dir_all("tmp")?;
+        env::set_current_dir(&current_directory)?;
+
+        return Ok(());
+    }
+}
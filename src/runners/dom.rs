@@ -0,0 +1,10 @@
+const DOM_SCRIPT: &str = include_str!("../../_vendor/mber-dom/dom.js");
+const CONTEXT_SCRIPT: &str = include_str!("../../_vendor/mber-dom/context.js");
+
+// NOTE: node scripts that run built html files need dom.js as a string and the context helpers in scope
+pub fn script_with_dom(script: &str) -> String {
+    return format!(
+        "var DOM_SOURCE = {};\n{}\n{}",
+        serde_json::to_string(DOM_SCRIPT).unwrap_or_default(), CONTEXT_SCRIPT, script
+    );
+}
@@ -0,0 +1,239 @@
+use std::collections::BTreeSet;
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use notify::{watcher, DebouncedEvent, RecursiveMode, Watcher};
+use serde_json::json;
+use yansi::Paint;
+use super::super::builders::{
+    self, application, config, css, documentation_css, documentation_js, index_html, memserver, test_files
+};
+use super::super::linters::LintedFile;
+use super::super::types::{Config, MberError};
+use super::super::utils::console;
+
+const WATCHED_PATHS: [&str; 6] = ["src", "tests", "memserver", "documentation", "config", "index.html"];
+const DEBOUNCE_DURATION_IN_MS: u64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Builder {
+    All,
+    Css,
+    DocumentationCss,
+    Application,
+    DocumentationJs,
+    Memserver,
+    Tests,
+    IndexHtml
+}
+
+// NOTE: spawns the watcher thread, it owns the config since config/ changes rebuild it from scratch.
+// on_rebuild gets the linted files with problems of the rebuilt bundles or the build error, clients should only reload
+// after successful rebuilds so they keep the last working build on errors
+pub fn start<F>(config: Config, on_rebuild: F) -> Result<JoinHandle<()>, Box<dyn Error>>
+    where F: Fn(&BTreeSet<Builder>, Result<&Vec<LintedFile>, &MberError>) + Send + 'static {
+    let (sender, receiver) = channel();
+    let mut file_watcher = watcher(sender, Duration::from_millis(DEBOUNCE_DURATION_IN_MS))?;
+
+    for path in WATCHED_PATHS.iter().map(|path| config.project_root.join(path)).filter(|path| path.exists()) {
+        file_watcher.watch(&path, RecursiveMode::Recursive)?;
+    }
+
+    console::log(format!("{} {}", Paint::yellow("Watching:"), WATCHED_PATHS.join(", ")));
+
+    return Ok(thread::spawn(move || {
+        let _file_watcher = file_watcher;
+        let mut config = config;
+
+        while let Some(changed_paths) = receive_changes(&receiver) {
+            let builders_to_run = changed_paths.iter()
+                .flat_map(|path| builders_for_path(path.strip_prefix(&config.project_root).unwrap_or(path)))
+                .collect::<BTreeSet<Builder>>();
+
+            if builders_to_run.is_empty() {
+                continue;
+            }
+
+            changed_paths.iter().for_each(|path| {
+                console::log(format!("{} {}", Paint::yellow("CHANGED:"), path.strip_prefix(&config.project_root).unwrap_or(path).display()));
+            });
+
+            on_rebuild(&builders_to_run, rebuild(&builders_to_run, &mut config).as_ref());
+        }
+    }));
+}
+
+// NOTE: blocks until the first change, then drains whatever else arrived within the debounce window
+fn receive_changes(receiver: &Receiver<DebouncedEvent>) -> Option<BTreeSet<PathBuf>> {
+    let mut changed_paths = BTreeSet::new();
+    let mut event = Some(receiver.recv().ok()?);
+
+    while let Some(current_event) = event {
+        match current_event {
+            DebouncedEvent::Create(path) | DebouncedEvent::Write(path) | DebouncedEvent::Remove(path) => {
+                changed_paths.insert(path);
+            },
+            DebouncedEvent::Rename(old_path, new_path) => {
+                changed_paths.insert(old_path);
+                changed_paths.insert(new_path);
+            },
+            DebouncedEvent::Error(error, _) => console::error(format!("File watcher error: {}", error)),
+            _ => {}
+        };
+
+        event = receiver.recv_timeout(Duration::from_millis(DEBOUNCE_DURATION_IN_MS)).ok();
+    }
+
+    return Some(changed_paths);
+}
+
+pub fn builders_for_path(relative_path: &Path) -> Vec<Builder> {
+    let path = relative_path.to_string_lossy().replace('\\', "/");
+    let is_style = path.ends_with(".scss") || path.ends_with(".css");
+    let is_module = path.ends_with(".js") || path.ends_with(".ts") || path.ends_with(".hbs");
+    let is_test = path.ends_with("-test.js") || path.ends_with("-test.ts");
+
+    return match path.split('/').next().unwrap_or("") {
+        "config" => vec![Builder::All],
+        "index.html" => vec![Builder::IndexHtml],
+        "src" if is_style => vec![Builder::Css],
+        "src" if is_test => vec![Builder::Tests],
+        "src" if is_module => vec![Builder::Application],
+        "tests" if path == "tests/index.html" => vec![Builder::IndexHtml],
+        "tests" if is_module => vec![Builder::Tests],
+        "memserver" if is_module => vec![Builder::Memserver],
+        "documentation" if is_style => vec![Builder::DocumentationCss],
+        "documentation" if is_module => vec![Builder::DocumentationJs],
+        _ => vec![]
+    };
+}
+
+// NOTE: stylesheet only changes get hot swapped on the client, everything else reloads the page
+pub fn reload_message(builders: &BTreeSet<Builder>) -> String {
+    let stylesheets = builders.iter().map(|builder| match builder {
+        Builder::Css => Some("/assets/application.css"),
+        Builder::DocumentationCss => Some("/assets/documentation.css"),
+        _ => None
+    }).collect::<Option<Vec<&str>>>();
+
+    return match stylesheets {
+        Some(files) => json!({ "type": "css", "files": files }).to_string(),
+        None => json!({ "type": "reload" }).to_string()
+    };
+}
+
+pub fn rebuild(builders_to_run: &BTreeSet<Builder>, config: &mut Config) -> Result<Vec<LintedFile>, MberError> {
+    let project_root = config.project_root.display().to_string();
+    let memserver_is_enabled = config.env["memserver"]["enabled"].as_bool().unwrap_or(false);
+    let documentation_is_enabled = config.env["documentation"]["enabled"].as_bool().unwrap_or(false);
+    let should_lint = config.cli_arguments.lint != "off";
+    let mut linted_files = Vec::new();
+
+    for builder in builders_to_run {
+        match builder {
+            Builder::All => {
+                let new_config = config::build(&config.project_root, (*config.cli_arguments).clone())?;
+
+                *config = new_config;
+
+                return builders::build_all_assets(config);
+            },
+            Builder::Css => { css::build(config)?; },
+            Builder::DocumentationCss if documentation_is_enabled => { documentation_css::build(config)?; },
+            Builder::Application => { linted_files.extend(application::build(config, should_lint)?.2); },
+            Builder::DocumentationJs if documentation_is_enabled => { linted_files.extend(documentation_js::build(config, should_lint)?.2); },
+            Builder::Memserver if memserver_is_enabled => { linted_files.extend(memserver::build(config, should_lint)?.2); },
+            Builder::Tests if config.cli_arguments.testing => { linted_files.extend(test_files::build(config, should_lint)?.2); },
+            Builder::IndexHtml => {
+                index_html::build(&format!("{}/index.html", project_root), config)?;
+
+                if documentation_is_enabled {
+                    index_html::build_documentation_html(&format!("{}/index.html", project_root), config)?;
+                }
+
+                if config.cli_arguments.testing {
+                    index_html::build(&format!("{}/tests/index.html", project_root), config)?;
+                }
+            },
+            _ => {}
+        };
+    }
+
+    return Ok(linted_files);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use std::collections::HashMap;
+    use super::*;
+    use super::super::super::transpilers::import_addon_folder_to_amd;
+    use super::super::super::types::BuildCache;
+
+    #[test]
+    fn builders_for_path_works() {
+        let builders = |path: &str| builders_for_path(Path::new(path));
+
+        assert_eq!(builders("src/ui/styles/application.scss"), vec![Builder::Css]);
+        assert_eq!(builders("src/ui/components/x-button/component.js"), vec![Builder::Application]);
+        assert_eq!(builders("src/ui/components/x-button/template.hbs"), vec![Builder::Application]);
+        assert_eq!(builders("src/ui/components/x-button/integration-test.js"), vec![Builder::Tests]);
+        assert_eq!(builders("tests/helpers/setup.js"), vec![Builder::Tests]);
+        assert_eq!(builders("tests/index.html"), vec![Builder::IndexHtml]);
+        assert_eq!(builders("memserver/models/user.js"), vec![Builder::Memserver]);
+        assert_eq!(builders("documentation/ui/routes/index/template.hbs"), vec![Builder::DocumentationJs]);
+        assert_eq!(builders("documentation/ui/styles/application.scss"), vec![Builder::DocumentationCss]);
+        assert_eq!(builders("config/environment.js"), vec![Builder::All]);
+        assert_eq!(builders("index.html"), vec![Builder::IndexHtml]);
+        assert_eq!(builders("src/ui/components/x-button/.component.js.swp"), Vec::<Builder>::new());
+        assert_eq!(builders("tmp/assets/application.js"), Vec::<Builder>::new());
+    }
+
+    #[test]
+    fn reload_message_works() {
+        let message = |builders: Vec<Builder>| reload_message(&builders.into_iter().collect());
+
+        assert_eq!(message(vec![Builder::Css]), r#"{"files":["/assets/application.css"],"type":"css"}"#);
+        assert_eq!(
+            message(vec![Builder::DocumentationCss, Builder::Css]),
+            r#"{"files":["/assets/application.css","/assets/documentation.css"],"type":"css"}"#
+        );
+        assert_eq!(message(vec![Builder::Css, Builder::Application]), r#"{"type":"reload"}"#);
+        assert_eq!(message(vec![Builder::All]), r#"{"type":"reload"}"#);
+    }
+
+    #[test]
+    fn rebuild_runs_only_the_affected_builders_and_returns_errors() -> Result<(), Box<dyn Error>> {
+        let project_directory = env::temp_dir().join(format!("mber-file-watcher-test-{}", std::process::id()));
+
+        import_addon_folder_to_amd::copy_boilerplate(&project_directory)?;
+        fs::create_dir_all(project_directory.join("tmp/assets"))?;
+
+        let mut config = Config::new(
+            json!({ "environment": "development", "modulePrefix": "frontend" }),
+            HashMap::new(),
+            BuildCache::new(),
+            project_directory.clone()
+        );
+        let result = rebuild(&vec![Builder::Application, Builder::IndexHtml].into_iter().collect(), &mut config);
+        let application_js_exists = project_directory.join("tmp/assets/application.js").exists();
+        let index_html_exists = project_directory.join("tmp/index.html").exists();
+        let vendor_js_exists = project_directory.join("tmp/assets/vendor.js").exists();
+
+        fs::write(project_directory.join("src/broken-watch.js"), "export default {")?;
+
+        let error = rebuild(&vec![Builder::Application].into_iter().collect(), &mut config).unwrap_err().to_string();
+
+        fs::remove_dir_all(&project_directory)?;
+
+        assert!(result.is_ok());
+        assert!(application_js_exists && index_html_exists);
+        assert!(!vendor_js_exists);
+        assert_eq!(error, "src/broken-watch.js:1:17 Unexpected end of input");
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,298 @@
+use std::error::Error;
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+use hyper::{Body, Client, Request, Response, Server, StatusCode, Uri};
+use hyper::header::{CONTENT_TYPE, HOST};
+use futures::future;
+use hyper::rt::{self, Future};
+use hyper::service::service_fn;
+use yansi::Paint;
+use super::super::types::Config;
+use super::super::utils::{console, file};
+
+type ResponseFuture = Box<dyn Future<Item = Response<Body>, Error = hyper::Error> + Send>;
+
+#[derive(Debug, Clone)]
+pub struct ServerOptions {
+    pub project_root: PathBuf,
+    pub documentation_path: Option<String>,
+    pub proxy: Option<Uri>
+}
+
+impl ServerOptions {
+    pub fn from_config(config: &Config) -> Self {
+        let documentation_is_enabled = config.env["documentation"]["enabled"].as_bool().unwrap_or(false);
+
+        ServerOptions {
+            project_root: config.project_root.clone(),
+            documentation_path: match documentation_is_enabled {
+                true => Some(config.env["documentation"]["path"].as_str().unwrap_or("/styleguide").to_string()),
+                false => None
+            },
+            proxy: config.cli_arguments.proxy.as_ref().map(|proxy| proxy.uri.clone())
+        }
+    }
+}
+
+// NOTE: blocks the current thread until the server stops
+pub fn start(port: u16, options: ServerOptions) -> Result<(), Box<dyn Error>> {
+    let address = SocketAddr::from(([0, 0, 0, 0], port));
+    let server = serve(&address, options)?;
+
+    console::log(format!("{} http://localhost:{}", Paint::green("Server is running on"), port));
+
+    rt::run(server);
+
+    return Ok(());
+}
+
+pub fn serve(address: &SocketAddr, options: ServerOptions) -> Result<impl Future<Item = (), Error = ()>, Box<dyn Error>> {
+    let options = Arc::new(options);
+    let server = Server::try_bind(address)
+        .map_err(|error| format!("port {} could not be used: {}", address.port(), error))?
+        .serve(move || {
+            let options = options.clone();
+
+            return service_fn(move |request| handle_request(request, &options));
+        })
+        .map_err(|error| console::error(format!("Server error: {}", error)));
+
+    return Ok(server);
+}
+
+fn handle_request(mut request: Request<Body>, options: &ServerOptions) -> ResponseFuture {
+    let request_start = Instant::now();
+    let method = request.method().to_string();
+    let path = request.uri().path().to_string();
+    let accepts_html = request.headers().get("accept")
+        .and_then(|accept| accept.to_str().ok())
+        .map_or(false, |accept| accept.contains("text/html"));
+    let response: ResponseFuture = match (resolve_file(&path, options, accepts_html), &options.proxy) {
+        (None, Some(proxy)) => {
+            *request.uri_mut() = proxy_uri(proxy, request.uri());
+            request.headers_mut().remove(HOST);
+
+            Box::new(Client::new().request(request))
+        },
+        (resolved_file, _) => Box::new(future::ok(file_response(resolved_file)))
+    };
+
+    return Box::new(response.then(move |result| {
+        let status = match &result {
+            Ok(response) => response.status(),
+            Err(_) => StatusCode::BAD_GATEWAY
+        };
+        let colored_status = match status.as_u16() {
+            200..=399 => Paint::green(status.as_u16()),
+            _ => Paint::red(status.as_u16())
+        };
+
+        console::log(format!(
+            "{} {} {} {}", Paint::yellow(method), path, colored_status,
+            file::format_time_passed(request_start.elapsed().as_millis())
+        ));
+
+        return result.or_else(|error| {
+            console::error(format!("Proxy error: {}", error));
+
+            return Ok(Response::builder().status(StatusCode::BAD_GATEWAY).body(Body::from("502 Bad Gateway")).unwrap());
+        });
+    }));
+}
+
+// NOTE: order is /tests, documentation path, tmp/, public/ and then index.html fallback for history api routes.
+// Paths with a file extension never fall back, a missing asset should be a 404
+pub fn resolve_file(request_path: &str, options: &ServerOptions, accepts_html: bool) -> Option<PathBuf> {
+    let path = request_path.trim_end_matches('/');
+    let tmp_path = options.project_root.join("tmp");
+
+    if path.split('/').any(|segment| segment == "..") {
+        return None;
+    } else if path == "/tests" {
+        return existing_file(tmp_path.join("tests.html"));
+    } else if let Some(documentation_path) = options.documentation_path.as_ref().map(|path| path.trim_end_matches('/')) {
+        if path == documentation_path || path.starts_with(&format!("{}/", documentation_path)) {
+            return existing_file(tmp_path.join(format!("{}.html", documentation_path.trim_start_matches('/'))));
+        }
+    }
+
+    let relative_path = path.trim_start_matches('/');
+
+    if !relative_path.is_empty() {
+        if let Some(file_path) = existing_file(tmp_path.join(relative_path))
+            .or_else(|| existing_file(options.project_root.join("public").join(relative_path))) {
+            return Some(file_path);
+        }
+    }
+
+    let has_extension = Path::new(relative_path).extension().is_some();
+
+    return match has_extension || (options.proxy.is_some() && !accepts_html) {
+        true => None,
+        false => existing_file(tmp_path.join("index.html"))
+    };
+}
+
+fn existing_file(path: PathBuf) -> Option<PathBuf> {
+    return match path.is_file() {
+        true => Some(path),
+        false => None
+    };
+}
+
+fn file_response(file_path: Option<PathBuf>) -> Response<Body> {
+    let content = file_path.as_ref().and_then(|path| fs::read(path).ok());
+
+    return match (file_path, content) {
+        (Some(path), Some(content)) => Response::builder()
+            .header(CONTENT_TYPE, content_type(&path))
+            .body(Body::from(content))
+            .unwrap(),
+        _ => Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .header(CONTENT_TYPE, "text/plain; charset=utf-8")
+            .body(Body::from("404 Not Found"))
+            .unwrap()
+    };
+}
+
+pub fn content_type(path: &Path) -> &'static str {
+    return match path.extension().and_then(|extension| extension.to_str()).unwrap_or("") {
+        "html" => "text/html; charset=utf-8",
+        "js" => "application/javascript; charset=utf-8",
+        "css" => "text/css; charset=utf-8",
+        "json" | "map" => "application/json; charset=utf-8",
+        "txt" => "text/plain; charset=utf-8",
+        "xml" => "application/xml; charset=utf-8",
+        "svg" => "image/svg+xml",
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "ico" => "image/x-icon",
+        "webp" => "image/webp",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        "wasm" => "application/wasm",
+        _ => "application/octet-stream"
+    };
+}
+
+fn proxy_uri(proxy: &Uri, request_uri: &Uri) -> Uri {
+    let path_and_query = request_uri.path_and_query().map_or("/", |path_and_query| path_and_query.as_str());
+
+    return format!("{}{}", proxy.to_string().trim_end_matches('/'), path_and_query).parse().unwrap_or_else(|_| proxy.clone());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::thread;
+    use std::time::Duration;
+    use super::*;
+
+    fn setup_project(name: &str) -> Result<PathBuf, Box<dyn Error>> {
+        let project_root = env::current_dir()?.join(name);
+
+        fs::create_dir_all(project_root.join("tmp/assets"))?;
+        fs::create_dir_all(project_root.join("public/images"))?;
+        fs::write(project_root.join("tmp/index.html"), "<html>index</html>")?;
+        fs::write(project_root.join("tmp/tests.html"), "<html>tests</html>")?;
+        fs::write(project_root.join("tmp/styleguide.html"), "<html>styleguide</html>")?;
+        fs::write(project_root.join("tmp/assets/application.js"), "window.app = true;")?;
+        fs::write(project_root.join("public/robots.txt"), "User-agent: *")?;
+        fs::write(project_root.join("public/images/logo.svg"), "<svg></svg>")?;
+
+        return Ok(project_root);
+    }
+
+    fn options_for(project_root: &Path, proxy: Option<&str>) -> ServerOptions {
+        return ServerOptions {
+            project_root: project_root.to_path_buf(),
+            documentation_path: Some(String::from("/styleguide")),
+            proxy: proxy.map(|proxy| proxy.parse().unwrap())
+        };
+    }
+
+    fn get(port: u16, path: &str) -> Result<String, Box<dyn Error>> {
+        let mut stream = TcpStream::connect(("127.0.0.1", port))?;
+        let mut response = String::new();
+
+        stream.write_all(format!("GET {} HTTP/1.1\r\nHost: localhost\r\nAccept: text/html\r\nConnection: close\r\n\r\n", path).as_bytes())?;
+        stream.read_to_string(&mut response)?;
+
+        return Ok(response);
+    }
+
+    #[test]
+    fn resolve_file_works() -> Result<(), Box<dyn Error>> {
+        let project_root = setup_project("http-server-resolve-test")?;
+        let options = options_for(&project_root, None);
+        let resolve = |path: &str, accepts_html: bool| resolve_file(path, &options, accepts_html)
+            .map(|file| file.strip_prefix(&project_root).unwrap().to_string_lossy().to_string());
+
+        assert_eq!(resolve("/", true), Some(String::from("tmp/index.html")));
+        assert_eq!(resolve("/assets/application.js", false), Some(String::from("tmp/assets/application.js")));
+        assert_eq!(resolve("/robots.txt", false), Some(String::from("public/robots.txt")));
+        assert_eq!(resolve("/images/logo.svg", false), Some(String::from("public/images/logo.svg")));
+        assert_eq!(resolve("/tests", true), Some(String::from("tmp/tests.html")));
+        assert_eq!(resolve("/tests/", true), Some(String::from("tmp/tests.html")));
+        assert_eq!(resolve("/styleguide", true), Some(String::from("tmp/styleguide.html")));
+        assert_eq!(resolve("/styleguide/components/x-button", true), Some(String::from("tmp/styleguide.html")));
+        assert_eq!(resolve("/users/1/edit", true), Some(String::from("tmp/index.html")));
+        assert_eq!(resolve("/assets/missing.js", false), None);
+        assert_eq!(resolve("/../Cargo.toml", false), None);
+
+        let proxy_options = options_for(&project_root, Some("http://localhost:3000"));
+
+        assert_eq!(resolve_file("/api/users", &proxy_options, false), None);
+        assert!(resolve_file("/users", &proxy_options, true).unwrap().ends_with("tmp/index.html"));
+
+        fs::remove_dir_all(&project_root)?;
+
+        return Ok(());
+    }
+
+    #[test]
+    fn content_type_and_proxy_uri_works() {
+        assert_eq!(content_type(Path::new("tmp/index.html")), "text/html; charset=utf-8");
+        assert_eq!(content_type(Path::new("tmp/assets/vendor.js")), "application/javascript; charset=utf-8");
+        assert_eq!(content_type(Path::new("public/favicon.ico")), "image/x-icon");
+        assert_eq!(content_type(Path::new("public/unknown")), "application/octet-stream");
+        assert_eq!(
+            proxy_uri(&"http://localhost:3000".parse().unwrap(), &"/api/users?page=2".parse().unwrap()).to_string(),
+            "http://localhost:3000/api/users?page=2"
+        );
+    }
+
+    #[test]
+    fn serve_responds_with_files_and_fallbacks() -> Result<(), Box<dyn Error>> {
+        let project_root = setup_project("http-server-serve-test")?;
+        let address = SocketAddr::from(([127, 0, 0, 1], 48231));
+        let server = serve(&address, options_for(&project_root, None))?;
+
+        thread::spawn(move || rt::run(server));
+        thread::sleep(Duration::from_millis(200));
+
+        let index_response = get(48231, "/users/1")?;
+        let asset_response = get(48231, "/assets/application.js")?;
+        let missing_response = get(48231, "/assets/missing.js")?;
+
+        fs::remove_dir_all(&project_root)?;
+
+        assert!(index_response.starts_with("HTTP/1.1 200 OK"));
+        assert!(index_response.contains("content-type: text/html; charset=utf-8"));
+        assert!(index_response.ends_with("<html>index</html>"));
+        assert!(asset_response.contains("content-type: application/javascript; charset=utf-8"));
+        assert!(asset_response.ends_with("window.app = true;"));
+        assert!(missing_response.starts_with("HTTP/1.1 404 Not Found"));
+        assert!(serve(&address, options_for(&project_root, None)).is_err());
+
+        return Ok(());
+    }
+}
@@ -0,0 +1,7 @@
+pub mod console;
+pub mod dom;
+pub mod file_watcher;
+pub mod http_server;
+pub mod test_reporter;
+pub mod test_runner;
+pub mod websocket_server;
@@ -0,0 +1,360 @@
+use std::error::Error;
+use yansi::Paint;
+use serde_json::{json, Value};
+use super::test_runner::{Assertion, TestResult, TestRun, TestStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Reporter {
+    Console,
+    Tap,
+    JUnit,
+    Json
+}
+
+impl Reporter {
+    pub fn parse(name: &str) -> Result<Self, Box<dyn Error>> {
+        return match name {
+            "console" => Ok(Reporter::Console),
+            "tap" => Ok(Reporter::Tap),
+            "junit" => Ok(Reporter::JUnit),
+            "json" => Ok(Reporter::Json),
+            _ => Err(format!("--reporter value {} is not valid, use console, tap, junit or json", name).into())
+        };
+    }
+
+    // NOTE: console and tap print every test as it finishes, junit and json only print the final report
+    pub fn stream_result(&self, index: usize, result: &TestResult) -> Option<String> {
+        return match self {
+            Reporter::Console => Some(console_line(result, true)),
+            Reporter::Tap => Some(tap_result(index, result)),
+            _ => None
+        };
+    }
+
+    pub fn stream_summary(&self, test_run: &TestRun) -> String {
+        return match self {
+            Reporter::Console => console_summary(test_run, true),
+            Reporter::Tap => tap_summary(test_run),
+            Reporter::JUnit => junit_report(test_run),
+            Reporter::Json => json_report(test_run).to_string()
+        };
+    }
+
+    // NOTE: complete uncolored report, used for --output files
+    pub fn report(&self, test_run: &TestRun) -> String {
+        return match self {
+            Reporter::Console => test_run.results.iter()
+                .map(|result| console_line(result, false))
+                .chain(vec![console_summary(test_run, false)])
+                .collect::<Vec<String>>()
+                .join("\n"),
+            Reporter::Tap => vec![String::from("TAP version 13")].into_iter()
+                .chain(test_run.results.iter().enumerate().map(|(index, result)| tap_result(index + 1, result)))
+                .chain(vec![tap_summary(test_run)])
+                .collect::<Vec<String>>()
+                .join("\n"),
+            Reporter::JUnit => junit_report(test_run),
+            Reporter::Json => serde_json::to_string_pretty(&json_report(test_run)).unwrap_or_default()
+        };
+    }
+}
+
+fn full_name(result: &TestResult) -> String {
+    return format!("{} | {}", result.module, result.name);
+}
+
+fn console_line(result: &TestResult, colored: bool) -> String {
+    let (symbol, name) = match result.status {
+        TestStatus::Passed => ("✔", full_name(result)),
+        TestStatus::Failed => ("✘", full_name(result)),
+        TestStatus::Skipped => ("-", format!("{} (skipped)", full_name(result))),
+        TestStatus::Todo => ("-", format!("{} (todo)", full_name(result)))
+    };
+    let line = match colored {
+        true => match result.status {
+            TestStatus::Passed => format!("{} {} {}", Paint::green(symbol), name, Paint::fixed(8, format!("({}ms)", result.runtime))),
+            TestStatus::Failed => format!("{} {} {}", Paint::red(symbol), Paint::red(name), Paint::fixed(8, format!("({}ms)", result.runtime))),
+            _ => format!("{} {}", Paint::yellow(symbol), Paint::yellow(name))
+        },
+        false => match result.status {
+            TestStatus::Passed | TestStatus::Failed => format!("{} {} ({}ms)", symbol, name, result.runtime),
+            _ => format!("{} {}", symbol, name)
+        }
+    };
+
+    return result.failed_assertions.iter().fold(line, |line, assertion| {
+        return format!("{}\n{}", line, indent(&assertion_details(assertion), "    "));
+    });
+}
+
+fn console_summary(test_run: &TestRun, colored: bool) -> String {
+    let failed = test_run.count(TestStatus::Failed);
+    let summary = format!(
+        "{} tests, {} passed, {} failed, {} skipped, {} todo ({} assertions in {}ms)",
+        test_run.results.len(), test_run.count(TestStatus::Passed), failed, test_run.count(TestStatus::Skipped),
+        test_run.count(TestStatus::Todo), test_run.assertion_total, test_run.runtime
+    );
+
+    return match (colored, failed) {
+        (false, _) => summary,
+        (true, 0) => Paint::green(summary).to_string(),
+        (true, _) => Paint::red(summary).to_string()
+    };
+}
+
+fn assertion_details(assertion: &Assertion) -> String {
+    let mut lines = vec![assertion.message.clone()];
+
+    if let Some(expected) = &assertion.expected {
+        lines.push(format!("expected: {}", expected));
+    }
+
+    if let Some(actual) = &assertion.actual {
+        lines.push(format!("actual: {}", actual));
+    }
+
+    if let Some(stack) = &assertion.stack {
+        lines.push(format!("at: {}", stack.lines().next().unwrap_or("").trim()));
+    }
+
+    return lines.join("\n");
+}
+
+fn indent(text: &str, prefix: &str) -> String {
+    return text.lines().map(|line| format!("{}{}", prefix, line)).collect::<Vec<String>>().join("\n");
+}
+
+fn tap_result(index: usize, result: &TestResult) -> String {
+    let name = full_name(result).replace("#", "\\#");
+
+    return match result.status {
+        TestStatus::Passed => format!("ok {} - {}", index, name),
+        TestStatus::Skipped => format!("ok {} - {} # SKIP", index, name),
+        TestStatus::Todo => format!("not ok {} - {} # TODO", index, name),
+        TestStatus::Failed => {
+            let diagnostics = result.failed_assertions.iter().map(|assertion| {
+                let mut lines = vec![
+                    String::from("---"),
+                    format!("message: {}", serde_json::to_string(&assertion.message).unwrap_or_default())
+                ];
+
+                if let Some(expected) = &assertion.expected {
+                    lines.push(format!("expected: {}", serde_json::to_string(expected).unwrap_or_default()));
+                }
+
+                if let Some(actual) = &assertion.actual {
+                    lines.push(format!("actual: {}", serde_json::to_string(actual).unwrap_or_default()));
+                }
+
+                if let Some(stack) = &assertion.stack {
+                    lines.push(format!("stack: {}", serde_json::to_string(stack).unwrap_or_default()));
+                }
+
+                lines.push(String::from("..."));
+
+                return indent(&lines.join("\n"), "  ");
+            }).collect::<Vec<String>>();
+
+            vec![format!("not ok {} - {}", index, name)].into_iter().chain(diagnostics).collect::<Vec<String>>().join("\n")
+        }
+    };
+}
+
+fn tap_summary(test_run: &TestRun) -> String {
+    return vec![
+        format!("1..{}", test_run.results.len()),
+        format!("# tests {}", test_run.results.len()),
+        format!("# pass {}", test_run.count(TestStatus::Passed)),
+        format!("# skip {}", test_run.count(TestStatus::Skipped)),
+        format!("# todo {}", test_run.count(TestStatus::Todo)),
+        format!("# fail {}", test_run.count(TestStatus::Failed))
+    ].join("\n");
+}
+
+pub(crate) fn escape_xml(text: &str) -> String {
+    return text.replace("&", "&amp;").replace("<", "&lt;").replace(">", "&gt;")
+        .replace("\"", "&quot;").replace("'", "&apos;");
+}
+
+fn junit_report(test_run: &TestRun) -> String {
+    let mut modules: Vec<&str> = Vec::new();
+
+    for result in &test_run.results {
+        if !modules.contains(&result.module.as_str()) {
+            modules.push(&result.module);
+        }
+    }
+
+    let test_suites = modules.iter().map(|module| {
+        let results = test_run.results.iter().filter(|result| &result.module == module).collect::<Vec<&TestResult>>();
+        let count = |status: TestStatus| results.iter().filter(|result| result.status == status).count();
+        let test_cases = results.iter().map(|result| {
+            let attributes = format!(
+                "name=\"{}\" classname=\"{}\" time=\"{:.3}\"",
+                escape_xml(&result.name), escape_xml(module), result.runtime as f64 / 1000.0
+            );
+
+            return match result.status {
+                TestStatus::Passed => format!("    <testcase {}/>", attributes),
+                TestStatus::Skipped | TestStatus::Todo => format!("    <testcase {}>\n      <skipped/>\n    </testcase>", attributes),
+                TestStatus::Failed => format!(
+                    "    <testcase {}>\n{}\n    </testcase>",
+                    attributes,
+                    result.failed_assertions.iter().map(|assertion| format!(
+                        "      <failure message=\"{}\">{}</failure>",
+                        escape_xml(&assertion.message), escape_xml(&assertion_details(assertion))
+                    )).collect::<Vec<String>>().join("\n")
+                )
+            };
+        }).collect::<Vec<String>>().join("\n");
+
+        return format!(
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" skipped=\"{}\" time=\"{:.3}\">\n{}\n  </testsuite>",
+            escape_xml(module), results.len(), count(TestStatus::Failed),
+            count(TestStatus::Skipped) + count(TestStatus::Todo),
+            results.iter().map(|result| result.runtime).sum::<u64>() as f64 / 1000.0, test_cases
+        );
+    }).collect::<Vec<String>>();
+
+    return format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites tests=\"{}\" failures=\"{}\" time=\"{:.3}\">\n{}</testsuites>",
+        test_run.results.len(), test_run.count(TestStatus::Failed), test_run.runtime as f64 / 1000.0,
+        test_suites.iter().map(|test_suite| format!("{}\n", test_suite)).collect::<String>()
+    );
+}
+
+fn json_report(test_run: &TestRun) -> Value {
+    return json!({
+        "summary": {
+            "tests": test_run.results.len(),
+            "passed": test_run.count(TestStatus::Passed),
+            "failed": test_run.count(TestStatus::Failed),
+            "skipped": test_run.count(TestStatus::Skipped),
+            "todo": test_run.count(TestStatus::Todo),
+            "assertions": test_run.assertion_total,
+            "runtime": test_run.runtime
+        },
+        "tests": test_run.results.iter().map(|result| json!({
+            "module": result.module,
+            "name": result.name,
+            "status": format!("{:?}", result.status).to_lowercase(),
+            "runtime": result.runtime,
+            "assertions": result.assertion_count,
+            "failedAssertions": result.failed_assertions.iter().map(|assertion| json!({
+                "message": assertion.message,
+                "actual": assertion.actual,
+                "expected": assertion.expected,
+                "stack": assertion.stack
+            })).collect::<Vec<Value>>()
+        })).collect::<Vec<Value>>()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_run() -> TestRun {
+        return TestRun {
+            results: vec![
+                TestResult {
+                    module: String::from("Unit | Route | index"), name: String::from("it exists"),
+                    status: TestStatus::Passed, runtime: 12, assertion_count: 1, failed_assertions: vec![]
+                },
+                TestResult {
+                    module: String::from("Unit | Route | index"), name: String::from("renders <h1> & #title"),
+                    status: TestStatus::Failed, runtime: 1500, assertion_count: 2,
+                    failed_assertions: vec![Assertion {
+                        message: String::from("title is \"Welcome\""), actual: Some(String::from("\"Hi\"")),
+                        expected: Some(String::from("\"Welcome\"")), stack: Some(String::from("at tests.js:10:5\nat run"))
+                    }]
+                },
+                TestResult {
+                    module: String::from("Acceptance | home"), name: String::from("later"),
+                    status: TestStatus::Skipped, runtime: 0, assertion_count: 0, failed_assertions: vec![]
+                }
+            ],
+            assertion_total: 3,
+            assertion_passed: 2,
+            assertion_failed: 1,
+            runtime: 1520
+        };
+    }
+
+    #[test]
+    fn parse_works() {
+        assert_eq!(Reporter::parse("console").unwrap(), Reporter::Console);
+        assert_eq!(Reporter::parse("tap").unwrap(), Reporter::Tap);
+        assert_eq!(Reporter::parse("junit").unwrap(), Reporter::JUnit);
+        assert_eq!(Reporter::parse("json").unwrap(), Reporter::Json);
+        assert_eq!(
+            Reporter::parse("xml").unwrap_err().to_string(),
+            "--reporter value xml is not valid, use console, tap, junit or json"
+        );
+    }
+
+    #[test]
+    fn report_works_for_console_and_tap() {
+        let test_run = test_run();
+
+        assert_eq!(Reporter::JUnit.stream_result(1, &test_run.results[0]), None);
+        assert_eq!(Reporter::Console.report(&test_run), vec![
+            "✔ Unit | Route | index | it exists (12ms)",
+            "✘ Unit | Route | index | renders <h1> & #title (1500ms)",
+            "    title is \"Welcome\"",
+            "    expected: \"Welcome\"",
+            "    actual: \"Hi\"",
+            "    at: at tests.js:10:5",
+            "- Acceptance | home | later (skipped)",
+            "3 tests, 1 passed, 1 failed, 1 skipped, 0 todo (3 assertions in 1520ms)"
+        ].join("\n"));
+        assert_eq!(Reporter::Tap.report(&test_run), vec![
+            "TAP version 13",
+            "ok 1 - Unit | Route | index | it exists",
+            "not ok 2 - Unit | Route | index | renders <h1> & \\#title",
+            "  ---",
+            "  message: \"title is \\\"Welcome\\\"\"",
+            "  expected: \"\\\"Welcome\\\"\"",
+            "  actual: \"\\\"Hi\\\"\"",
+            "  stack: \"at tests.js:10:5\\nat run\"",
+            "  ...",
+            "ok 3 - Acceptance | home | later # SKIP",
+            "1..3",
+            "# tests 3",
+            "# pass 1",
+            "# skip 1",
+            "# todo 0",
+            "# fail 1"
+        ].join("\n"));
+    }
+
+    #[test]
+    fn report_works_for_junit_and_json() -> Result<(), Box<dyn Error>> {
+        let test_run = test_run();
+        let junit = Reporter::JUnit.report(&test_run);
+
+        assert!(junit.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites tests=\"3\" failures=\"1\" time=\"1.520\">"));
+        assert!(junit.contains(
+            "  <testsuite name=\"Unit | Route | index\" tests=\"2\" failures=\"1\" skipped=\"0\" time=\"1.512\">\n    \
+            <testcase name=\"it exists\" classname=\"Unit | Route | index\" time=\"0.012\"/>"
+        ));
+        assert!(junit.contains("<testcase name=\"renders &lt;h1&gt; &amp; #title\" classname=\"Unit | Route | index\" time=\"1.500\">"));
+        assert!(junit.contains("<failure message=\"title is &quot;Welcome&quot;\">"));
+        assert!(junit.contains(
+            "  <testsuite name=\"Acceptance | home\" tests=\"1\" failures=\"0\" skipped=\"1\" time=\"0.000\">\n    \
+            <testcase name=\"later\" classname=\"Acceptance | home\" time=\"0.000\">\n      <skipped/>"
+        ));
+        assert!(junit.ends_with("  </testsuite>\n</testsuites>"));
+
+        let json: Value = serde_json::from_str(&Reporter::Json.report(&test_run))?;
+
+        assert_eq!(json["summary"], json!({
+            "tests": 3, "passed": 1, "failed": 1, "skipped": 1, "todo": 0, "assertions": 3, "runtime": 1520
+        }));
+        assert_eq!(json["tests"][1]["status"], "failed");
+        assert_eq!(json["tests"][1]["failedAssertions"][0]["expected"], "\"Welcome\"");
+        assert_eq!(json["tests"][2]["status"], "skipped");
+
+        return Ok(());
+    }
+}
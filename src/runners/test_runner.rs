@@ -0,0 +1,210 @@
+use std::error::Error;
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+use serde::Deserialize;
+use serde_json::json;
+use super::dom;
+
+const RUNNER_SCRIPT: &str = include_str!("../../_vendor/mber-test-runner/index.js");
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TestRunOptions {
+    pub filter: Option<String>,
+    pub module: Option<String>
+}
+
+impl TestRunOptions {
+    // NOTE: QUnit reads filter and module from the url query string of tests.html
+    pub fn url(&self, port: u16) -> String {
+        let query = vec![("filter", &self.filter), ("module", &self.module)].into_iter()
+            .filter_map(|(key, value)| value.as_ref().map(|value| format!("{}={}", key, encode_query_value(value))))
+            .collect::<Vec<String>>()
+            .join("&");
+
+        return match query.is_empty() {
+            true => format!("http://localhost:{}/tests", port),
+            false => format!("http://localhost:{}/tests?{}", port, query)
+        };
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum TestStatus {
+    Passed,
+    Failed,
+    Skipped,
+    Todo
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct Assertion {
+    pub message: String,
+    pub actual: Option<String>,
+    pub expected: Option<String>,
+    pub stack: Option<String>
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TestResult {
+    pub module: String,
+    pub name: String,
+    pub status: TestStatus,
+    pub runtime: u64,
+    pub assertion_count: u64,
+    pub failed_assertions: Vec<Assertion>
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TestRun {
+    pub results: Vec<TestResult>,
+    pub assertion_total: u64,
+    pub assertion_passed: u64,
+    pub assertion_failed: u64,
+    pub runtime: u64
+}
+
+impl TestRun {
+    pub fn count(&self, status: TestStatus) -> usize {
+        return self.results.iter().filter(|result| result.status == status).count();
+    }
+
+    pub fn is_successful(&self) -> bool {
+        return self.count(TestStatus::Failed) == 0 && self.assertion_failed == 0;
+    }
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type")]
+enum RunnerEvent {
+    #[serde(rename = "testDone")]
+    TestDone(TestResult),
+    #[serde(rename = "done")]
+    Done { total: u64, passed: u64, failed: u64, runtime: u64 },
+    #[serde(rename = "error")]
+    Error { message: String }
+}
+
+// NOTE: runs the already built tmp/tests.html in node with a minimal DOM, results are streamed as each test finishes
+pub fn run<F>(project_root: &Path, url: &str, mut on_result: F) -> Result<TestRun, Box<dyn Error>>
+    where F: FnMut(&TestResult) {
+    let script = dom::script_with_dom(RUNNER_SCRIPT);
+    let mut child = Command::new("node")
+        .args(&["-e", &script])
+        .current_dir(project_root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|error| format!("node could not be spawned to run the tests: {}", error))?;
+
+    child.stdin.take().ok_or("node stdin is unavailable")?
+        .write_all(json!({ "projectRoot": project_root.to_string_lossy(), "url": url }).to_string().as_bytes())?;
+
+    let mut test_run = TestRun::default();
+    let mut finished = false;
+    let stdout = child.stdout.take().ok_or("node stdout is unavailable")?;
+
+    for line in BufReader::new(stdout).lines() {
+        let line = line?;
+
+        match serde_json::from_str::<RunnerEvent>(&line) {
+            Ok(RunnerEvent::TestDone(result)) => {
+                on_result(&result);
+                test_run.results.push(result);
+            },
+            Ok(RunnerEvent::Done { total, passed, failed, runtime }) => {
+                test_run.assertion_total = total;
+                test_run.assertion_passed = passed;
+                test_run.assertion_failed = failed;
+                test_run.runtime = runtime;
+                finished = true;
+            },
+            Ok(RunnerEvent::Error { message }) => {
+                child.wait()?;
+
+                return Err(format!("tests could not run: {}", message).into());
+            },
+            Err(_) => println!("{}", line) // NOTE: application code can write to stdout directly
+        };
+    }
+
+    let status = child.wait()?;
+
+    if !finished {
+        return Err(format!("test runner exited before the tests finished [{}]", status).into());
+    }
+
+    return Ok(test_run);
+}
+
+fn encode_query_value(value: &str) -> String {
+    return value.bytes().map(|byte| match byte {
+        b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+        _ => format!("%{:02X}", byte)
+    }).collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::fs;
+    use super::*;
+    use super::super::super::builders::{build_all_assets, config};
+    use super::super::super::transpilers::import_addon_folder_to_amd;
+    use super::super::super::types::CLIArguments;
+
+    #[test]
+    fn url_works_for_filter_and_module() {
+        let options = TestRunOptions { filter: Some(String::from("it exists")), module: None };
+
+        assert_eq!(TestRunOptions::default().url(1234), "http://localhost:1234/tests");
+        assert_eq!(options.url(4200), "http://localhost:4200/tests?filter=it%20exists");
+        assert_eq!(
+            TestRunOptions { module: Some(String::from("Unit | Route | index")), ..options }.url(1234),
+            "http://localhost:1234/tests?filter=it%20exists&module=Unit%20%7C%20Route%20%7C%20index"
+        );
+    }
+
+    #[test]
+    fn run_works_for_boilerplate_tests() -> Result<(), Box<dyn Error>> {
+        let current_directory = env::current_dir()?;
+        let project_root = import_addon_folder_to_amd::test_project()?;
+
+        env::set_current_dir(&project_root)?;
+        fs::remove_dir_all("tmp").unwrap_or_else(|_| {});
+
+        let mut cli_arguments = CLIArguments::new();
+
+        cli_arguments.env = Some(String::from("test"));
+        cli_arguments.testing = true;
+
+        let config = config::build(&project_root, cli_arguments)?;
+
+        build_all_assets(&config)?;
+
+        let mut streamed_test_names = Vec::new();
+        let test_run = run(&project_root, &TestRunOptions::default().url(1234), |result| {
+            streamed_test_names.push(result.name.clone());
+        })?;
+
+        assert!(test_run.is_successful());
+        assert_eq!(test_run.count(TestStatus::Passed), 4);
+        assert_eq!(streamed_test_names, test_run.results.iter().map(|result| result.name.clone()).collect::<Vec<_>>());
+        assert!(test_run.results.iter().any(|result| result.module == "Integration | Component | welcome-page"));
+
+        let filtered_run = run(&project_root, &TestRunOptions {
+            filter: None, module: Some(String::from("Unit | Route | index"))
+        }.url(1234), |_| {})?;
+
+        assert_eq!(filtered_run.results.len(), 1);
+        assert_eq!(filtered_run.results[0].name, "it exists");
+
+        fs::remove_dir_all("tmp")?;
+        env::set_current_dir(&current_directory)?;
+
+        return Ok(());
+    }
+}
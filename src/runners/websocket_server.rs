@@ -0,0 +1,95 @@
+use std::error::Error;
+use std::thread;
+use yansi::Paint;
+use ws::{Sender, WebSocket};
+use super::super::utils::console;
+
+// NOTE: clients only listen, incoming messages are ignored
+pub struct Broadcaster {
+    sender: Sender
+}
+
+impl Broadcaster {
+    pub fn broadcast(&self, message: &str) -> Result<(), Box<dyn Error>> {
+        self.sender.broadcast(message).map_err(|error| error_message(&error))?;
+
+        return Ok(());
+    }
+}
+
+// NOTE: runs the socket server on its own thread, the returned broadcaster sends to every connected client
+pub fn start(port: u16) -> Result<Broadcaster, Box<dyn Error>> {
+    let socket = WebSocket::new(|_sender: Sender| |_message: ws::Message| Ok(()))
+        .and_then(|socket| socket.bind(("0.0.0.0", port)))
+        .map_err(|error| format!("socket port {} could not be used: {}", port, error_message(&error)))?;
+    let broadcaster = Broadcaster { sender: socket.broadcaster() };
+
+    thread::spawn(move || {
+        if let Err(error) = socket.run() {
+            console::error(format!("Socket server error: {}", error_message(&error)));
+        }
+    });
+
+    console::log(format!("{} ws://localhost:{}", Paint::green("Socket server is running on"), port));
+
+    return Ok(broadcaster);
+}
+
+// NOTE: ws::Error display relies on the deprecated Error::description, so io errors are formatted directly
+fn error_message(error: &ws::Error) -> String {
+    return match &error.kind {
+        ws::ErrorKind::Io(io_error) => io_error.to_string(),
+        _ => error.details.to_string()
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+    use std::time::Duration;
+    use super::*;
+
+    fn connect(port: u16) -> Result<TcpStream, Box<dyn Error>> {
+        let mut stream = TcpStream::connect(("127.0.0.1", port))?;
+        let mut response = [0; 1024];
+
+        stream.set_read_timeout(Some(Duration::from_secs(2)))?;
+        stream.write_all(format!(
+            "GET / HTTP/1.1\r\nHost: localhost:{}\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+            Sec-WebSocket-Key: dGhlIHNhbXBsZSBub25jZQ==\r\nSec-WebSocket-Version: 13\r\n\r\n", port
+        ).as_bytes())?;
+
+        let response_length = stream.read(&mut response)?;
+
+        assert!(String::from_utf8_lossy(&response[..response_length]).starts_with("HTTP/1.1 101 Switching Protocols"));
+
+        return Ok(stream);
+    }
+
+    #[test]
+    fn start_broadcasts_messages_to_all_clients() -> Result<(), Box<dyn Error>> {
+        let broadcaster = start(48241)?;
+        let mut first_client = connect(48241)?;
+        let mut second_client = connect(48241)?;
+
+        thread::sleep(Duration::from_millis(100));
+        broadcaster.broadcast("{\"type\":\"reload\"}")?;
+
+        for client in vec![&mut first_client, &mut second_client] {
+            let mut frame = [0; 64];
+            let frame_length = client.read(&mut frame)?;
+
+            assert_eq!(frame[0], 0x81);
+            assert_eq!(frame[1] as usize, frame_length - 2);
+            assert_eq!(String::from_utf8_lossy(&frame[2..frame_length]), "{\"type\":\"reload\"}");
+        }
+
+        assert_eq!(
+            start(48241).err().unwrap().to_string(),
+            "socket port 48241 could not be used: Address already in use (os error 98)"
+        );
+
+        return Ok(());
+    }
+}
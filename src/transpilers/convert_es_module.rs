@@ -1,50 +1,1415 @@
-use std::path::Path;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use rayon::prelude::*;
+use inflector::cases::camelcase::to_camel_case;
+use super::tokenizer::{self, Token, TokenKind};
+use super::super::types::{MberError, TranspileError};
+use super::super::utils::transpile_cache;
 
-// TODO: turn return types to Result
-pub fn from_file<'a>(_file: &Path, _minify: bool) -> &'a str {
-    return "function(){{ }};";
+const STATEMENT_CONTINUATIONS: [&str; 40] = [
+    ".", "?.", "(", "[", ",", ";", "=", "+", "-", "*", "/", "%", "**", "==", "===", "!=", "!==", "<", ">",
+    "<=", ">=", "&&", "||", "??", "?", ":", "&", "|", "^", "<<", ">>", ">>>", "=>", "+=", "-=", "*=", "/=",
+    "instanceof", "in", "of"
+];
+
+struct ImportDeclaration {
+    source: String,
+    default: Option<String>,
+    namespace: Option<String>,
+    named: Vec<(String, String)>
+}
+
+enum ExportBinding {
+    Local { exported: String, local: String },
+    ReExport { exported: String, source: String, imported: String },
+    ReExportNamespace { exported: String, source: String },
+    ReExportAll { source: String }
+}
+
+struct Module<'a> {
+    source: &'a str,
+    module_name: &'a str,
+    tokens: Vec<Token>,
+    significant: Vec<usize>,
+    imports: Vec<ImportDeclaration>,
+    exports: Vec<ExportBinding>,
+    replacements: Vec<(usize, usize, String)>
+}
+
+// NOTE: significant indexes of the matching bracket and of the innermost open bracket around each token
+struct Brackets {
+    matches: Vec<Option<usize>>,
+    enclosing: Vec<Option<usize>>
+}
+
+struct ImportReference {
+    local: String,
+    value: String,
+    is_member: bool
+}
+
+pub fn from_file(file: &Path, project_root: &Path, application_name: &str) -> Result<String, MberError> {
+    let code = fs::read_to_string(file).map_err(MberError::io("could not read", file))?;
+    let module_name = super::module_name_from_path(file, project_root, application_name);
+
+    return from_string(&code, &module_name).map_err(|error| {
+        let relative_path = file.strip_prefix(project_root).unwrap_or(file).to_string_lossy().to_string();
+
+        return MberError::Transpile(TranspileError { file_name: relative_path, ..error });
+    });
+}
+
+// NOTE: files are transpiled across all cores, the output keeps the order of the files
+pub fn from_files(files: &Vec<PathBuf>, project_root: &Path, application_name: &str) -> Result<Vec<String>, MberError> {
+    return files.par_iter()
+        .map(|file| from_file(file, project_root, application_name))
+        .collect::<Result<Vec<String>, MberError>>();
+}
+
+// NOTE: output keeps every body line on its original line number, the AMD preamble shares line 1. Modules are not
+// minified here, builders minify the whole bundle
+pub fn from_string(code_string: &str, module_name: &str) -> Result<String, TranspileError> {
+    return transpile_cache::fetch(transpiler_id(), module_name, code_string, || transpile(code_string, module_name));
+}
+
+// NOTE: cached outputs are invalidated whenever this file or the tokenizer changes
+fn transpiler_id() -> &'static str {
+    static TRANSPILER_ID: OnceLock<String> = OnceLock::new();
+
+    return TRANSPILER_ID.get_or_init(|| {
+        return transpile_cache::key("es-module", include_str!("convert_es_module.rs"), include_str!("tokenizer.rs"));
+    });
+}
+
+fn transpile(code_string: &str, module_name: &str) -> Result<String, TranspileError> {
+    let tokens = tokenizer::tokenize(code_string, module_name)?;
+    let significant = tokens.iter().enumerate()
+        .filter(|(_, token)| !token.is_trivia())
+        .map(|(index, _)| index)
+        .collect();
+    let mut module = Module {
+        source: code_string,
+        module_name: module_name,
+        tokens: tokens,
+        significant: significant,
+        imports: Vec::new(),
+        exports: Vec::new(),
+        replacements: Vec::new()
+    };
+
+    module.reject_unsupported_class_syntax()?;
+    module.collect_module_declarations()?;
+
+    return module.to_amd();
+}
+
+pub fn resolve_import_path(module_name: &str, import_path: &str) -> String {
+    if !import_path.starts_with("./") && !import_path.starts_with("../") {
+        return import_path.to_string();
+    }
+
+    let mut segments = module_name.split('/').collect::<Vec<&str>>();
+
+    segments.pop();
+
+    for segment in import_path.split('/') {
+        match segment {
+            "" | "." => {},
+            ".." => { segments.pop(); },
+            _ => segments.push(segment)
+        }
+    }
+
+    let resolved_path = segments.join("/");
+
+    return match resolved_path.ends_with(".js") || resolved_path.ends_with(".ts") {
+        true => resolved_path[..resolved_path.len() - 3].to_string(),
+        false => resolved_path
+    };
+}
+
+impl<'a> Module<'a> {
+    fn token(&self, significant_index: usize) -> Option<&Token> {
+        return self.significant.get(significant_index).map(|index| &self.tokens[*index]);
+    }
+
+    fn text(&self, significant_index: usize) -> &'a str {
+        let source = self.source;
+
+        return self.token(significant_index).map_or("", |token| token.text(source));
+    }
+
+    fn is(&self, significant_index: usize, text: &str) -> bool {
+        return self.text(significant_index) == text;
+    }
+
+    fn kind(&self, significant_index: usize) -> Option<TokenKind> {
+        return self.token(significant_index).map(|token| token.kind);
+    }
+
+    fn error(&self, significant_index: usize, message: &str) -> TranspileError {
+        return match self.token(significant_index) {
+            Some(token) => TranspileError::new(self.module_name, token.line, token.column, message),
+            None => {
+                let line = self.source.lines().count().max(1);
+                let column = self.source.lines().last().map_or(0, |line| line.chars().count()) + 1;
+
+                TranspileError::new(self.module_name, line, column, "Unexpected end of input")
+            }
+        };
+    }
+
+    fn expect_identifier(&self, significant_index: usize) -> Result<String, TranspileError> {
+        return match self.kind(significant_index) {
+            Some(TokenKind::Identifier) => Ok(self.text(significant_index).to_string()),
+            _ => Err(self.error(significant_index, format!("Unexpected token '{}'", self.text(significant_index)).as_str()))
+        };
+    }
+
+    fn expect_string(&self, significant_index: usize) -> Result<String, TranspileError> {
+        return match self.kind(significant_index) {
+            Some(TokenKind::String) => Ok(unquote(self.text(significant_index))),
+            _ => Err(self.error(significant_index, format!("Expected a module path string, found '{}'", self.text(significant_index)).as_str()))
+        };
+    }
+
+    fn expect_module_export_name(&self, significant_index: usize) -> Result<String, TranspileError> {
+        return match self.kind(significant_index) {
+            Some(TokenKind::String) => Ok(unquote(self.text(significant_index))),
+            _ => self.expect_identifier(significant_index)
+        };
+    }
+
+    fn has_line_break_before(&self, significant_index: usize) -> bool {
+        if significant_index == 0 || significant_index >= self.significant.len() {
+            return false;
+        }
+
+        let source = self.source;
+
+        return self.tokens[self.significant[significant_index - 1] + 1..self.significant[significant_index]].iter()
+            .any(|token| token.kind == TokenKind::LineTerminator || token.text(source).contains('\n'));
+    }
+
+    fn depth_change(&self, significant_index: usize) -> i32 {
+        let text = self.text(significant_index);
+
+        return match self.kind(significant_index) {
+            Some(TokenKind::Punctuator) => match text {
+                "{" | "(" | "[" => 1,
+                "}" | ")" | "]" => -1,
+                _ => 0
+            },
+            Some(TokenKind::Template) => {
+                (if text.ends_with("${") { 1 } else { 0 }) - (if text.starts_with('}') { 1 } else { 0 })
+            },
+            _ => 0
+        };
+    }
+
+    fn expect_token(&self, significant_index: usize) -> Result<&Token, TranspileError> {
+        return self.token(significant_index).ok_or_else(|| self.error(significant_index, "Unexpected end of input"));
+    }
+
+    fn replace_statement(&mut self, first: usize, last: usize, replacement: &str) -> Result<(), TranspileError> {
+        let start = self.expect_token(first)?.start;
+        let end = self.expect_token(last)?.end;
+        let line_breaks = self.source[start..end].matches('\n').count();
+
+        self.replacements.push((start, end, format!("{}{}", replacement, "\n".repeat(line_breaks))));
+
+        return Ok(());
+    }
+
+    fn statement_end(&self, significant_index: usize) -> usize {
+        return match self.is(significant_index + 1, ";") {
+            true => significant_index + 1,
+            false => significant_index
+        };
+    }
+
+    fn collect_module_declarations(&mut self) -> Result<(), TranspileError> {
+        let mut depth = 0;
+        let mut index = 0;
+
+        while index < self.significant.len() {
+            let previous_is_member_access = index > 0 && (self.is(index - 1, ".") || self.is(index - 1, "?."));
+            let is_module_keyword = self.kind(index) == Some(TokenKind::Identifier) && !previous_is_member_access;
+
+            if is_module_keyword && self.is(index, "import") && self.is(index + 1, "(") {
+                if self.kind(index + 2) == Some(TokenKind::String) && self.is(index + 3, ")") {
+                    let resolved_path = resolve_import_path(self.module_name, &unquote(self.text(index + 2)));
+                    let (path_start, path_end) = self.expect_token(index + 2).map(|token| (token.start, token.end))?;
+
+                    self.replacements.push((path_start, path_end, format!("\"{}\"", resolved_path)));
+                }
+
+                let (import_start, import_end) = self.expect_token(index).map(|token| (token.start, token.end))?;
+
+                self.replacements.push((
+                    import_start,
+                    import_end,
+                    String::from("(function (name) { return new Promise(function (resolve) { resolve(require(name)); }); })")
+                ));
+            } else if is_module_keyword && self.is(index, "import") && !self.is(index + 1, ".") {
+                if depth > 0 {
+                    if self.starts_declaration_after_import(index + 1) {
+                        return Err(self.error(index, "'import' and 'export' may only appear at the top level"));
+                    }
+                } else {
+                    index = self.parse_import(index)?;
+
+                    continue;
+                }
+            } else if is_module_keyword && self.is(index, "export") {
+                if depth > 0 {
+                    if self.starts_declaration_after_export(index + 1) {
+                        return Err(self.error(index, "'import' and 'export' may only appear at the top level"));
+                    }
+                } else {
+                    index = self.parse_export(index)?;
+
+                    continue;
+                }
+            }
+
+            depth += self.depth_change(index);
+
+            if depth < 0 {
+                return Err(self.error(index, format!("Unexpected token '{}'", self.text(index)).as_str()));
+            }
+
+            index += 1;
+        }
+
+        if depth != 0 {
+            return Err(self.error(self.significant.len(), "Unexpected end of input"));
+        }
+
+        return Ok(());
+    }
+
+    fn starts_declaration_after_import(&self, index: usize) -> bool {
+        return self.is(index, "{") || self.is(index, "*") ||
+            (self.kind(index) == Some(TokenKind::Identifier) && (self.is(index + 1, "from") || self.is(index + 1, ","))) ||
+            (self.kind(index) == Some(TokenKind::String) && !self.is(index + 1, ":"));
+    }
+
+    fn starts_declaration_after_export(&self, index: usize) -> bool {
+        return ["default", "const", "let", "var", "function", "class", "async", "*"].contains(&self.text(index)) ||
+            (self.is(index, "{") && !self.is(index - 1, "."));
+    }
+
+    fn parse_import(&mut self, start: usize) -> Result<usize, TranspileError> {
+        let mut index = start + 1;
+        let mut declaration = ImportDeclaration { source: String::new(), default: None, namespace: None, named: Vec::new() };
+
+        if self.kind(index) == Some(TokenKind::String) {
+            declaration.source = resolve_import_path(self.module_name, &self.expect_string(index)?);
+
+            let end = self.statement_end(index);
+
+            self.imports.push(declaration);
+            self.replace_statement(start, end, "")?;
+
+            return Ok(end + 1);
+        }
+
+        if self.kind(index) == Some(TokenKind::Identifier) && !(self.is(index, "from") && self.kind(index + 1) == Some(TokenKind::String)) {
+            declaration.default = Some(self.expect_identifier(index)?);
+            index += 1;
+
+            if self.is(index, ",") {
+                index += 1;
+            }
+        }
+
+        if self.is(index, "*") {
+            if !self.is(index + 1, "as") {
+                return Err(self.error(index + 1, "Expected 'as' after '*'"));
+            }
+
+            declaration.namespace = Some(self.expect_identifier(index + 2)?);
+            index += 3;
+        } else if self.is(index, "{") {
+            index += 1;
+
+            while !self.is(index, "}") {
+                let imported = self.expect_module_export_name(index)?;
+                let local = match self.is(index + 1, "as") {
+                    true => {
+                        index += 2;
+
+                        self.expect_identifier(index)?
+                    },
+                    false => imported.clone()
+                };
+
+                declaration.named.push((imported, local));
+                index += 1;
+
+                if self.is(index, ",") {
+                    index += 1;
+                } else if !self.is(index, "}") {
+                    return Err(self.error(index, format!("Unexpected token '{}'", self.text(index)).as_str()));
+                }
+            }
+
+            index += 1;
+        }
+
+        if !self.is(index, "from") {
+            return Err(self.error(index, format!("Expected 'from', found '{}'", self.text(index)).as_str()));
+        }
+
+        declaration.source = resolve_import_path(self.module_name, &self.expect_string(index + 1)?);
+
+        let end = self.statement_end(index + 1);
+
+        self.imports.push(declaration);
+        self.replace_statement(start, end, "")?;
+
+        return Ok(end + 1);
+    }
+
+    fn parse_export(&mut self, start: usize) -> Result<usize, TranspileError> {
+        let index = start + 1;
+
+        match self.text(index) {
+            "default" => {
+                let declaration_start = match self.is(index + 1, "async") && self.is(index + 2, "function") {
+                    true => index + 2,
+                    false => index + 1
+                };
+                let name_index = match self.text(declaration_start) {
+                    "function" if self.is(declaration_start + 1, "*") => Some(declaration_start + 2),
+                    "function" | "class" => Some(declaration_start + 1),
+                    _ => None
+                };
+                let declaration_name = name_index
+                    .filter(|name_index| self.kind(*name_index) == Some(TokenKind::Identifier) && !self.is(*name_index, "extends"))
+                    .map(|name_index| self.text(name_index).to_string());
+
+                match declaration_name {
+                    Some(name) => {
+                        self.add_export(start, ExportBinding::Local { exported: String::from("default"), local: name })?;
+                        self.remove_tokens(start, index)?;
+                    },
+                    None => {
+                        self.add_export(start, ExportBinding::Local { exported: String::from("default"), local: String::new() })?;
+                        self.replace_statement(start, index, "_exports.default =")?;
+                    }
+                }
+
+                return Ok(index + 1);
+            },
+            "const" | "let" | "var" => {
+                let (names, _) = self.parse_declarators(index + 1)?;
+
+                for name in names {
+                    self.add_export(start, ExportBinding::Local { exported: name.clone(), local: name })?;
+                }
+
+                self.remove_tokens(start, start)?;
+
+                return Ok(index);
+            },
+            "function" | "class" | "async" => {
+                let keyword_index = match self.is(index, "async") { true => index + 1, false => index };
+                let name_index = match self.is(keyword_index + 1, "*") { true => keyword_index + 2, false => keyword_index + 1 };
+                let name = self.expect_identifier(name_index)?;
+
+                if self.is(index, "async") && !self.is(keyword_index, "function") {
+                    return Err(self.error(keyword_index, "Expected 'function' after 'async'"));
+                }
+
+                self.add_export(start, ExportBinding::Local { exported: name.clone(), local: name })?;
+                self.remove_tokens(start, start)?;
+
+                return Ok(index);
+            },
+            "{" => {
+                let mut specifiers = Vec::new();
+                let mut current = index + 1;
+
+                while !self.is(current, "}") {
+                    let local = self.expect_module_export_name(current)?;
+                    let exported = match self.is(current + 1, "as") {
+                        true => {
+                            current += 2;
+
+                            self.expect_module_export_name(current)?
+                        },
+                        false => local.clone()
+                    };
+
+                    specifiers.push((local, exported));
+                    current += 1;
+
+                    if self.is(current, ",") {
+                        current += 1;
+                    } else if !self.is(current, "}") {
+                        return Err(self.error(current, format!("Unexpected token '{}'", self.text(current)).as_str()));
+                    }
+                }
+
+                let source = match self.is(current + 1, "from") {
+                    true => {
+                        current += 2;
+
+                        Some(resolve_import_path(self.module_name, &self.expect_string(current)?))
+                    },
+                    false => None
+                };
+
+                for (local, exported) in specifiers {
+                    let binding = match &source {
+                        Some(source) => ExportBinding::ReExport { exported: exported, source: source.clone(), imported: local },
+                        None => ExportBinding::Local { exported: exported, local: local }
+                    };
+
+                    self.add_export(start, binding)?;
+                }
+
+                let end = self.statement_end(current);
+
+                self.replace_statement(start, end, "")?;
+
+                return Ok(end + 1);
+            },
+            "*" => {
+                let (binding, source_index) = match self.is(index + 1, "as") {
+                    true => {
+                        let exported = self.expect_module_export_name(index + 2)?;
+
+                        (Some(exported), index + 4)
+                    },
+                    false => (None, index + 2)
+                };
+
+                if !self.is(source_index - 1, "from") {
+                    return Err(self.error(source_index - 1, format!("Expected 'from', found '{}'", self.text(source_index - 1)).as_str()));
+                }
+
+                let source = resolve_import_path(self.module_name, &self.expect_string(source_index)?);
+                let end = self.statement_end(source_index);
+
+                match binding {
+                    Some(exported) => self.add_export(start, ExportBinding::ReExportNamespace { exported: exported, source: source })?,
+                    None => self.add_export(start, ExportBinding::ReExportAll { source: source })?
+                };
+
+                self.replace_statement(start, end, "")?;
+
+                return Ok(end + 1);
+            },
+            _ => Err(self.error(index, format!("Unexpected token '{}'", self.text(index)).as_str()))
+        }
+    }
+
+    fn add_export(&mut self, significant_index: usize, binding: ExportBinding) -> Result<(), TranspileError> {
+        let exported_name = match &binding {
+            ExportBinding::Local { exported, .. } | ExportBinding::ReExport { exported, .. } |
+                ExportBinding::ReExportNamespace { exported, .. } => Some(exported.clone()),
+            ExportBinding::ReExportAll { .. } => None
+        };
+
+        if let Some(name) = exported_name {
+            let is_duplicate = self.exports.iter().any(|export| match export {
+                ExportBinding::Local { exported, .. } | ExportBinding::ReExport { exported, .. } |
+                    ExportBinding::ReExportNamespace { exported, .. } => exported == &name,
+                ExportBinding::ReExportAll { .. } => false
+            });
+
+            if is_duplicate {
+                return Err(self.error(significant_index, format!("Duplicate export '{}'", name).as_str()));
+            }
+        }
+
+        self.exports.push(binding);
+
+        return Ok(());
+    }
+
+    fn remove_tokens(&mut self, first: usize, last: usize) -> Result<(), TranspileError> {
+        let start = self.expect_token(first)?.start;
+        let end = self.token(last + 1).map_or(self.source.len(), |token| token.start);
+        let line_breaks = self.source[start..end].matches('\n').count();
+
+        self.replacements.push((start, end, "\n".repeat(line_breaks)));
+
+        return Ok(());
+    }
+
+    // NOTE: returns the bound names of `a = 1, { b, c: [d] } = e` declarator lists
+    fn parse_declarators(&self, start: usize) -> Result<(Vec<String>, usize), TranspileError> {
+        let mut names = Vec::new();
+        let mut index = start;
+
+        loop {
+            index = self.parse_binding_pattern(index, &mut names)?;
+
+            if self.is(index, "=") {
+                index = self.skip_expression(index + 1, &[",", ";"]);
+            }
+
+            if self.is(index, ",") && !self.has_statement_ended(index) {
+                index += 1;
+            } else {
+                return Ok((names, index));
+            }
+        }
+    }
+
+    fn parse_binding_pattern(&self, start: usize, names: &mut Vec<String>) -> Result<usize, TranspileError> {
+        return match self.text(start) {
+            "{" => {
+                let mut index = start + 1;
+
+                while !self.is(index, "}") {
+                    if self.is(index, "...") {
+                        index = self.parse_binding_pattern(index + 1, names)?;
+                    } else {
+                        let key_index = index;
+
+                        index = match self.is(index, "[") {
+                            true => self.skip_expression(index + 1, &["]"]) + 1,
+                            false => index + 1
+                        };
+
+                        if self.is(index, ":") {
+                            index = self.parse_binding_pattern(index + 1, names)?;
+                        } else {
+                            names.push(self.expect_identifier(key_index)?);
+                        }
+
+                        if self.is(index, "=") {
+                            index = self.skip_expression(index + 1, &[",", "}"]);
+                        }
+                    }
+
+                    if self.is(index, ",") {
+                        index += 1;
+                    } else if !self.is(index, "}") {
+                        return Err(self.error(index, format!("Unexpected token '{}'", self.text(index)).as_str()));
+                    }
+                }
+
+                Ok(index + 1)
+            },
+            "[" => {
+                let mut index = start + 1;
+
+                while !self.is(index, "]") {
+                    if self.is(index, ",") {
+                        index += 1;
+
+                        continue;
+                    }
+
+                    index = match self.is(index, "...") {
+                        true => self.parse_binding_pattern(index + 1, names)?,
+                        false => self.parse_binding_pattern(index, names)?
+                    };
+
+                    if self.is(index, "=") {
+                        index = self.skip_expression(index + 1, &[",", "]"]);
+                    }
+
+                    if self.is(index, ",") {
+                        index += 1;
+                    } else if !self.is(index, "]") {
+                        return Err(self.error(index, format!("Unexpected token '{}'", self.text(index)).as_str()));
+                    }
+                }
+
+                Ok(index + 1)
+            },
+            _ => {
+                names.push(self.expect_identifier(start)?);
+
+                Ok(start + 1)
+            }
+        };
+    }
+
+    fn skip_expression(&self, start: usize, terminators: &[&str]) -> usize {
+        let mut depth = 0;
+        let mut index = start;
+
+        while index < self.significant.len() {
+            if depth == 0 && (terminators.contains(&self.text(index)) || (index > start && self.has_statement_ended(index))) {
+                return index;
+            }
+
+            depth += self.depth_change(index);
+
+            if depth < 0 {
+                return index;
+            }
+
+            index += 1;
+        }
+
+        return index;
+    }
+
+    // NOTE: automatic semicolon insertion, a line break ends the statement unless the next token continues it
+    fn has_statement_ended(&self, significant_index: usize) -> bool {
+        if !self.has_line_break_before(significant_index) {
+            return false;
+        }
+
+        let previous_text = self.text(significant_index - 1);
+        let previous_ends_expression = match self.kind(significant_index - 1) {
+            Some(TokenKind::Punctuator) => [")", "]", "}", "++", "--"].contains(&previous_text),
+            Some(TokenKind::Template) => previous_text.ends_with('`'),
+            Some(TokenKind::Identifier) => !STATEMENT_CONTINUATIONS.contains(&previous_text) &&
+                !["typeof", "void", "delete", "new", "await", "yield"].contains(&previous_text),
+            _ => true
+        };
+        let next_continues_expression = match self.kind(significant_index) {
+            Some(TokenKind::Template) => true,
+            _ => STATEMENT_CONTINUATIONS.contains(&self.text(significant_index)) && !self.is(significant_index, ";")
+        };
+
+        return previous_ends_expression && !next_continues_expression;
+    }
+
+    fn to_amd(&self) -> Result<String, TranspileError> {
+        let mut dependencies: Vec<String> = Vec::new();
+
+        self.imports.iter().map(|declaration| &declaration.source)
+            .chain(self.exports.iter().filter_map(|binding| match binding {
+                ExportBinding::ReExport { source, .. } | ExportBinding::ReExportNamespace { source, .. } |
+                    ExportBinding::ReExportAll { source } => Some(source),
+                ExportBinding::Local { .. } => None
+            }))
+            .for_each(|source| {
+                if !dependencies.contains(source) {
+                    dependencies.push(source.clone());
+                }
+            });
+
+        let identifiers = self.tokens.iter()
+            .filter(|token| token.kind == TokenKind::Identifier)
+            .map(|token| token.text(self.source))
+            .collect::<Vec<&str>>();
+        let parameters = dependencies.iter().fold(Vec::new(), |mut result: Vec<String>, dependency| {
+            let base_name = format!("_{}", to_camel_case(dependency.split('/').last().unwrap_or("module")));
+            let base_name = match base_name.as_str() {
+                "_" => String::from("_module"),
+                _ => base_name
+            };
+            let mut parameter = base_name.clone();
+            let mut suffix = 1;
+
+            while result.contains(&parameter) || identifiers.contains(&parameter.as_str()) || parameter == "_exports" {
+                suffix += 1;
+                parameter = format!("{}{}", base_name, suffix);
+            }
+
+            result.push(parameter);
+
+            return result;
+        });
+        let parameter_for = |source: &String| -> Result<&String, TranspileError> {
+            return dependencies.iter().position(|dependency| dependency == source)
+                .map(|position| &parameters[position])
+                .ok_or_else(|| TranspileError::new(self.module_name, 1, 1, &format!("Unresolved module '{}'", source)));
+        };
+        let import_parameters = self.imports.iter()
+            .map(|declaration| parameter_for(&declaration.source))
+            .collect::<Result<Vec<&String>, TranspileError>>()?;
+        let import_references = self.imports.iter().zip(import_parameters).flat_map(|(declaration, parameter)| {
+            return declaration.default.iter().map(move |local| {
+                ImportReference { local: local.clone(), value: format!("{}.default", parameter), is_member: true }
+            })
+                .chain(declaration.namespace.iter().map(move |local| {
+                    ImportReference { local: local.clone(), value: parameter.clone(), is_member: false }
+                }))
+                .chain(declaration.named.iter().map(move |(imported, local)| {
+                    ImportReference { local: local.clone(), value: property_access(parameter, imported), is_member: true }
+                }));
+        }).collect::<Vec<ImportReference>>();
+        let export_getters = self.exports.iter().map(|binding| -> Result<Option<String>, TranspileError> {
+            let (exported, value) = match binding {
+                ExportBinding::Local { local, .. } if local.is_empty() => return Ok(None),
+                ExportBinding::Local { exported, local } => (exported, import_references.iter()
+                    .find(|reference| &reference.local == local)
+                    .map_or(local.clone(), |reference| reference.value.clone())),
+                ExportBinding::ReExport { exported, source, imported } => (exported, property_access(parameter_for(source)?, imported)),
+                ExportBinding::ReExportNamespace { exported, source } => (exported, parameter_for(source)?.clone()),
+                ExportBinding::ReExportAll { .. } => return Ok(None)
+            };
+
+            return Ok(Some(format!(
+                "Object.defineProperty(_exports, {}, {{ enumerable: true, get: function () {{ return {}; }} }});",
+                quote(exported), value
+            )));
+        }).collect::<Result<Vec<Option<String>>, TranspileError>>()?;
+        let star_exports = self.exports.iter().map(|binding| match binding {
+            ExportBinding::ReExportAll { source } => Ok(Some(format!(
+                "Object.keys({0}).forEach(function (key) {{ if (key === \"default\" || key === \"__esModule\" || \
+                Object.prototype.hasOwnProperty.call(_exports, key)) return; Object.defineProperty(_exports, key, \
+                {{ enumerable: true, get: function () {{ return {0}[key]; }} }}); }});",
+                parameter_for(source)?
+            ))),
+            _ => Ok(None)
+        }).collect::<Result<Vec<Option<String>>, TranspileError>>()?;
+        let mut preamble = vec![
+            String::from("\"use strict\";"),
+            String::from("Object.defineProperty(_exports, \"__esModule\", { value: true });")
+        ];
+
+        preamble.extend(export_getters.into_iter().flatten());
+        preamble.extend(star_exports.into_iter().flatten());
+
+        let body = self.apply_replacements(self.import_reference_replacements(&import_references));
+        let body = body.trim_end();
+
+        return Ok(format!(
+            "define({}, [{}], function ({}) {{ {}{}{}\n}});",
+            quote(self.module_name),
+            vec![String::from("exports")].iter().chain(dependencies.iter()).map(|dependency| quote(dependency)).collect::<Vec<String>>().join(", "),
+            vec![String::from("_exports")].iter().chain(parameters.iter()).cloned().collect::<Vec<String>>().join(", "),
+            preamble.join(" "),
+            if body.starts_with('\n') || body.is_empty() { "" } else { " " },
+            body
+        ));
+    }
+
+    // NOTE: imports are read from the module object on every use like babel does, so cyclic imports and reassigned
+    // exports stay live. Calls are made through (0, _dep.name) so the module object is not passed as this
+    fn import_reference_replacements(&self, import_references: &Vec<ImportReference>) -> Vec<(usize, usize, String)> {
+        if import_references.is_empty() {
+            return Vec::new();
+        }
+
+        let brackets = self.brackets();
+        let names = import_references.iter().map(|reference| reference.local.as_str()).collect::<Vec<&str>>();
+        let shadowed_ranges = self.shadowed_ranges(&names, &brackets);
+        let class_bodies = self.class_bodies(&brackets);
+
+        return (0..self.significant.len()).filter_map(|index| {
+            let text = self.text(index);
+            let reference = import_references.iter().find(|reference| reference.local == text)?;
+            let previous = if index > 0 { self.text(index - 1) } else { "" };
+            let next = self.text(index + 1);
+
+            if self.kind(index) != Some(TokenKind::Identifier) || previous == "." || previous == "?." ||
+                shadowed_ranges.iter().any(|(name, start, end)| name == text && *start <= index && index <= *end) {
+                return None;
+            }
+
+            let is_property_key = |separators: &[&str]| {
+                return separators.contains(&previous) ||
+                    (["get", "set", "async", "static", "*"].contains(&previous) && index > 1 && separators.contains(&self.text(index - 2)));
+            };
+            let replacement = match brackets.enclosing[index] {
+                Some(opener) if class_bodies.contains(&opener) => {
+                    let is_value = previous == "@" || ["new", "typeof", "void", "await", "yield", "in", "instanceof"].contains(&previous) ||
+                        (self.kind(index - 1) == Some(TokenKind::Punctuator) && ![";", "{", "}", "*", ")", "]"].contains(&previous));
+
+                    match is_value {
+                        true => reference.value.clone(),
+                        false => return None
+                    }
+                },
+                Some(opener) if self.is_object_literal(opener, &brackets) && is_property_key(&["{", ","]) => match next {
+                    ":" | "(" | "=" => return None,
+                    "," | "}" if previous == "{" || previous == "," => format!("{}: {}", text, reference.value),
+                    _ => reference.value.clone()
+                },
+                _ => {
+                    let is_call = next == "(" || (self.kind(index + 1) == Some(TokenKind::Template) && next.starts_with('`'));
+
+                    match reference.is_member && is_call && previous != "new" && previous != "@" {
+                        true => format!("(0, {})", reference.value),
+                        false => reference.value.clone()
+                    }
+                }
+            };
+            let token = self.token(index)?;
+
+            return Some((token.start, token.end, replacement));
+        }).collect();
+    }
+
+    fn brackets(&self) -> Brackets {
+        let mut brackets = Brackets { matches: vec![None; self.significant.len()], enclosing: vec![None; self.significant.len()] };
+        let mut stack: Vec<usize> = Vec::new();
+
+        for index in 0..self.significant.len() {
+            let text = self.text(index);
+            let (closes, opens) = match self.kind(index) {
+                Some(TokenKind::Punctuator) => (["}", ")", "]"].contains(&text), ["{", "(", "["].contains(&text)),
+                Some(TokenKind::Template) => (text.starts_with('}'), text.ends_with("${")),
+                _ => (false, false)
+            };
+
+            if let Some(opener) = stack.last().cloned().filter(|_| closes) {
+                stack.pop();
+                brackets.matches[opener] = Some(index);
+                brackets.matches[index] = Some(opener);
+            }
+
+            brackets.enclosing[index] = stack.last().cloned();
+
+            if opens {
+                stack.push(index);
+            }
+        }
+
+        return brackets;
+    }
+
+    // NOTE: significant index ranges where a declaration or a parameter hides an imported name
+    fn shadowed_ranges(&self, names: &Vec<&str>, brackets: &Brackets) -> Vec<(String, usize, usize)> {
+        let mut ranges = Vec::new();
+
+        for index in 0..self.significant.len() {
+            if index > 0 && (self.is(index - 1, ".") || self.is(index - 1, "?.")) {
+                continue;
+            }
+
+            let (declared_names, start, end) = match (self.kind(index), self.text(index)) {
+                (Some(TokenKind::Identifier), "var" | "let" | "const")
+                    if self.kind(index + 1) == Some(TokenKind::Identifier) || self.is(index + 1, "{") || self.is(index + 1, "[") => {
+                    let declared_names = self.parse_declarators(index + 1).map_or(Vec::new(), |(declared_names, _)| declared_names);
+                    let (start, end) = match self.is(index, "var") {
+                        true => self.function_range(index, brackets),
+                        false => self.block_range(index, brackets)
+                    };
+
+                    (declared_names, start, end)
+                },
+                (Some(TokenKind::Identifier), "function" | "class") => {
+                    let name_index = if self.is(index + 1, "*") { index + 2 } else { index + 1 };
+
+                    if self.kind(name_index) != Some(TokenKind::Identifier) || self.is(name_index, "extends") {
+                        continue;
+                    }
+
+                    let (start, end) = self.block_range(index, brackets);
+
+                    (vec![self.text(name_index).to_string()], start, end)
+                },
+                (Some(TokenKind::Identifier), name) if self.is(index + 1, "=>") => {
+                    (vec![name.to_string()], index, self.arrow_body_end(index + 2, brackets))
+                },
+                (Some(TokenKind::Punctuator), "(") => {
+                    let close = match brackets.matches[index] {
+                        Some(close) => close,
+                        None => continue
+                    };
+                    let end = match self.text(close + 1) {
+                        "{" if !self.follows_control_keyword(index) => brackets.matches[close + 1],
+                        "=>" => Some(self.arrow_body_end(close + 2, brackets)),
+                        _ => None
+                    };
+
+                    match end {
+                        Some(end) => (self.parse_parameters(index + 1, close), index, end),
+                        None => continue
+                    }
+                },
+                _ => continue
+            };
+
+            declared_names.into_iter()
+                .filter(|name| names.contains(&name.as_str()))
+                .for_each(|name| ranges.push((name, start, end)));
+        }
+
+        return ranges;
+    }
+
+    fn parse_parameters(&self, start: usize, end: usize) -> Vec<String> {
+        let mut names = Vec::new();
+        let mut index = start;
+
+        while index < end {
+            let pattern_start = if self.is(index, "...") { index + 1 } else { index };
+
+            index = match self.parse_binding_pattern(pattern_start, &mut names) {
+                Ok(next_index) => next_index,
+                Err(_) => return names
+            };
+
+            if self.is(index, "=") {
+                index = self.skip_expression(index + 1, &[",", ")"]);
+            }
+
+            if !self.is(index, ",") {
+                return names;
+            }
+
+            index += 1;
+        }
+
+        return names;
+    }
+
+    fn block_range(&self, significant_index: usize, brackets: &Brackets) -> (usize, usize) {
+        let last = self.significant.len().saturating_sub(1);
+
+        return match brackets.enclosing[significant_index] {
+            Some(opener) if self.is(opener, "(") && self.follows_control_keyword(opener) => {
+                let close = brackets.matches[opener].unwrap_or(last);
+                let end = match self.is(close + 1, "{") {
+                    true => brackets.matches[close + 1].unwrap_or(last),
+                    false => self.skip_expression(close + 1, &[";"])
+                };
+
+                (opener, end)
+            },
+            Some(opener) => (opener, brackets.matches[opener].unwrap_or(last)),
+            None => (0, last)
+        };
+    }
+
+    // NOTE: var declarations are hoisted to the closest function body
+    fn function_range(&self, significant_index: usize, brackets: &Brackets) -> (usize, usize) {
+        let mut opener = brackets.enclosing[significant_index];
+
+        while let Some(current) = opener {
+            let is_function_body = self.is(current, "{") && current > 0 && (self.is(current - 1, "=>") || (self.is(current - 1, ")") &&
+                brackets.matches[current - 1].map_or(false, |parameters| {
+                    return !self.follows_control_keyword(parameters) && !(parameters > 0 && self.is(parameters - 1, "catch"));
+                })));
+
+            if is_function_body {
+                return (current, brackets.matches[current].unwrap_or(self.significant.len().saturating_sub(1)));
+            }
+
+            opener = brackets.enclosing[current];
+        }
+
+        return (0, self.significant.len().saturating_sub(1));
+    }
+
+    fn arrow_body_end(&self, significant_index: usize, brackets: &Brackets) -> usize {
+        return match self.is(significant_index, "{") {
+            true => brackets.matches[significant_index].unwrap_or(self.significant.len().saturating_sub(1)),
+            false => self.skip_expression(significant_index, &[",", ";"])
+        };
+    }
+
+    fn follows_control_keyword(&self, opener: usize) -> bool {
+        return opener > 0 && (["if", "for", "while", "switch", "with"].contains(&self.text(opener - 1)) ||
+            (opener > 1 && self.is(opener - 1, "await") && self.is(opener - 2, "for")));
+    }
+
+    fn class_bodies(&self, brackets: &Brackets) -> Vec<usize> {
+        let mut class_bodies = Vec::new();
+
+        for index in 0..self.significant.len() {
+            let is_class_keyword = self.kind(index) == Some(TokenKind::Identifier) && self.is(index, "class") &&
+                !(index > 0 && (self.is(index - 1, ".") || self.is(index - 1, "?."))) && !self.is(index + 1, ":");
+
+            if !is_class_keyword {
+                continue;
+            }
+
+            let mut current = index + 1;
+
+            while current < self.significant.len() && !self.is(current, ";") {
+                if self.is(current, "{") {
+                    class_bodies.push(current);
+
+                    break;
+                }
+
+                current = match self.is(current, "(") || self.is(current, "[") {
+                    true => brackets.matches[current].map_or(self.significant.len(), |close| close + 1),
+                    false => current + 1
+                };
+            }
+        }
+
+        return class_bodies;
+    }
+
+    // NOTE: decorators and class fields are not transpiled, they would reach browsers that can not parse them.
+    // Members are skipped by their brackets, a key followed by =, ; or a new line without parameters is a field
+    fn reject_unsupported_class_syntax(&self) -> Result<(), TranspileError> {
+        if let Some(decorator) = (0..self.significant.len()).find(|index| self.kind(*index) == Some(TokenKind::Punctuator) && self.is(*index, "@")) {
+            return Err(self.error(decorator, "Decorators are not supported, use the classic Ember.Object.extend() syntax"));
+        }
+
+        let brackets = self.brackets();
+
+        for body in self.class_bodies(&brackets) {
+            let body_end = brackets.matches[body].unwrap_or(self.significant.len());
+            let mut member = body + 1;
+
+            while member < body_end {
+                if self.is(member, ";") {
+                    member += 1;
+
+                    continue;
+                }
+
+                let mut current = member;
+
+                member = loop {
+                    let is_field = current >= body_end || self.is(current, "=") || self.is(current, ";") ||
+                        (current > member && self.has_line_break_before(current) && !self.is(current, "(") &&
+                            !["static", "get", "set", "async", "*"].contains(&self.text(current - 1)));
+
+                    if is_field {
+                        return Err(self.error(member, format!(
+                            "Class fields are not supported, assign '{}' in the constructor instead", self.text(member)
+                        ).as_str()));
+                    } else if self.is(current, "(") {
+                        let body_start = brackets.matches[current].map_or(body_end, |close| close + 1);
+
+                        break brackets.matches[body_start].map_or(body_end, |close| close + 1);
+                    } else if self.is(current, "{") {
+                        break brackets.matches[current].map_or(body_end, |close| close + 1);
+                    }
+
+                    current = match self.is(current, "[") {
+                        true => brackets.matches[current].map_or(body_end, |close| close + 1),
+                        false => current + 1
+                    };
+                };
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn is_object_literal(&self, opener: usize, brackets: &Brackets) -> bool {
+        if !self.is(opener, "{") || opener == 0 {
+            return false;
+        }
+
+        let previous = self.text(opener - 1);
+
+        return match self.kind(opener - 1) {
+            Some(TokenKind::Punctuator) if previous == ":" => self.colon_starts_value(opener - 1, brackets),
+            Some(TokenKind::Punctuator) => ![")", "]", "}", ";", "{", "=>"].contains(&previous),
+            Some(TokenKind::Identifier) => ["return", "default", "in", "of", "typeof", "void", "await", "yield", "throw"].contains(&previous),
+            _ => false
+        };
+    }
+
+    // NOTE: a colon before a brace is either a conditional or an object property, case and label colons start blocks
+    fn colon_starts_value(&self, colon: usize, brackets: &Brackets) -> bool {
+        let mut index = colon;
+
+        while index > 0 {
+            index -= 1;
+
+            match self.text(index) {
+                "?" => return true,
+                ";" | "{" | "(" | "[" | "," | ":" | "case" | "default" => break,
+                _ => {}
+            }
+
+            if let Some(opener) = brackets.matches[index].filter(|opener| *opener < index) {
+                index = opener;
+            }
+        }
+
+        return brackets.enclosing[colon].map_or(false, |opener| self.is_object_literal(opener, brackets));
+    }
+
+    fn apply_replacements(&self, reference_replacements: Vec<(usize, usize, String)>) -> String {
+        let mut replacements = self.replacements.iter().chain(reference_replacements.iter()).collect::<Vec<_>>();
+        let mut result = String::with_capacity(self.source.len());
+        let mut cursor = 0;
+
+        replacements.sort_by_key(|(start, _, _)| *start);
+
+        for (start, end, replacement) in replacements {
+            if *start < cursor {
+                continue;
+            }
+
+            result.push_str(&self.source[cursor..*start]);
+            result.push_str(replacement);
+            cursor = *end;
+        }
+
+        result.push_str(&self.source[cursor..]);
+
+        return result;
+    }
+}
+
+fn unquote(string_literal: &str) -> String {
+    let inner = &string_literal[1..string_literal.len() - 1];
+
+    return inner.replace("\\'", "'").replace("\\\"", "\"").replace("\\\\", "\\");
 }
 
-pub fn from_string<'a>(_code_string: &'a str, _file_name: &'a str, _minify: bool) -> &'a str {
-    return "function(){{ }};";
+fn quote(value: &str) -> String {
+    return format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""));
+}
+
+fn property_access(object: &str, property: &str) -> String {
+    let is_identifier = property.chars().next().map_or(false, |character| character.is_alphabetic() || character == '_' || character == '$') &&
+        property.chars().all(|character| character.is_alphanumeric() || character == '_' || character == '$');
+
+    return match is_identifier {
+        true => format!("{}.{}", object, property),
+        false => format!("{}[{}]", object, quote(property))
+    };
 }
 
 #[cfg(test)]
 mod tests {
     use std::io;
-    // use std::fs;
+    use std::env;
+    use std::error::Error;
+    use std::path::PathBuf;
     use super::*;
 
+    fn line(output: &str, line_number: usize) -> &str {
+        return output.lines().nth(line_number - 1).unwrap_or("");
+    }
+
     #[test]
     fn convert_es_module_from_string_works() -> io::Result<()> {
         let code = "import EmberRouter from '@ember/routing/router';
 import DocumentationRouter from 'mber-documentation';
 import ENV from '../config/environment';
 
-const Router = EmberRouter.extend({{
+const Router = EmberRouter.extend({
   location: ENV.locationType,
   rootURL: ENV.rootURL
-}});
+});
 
-Router.map(function() {{
-  this.route('index', {{ path: '/' }});
+Router.map(function() {
+  this.route('index', { path: '/' });
 
-  if (ENV.documentation && ENV.documentation.enabled) {{
+  if (ENV.documentation && ENV.documentation.enabled) {
     DocumentationRouter.apply(this, [ENV]);
-  }}
+  }
 
-  this.route('not-found', {{ path: '/*path' }});
-}});
+  this.route('not-found', { path: '/*path' });
+});
 
 export default Router;";
-        let expected_output = "function(){{ }};";
+        let output = from_string(code, "frontend/src/router").unwrap();
 
-        assert_eq!(from_string(code, "src/router", false), expected_output);
+        assert!(output.starts_with(
+            "define(\"frontend/src/router\", [\"exports\", \"@ember/routing/router\", \"mber-documentation\", \
+            \"frontend/config/environment\"], function (_exports, _router, _mberDocumentation, _environment) { \"use strict\"; \
+            Object.defineProperty(_exports, \"__esModule\", { value: true });\n"
+        ));
+        assert!(output.ends_with("\n});"));
+        assert!(!output.contains("import "));
+        assert!(!output.contains("export "));
+        assert_eq!(line(&output, 5), "const Router = _router.default.extend({");
+        assert_eq!(line(&output, 6), "  location: _environment.default.locationType,");
+        assert_eq!(line(&output, 14), "    _mberDocumentation.default.apply(this, [_environment.default]);");
+        assert_eq!(line(&output, 20), "_exports.default = Router;");
 
         Ok(())
     }
 
-    // #[test]
-    // fn convert_es_module_from_file_works() -> io::Result<()> {
-    // }
+    #[test]
+    fn convert_es_module_keeps_original_line_numbers() {
+        let code = "import {\n  computed\n} from '@ember/object';\n\nexport const a = computed();\nthrow new Error('line 6');";
+        let output = from_string(code, "frontend/src/utils/a").unwrap();
+
+        assert_eq!(line(&output, 5), "const a = (0, _object.computed)();");
+        assert_eq!(line(&output, 6), "throw new Error('line 6');");
+    }
+
+    #[test]
+    fn convert_es_module_handles_named_and_namespace_imports() {
+        let code = "import Component, { tracked as t, 'string name' as s } from '@glimmer/component';
+import * as helpers from './helpers';
+import './side-effect';
+import { a } from './helpers';
+new Component(t, s, helpers.b, a);";
+        let output = from_string(code, "frontend/src/ui/components/x/component").unwrap();
+
+        assert!(output.contains(
+            "[\"exports\", \"@glimmer/component\", \"frontend/src/ui/components/x/helpers\", \
+            \"frontend/src/ui/components/x/side-effect\"], function (_exports, _component, _helpers, _sideEffect)"
+        ));
+        assert!(output.ends_with("new _component.default(_component.tracked, _component[\"string name\"], _helpers.b, _helpers.a);\n});"));
+    }
+
+    #[test]
+    fn convert_es_module_handles_named_exports() {
+        let code = "export const a = 1, { b, c: [d, ...e] } = obj, f = g(1, 2);
+export let h;
+export function i() {}
+export async function j() {}
+export class K extends L {}
+const m = 1;
+export { m, m as n, m as default };";
+        let output = from_string(code, "frontend/src/utils/named").unwrap();
+
+        ["a", "b", "d", "e", "f", "h", "i", "j", "K", "m"].iter().for_each(|name| {
+            assert!(output.contains(
+                &format!("Object.defineProperty(_exports, \"{}\", {{ enumerable: true, get: function () {{ return {}; }} }});", name, name)
+            ));
+        });
+        assert!(output.contains("Object.defineProperty(_exports, \"n\", { enumerable: true, get: function () { return m; } });"));
+        assert!(output.contains("Object.defineProperty(_exports, \"default\", { enumerable: true, get: function () { return m; } });"));
+        assert!(output.contains(" const a = 1, { b, c: [d, ...e] } = obj, f = g(1, 2);\nlet h;\nfunction i() {}\nasync function j() {}\nclass K extends L {}"));
+    }
+
+    #[test]
+    fn convert_es_module_handles_default_exports() {
+        let anonymous = from_string("export default {\n  a: 1\n};", "frontend/src/a").unwrap();
+        let named_function = from_string("export default function setup(hooks) {}", "frontend/src/b").unwrap();
+        let anonymous_class = from_string("export default class extends Base {}", "frontend/src/c").unwrap();
+
+        assert!(anonymous.ends_with(" _exports.default = {\n  a: 1\n};\n});"));
+        assert!(named_function.contains("get: function () { return setup; }"));
+        assert!(named_function.ends_with(" function setup(hooks) {}\n});"));
+        assert!(anonymous_class.ends_with(" _exports.default = class extends Base {}\n});"));
+    }
+
+    #[test]
+    fn convert_es_module_handles_re_exports() {
+        let code = "export { default } from './component';
+export { a as b } from 'some-addon/utils';
+export * from './helpers';
+export * as everything from './helpers';";
+        let output = from_string(code, "frontend/src/index").unwrap();
+
+        assert!(output.contains("[\"exports\", \"frontend/src/component\", \"some-addon/utils\", \"frontend/src/helpers\"]"));
+        assert!(output.contains("function (_exports, _component, _utils, _helpers)"));
+        assert!(output.contains("Object.defineProperty(_exports, \"default\", { enumerable: true, get: function () { return _component.default; } });"));
+        assert!(output.contains("Object.defineProperty(_exports, \"b\", { enumerable: true, get: function () { return _utils.a; } });"));
+        assert!(output.contains("Object.defineProperty(_exports, \"everything\", { enumerable: true, get: function () { return _helpers; } });"));
+        assert!(output.contains("Object.keys(_helpers).forEach(function (key) {"));
+    }
+
+    #[test]
+    fn convert_es_module_ignores_module_keywords_in_strings_comments_and_properties() {
+        let code = "// import a from 'b';\nconst c = 'export default 1';\nconst d = `import ${e}`;\nf.import(g);\nconst h = { export: 1 };";
+        let output = from_string(code, "frontend/src/a").unwrap();
+
+        assert!(output.contains("[\"exports\"], function (_exports)"));
+        assert!(output.contains(code));
+    }
+
+    #[test]
+    fn convert_es_module_resolves_dynamic_imports() {
+        let output = from_string("import('./lazy').then((m) => m.default);", "frontend/src/a").unwrap();
+
+        assert!(output.contains("resolve(require(name)); }); })(\"frontend/src/lazy\").then((m) => m.default);"));
+    }
+
+    #[test]
+    fn convert_es_module_avoids_parameter_name_collisions() {
+        let output = from_string("import a from 'router';\nconst _router = a;", "frontend/src/a").unwrap();
+
+        assert!(output.contains("function (_exports, _router2)"));
+        assert!(output.contains("const _router = _router2.default;"));
+    }
+
+    #[test]
+    fn convert_es_module_reports_errors_with_line_and_column() {
+        let missing_from = from_string("import a from 'b';\nimport c 'd';", "frontend/src/a").unwrap_err();
+        let nested_import = from_string("if (a) {\n  import b from 'c';\n}", "frontend/src/b").unwrap_err();
+        let unterminated = from_string("const a = 'oops;", "frontend/src/c").unwrap_err();
+        let duplicate = from_string("export const a = 1;\nexport { a };", "frontend/src/d").unwrap_err();
+        let unbalanced = from_string("function a() {\n", "frontend/src/e").unwrap_err();
+
+        assert_eq!(missing_from.to_string(), "frontend/src/a:2:10 Expected 'from', found ''d''");
+        assert_eq!((nested_import.line, nested_import.column), (2, 3));
+        assert_eq!((unterminated.line, unterminated.column), (1, 11));
+        assert_eq!((duplicate.line, duplicate.column, duplicate.message.as_str()), (2, 1, "Duplicate export 'a'"));
+        assert_eq!(unbalanced.message, "Unexpected end of input");
+    }
+
+    #[test]
+    fn convert_es_module_reads_imports_live_from_the_module_object() {
+        let code = "import Service, { inject as service } from '@ember/service';
+import { tracked } from '@glimmer/tracking';
+import { helper, count } from './helpers';
+export { count };
+export default class Session extends Service {
+  router() { return service; }
+  static [tracked]() { return helper; }
+  count() { return count + 1; }
+  get helper2() { return { helper, count: count, tracked }; }
+}
+helper`template`;
+function shadow(helper, { count = tracked }) {
+  let service = 1;
+  return [helper, count, service, (tracked) => tracked];
+}
+for (const helper of []) { helper(); }
+var object = { helper() {}, count: 1, service };";
+        let output = from_string(code, "frontend/src/services/session").unwrap();
+
+        assert!(!output.contains("var Service"));
+        assert!(output.contains("Object.defineProperty(_exports, \"count\", { enumerable: true, get: function () { return _helpers.count; } });"));
+        assert_eq!(line(&output, 5), "class Session extends _service.default {");
+        assert_eq!(line(&output, 6), "  router() { return _service.inject; }");
+        assert_eq!(line(&output, 7), "  static [_tracking.tracked]() { return _helpers.helper; }");
+        assert_eq!(line(&output, 8), "  count() { return _helpers.count + 1; }");
+        assert_eq!(line(&output, 9), "  get helper2() { return { helper: _helpers.helper, count: _helpers.count, tracked: _tracking.tracked }; }");
+        assert_eq!(line(&output, 11), "(0, _helpers.helper)`template`;");
+        assert_eq!(line(&output, 12), "function shadow(helper, { count = _tracking.tracked }) {");
+        assert_eq!(line(&output, 14), "  return [helper, count, service, (tracked) => tracked];");
+        assert_eq!(line(&output, 16), "for (const helper of []) { helper(); }");
+        assert_eq!(line(&output, 17), "var object = { helper() {}, count: 1, service: _service.inject };");
+    }
+
+    #[test]
+    fn convert_es_module_rejects_decorators_and_class_fields() {
+        let decorator = from_string("import { tracked } from '@glimmer/tracking';\nclass A {\n  @tracked count;\n}", "frontend/src/a")
+            .unwrap_err();
+        let field = from_string("class A extends B {\n  static get b() { return 1; }\n  [c]() {}\n  count = 0;\n}", "frontend/src/b")
+            .unwrap_err();
+        let field_without_semicolon = from_string("class A {\n  static { init(); }\n  count\n  b() {}\n}", "frontend/src/c")
+            .unwrap_err();
+
+        assert_eq!(decorator.to_string(), "frontend/src/a:3:3 Decorators are not supported, use the classic Ember.Object.extend() syntax");
+        assert_eq!(field.to_string(), "frontend/src/b:4:3 Class fields are not supported, assign 'count' in the constructor instead");
+        assert_eq!((field_without_semicolon.line, field_without_semicolon.column), (3, 3));
+        assert!(from_string("class A {\n  constructor() { this.count = 0; }\n  async *b() {}\n  get\n  c() {}\n}", "frontend/src/d").is_ok());
+        assert!(from_string("var a = { b: 1, c() {} };\nclass D { static e() {} }", "frontend/src/e").is_ok());
+    }
+
+    #[test]
+    fn resolve_import_path_works() {
+        assert_eq!(resolve_import_path("frontend/src/router", "../config/environment"), "frontend/config/environment");
+        assert_eq!(resolve_import_path("frontend/src/main", "./resolver"), "frontend/src/resolver");
+        assert_eq!(resolve_import_path("frontend/src/main", "./resolver.js"), "frontend/src/resolver");
+        assert_eq!(resolve_import_path("frontend/src/main", "ember-resolver"), "ember-resolver");
+        assert_eq!(
+            resolve_import_path("frontend/src/ui/components/welcome-page/integration-test", "../../../../tests/helpers"),
+            "frontend/tests/helpers"
+        );
+    }
+
+    #[test]
+    fn convert_es_module_from_file_works() -> Result<(), Box<dyn Error>> {
+        let project_root = PathBuf::from(format!("{}/ember-app-boilerplate", env::current_dir()?.display()));
+        let outputs = from_files(
+            &vec![project_root.join("src/router.js"), project_root.join("src/main.js")], &project_root, "frontend"
+        )?;
+
+        assert!(outputs[0].starts_with("define(\"frontend/src/router\", [\"exports\", \"@ember/routing/router\""));
+        assert!(outputs[1].starts_with("define(\"frontend/src/main\""));
+
+        let temporary_root = env::temp_dir().join(format!("mber-convert-es-module-{}", std::process::id()));
+
+        fs::create_dir_all(&temporary_root)?;
+        fs::write(temporary_root.join("broken-module.js"), "export default {")?;
+
+        let error = from_file(&temporary_root.join("broken-module.js"), &temporary_root, "frontend").unwrap_err();
+
+        fs::remove_dir_all(&temporary_root)?;
+
+        assert_eq!(error.to_string(), "broken-module.js:1:17 Unexpected end of input");
+
+        return Ok(());
+    }
 }
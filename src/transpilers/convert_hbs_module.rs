@@ -1,9 +1,267 @@
-// use std::path::Path;
+use std::cell::RefCell;
+use std::cmp;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use rayon::prelude::*;
+use rquickjs::{Context, Ctx, Function, Runtime};
+use rquickjs::context::EvalOptions;
+use serde_json::Value;
+use super::super::types::{MberError, TranspileError};
+use super::super::utils::transpile_cache;
+use super::module_name_from_path;
 
-// pub fn from_file(file: Path) -> String {
+const MINIMUM_TEMPLATES_PER_COMPILER: usize = 100;
+const TEMPLATE_COMPILER: &str = include_str!("../../_vendor/ember-template-compiler.js");
 
-// }
+// NOTE: evaluated after the compiler, precompileTemplate returns the compiled template or its error as JSON
+const PRECOMPILE_SCRIPT: &str = "
+  function precompileTemplate(moduleName, source) {
+    try {
+      return JSON.stringify({ code: module.exports.precompile(source, { moduleName: moduleName }) });
+    } catch (error) {
+      return JSON.stringify({ error: Object.assign({ message: error.message.split('\\n').pop() }, findLocation(error)) });
+    }
+  }
 
-// pub fn from_string(code_string: String, file_name: String) -> String {
+  function findLocation(error) {
+    if (error.location && error.location.start) {
+      return { line: error.location.start.line, column: error.location.start.column + 1 };
+    }
 
-// }
+    const parseError = error.message.match(/^Parse error on line (\\d+):\\n.*\\n(-*)\\^/);
+
+    if (parseError) {
+      return { line: Number(parseError[1]), column: parseError[2].length + 1 };
+    }
+
+    const blockError = error.message.match(/ - (\\d+):(\\d+)$/);
+
+    if (blockError) {
+      return { line: Number(blockError[1]), column: Number(blockError[2]) + 1 };
+    }
+
+    return { line: 1, column: 1 };
+  }
+";
+
+pub fn from_file(file: &Path, project_root: &Path, application_name: &str) -> Result<String, MberError> {
+    return Ok(from_files(&vec![file.to_path_buf()], project_root, application_name)?.remove(0));
+}
+
+pub fn from_files(files: &Vec<PathBuf>, project_root: &Path, application_name: &str) -> Result<Vec<String>, MberError> {
+    let templates = files.iter()
+        .map(|file| -> Result<(String, String), MberError> {
+            let content = fs::read_to_string(file).map_err(MberError::io("could not read", file))?;
+
+            return Ok((module_name_from_path(file, project_root, application_name), content));
+        })
+        .collect::<Result<Vec<(String, String)>, MberError>>()?;
+
+    return from_strings(&templates).map_err(|error| {
+        let file = &files[templates.iter().position(|(module_name, _)| module_name == &error.file_name).unwrap_or(0)];
+        let relative_path = file.strip_prefix(project_root).unwrap_or(file).to_string_lossy().to_string();
+
+        return MberError::Transpile(TranspileError { file_name: relative_path, ..error });
+    });
+}
+
+pub fn from_string(code_string: &str, module_name: &str) -> Result<String, TranspileError> {
+    return Ok(from_strings(&vec![(module_name.to_string(), code_string.to_string())])?.remove(0));
+}
+
+// NOTE: templates are (module_name, source) pairs, errors carry the module_name as their file_name.
+// Only the templates missing from the transpile cache are compiled
+pub fn from_strings(templates: &Vec<(String, String)>) -> Result<Vec<String>, TranspileError> {
+    let keys = templates.iter()
+        .map(|(module_name, source)| transpile_cache::key(transpiler_id(), module_name, source))
+        .collect::<Vec<String>>();
+    let cached_outputs = keys.iter().map(|key| transpile_cache::get(key)).collect::<Vec<Option<String>>>();
+    let missing_templates = templates.iter().zip(cached_outputs.iter())
+        .filter(|(_, cached_output)| cached_output.is_none())
+        .map(|(template, _)| template.clone())
+        .collect::<Vec<(String, String)>>();
+    let mut compiled_outputs = compile(&missing_templates)?.into_iter();
+
+    return Ok(cached_outputs.into_iter().zip(keys.iter()).map(|(cached_output, key)| {
+        return cached_output.unwrap_or_else(|| {
+            let output = compiled_outputs.next().unwrap_or_default();
+
+            transpile_cache::set(key, &output);
+
+            return output;
+        });
+    }).collect());
+}
+
+// NOTE: cached outputs are invalidated whenever this file or the bundled template compiler changes
+fn transpiler_id() -> &'static str {
+    static TRANSPILER_ID: OnceLock<String> = OnceLock::new();
+
+    return TRANSPILER_ID.get_or_init(|| transpile_cache::key("hbs-module", include_str!("convert_hbs_module.rs"), TEMPLATE_COMPILER));
+}
+
+fn compile(templates: &Vec<(String, String)>) -> Result<Vec<String>, TranspileError> {
+    if templates.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let threads = rayon::current_num_threads();
+    let chunk_size = cmp::max(MINIMUM_TEMPLATES_PER_COMPILER, (templates.len() + threads - 1) / threads);
+    let results = templates.par_chunks(chunk_size)
+        .map(|chunk| precompile(chunk).map_err(|error| {
+            return TranspileError::new(&chunk[0].0, 1, 1, &format!("Could not run ember-template-compiler: {}", error));
+        }))
+        .collect::<Result<Vec<Vec<Value>>, TranspileError>>()?
+        .concat();
+
+    return templates.iter().zip(results.iter())
+        .map(|((module_name, _), result)| {
+            if let Some(error) = result.get("error") {
+                return Err(TranspileError::new(
+                    module_name,
+                    error["line"].as_u64().unwrap_or(1) as usize,
+                    error["column"].as_u64().unwrap_or(1) as usize,
+                    error["message"].as_str().unwrap_or("Template syntax error")
+                ));
+            }
+
+            return Ok(to_amd(module_name, result["code"].as_str().unwrap_or("{}")));
+        })
+        .collect();
+}
+
+thread_local! {
+    // NOTE: each rayon thread evaluates the compiler once in an embedded QuickJS context and keeps it for later builds
+    static COMPILER: RefCell<Option<Context>> = RefCell::new(None);
+}
+
+fn precompile(templates: &[(String, String)]) -> Result<Vec<Value>, String> {
+    return COMPILER.with(|compiler| {
+        let mut compiler = compiler.borrow_mut();
+
+        if compiler.is_none() {
+            *compiler = Some(load_compiler()?);
+        }
+
+        return compiler.as_ref().unwrap().with(|ctx| {
+            let precompile_template: Function = ctx.globals().get("precompileTemplate")
+                .map_err(|error| exception_message(&ctx, error))?;
+
+            return templates.iter()
+                .map(|(module_name, source)| {
+                    let result: String = precompile_template.call((module_name.as_str(), source.as_str()))
+                        .map_err(|error| exception_message(&ctx, error))?;
+
+                    return serde_json::from_str(&result).map_err(|error| error.to_string());
+                })
+                .collect();
+        });
+    });
+}
+
+// NOTE: compiler is evaluated in sloppy mode, it assigns to undeclared globals
+fn load_compiler() -> Result<Context, String> {
+    let runtime = Runtime::new().map_err(|error| error.to_string())?;
+    let context = Context::full(&runtime).map_err(|error| error.to_string())?;
+    let script = format!("var module = {{ exports: {{}} }};\n(function (module) {{\n{}\n}})(module);\n{}", TEMPLATE_COMPILER, PRECOMPILE_SCRIPT);
+
+    let mut options = EvalOptions::default();
+
+    options.strict = false;
+    context.with(|ctx| ctx.eval_with_options::<(), _>(script, options).map_err(|error| exception_message(&ctx, error)))?;
+
+    return Ok(context);
+}
+
+fn exception_message(ctx: &Ctx, error: rquickjs::Error) -> String {
+    return match error {
+        rquickjs::Error::Exception => {
+            let exception = ctx.catch();
+
+            exception.as_exception().and_then(|exception| exception.message()).unwrap_or_else(|| format!("{:?}", exception))
+        },
+        error => error.to_string()
+    };
+}
+
+fn to_amd(module_name: &str, precompiled_template: &str) -> String {
+    return format!(
+        "define(\"{}\", [\"exports\"], function (_exports) {{ \"use strict\"; \
+        Object.defineProperty(_exports, \"__esModule\", {{ value: true }}); \
+        _exports.default = Ember.HTMLBars.template({});\n}});",
+        module_name, precompiled_template
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::error::Error;
+    use super::*;
+
+    #[test]
+    fn convert_hbs_module_from_string_works() {
+        let output = from_string("<p>{{this.title}}</p>", "frontend/src/ui/components/x/template").unwrap();
+
+        assert!(output.starts_with(
+            "define(\"frontend/src/ui/components/x/template\", [\"exports\"], function (_exports) { \"use strict\"; \
+            Object.defineProperty(_exports, \"__esModule\", { value: true }); _exports.default = Ember.HTMLBars.template({"
+        ));
+        assert!(output.contains("\"moduleName\":\"frontend/src/ui/components/x/template\""));
+        assert!(output.ends_with(");\n});"));
+    }
+
+    #[test]
+    fn convert_hbs_module_from_strings_keeps_order() {
+        let output = from_strings(&vec![
+            (String::from("frontend/src/a/template"), String::from("a")),
+            (String::from("frontend/src/b/template"), String::from("{{b}}"))
+        ]).unwrap();
+
+        assert_eq!(output.len(), 2);
+        assert!(output[0].starts_with("define(\"frontend/src/a/template\""));
+        assert!(output[1].starts_with("define(\"frontend/src/b/template\""));
+        assert_eq!(from_strings(&Vec::new()).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn convert_hbs_module_reports_errors_with_line_and_column() {
+        let unclosed_tag = from_string("<div>\n  <p>\n</div>", "frontend/src/a/template").unwrap_err();
+        let unclosed_mustache = from_string("<p>\n  {{foo\n</p>", "frontend/src/b/template").unwrap_err();
+        let mismatched_block = from_string("{{#if a}}\n  b\n{{/each}}", "frontend/src/c/template").unwrap_err();
+
+        assert_eq!((unclosed_tag.line, unclosed_tag.column), (2, 3));
+        assert_eq!(
+            unclosed_tag.to_string(),
+            "frontend/src/a/template:2:3 Closing tag `div` (on line 3) did not match last open tag `p` (on line 2)."
+        );
+        assert_eq!(unclosed_mustache.file_name, "frontend/src/b/template");
+        assert_eq!(unclosed_mustache.line, 2);
+        assert_eq!(mismatched_block.line, 1);
+        assert!(mismatched_block.message.starts_with("if doesn't match each"));
+    }
+
+    #[test]
+    fn convert_hbs_module_from_file_reports_relative_path() -> Result<(), Box<dyn Error>> {
+        let project_root = env::current_dir()?;
+
+        fs::create_dir_all("hbs-test/ui")?;
+        fs::write("hbs-test/ui/template.hbs", "<h1>{{title}}</h1>")?;
+        fs::write("hbs-test/ui/broken.hbs", "\n\n{{#each items}}")?;
+
+        let output = from_file(&project_root.join("hbs-test/ui/template.hbs"), &project_root, "frontend")?;
+        let error = from_files(
+            &vec![project_root.join("hbs-test/ui/template.hbs"), project_root.join("hbs-test/ui/broken.hbs")],
+            &project_root,
+            "frontend"
+        ).unwrap_err();
+
+        fs::remove_dir_all("hbs-test")?;
+
+        assert!(output.starts_with("define(\"frontend/hbs-test/ui/template\", [\"exports\"]"));
+        assert!(error.to_string().starts_with("hbs-test/ui/broken.hbs:"));
+
+        return Ok(());
+    }
+}
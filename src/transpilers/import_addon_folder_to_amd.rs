@@ -1,5 +1,238 @@
-use super::super::types::Config;
-// TODO: implement and add tests
-pub fn to_string(_addon_name: &str, _config: &Config) -> String {
-    return String::from("");
+use std::fs;
+use std::path::{Path, PathBuf};
+use super::super::types::{Config, MberError, TranspileError};
+use super::super::utils::recursive_file_lookup;
+use super::{convert_es_module, convert_hbs_module, module_name_from_path};
+
+// NOTE: addon_path is "{addon_name}/{sub_folder}", modules get the addon namespace: ember-data/app/x.js -> ember-data/x
+pub fn to_string(addon_path: &str, config: &Config) -> Result<String, MberError> {
+    return to_string_skipping(addon_path, config, &[]);
+}
+
+// NOTE: modules named in defined_modules are left out, they are already defined by a prebuilt vendor file
+pub fn to_string_skipping(addon_path: &str, config: &Config, defined_modules: &[String]) -> Result<String, MberError> {
+    let (addon_name, sub_folder) = split_addon_path(addon_path);
+    let addon_root = config.project_root.join("node_modules").join(&addon_name);
+
+    if !addon_root.is_dir() {
+        return Err(MberError::config(&addon_root, format!(
+            "{} addon is missing in {}, did you run npm install?",
+            addon_name, config.project_root.join("node_modules").display()
+        )));
+    }
+
+    let folder_path = addon_root.join(&sub_folder);
+
+    if !folder_path.is_dir() {
+        return Err(MberError::config(&folder_path, format!("{} folder does not exist in the {} addon", sub_folder, addon_name)));
+    }
+
+    let (template_files, module_files): (Vec<PathBuf>, Vec<PathBuf>) = recursive_file_lookup::lookup_for_extensions(
+        &folder_path,
+        vec![".js", ".ts", ".hbs"]
+    )?.into_iter()
+    .filter(|file| !defined_modules.contains(&module_name_from_path(file, &folder_path, &addon_name)))
+    .partition(|file| file.extension().map_or(false, |extension| extension == "hbs"));
+    let mut modules = module_files.iter()
+        .map(|file| -> Result<String, MberError> {
+            let module_name = module_name_from_path(file, &folder_path, &addon_name);
+            let code = fs::read_to_string(file).map_err(MberError::io("could not read", file))?;
+
+            return Ok(convert_es_module::from_string(&code, &module_name)
+                .map_err(|error| TranspileError { file_name: relative_path(file, &config.project_root), ..error })?);
+        })
+        .collect::<Result<Vec<String>, MberError>>()?;
+    let templates = template_files.iter()
+        .map(|file| -> Result<(String, String), MberError> {
+            let content = fs::read_to_string(file).map_err(MberError::io("could not read", file))?;
+
+            return Ok((module_name_from_path(file, &folder_path, &addon_name), content));
+        })
+        .collect::<Result<Vec<(String, String)>, MberError>>()?;
+    let mut compiled_templates = convert_hbs_module::from_strings(&templates).map_err(|error| {
+        let index = templates.iter().position(|(module_name, _)| module_name == &error.file_name).unwrap_or(0);
+
+        return TranspileError { file_name: relative_path(&template_files[index], &config.project_root), ..error };
+    })?;
+
+    modules.append(&mut compiled_templates);
+
+    return Ok(modules.join("\n"));
+}
+
+// NOTE: scoped packages keep their scope: @ember-data/model/addon -> (@ember-data/model, addon)
+fn split_addon_path(addon_path: &str) -> (String, String) {
+    let name_segment_count = if addon_path.starts_with('@') { 2 } else { 1 };
+    let segments = addon_path.trim_matches('/').split('/').collect::<Vec<&str>>();
+    let split_index = name_segment_count.min(segments.len());
+
+    return (segments[..split_index].join("/"), segments[split_index..].join("/"));
+}
+
+fn relative_path(file: &Path, project_root: &Path) -> String {
+    return file.strip_prefix(project_root).unwrap_or(file).to_string_lossy().to_string();
+}
+
+// NOTE: a copy of ember-app-boilerplate in the temporary directory with minimal stand-ins for the addons vendor.js and
+// the boilerplate index.js import, tests build it without npm install and never write into the fixture itself
+#[cfg(test)]
+pub fn test_project() -> std::io::Result<PathBuf> {
+    static TEST_PROJECT: std::sync::Mutex<Option<PathBuf>> = std::sync::Mutex::new(None);
+
+    let mut test_project = TEST_PROJECT.lock().unwrap_or_else(|error| error.into_inner());
+
+    if let Some(project_root) = test_project.as_ref() {
+        return Ok(project_root.clone());
+    }
+
+    let project_root = std::env::temp_dir().join("mber-test-project");
+    let addon_files = vec![
+        (
+            "node_modules/ember-data/app/initializers/ember-data.js",
+            "import setupContainer from 'ember-data/setup-container';\nimport 'ember-data';\n\nexport default {\n  name: 'ember-data',\n  initialize: setupContainer\n};\n"
+        ),
+        (
+            "node_modules/mber-head/addon/services/head-data.js",
+            "import Service from '@ember/service';\n\nexport default Service.extend({\n  title: null\n});\n"
+        ),
+        (
+            "node_modules/ember-cli-fastboot/app/instance-initializers/clear-double-boot.js",
+            "import { clearHtml } from 'ember-cli-fastboot/clear-double-boot';\n\nexport default {\n  name: 'clear-double-boot',\n  initialize: clearHtml\n};\n"
+        )
+    ];
+
+    copy_boilerplate(&project_root)?;
+
+    for (path, content) in addon_files {
+        let file_path = project_root.join(path);
+
+        fs::create_dir_all(file_path.parent().unwrap())?;
+        fs::write(file_path, content)?;
+    }
+
+    *test_project = Some(project_root.clone());
+
+    return Ok(project_root);
+}
+
+// NOTE: copies the sources of ember-app-boilerplate, build outputs and node_modules are left out
+#[cfg(test)]
+pub fn copy_boilerplate(destination: &Path) -> std::io::Result<()> {
+    let boilerplate = Path::new(env!("CARGO_MANIFEST_DIR")).join("ember-app-boilerplate");
+    let skipped_folders = ["node_modules", "tmp", "test-tmp", "dist", "dist-previous"];
+
+    fs::remove_dir_all(destination).unwrap_or_else(|_| {});
+
+    for entry in walkdir::WalkDir::new(&boilerplate).into_iter().filter_entry(|entry| {
+        return entry.depth() != 1 || !skipped_folders.contains(&entry.file_name().to_string_lossy().as_ref());
+    }) {
+        let entry = entry?;
+        let target = destination.join(entry.path().strip_prefix(&boilerplate).unwrap());
+
+        match entry.file_type().is_dir() {
+            true => fs::create_dir_all(&target)?,
+            false => { fs::copy(entry.path(), &target)?; }
+        };
+    }
+
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::error::Error;
+    use std::collections::HashMap;
+    use serde_json::json;
+    use super::*;
+    use super::super::super::types::BuildCache;
+
+    fn setup_config(project_root: &Path) -> Config {
+        return Config::new(
+            json!({ "environment": "development", "modulePrefix": "frontend" }),
+            HashMap::new(),
+            BuildCache::new(),
+            project_root.to_path_buf()
+        );
+    }
+
+    #[test]
+    fn split_addon_path_works() {
+        assert_eq!(split_addon_path("ember-data/app"), (String::from("ember-data"), String::from("app")));
+        assert_eq!(split_addon_path("@ember-data/model/addon"), (String::from("@ember-data/model"), String::from("addon")));
+        assert_eq!(
+            split_addon_path("@glimmer/resolver/dist/modules/es2017"),
+            (String::from("@glimmer/resolver"), String::from("dist/modules/es2017"))
+        );
+        assert_eq!(split_addon_path("ember-data"), (String::from("ember-data"), String::from("")));
+    }
+
+    #[test]
+    fn to_string_transpiles_addon_folder_under_addon_namespace() -> Result<(), Box<dyn Error>> {
+        let project_root = env::temp_dir().join(format!("mber-addon-folder-test-{}", std::process::id()));
+        let addon_path = project_root.join("node_modules/@scope/some-addon/addon");
+
+        fs::create_dir_all(addon_path.join("components/x-button"))?;
+        fs::write(addon_path.join("index.js"), "import Service from '@ember/service';\nexport default Service;")?;
+        fs::write(addon_path.join("components/x-button/component.js"), "import Component from '@ember/component';\nexport default Component.extend({});")?;
+        fs::write(addon_path.join("components/x-button/template.hbs"), "<button>{{yield}}</button>")?;
+
+        let output = to_string("@scope/some-addon/addon", &setup_config(&project_root));
+
+        fs::remove_dir_all(&project_root)?;
+
+        let output = output?;
+
+        assert!(output.contains("define(\"@scope/some-addon/index\", [\"exports\", \"@ember/service\"]"));
+        assert!(output.contains("define(\"@scope/some-addon/components/x-button/component\", [\"exports\", \"@ember/component\"]"));
+        assert!(output.contains("define(\"@scope/some-addon/components/x-button/template\", [\"exports\"]"));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn to_string_skipping_leaves_out_defined_modules() -> Result<(), Box<dyn Error>> {
+        let project_root = env::temp_dir().join(format!("mber-addon-folder-skip-test-{}", std::process::id()));
+        let app_path = project_root.join("node_modules/some-addon/app");
+
+        fs::create_dir_all(app_path.join("services"))?;
+        fs::write(app_path.join("services/store.js"), "export default {};")?;
+        fs::write(app_path.join("services/session.js"), "export default {};")?;
+
+        let output = to_string_skipping(
+            "some-addon/app", &setup_config(&project_root), &[String::from("some-addon/services/store")]
+        );
+
+        fs::remove_dir_all(&project_root)?;
+
+        let output = output?;
+
+        assert!(output.contains("define(\"some-addon/services/session\""));
+        assert!(!output.contains("define(\"some-addon/services/store\""));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn to_string_reports_missing_addons_and_broken_files() -> Result<(), Box<dyn Error>> {
+        let project_root = env::temp_dir().join(format!("mber-addon-folder-error-test-{}", std::process::id()));
+
+        fs::create_dir_all(project_root.join("node_modules/broken-addon/app"))?;
+        fs::write(project_root.join("node_modules/broken-addon/app/index.js"), "export default {")?;
+
+        let config = setup_config(&project_root);
+        let missing_addon_error = to_string("ember-data/app", &config).unwrap_err().to_string();
+        let missing_folder_error = to_string("broken-addon/addon", &config).unwrap_err().to_string();
+        let broken_file_error = to_string("broken-addon/app", &config).unwrap_err().to_string();
+
+        fs::remove_dir_all(&project_root)?;
+
+        assert_eq!(missing_addon_error, format!(
+            "ember-data addon is missing in {}/node_modules, did you run npm install?", project_root.display()
+        ));
+        assert_eq!(missing_folder_error, "addon folder does not exist in the broken-addon addon");
+        assert_eq!(broken_file_error, "node_modules/broken-addon/app/index.js:1:17 Unexpected end of input");
+
+        return Ok(());
+    }
 }
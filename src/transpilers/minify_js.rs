@@ -0,0 +1,1403 @@
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
+use std::sync::OnceLock;
+use super::tokenizer::{self, TokenKind};
+use super::super::types::TranspileError;
+use super::super::utils::source_map::{self, Mapping, SourceMap};
+use super::super::utils::transpile_cache;
+
+const NONE: usize = usize::MAX;
+const RESERVED_WORDS: [&str; 46] = [
+    "await", "break", "case", "catch", "class", "const", "continue", "debugger", "default", "delete", "do", "else",
+    "enum", "export", "extends", "false", "finally", "for", "function", "if", "implements", "import", "in",
+    "instanceof", "interface", "let", "new", "null", "package", "private", "protected", "public", "return", "static",
+    "super", "switch", "this", "throw", "true", "try", "typeof", "var", "void", "while", "with", "yield"
+];
+const KEPT_NAMES: [&str; 9] = ["arguments", "eval", "undefined", "NaN", "Infinity", "async", "of", "get", "set"];
+const KEYWORDS_BEFORE_OBJECT: [&str; 17] = [
+    "return", "typeof", "void", "delete", "in", "of", "instanceof", "new", "throw", "case", "yield", "await",
+    "extends", "var", "let", "const", "export"
+];
+const KEYWORDS_BEFORE_BLOCK: [&str; 4] = ["else", "try", "finally", "do"];
+const KEYWORDS_CONTINUING_STATEMENT: [&str; 12] = [
+    "typeof", "void", "delete", "new", "in", "instanceof", "case", "else", "do", "var", "const", "extends"
+];
+const RESTRICTED_KEYWORDS: [&str; 6] = ["return", "throw", "break", "continue", "yield", "async"];
+const PUNCTUATORS_STARTING_STATEMENT: [&str; 7] = ["{", "!", "~", "++", "--", "...", "@"];
+const NAME_START_CHARACTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_$";
+const NAME_CHARACTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ_$0123456789";
+
+#[derive(Debug, Clone)]
+pub(crate) struct Piece<'a> {
+    pub(crate) kind: TokenKind,
+    pub(crate) text: &'a str,
+    pub(crate) newline_before: bool,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    comments: Vec<&'a str>
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Role {
+    Other,
+    Keyword,
+    Property,
+    Shorthand,
+    Modifier,
+    Label,
+    Reference
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Frame {
+    Root,
+    Paren,
+    Bracket,
+    Template,
+    Block,
+    FunctionBody,
+    Object,
+    ClassBody
+}
+
+struct FrameState {
+    kind: Frame,
+    opener: usize,
+    ternaries: usize,
+    pending_case: bool
+}
+
+pub(crate) struct FunctionHead {
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+    pub(crate) params: Vec<usize>,
+    pub(crate) own_name: Option<usize>
+}
+
+pub(crate) struct Analysis {
+    pub(crate) roles: Vec<Role>,
+    pub(crate) functions: Vec<FunctionHead>,
+    declarations: Vec<usize>,
+    unsafe_positions: Vec<usize>,
+    can_mangle: bool
+}
+
+struct Analyzer<'a, 'b> {
+    pieces: &'b [Piece<'a>],
+    closer: &'b [usize],
+    opener: &'b [usize],
+    root: FrameState,
+    frames: Vec<FrameState>,
+    statement_colons: Vec<bool>,
+    forced_bodies: HashSet<usize>,
+    claimed_parens: HashSet<usize>,
+    pending_class: Option<usize>,
+    analysis: Analysis
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+enum NameKey<'a> {
+    Global(&'a str),
+    Binding(usize)
+}
+
+struct Scope<'a> {
+    end: usize,
+    parent: usize,
+    is_unsafe: bool,
+    bindings: HashMap<&'a str, usize>,
+    outside: HashSet<NameKey<'a>>
+}
+
+struct Binding<'a> {
+    name: &'a str,
+    scope: usize,
+    references: usize,
+    first: usize,
+    new_name: Option<String>
+}
+
+// NOTE: strips whitespace and comments, removes constant if branches and debugger statements, then renames the
+// function scoped bindings. Comments starting with /*! or containing @license or @preserve are kept
+pub fn from_string(code: &str, file_name: &str) -> Result<String, TranspileError> {
+    return transpile_cache::fetch(transpiler_id(), "", code, || minify(code, file_name).map(|(output, _)| output));
+}
+
+// NOTE: the map points into the given code, compose it with the map of the code to reach the original files
+pub fn from_string_with_source_map(code: &str, file_name: &str) -> Result<(String, SourceMap), TranspileError> {
+    let cached_output = transpile_cache::fetch(transpiler_id(), "source-map", code, || {
+        let (output, map) = minify(code, file_name)?;
+
+        return Ok(serde_json::json!({ "code": output, "map": map.to_json() }).to_string());
+    })?;
+    let cached_output: serde_json::Value = serde_json::from_str(&cached_output)
+        .map_err(|error| TranspileError::new(file_name, 1, 1, &format!("Invalid cached output: {}", error)))?;
+    let map = SourceMap::from_json(cached_output["map"].as_str().unwrap_or("{}"))
+        .map_err(|error| TranspileError::new(file_name, 1, 1, &format!("Invalid cached source map: {}", error)))?;
+
+    return Ok((cached_output["code"].as_str().unwrap_or("").to_string(), map));
+}
+
+// NOTE: cached outputs are invalidated whenever this file or the tokenizer changes
+fn transpiler_id() -> &'static str {
+    static TRANSPILER_ID: OnceLock<String> = OnceLock::new();
+
+    return TRANSPILER_ID.get_or_init(|| {
+        return transpile_cache::key("minify-js", include_str!("minify_js.rs"), include_str!("tokenizer.rs"));
+    });
+}
+
+// NOTE: used by the linter, so its identifier roles and function scopes always match the ones the renaming uses
+pub(crate) fn analyze_code<'a>(code: &'a str, file_name: &str) -> Result<(Vec<Piece<'a>>, Vec<usize>, Analysis), TranspileError> {
+    let (pieces, _) = to_pieces(code, file_name)?;
+    let (closer, opener) = match_brackets(&pieces, file_name)?;
+    let analysis = analyze(&pieces, &closer, &opener);
+
+    return Ok((pieces, closer, analysis));
+}
+
+fn minify(code: &str, file_name: &str) -> Result<(String, SourceMap), TranspileError> {
+    let (pieces, trailing_comments) = to_pieces(code, file_name)?;
+    let (closer, _) = match_brackets(&pieces, file_name)?;
+    let pieces = eliminate_dead_code(pieces, &closer);
+    let (closer, opener) = match_brackets(&pieces, file_name)?;
+    let analysis = analyze(&pieces, &closer, &opener);
+    let names = match analysis.can_mangle {
+        true => mangle(&pieces, &analysis).unwrap_or_else(|| vec![None; pieces.len()]),
+        false => vec![None; pieces.len()]
+    };
+
+    return Ok(emit(&pieces, &analysis.roles, &names, &trailing_comments, file_name));
+}
+
+fn to_pieces<'a>(code: &'a str, file_name: &str) -> Result<(Vec<Piece<'a>>, Vec<&'a str>), TranspileError> {
+    let tokens = tokenizer::tokenize(code, file_name)?;
+    let mut pieces = Vec::with_capacity(tokens.len() / 2);
+    let mut comments = Vec::new();
+    let mut newline_before = false;
+
+    for token in tokens.iter() {
+        let text = token.text(code);
+
+        match token.kind {
+            TokenKind::LineTerminator => { newline_before = true; },
+            TokenKind::Whitespace | TokenKind::LineComment => {},
+            TokenKind::BlockComment => {
+                if text.starts_with("/*!") || text.contains("@license") || text.contains("@preserve") {
+                    comments.push(text);
+                }
+
+                if text.contains(|character: char| character == '\n' || character == '\r' || character == '\u{2028}' || character == '\u{2029}') {
+                    newline_before = true;
+                }
+            },
+            _ => {
+                pieces.push(Piece {
+                    kind: token.kind,
+                    text: text,
+                    newline_before: newline_before,
+                    line: token.line,
+                    column: token.column,
+                    comments: comments.drain(..).collect()
+                });
+                newline_before = false;
+            }
+        }
+    }
+
+    return Ok((pieces, comments));
+}
+
+fn is_opener(piece: &Piece) -> bool {
+    return match piece.kind {
+        TokenKind::Punctuator => piece.text == "(" || piece.text == "[" || piece.text == "{",
+        TokenKind::Template => piece.text.ends_with("${"),
+        _ => false
+    };
+}
+
+fn is_closer(piece: &Piece) -> bool {
+    return match piece.kind {
+        TokenKind::Punctuator => piece.text == ")" || piece.text == "]" || piece.text == "}",
+        TokenKind::Template => piece.text.starts_with('}'),
+        _ => false
+    };
+}
+
+fn is_punctuator(piece: &Piece, text: &str) -> bool {
+    return piece.kind == TokenKind::Punctuator && piece.text == text;
+}
+
+fn text_at<'a>(pieces: &[Piece<'a>], index: usize) -> &'a str {
+    return pieces.get(index).map(|piece| piece.text).unwrap_or("");
+}
+
+// NOTE: template chunks like `}...${` close one substitution and open the next one
+fn match_brackets(pieces: &[Piece], file_name: &str) -> Result<(Vec<usize>, Vec<usize>), TranspileError> {
+    let mut closer = vec![NONE; pieces.len()];
+    let mut opener = vec![NONE; pieces.len()];
+    let mut stack: Vec<usize> = Vec::new();
+
+    for (index, piece) in pieces.iter().enumerate() {
+        if is_closer(piece) {
+            let open_index = stack.pop().ok_or_else(|| {
+                return TranspileError::new(file_name, piece.line, piece.column, &format!("Unexpected '{}'", piece.text));
+            })?;
+            let expected_closer = match pieces[open_index].text {
+                "(" => ")",
+                "[" => "]",
+                _ => "}"
+            };
+
+            if !piece.text.starts_with(expected_closer) || (pieces[open_index].kind == TokenKind::Template) != (piece.kind == TokenKind::Template) {
+                return Err(TranspileError::new(file_name, piece.line, piece.column, &format!("Unexpected '{}'", piece.text)));
+            }
+
+            closer[open_index] = index;
+            opener[index] = open_index;
+        }
+
+        if is_opener(piece) {
+            stack.push(index);
+        }
+    }
+
+    return match stack.pop() {
+        Some(index) => Err(TranspileError::new(
+            file_name, pieces[index].line, pieces[index].column, &format!("Unclosed '{}'", pieces[index].text)
+        )),
+        None => Ok((closer, opener))
+    };
+}
+
+fn eliminate_dead_code<'a>(pieces: Vec<Piece<'a>>, closer: &[usize]) -> Vec<Piece<'a>> {
+    let mut removed = vec![false; pieces.len()];
+    let mut replaced_with_semicolon = vec![false; pieces.len()];
+    let mut index = 0;
+
+    while index < pieces.len() {
+        let piece = &pieces[index];
+        let previous_text = if index > 0 { pieces[index - 1].text } else { "" };
+
+        if removed[index] || piece.kind != TokenKind::Identifier || previous_text == "." || previous_text == "?." {
+            index += 1;
+
+            continue;
+        }
+
+        if piece.text == "debugger" && !["(", ":", "="].contains(&text_at(&pieces, index + 1)) {
+            match text_at(&pieces, index + 1) == ";" {
+                true => { removed[index] = true; },
+                false => { replaced_with_semicolon[index] = true; }
+            }
+        } else if piece.text == "if" && text_at(&pieces, index + 1) == "(" && ["", ";", "{", "}", "else"].contains(&previous_text) {
+            if let Some(next_index) = eliminate_if_statement(&pieces, closer, index, &mut removed, &mut replaced_with_semicolon) {
+                index = next_index;
+
+                continue;
+            }
+        }
+
+        index += 1;
+    }
+
+    return pieces.into_iter().enumerate()
+        .filter(|(index, _)| !removed[*index])
+        .map(|(index, piece)| match replaced_with_semicolon[index] {
+            true => Piece { kind: TokenKind::Punctuator, text: ";", ..piece },
+            false => piece
+        })
+        .collect();
+}
+
+// NOTE: only handles braced branches so the kept branch stays a block, branches declaring var or function are
+// never removed since their declarations are hoisted
+fn eliminate_if_statement(
+    pieces: &[Piece], closer: &[usize], index: usize, removed: &mut Vec<bool>, replaced_with_semicolon: &mut Vec<bool>
+) -> Option<usize> {
+    let condition_end = closer[index + 1];
+    let condition = constant_condition(pieces, closer, index + 2, condition_end)?;
+    let consequent_start = condition_end + 1;
+
+    if text_at(pieces, consequent_start) != "{" {
+        return None;
+    }
+
+    let consequent_end = closer[consequent_start];
+    let alternate_start = match text_at(pieces, consequent_end + 1) {
+        "else" => Some(consequent_end + 2),
+        _ => None
+    };
+    let alternate_end = match alternate_start {
+        Some(start) => Some(statement_end(pieces, closer, start)?),
+        None => None
+    };
+    let (kept_start, kept_end) = match (condition, alternate_start, alternate_end) {
+        (true, _, _) => (consequent_start, consequent_end),
+        (false, Some(start), Some(end)) => (start, end),
+        (false, _, _) => (NONE, NONE)
+    };
+    let statement_end = alternate_end.unwrap_or(consequent_end);
+    let declares_hoisted_names = (index..=statement_end)
+        .filter(|position| kept_start == NONE || *position < kept_start || *position > kept_end)
+        .any(|position| pieces[position].kind == TokenKind::Identifier && (pieces[position].text == "var" || pieces[position].text == "function"));
+
+    if declares_hoisted_names {
+        return None;
+    }
+
+    for position in index..=statement_end {
+        if kept_start == NONE || position < kept_start || position > kept_end {
+            removed[position] = true;
+        }
+    }
+
+    if kept_start == NONE {
+        let previous_text = if index > 0 { pieces[index - 1].text } else { "" };
+
+        if previous_text == "}" || previous_text == "else" {
+            removed[index] = false;
+            replaced_with_semicolon[index] = true;
+        }
+
+        return Some(statement_end + 1);
+    }
+
+    return Some(kept_start);
+}
+
+fn statement_end(pieces: &[Piece], closer: &[usize], start: usize) -> Option<usize> {
+    return match text_at(pieces, start) {
+        "{" => Some(closer[start]),
+        "if" if text_at(pieces, start + 1) == "(" => {
+            let consequent_start = closer[start + 1] + 1;
+
+            if text_at(pieces, consequent_start) != "{" {
+                return None;
+            }
+
+            let consequent_end = closer[consequent_start];
+
+            match text_at(pieces, consequent_end + 1) {
+                "else" => statement_end(pieces, closer, consequent_end + 2),
+                _ => Some(consequent_end)
+            }
+        },
+        _ => None
+    };
+}
+
+// NOTE: `false && ...` chains are only falsy when nothing at their top level could turn them truthy
+fn constant_condition(pieces: &[Piece], closer: &[usize], start: usize, end: usize) -> Option<bool> {
+    if start > end || text_at(pieces, start - 1) != "(" {
+        return None;
+    }
+
+    let texts = pieces[start..end].iter().map(|piece| piece.text).collect::<Vec<&str>>();
+
+    match texts.as_slice() {
+        ["true"] | ["1"] | ["!", "0"] => return Some(true),
+        ["false"] | ["0"] | ["!", "1"] => return Some(false),
+        _ => {}
+    };
+
+    let falsy_length = match texts.as_slice() {
+        ["false", "&&", ..] | ["0", "&&", ..] => 1,
+        ["!", "1", "&&", ..] => 2,
+        _ => return None
+    };
+    let mut index = start + falsy_length;
+
+    while index < end {
+        let piece = &pieces[index];
+
+        if piece.kind == TokenKind::Punctuator && (["||", "??", "?", ","].contains(&piece.text) || is_assignment(piece.text)) {
+            return None;
+        } else if is_opener(piece) {
+            index = closer[index];
+        }
+
+        index += 1;
+    }
+
+    return Some(false);
+}
+
+fn is_assignment(text: &str) -> bool {
+    return text.ends_with('=') && !["==", "===", "!=", "!==", "<=", ">="].contains(&text);
+}
+
+fn analyze(pieces: &[Piece], closer: &[usize], opener: &[usize]) -> Analysis {
+    let mut analyzer = Analyzer {
+        pieces: pieces,
+        closer: closer,
+        opener: opener,
+        root: FrameState { kind: Frame::Root, opener: NONE, ternaries: 0, pending_case: false },
+        frames: Vec::new(),
+        statement_colons: vec![false; pieces.len()],
+        forced_bodies: HashSet::new(),
+        claimed_parens: HashSet::new(),
+        pending_class: None,
+        analysis: Analysis {
+            roles: vec![Role::Other; pieces.len()],
+            functions: Vec::new(),
+            declarations: Vec::new(),
+            unsafe_positions: Vec::new(),
+            can_mangle: true
+        }
+    };
+
+    analyzer.run();
+
+    return analyzer.analysis;
+}
+
+impl<'a, 'b> Analyzer<'a, 'b> {
+    fn run(&mut self) {
+        for index in 0..self.pieces.len() {
+            let piece = &self.pieces[index];
+
+            match piece.kind {
+                TokenKind::Identifier => self.visit_identifier(index),
+                TokenKind::Punctuator => self.visit_punctuator(index),
+                TokenKind::Template => {
+                    if piece.text.starts_with('}') {
+                        self.pop_frame();
+                    }
+
+                    if piece.text.ends_with("${") {
+                        self.push_frame(Frame::Template, index);
+                    }
+                },
+                _ => {}
+            }
+        }
+    }
+
+    // NOTE: unbalanced closers never pop the root frame, so the top frame always exists
+    fn top(&self) -> &FrameState {
+        return self.frames.last().unwrap_or(&self.root);
+    }
+
+    fn top_mut(&mut self) -> &mut FrameState {
+        return match self.frames.last_mut() {
+            Some(frame) => frame,
+            None => &mut self.root
+        };
+    }
+
+    fn push_frame(&mut self, kind: Frame, opener: usize) {
+        self.frames.push(FrameState { kind: kind, opener: opener, ternaries: 0, pending_case: false });
+    }
+
+    fn pop_frame(&mut self) {
+        self.frames.pop();
+    }
+
+    fn visit_punctuator(&mut self, index: usize) {
+        match self.pieces[index].text {
+            "(" => {
+                let is_method = !self.claimed_parens.contains(&index) &&
+                    (self.top().kind == Frame::Object || self.top().kind == Frame::ClassBody) &&
+                    text_at(self.pieces, self.closer[index] + 1) == "{";
+
+                if is_method {
+                    self.add_function(index, index, None);
+                }
+
+                self.push_frame(Frame::Paren, index);
+            },
+            "[" => self.push_frame(Frame::Bracket, index),
+            "{" => {
+                let kind = self.brace_kind(index);
+
+                self.push_frame(kind, index);
+            },
+            ")" | "]" | "}" => self.pop_frame(),
+            "?" => { self.top_mut().ternaries += 1; },
+            ":" => {
+                let previous_is_label = index > 0 && (self.analysis.roles[index - 1] == Role::Label ||
+                    (self.analysis.roles[index - 1] == Role::Keyword && self.pieces[index - 1].text == "default"));
+                let frame = self.top_mut();
+
+                if frame.ternaries > 0 {
+                    frame.ternaries -= 1;
+                } else if frame.pending_case {
+                    frame.pending_case = false;
+                    self.statement_colons[index] = true;
+                } else if previous_is_label {
+                    self.statement_colons[index] = true;
+                }
+            },
+            "=>" => self.add_arrow(index),
+            _ => {}
+        }
+    }
+
+    fn brace_kind(&mut self, index: usize) -> Frame {
+        if self.forced_bodies.contains(&index) {
+            return Frame::FunctionBody;
+        } else if self.pending_class == Some(self.frames.len()) {
+            self.pending_class = None;
+
+            return Frame::ClassBody;
+        } else if index == 0 {
+            return Frame::Block;
+        }
+
+        let previous = &self.pieces[index - 1];
+
+        return match previous.kind {
+            TokenKind::Punctuator => match previous.text {
+                ")" | "]" | "}" | ";" | "{" => Frame::Block,
+                ":" if self.statement_colons[index - 1] => Frame::Block,
+                _ => Frame::Object
+            },
+            TokenKind::Template => Frame::Object,
+            TokenKind::Identifier => match self.analysis.roles[index - 1] {
+                Role::Keyword if KEYWORDS_BEFORE_BLOCK.contains(&previous.text) => Frame::Block,
+                Role::Keyword if KEYWORDS_BEFORE_OBJECT.contains(&previous.text) => Frame::Object,
+                Role::Reference if previous.text == "of" => Frame::Object,
+                _ => Frame::Block
+            },
+            _ => Frame::Block
+        };
+    }
+
+    fn visit_identifier(&mut self, index: usize) {
+        let role = self.identifier_role(index);
+        let text = self.pieces[index].text;
+        let next_text = text_at(self.pieces, index + 1);
+
+        self.analysis.roles[index] = role;
+
+        if text.contains('\\') {
+            self.analysis.can_mangle = false;
+        } else if role == Role::Reference && text == "eval" && next_text == "(" {
+            self.analysis.unsafe_positions.push(index);
+        } else if role == Role::Keyword {
+            match text {
+                "function" => self.visit_function(index),
+                "class" => self.visit_class(index),
+                "var" | "const" => self.visit_declaration(index),
+                "let" if next_text == "[" || next_text == "{" || self.pieces.get(index + 1).map_or(false, |next| next.kind == TokenKind::Identifier) => {
+                    self.visit_declaration(index);
+                },
+                "case" => { self.top_mut().pending_case = true; },
+                "with" => self.analysis.unsafe_positions.push(index),
+                "import" if next_text != "(" && next_text != "." => { self.analysis.can_mangle = false; },
+                "export" => { self.analysis.can_mangle = false; },
+                _ => {}
+            }
+        }
+    }
+
+    fn identifier_role(&self, index: usize) -> Role {
+        let piece = &self.pieces[index];
+        let previous = if index > 0 { self.pieces.get(index - 1) } else { None };
+        let next = self.pieces.get(index + 1);
+        let next_text = next.map(|next| next.text).unwrap_or("");
+        let frame = self.top();
+
+        if piece.text.starts_with('#') || previous.map_or(false, |previous| is_punctuator(previous, ".") || is_punctuator(previous, "?.")) {
+            return Role::Property;
+        } else if frame.kind == Frame::Object && self.is_object_key_position(index) {
+            return match piece.text {
+                "get" | "set" | "async" if self.is_member_name(index + 1) => Role::Modifier,
+                _ if next_text == "," || next_text == "}" || next_text == "=" => Role::Shorthand,
+                _ => Role::Property
+            };
+        } else if frame.kind == Frame::ClassBody && self.is_class_key_position(index) {
+            return match piece.text {
+                "get" | "set" | "static" | "async" if self.is_member_name(index + 1) => Role::Modifier,
+                _ => Role::Property
+            };
+        } else if piece.text == "async" && next.map_or(false, |next| !next.newline_before) && self.is_async_keyword(index) {
+            return Role::Keyword;
+        } else if RESERVED_WORDS.contains(&piece.text) {
+            return Role::Keyword;
+        }
+
+        let is_label_declaration = next_text == ":" && frame.ternaries == 0 && !frame.pending_case &&
+            [Frame::Root, Frame::Block, Frame::FunctionBody].contains(&frame.kind) && self.is_statement_start(index);
+        let is_label_reference = previous.map_or(false, |previous| previous.text == "break" || previous.text == "continue") &&
+            !piece.newline_before && self.analysis.roles[index - 1] == Role::Keyword;
+
+        return match is_label_declaration || is_label_reference {
+            true => Role::Label,
+            false => Role::Reference
+        };
+    }
+
+    fn is_member_name(&self, index: usize) -> bool {
+        return match self.pieces.get(index) {
+            Some(piece) if !piece.newline_before => match piece.kind {
+                TokenKind::Identifier | TokenKind::String | TokenKind::Number => true,
+                TokenKind::Punctuator => piece.text == "[" || piece.text == "*",
+                _ => false
+            },
+            _ => false
+        };
+    }
+
+    fn is_async_keyword(&self, index: usize) -> bool {
+        let next = &self.pieces[index + 1];
+
+        return match next.kind {
+            TokenKind::Identifier => next.text == "function" || text_at(self.pieces, index + 2) == "=>",
+            TokenKind::Punctuator => next.text == "(" && text_at(self.pieces, self.closer[index + 1] + 1) == "=>",
+            _ => false
+        };
+    }
+
+    fn is_object_key_position(&self, index: usize) -> bool {
+        let previous_index = index.wrapping_sub(1);
+
+        return match self.pieces.get(previous_index) {
+            None => false,
+            Some(previous) if is_punctuator(previous, "*") => self.is_object_key_position(previous_index),
+            Some(previous) => previous_index == self.top().opener || is_punctuator(previous, ",") ||
+                self.analysis.roles[previous_index] == Role::Modifier
+        };
+    }
+
+    fn is_class_key_position(&self, index: usize) -> bool {
+        let previous_index = index.wrapping_sub(1);
+
+        return match self.pieces.get(previous_index) {
+            None => false,
+            Some(previous) if is_punctuator(previous, "*") => self.is_class_key_position(previous_index),
+            Some(previous) => previous_index == self.top().opener || is_punctuator(previous, ";") ||
+                is_punctuator(previous, "}") || self.analysis.roles[previous_index] == Role::Modifier ||
+                (self.pieces[index].newline_before && !continues_statement(self.pieces, index))
+        };
+    }
+
+    fn is_statement_start(&self, index: usize) -> bool {
+        if index == 0 {
+            return true;
+        }
+
+        let previous = &self.pieces[index - 1];
+
+        return match previous.kind {
+            TokenKind::Punctuator if [";", "{", "}"].contains(&previous.text) => true,
+            TokenKind::Punctuator if previous.text == ":" => self.statement_colons[index - 1],
+            _ => self.pieces[index].newline_before && !continues_statement(self.pieces, index)
+        };
+    }
+
+    // NOTE: Annex B lets sloppy code declare functions as the body of if, else and loops without braces
+    fn is_statement_body(&self, index: usize) -> bool {
+        if index == 0 {
+            return false;
+        }
+
+        let previous = &self.pieces[index - 1];
+
+        return match previous.kind {
+            TokenKind::Punctuator if previous.text == ")" => {
+                let keyword_index = self.opener[index - 1].wrapping_sub(1);
+
+                self.pieces.get(keyword_index).map_or(false, |keyword| {
+                    return ["if", "while", "for", "with"].contains(&keyword.text) && self.analysis.roles[keyword_index] == Role::Keyword;
+                })
+            },
+            TokenKind::Identifier => ["else", "do"].contains(&previous.text) && self.analysis.roles[index - 1] == Role::Keyword,
+            _ => false
+        };
+    }
+
+    fn visit_function(&mut self, index: usize) {
+        let statement_index = match index > 0 && self.pieces[index - 1].text == "async" && self.analysis.roles[index - 1] == Role::Keyword {
+            true => index - 1,
+            false => index
+        };
+        let is_declaration = self.is_statement_start(statement_index) || self.is_statement_body(statement_index);
+        let mut cursor = index + 1;
+
+        if text_at(self.pieces, cursor) == "*" {
+            cursor += 1;
+        }
+
+        let name = match self.pieces.get(cursor) {
+            Some(piece) if piece.kind == TokenKind::Identifier => {
+                cursor += 1;
+
+                Some(cursor - 1)
+            },
+            _ => None
+        };
+
+        if text_at(self.pieces, cursor) != "(" {
+            self.analysis.can_mangle = false;
+
+            return;
+        }
+
+        match (is_declaration, name) {
+            (true, Some(name)) => {
+                if [Frame::Root, Frame::FunctionBody, Frame::Block].contains(&self.top().kind) {
+                    self.analysis.declarations.push(name);
+                }
+
+                self.add_function(cursor, cursor, None);
+            },
+            (false, Some(name)) => self.add_function(cursor, name, Some(name)),
+            (_, None) => self.add_function(cursor, cursor, None)
+        };
+    }
+
+    fn visit_class(&mut self, index: usize) {
+        self.pending_class = Some(self.frames.len());
+
+        match self.pieces.get(index + 1) {
+            Some(next) if next.kind == TokenKind::Identifier && next.text != "extends" => {
+                let is_top_level = self.top().kind == Frame::Root || self.top().kind == Frame::FunctionBody;
+
+                if is_top_level && self.is_statement_start(index) {
+                    self.analysis.declarations.push(index + 1);
+                }
+            },
+            _ => {}
+        };
+    }
+
+    // NOTE: let, const and class declarations inside blocks are not bound, they resolve like references so all
+    // the names they share in a function get renamed together
+    fn visit_declaration(&mut self, index: usize) {
+        let is_function_level = self.pieces[index].text == "var" ||
+            self.top().kind == Frame::Root || self.top().kind == Frame::FunctionBody;
+        let mut names = Vec::new();
+        let mut cursor = index + 1;
+
+        loop {
+            cursor = match pattern_element(self.pieces, self.closer, cursor, &mut names) {
+                Some(next) => next,
+                None => {
+                    self.analysis.can_mangle = false;
+
+                    return;
+                }
+            };
+
+            if text_at(self.pieces, cursor) == "=" {
+                cursor = expression_end(self.pieces, self.closer, cursor + 1);
+            }
+
+            if text_at(self.pieces, cursor) != "," {
+                break;
+            }
+
+            cursor += 1;
+        }
+
+        if is_function_level {
+            self.analysis.declarations.append(&mut names);
+        }
+    }
+
+    fn add_function(&mut self, params_start: usize, start: usize, own_name: Option<usize>) {
+        let params_end = self.closer[params_start];
+        let body_start = params_end + 1;
+        let mut params = Vec::new();
+
+        if text_at(self.pieces, body_start) != "{" || !pattern_list(self.pieces, self.closer, params_start + 1, params_end, &mut params) {
+            self.analysis.can_mangle = false;
+
+            return;
+        }
+
+        self.claimed_parens.insert(params_start);
+        self.forced_bodies.insert(body_start);
+        self.analysis.functions.push(FunctionHead { start: start, end: self.closer[body_start], params: params, own_name: own_name });
+    }
+
+    fn add_arrow(&mut self, index: usize) {
+        let previous_index = index.wrapping_sub(1);
+        let mut params = Vec::new();
+        let start = match self.pieces.get(previous_index) {
+            Some(previous) if is_punctuator(previous, ")") => {
+                let params_start = self.opener[previous_index];
+
+                if !pattern_list(self.pieces, self.closer, params_start + 1, previous_index, &mut params) {
+                    self.analysis.can_mangle = false;
+
+                    return;
+                }
+
+                params_start
+            },
+            Some(previous) if previous.kind == TokenKind::Identifier => {
+                params.push(previous_index);
+
+                previous_index
+            },
+            _ => {
+                self.analysis.can_mangle = false;
+
+                return;
+            }
+        };
+        let end = match text_at(self.pieces, index + 1) {
+            "{" => {
+                self.forced_bodies.insert(index + 1);
+
+                self.closer[index + 1]
+            },
+            _ => expression_end(self.pieces, self.closer, index + 1) - 1
+        };
+
+        self.analysis.functions.push(FunctionHead { start: start, end: end, params: params, own_name: None });
+    }
+}
+
+fn pattern_list(pieces: &[Piece], closer: &[usize], start: usize, end: usize, names: &mut Vec<usize>) -> bool {
+    let mut index = start;
+
+    while index < end {
+        if is_punctuator(&pieces[index], ",") {
+            index += 1;
+
+            continue;
+        } else if is_punctuator(&pieces[index], "...") {
+            index += 1;
+        }
+
+        index = match pattern_element(pieces, closer, index, names) {
+            Some(next) if next <= end => next,
+            _ => return false
+        };
+
+        if is_punctuator(&pieces[index], "=") {
+            index = skip_to_comma(pieces, closer, index + 1, end);
+        }
+
+        if index < end && !is_punctuator(&pieces[index], ",") {
+            return false;
+        }
+    }
+
+    return true;
+}
+
+pub(crate) fn pattern_element(pieces: &[Piece], closer: &[usize], index: usize, names: &mut Vec<usize>) -> Option<usize> {
+    let piece = pieces.get(index)?;
+
+    return match piece.kind {
+        TokenKind::Identifier => {
+            names.push(index);
+
+            Some(index + 1)
+        },
+        TokenKind::Punctuator if piece.text == "[" => match pattern_list(pieces, closer, index + 1, closer[index], names) {
+            true => Some(closer[index] + 1),
+            false => None
+        },
+        TokenKind::Punctuator if piece.text == "{" => match object_pattern(pieces, closer, index + 1, closer[index], names) {
+            true => Some(closer[index] + 1),
+            false => None
+        },
+        _ => None
+    };
+}
+
+fn object_pattern(pieces: &[Piece], closer: &[usize], start: usize, end: usize, names: &mut Vec<usize>) -> bool {
+    let mut index = start;
+
+    while index < end {
+        let piece = &pieces[index];
+
+        if is_punctuator(piece, ",") {
+            index += 1;
+
+            continue;
+        } else if is_punctuator(piece, "...") {
+            index = match pattern_element(pieces, closer, index + 1, names) {
+                Some(next) => next,
+                None => return false
+            };
+        } else {
+            let key_end = match piece.kind {
+                TokenKind::Punctuator if piece.text == "[" => closer[index] + 1,
+                TokenKind::Identifier | TokenKind::String | TokenKind::Number => index + 1,
+                _ => return false
+            };
+
+            index = match text_at(pieces, key_end) {
+                ":" => match pattern_element(pieces, closer, key_end + 1, names) {
+                    Some(next) => next,
+                    None => return false
+                },
+                _ if piece.kind == TokenKind::Identifier => {
+                    names.push(index);
+
+                    key_end
+                },
+                _ => return false
+            };
+
+            if index < end && is_punctuator(&pieces[index], "=") {
+                index = skip_to_comma(pieces, closer, index + 1, end);
+            }
+        }
+
+        if index < end && !is_punctuator(&pieces[index], ",") {
+            return false;
+        }
+    }
+
+    return true;
+}
+
+fn skip_to_comma(pieces: &[Piece], closer: &[usize], start: usize, end: usize) -> usize {
+    let mut index = start;
+
+    while index < end && !is_punctuator(&pieces[index], ",") {
+        if is_opener(&pieces[index]) {
+            index = skip_group(pieces, closer, index);
+        }
+
+        index += 1;
+    }
+
+    return index;
+}
+
+// NOTE: returns the closer of a bracket group, following template chunks until the closing backtick chunk
+fn skip_group(pieces: &[Piece], closer: &[usize], index: usize) -> usize {
+    let mut index = closer[index];
+
+    while is_opener(&pieces[index]) {
+        index = closer[index];
+    }
+
+    return index;
+}
+
+// NOTE: returns the exclusive end of an expression without braces like arrow bodies or initializers
+pub(crate) fn expression_end(pieces: &[Piece], closer: &[usize], start: usize) -> usize {
+    let mut ternaries = 0;
+    let mut index = start;
+
+    while index < pieces.len() {
+        let piece = &pieces[index];
+
+        if (index > start && piece.newline_before && keeps_newline(pieces, index)) || is_closer(piece) {
+            break;
+        } else if piece.kind == TokenKind::Punctuator {
+            match piece.text {
+                "," | ";" => break,
+                "?" => { ternaries += 1; },
+                ":" if ternaries == 0 => break,
+                ":" => { ternaries -= 1; },
+                _ => {}
+            };
+        }
+
+        if is_opener(piece) {
+            index = skip_group(pieces, closer, index);
+        }
+
+        index += 1;
+    }
+
+    return index;
+}
+
+fn is_keyword_at(pieces: &[Piece], index: usize, keywords: &[&str]) -> bool {
+    let piece = &pieces[index];
+
+    return piece.kind == TokenKind::Identifier && keywords.contains(&piece.text) &&
+        !(index > 0 && (is_punctuator(&pieces[index - 1], ".") || is_punctuator(&pieces[index - 1], "?.")));
+}
+
+// NOTE: true when the token before index can not end a statement, so a line break after it is never a semicolon
+fn continues_statement(pieces: &[Piece], index: usize) -> bool {
+    let previous = &pieces[index - 1];
+
+    return match previous.kind {
+        TokenKind::Punctuator => previous.text != ")" && previous.text != "]" && previous.text != "}",
+        TokenKind::Template => previous.text.ends_with("${"),
+        TokenKind::Identifier => is_keyword_at(pieces, index - 1, &KEYWORDS_CONTINUING_STATEMENT),
+        _ => false
+    };
+}
+
+// NOTE: a line break is kept wherever automatic semicolon insertion could depend on it
+fn keeps_newline(pieces: &[Piece], index: usize) -> bool {
+    let previous = &pieces[index - 1];
+    let next = &pieces[index];
+
+    if (previous.kind == TokenKind::Identifier && RESTRICTED_KEYWORDS.contains(&previous.text)) || is_punctuator(next, "++") || is_punctuator(next, "--") {
+        return true;
+    } else if is_punctuator(previous, "++") || is_punctuator(previous, "--") {
+        return !(next.kind == TokenKind::Punctuator && [")", "]", "}", ",", ";"].contains(&next.text));
+    } else if continues_statement(pieces, index) {
+        return false;
+    }
+
+    return match next.kind {
+        TokenKind::Punctuator => PUNCTUATORS_STARTING_STATEMENT.contains(&next.text),
+        TokenKind::Template => false,
+        TokenKind::Identifier => !(next.text == "in" || next.text == "instanceof"),
+        _ => true
+    };
+}
+
+fn is_word_character(character: char) -> bool {
+    return character.is_alphanumeric() || character == '$' || character == '_' || character == '\\' ||
+        character == '#' || character == '\u{200c}' || character == '\u{200d}';
+}
+
+fn needs_space(previous_text: &str, previous_kind: TokenKind, next_text: &str) -> bool {
+    let (last, first) = match (previous_text.chars().next_back(), next_text.chars().next()) {
+        (Some(last), Some(first)) => (last, first),
+        _ => return false
+    };
+
+    return (is_word_character(last) && is_word_character(first)) ||
+        (previous_kind == TokenKind::RegExp && is_word_character(first)) ||
+        (previous_kind == TokenKind::Number && first == '.') ||
+        (last == '+' && first == '+') || (last == '-' && first == '-') ||
+        (last == '/' && (first == '/' || first == '*')) ||
+        (last == '<' && first == '!') || (previous_text.ends_with("--") && first == '>');
+}
+
+fn is_mangleable(name: &str) -> bool {
+    return !RESERVED_WORDS.contains(&name) && !KEPT_NAMES.contains(&name) && !name.contains('\\') && !name.starts_with('#');
+}
+
+fn generate_name(index: usize) -> String {
+    let mut name = String::new();
+    let mut remaining = index / NAME_START_CHARACTERS.len();
+
+    name.push(NAME_START_CHARACTERS[index % NAME_START_CHARACTERS.len()] as char);
+
+    while remaining > 0 {
+        remaining -= 1;
+        name.push(NAME_CHARACTERS[remaining % NAME_CHARACTERS.len()] as char);
+        remaining /= NAME_CHARACTERS.len();
+    }
+
+    return name;
+}
+
+// NOTE: returns None when the function ranges do not nest, the code then only gets its whitespace removed
+fn mangle<'a>(pieces: &[Piece<'a>], analysis: &Analysis) -> Option<Vec<Option<String>>> {
+    let mut functions = analysis.functions.iter().collect::<Vec<&FunctionHead>>();
+
+    functions.sort_by_key(|function| (function.start, Reverse(function.end)));
+
+    let mut scopes = vec![Scope { end: NONE, parent: 0, is_unsafe: false, bindings: HashMap::new(), outside: HashSet::new() }];
+    let mut stack = vec![0];
+    let mut scope_of = vec![0; pieces.len()];
+
+    for function in functions.iter() {
+        while scopes[*stack.last()?].end < function.start {
+            stack.pop();
+        }
+
+        let parent = *stack.last()?;
+
+        if scopes[parent].end < function.end {
+            return None;
+        }
+
+        scopes.push(Scope { end: function.end, parent: parent, is_unsafe: false, bindings: HashMap::new(), outside: HashSet::new() });
+        stack.push(scopes.len() - 1);
+
+        for index in function.start..=function.end {
+            scope_of[index] = scopes.len() - 1;
+        }
+    }
+
+    let mut bindings: Vec<Binding<'a>> = Vec::new();
+    let mut declare = |scopes: &mut Vec<Scope<'a>>, scope: usize, index: usize| {
+        let name = pieces[index].text;
+
+        if scope == 0 || !is_mangleable(name) || scopes[scope].bindings.contains_key(name) {
+            return;
+        }
+
+        scopes[scope].bindings.insert(name, bindings.len());
+        bindings.push(Binding { name: name, scope: scope, references: 0, first: index, new_name: None });
+    };
+
+    for (position, function) in functions.iter().enumerate() {
+        for param in function.params.iter().chain(function.own_name.iter()) {
+            declare(&mut scopes, position + 1, *param);
+        }
+    }
+
+    for declaration in analysis.declarations.iter() {
+        declare(&mut scopes, scope_of[*declaration], *declaration);
+    }
+
+    let mut resolved = vec![NONE; pieces.len()];
+
+    for (index, piece) in pieces.iter().enumerate() {
+        let role = analysis.roles[index];
+
+        if (role != Role::Reference && role != Role::Shorthand) || !is_mangleable(piece.text) {
+            continue;
+        }
+
+        let mut scope = scope_of[index];
+        let target = loop {
+            if let Some(binding) = scopes[scope].bindings.get(piece.text) {
+                break Some(*binding);
+            } else if scope == 0 {
+                break None;
+            }
+
+            scope = scopes[scope].parent;
+        };
+        let (key, target_scope) = match target {
+            Some(binding) => (NameKey::Binding(binding), bindings[binding].scope),
+            None => (NameKey::Global(piece.text), 0)
+        };
+        let mut scope = scope_of[index];
+
+        while scope != target_scope {
+            scopes[scope].outside.insert(key);
+            scope = scopes[scope].parent;
+        }
+
+        if let Some(binding) = target {
+            bindings[binding].references += 1;
+            resolved[index] = binding;
+        }
+    }
+
+    for position in analysis.unsafe_positions.iter() {
+        let mut scope = scope_of[*position];
+
+        while scope != 0 && !scopes[scope].is_unsafe {
+            scopes[scope].is_unsafe = true;
+            scope = scopes[scope].parent;
+        }
+    }
+
+    for scope in 1..scopes.len() {
+        let mut scope_bindings = scopes[scope].bindings.values().cloned().collect::<Vec<usize>>();
+
+        if scopes[scope].is_unsafe {
+            for binding in scope_bindings {
+                bindings[binding].new_name = Some(bindings[binding].name.to_string());
+            }
+
+            continue;
+        }
+
+        let mut used_names = scopes[scope].outside.iter().map(|key| match key {
+            NameKey::Global(name) => name.to_string(),
+            NameKey::Binding(binding) => bindings[*binding].new_name.clone().unwrap_or_else(|| bindings[*binding].name.to_string())
+        }).collect::<HashSet<String>>();
+        let mut counter = 0;
+
+        scope_bindings.sort_by_key(|binding| (Reverse(bindings[*binding].references), bindings[*binding].first));
+
+        for binding in scope_bindings {
+            let name = loop {
+                let candidate = generate_name(counter);
+
+                counter += 1;
+
+                if !used_names.contains(&candidate) && is_mangleable(&candidate) {
+                    break candidate;
+                }
+            };
+
+            used_names.insert(name.clone());
+            bindings[binding].new_name = Some(name);
+        }
+    }
+
+    return Some(resolved.into_iter().enumerate().map(|(index, binding)| match binding {
+        NONE => None,
+        binding => bindings[binding].new_name.clone().filter(|name| name != pieces[index].text)
+    }).collect());
+}
+
+// NOTE: every piece is mapped back to its position in the input, renamed identifiers keep their original name
+fn emit(pieces: &[Piece], roles: &[Role], names: &[Option<String>], trailing_comments: &[&str], file_name: &str) -> (String, SourceMap) {
+    let mut output = String::with_capacity(pieces.iter().map(|piece| piece.text.len() + 1).sum());
+    let mut map = SourceMap::new(file_name);
+    let mut name_indexes: HashMap<&str, usize> = HashMap::new();
+    let mut position = (0, 0);
+
+    map.add_source(file_name, None);
+
+    for (index, piece) in pieces.iter().enumerate() {
+        let text = match (&names[index], roles[index]) {
+            (Some(name), Role::Shorthand) => format!("{}:{}", piece.text, name),
+            (Some(name), _) => name.clone(),
+            (None, _) => piece.text.to_string()
+        };
+
+        if index > 0 && piece.newline_before && keeps_newline(pieces, index) {
+            output.push('\n');
+            position = (position.0 + 1, 0);
+        } else if index > 0 && needs_space(&output, pieces[index - 1].kind, &text) {
+            output.push(' ');
+            position.1 += 1;
+        }
+
+        for comment in piece.comments.iter() {
+            output.push_str(comment);
+            position = source_map::advance(position, comment);
+        }
+
+        let name = match names[index] {
+            Some(_) => Some(*name_indexes.entry(piece.text).or_insert_with(|| {
+                map.names.push(piece.text.to_string());
+
+                return map.names.len() - 1;
+            })),
+            None => None
+        };
+
+        map.mappings.push(Mapping {
+            generated_line: position.0,
+            generated_column: position.1,
+            source: 0,
+            original_line: piece.line - 1,
+            original_column: piece.column - 1,
+            name: name
+        });
+        output.push_str(&text);
+        position = source_map::advance(position, &text);
+    }
+
+    for comment in trailing_comments.iter() {
+        output.push_str(comment);
+    }
+
+    return (output, map);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::process::Command;
+    use super::*;
+
+    #[test]
+    fn from_string_strips_whitespace_and_comments() {
+        let code = "/*! keep me */\n// line comment\nvar total = 1 /* inline */ + 2;\nfunction count() {\n  return\n    total;\n}\ntotal\n++total\nvar ratio = total / /2/.source.length, negative = 1 - -1, text = `a ${ total } b`;\n";
+
+        assert_eq!(
+            from_string(code, "test.js").unwrap(),
+            "/*! keep me */var total=1+2;function count(){return\ntotal;}\ntotal\n++total\nvar ratio=total/ /2/.source.length,negative=1- -1,text=`a ${total} b`;"
+        );
+    }
+
+    #[test]
+    fn from_string_mangles_function_scoped_names() {
+        let code = "
+            define('app/utils', ['exports', 'ember'], function (exports, _ember) {
+              var counter = { counter: 0 };
+              function increment(amount, { step = amount } = {}) {
+                let next = counter.counter + step;
+                counter.counter = next;
+                return { next, amount: amount, global: window.innerWidth };
+              }
+              exports.increment = (value) => increment(value);
+              label: for (var index in counter) { break label; }
+            });
+            function unsafe(first) { return eval('first'); }
+        ";
+
+        assert_eq!(from_string(code, "test.js").unwrap(), vec![
+            "define('app/utils',['exports','ember'],function(b,d){var a={counter:0};",
+            "function c(b,{step:d=b}={}){let c=a.counter+d;a.counter=c;return{next:c,amount:b,global:window.innerWidth};}",
+            "\nb.increment=(a)=>c(a);label:for(var e in a){break label;}});function unsafe(first){return eval('first');}"
+        ].join(""));
+    }
+
+    #[test]
+    fn from_string_binds_function_declarations_in_statement_position() {
+        let code = "
+            function run(flag) {
+              let count = 1;
+              if (flag) function check() { return count; } else function other() {}
+              { function nested() { return flag; } }
+              return [typeof check, typeof other, nested()];
+            }
+            console.log(JSON.stringify(run(true)));
+        ";
+        let run = |code: &str| String::from_utf8(Command::new("node").args(&["-e", code]).output().unwrap().stdout).unwrap();
+
+        assert_eq!(from_string(code, "test.js").unwrap(), vec![
+            "function run(a){let b=1;if(a)function c(){return b;}else function d(){}\n{function e(){return a;}}",
+            "\nreturn[typeof c,typeof d,e()];}\nconsole.log(JSON.stringify(run(true)));"
+        ].join(""));
+        assert_eq!(run(code), "[\"function\",\"undefined\",true]\n");
+        assert_eq!(run(&from_string(code, "test.js").unwrap()), run(code));
+    }
+
+    #[test]
+    fn from_string_removes_dead_branches() {
+        let code = "
+            function run() {
+              if (false) { cleanup(); } else { start(); }
+              if (true) { debugger; ready(); } else if (other) { never(); }
+              if (0 && check()) { never(); }
+              if (false) { var hoisted = 1; }
+              if (false && a || b) { maybe(); }
+            }
+        ";
+
+        assert_eq!(
+            from_string(code, "test.js").unwrap(),
+            "function run(){{start();}{;ready();};if(false){var c=1;}\nif(false&&a||b){maybe();}}"
+        );
+    }
+
+    #[test]
+    fn from_string_output_behaves_like_the_input() {
+        let code = "
+            var result = (function () {
+              class Stack { constructor(items) { this.items = items; } get size() { return this.items.length; } }
+              const stack = new Stack([1, 2, 3]), { size } = stack;
+              var total = 0
+              var doubled = stack.items.map(item => item * 2)
+              for (let item of doubled) total += item
+              return [size, total, `${doubled.join('-')}`, typeof missing, (a => b => a + b)(1)(2)];
+            })();
+            console.log(JSON.stringify(result));
+        ";
+        let run = |code: &str| String::from_utf8(Command::new("node").args(&["-e", code]).output().unwrap().stdout).unwrap();
+
+        assert_eq!(run(code), "[3,12,\"2-4-6\",\"undefined\",3]\n");
+        assert_eq!(run(&from_string(code, "test.js").unwrap()), run(code));
+    }
+
+    #[test]
+    fn from_string_with_source_map_maps_pieces_to_the_input() {
+        let code = "function add(first, second) {\n  // sums\n  return first +\n    second;\n}\nadd(1, 2);\n";
+        let (output, map) = from_string_with_source_map(code, "application.js").unwrap();
+        let position_of = |generated: (usize, usize)| map.mappings.iter()
+            .find(|mapping| (mapping.generated_line, mapping.generated_column) == generated)
+            .map(|mapping| (mapping.original_line, mapping.original_column, mapping.name.map(|name| map.names[name].as_str())));
+
+        assert_eq!(output, "function add(a,b){return a+b;}\nadd(1,2);");
+        assert_eq!(map.sources, vec!["application.js"]);
+        assert_eq!(position_of((0, 0)), Some((0, 0, None)));
+        assert_eq!(position_of((0, 13)), Some((0, 13, Some("first"))));
+        assert_eq!(position_of((0, 25)), Some((2, 9, Some("first"))));
+        assert_eq!(position_of((0, 26)), Some((2, 15, None)));
+        assert_eq!(position_of((0, 27)), Some((3, 4, Some("second"))));
+        assert_eq!(position_of((1, 4)), Some((5, 4, None)));
+        assert_eq!(from_string_with_source_map(code, "application.js").unwrap(), (output, map));
+    }
+
+    #[test]
+    fn from_string_reports_unbalanced_brackets() {
+        let error = from_string("function broken() {\n  return [1, 2);\n}", "src/broken.js").unwrap_err();
+
+        assert_eq!((error.file_name.as_str(), error.line, error.column), ("src/broken.js", 2, 15));
+        assert!(error.message.contains("Unexpected ')'"));
+        assert!(from_string("var a = `${b`;", "test.js").is_err());
+    }
+}
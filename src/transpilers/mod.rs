@@ -1,3 +1,13 @@
 pub mod convert_es_module;
 pub mod convert_hbs_module;
 pub mod import_addon_folder_to_amd;
+pub mod minify_js;
+pub mod tokenizer;
+
+use std::path::Path;
+
+pub fn module_name_from_path(file: &Path, project_root: &Path, application_name: &str) -> String {
+    let relative_path = file.strip_prefix(project_root).unwrap_or(file).with_extension("");
+
+    return format!("{}/{}", application_name, relative_path.to_string_lossy().replace('\\', "/"));
+}
@@ -0,0 +1,466 @@
+use std::cell::Cell;
+use super::super::types::TranspileError;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    Whitespace,
+    LineTerminator,
+    LineComment,
+    BlockComment,
+    Identifier,
+    Punctuator,
+    String,
+    Template,
+    Number,
+    RegExp
+}
+
+#[derive(Debug, Clone)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize
+}
+
+impl Token {
+    pub fn text<'a>(&self, source: &'a str) -> &'a str {
+        return &source[self.start..self.end];
+    }
+
+    pub fn is_trivia(&self) -> bool {
+        return match self.kind {
+            TokenKind::Whitespace | TokenKind::LineTerminator | TokenKind::LineComment | TokenKind::BlockComment => true,
+            _ => false
+        };
+    }
+
+    pub fn is(&self, source: &str, text: &str) -> bool {
+        return !self.is_trivia() && self.text(source) == text;
+    }
+}
+
+const PUNCTUATORS: [&str; 58] = [
+    ">>>=", "...", "===", "!==", "**=", "<<=", ">>=", ">>>", "&&=", "||=", "??=",
+    "=>", "==", "!=", "<=", ">=", "&&", "||", "??", "?.", "++", "--", "+=", "-=", "*=", "/=", "%=",
+    "&=", "|=", "^=", "**", "<<", ">>",
+    "{", "}", "(", ")", "[", "]", ";", ",", "<", ">", "+", "-", "*", "/", "%", "&", "|", "^", "!", "~",
+    "?", ":", "=", ".", "@"
+];
+const KEYWORDS_BEFORE_EXPRESSION: [&str; 16] = [
+    "return", "typeof", "instanceof", "in", "of", "new", "delete", "void", "throw", "case", "do", "else",
+    "yield", "await", "extends", "export"
+];
+
+#[derive(PartialEq)]
+enum BraceContext {
+    Block,
+    Template
+}
+
+struct Tokenizer<'a> {
+    source: &'a str,
+    bytes: &'a [u8],
+    file_name: &'a str,
+    position: usize,
+    line: usize,
+    line_start: usize,
+    column_cache: Cell<(usize, usize)>,
+    braces: Vec<BraceContext>,
+    tokens: Vec<Token>
+}
+
+// NOTE: produces a lossless token stream: concatenating every token text gives back the source
+pub fn tokenize(source: &str, file_name: &str) -> Result<Vec<Token>, TranspileError> {
+    let mut tokenizer = Tokenizer {
+        source: source,
+        bytes: source.as_bytes(),
+        file_name: file_name,
+        position: 0,
+        line: 1,
+        line_start: 0,
+        column_cache: Cell::new((0, 1)),
+        braces: Vec::new(),
+        tokens: Vec::new()
+    };
+
+    tokenizer.run()?;
+
+    return Ok(tokenizer.tokens);
+}
+
+pub fn significant_tokens(tokens: &[Token]) -> Vec<&Token> {
+    return tokens.iter().filter(|token| !token.is_trivia()).collect();
+}
+
+impl<'a> Tokenizer<'a> {
+    fn run(&mut self) -> Result<(), TranspileError> {
+        if self.source.starts_with("#!") {
+            let end = self.find_line_end(0);
+
+            self.push(TokenKind::LineComment, 0, end);
+        }
+
+        while self.position < self.bytes.len() {
+            let start = self.position;
+            let character = self.current_char();
+
+            match character {
+                '\n' | '\r' | '\u{2028}' | '\u{2029}' => {
+                    let length = if self.source[start..].starts_with("\r\n") { 2 } else { character.len_utf8() };
+
+                    self.push(TokenKind::LineTerminator, start, start + length);
+                    self.line += 1;
+                    self.line_start = self.position;
+                },
+                ' ' | '\t' | '\u{0b}' | '\u{0c}' | '\u{a0}' | '\u{feff}' => {
+                    let mut end = start;
+
+                    while end < self.bytes.len() {
+                        match self.char_at(end) {
+                            ' ' | '\t' | '\u{0b}' | '\u{0c}' | '\u{a0}' | '\u{feff}' => { end += self.char_at(end).len_utf8(); },
+                            _ => break
+                        }
+                    }
+
+                    self.push(TokenKind::Whitespace, start, end);
+                },
+                '/' if self.source[start..].starts_with("//") => {
+                    let end = self.find_line_end(start);
+
+                    self.push(TokenKind::LineComment, start, end);
+                },
+                '/' if self.source[start..].starts_with("/*") => {
+                    let end = match self.source[start + 2..].find("*/") {
+                        Some(index) => start + 2 + index + 2,
+                        None => return Err(self.error_at(start, "Unterminated comment"))
+                    };
+
+                    self.push_multiline(TokenKind::BlockComment, start, end);
+                },
+                '/' if self.regexp_allowed() => {
+                    let end = self.scan_regexp(start)?;
+
+                    self.push(TokenKind::RegExp, start, end);
+                },
+                '\'' | '"' => {
+                    let end = self.scan_string(start, character)?;
+
+                    self.push_multiline(TokenKind::String, start, end);
+                },
+                '`' => {
+                    self.scan_template(start + 1, start)?;
+                },
+                '}' if self.braces.last() == Some(&BraceContext::Template) => {
+                    self.braces.pop();
+                    self.scan_template(start + 1, start)?;
+                },
+                '0'..='9' => {
+                    let end = self.scan_number(start);
+
+                    self.push(TokenKind::Number, start, end);
+                },
+                '.' if self.bytes.get(start + 1).map_or(false, |byte| byte.is_ascii_digit()) => {
+                    let end = self.scan_number(start);
+
+                    self.push(TokenKind::Number, start, end);
+                },
+                '#' if start + 1 < self.bytes.len() && is_identifier_start(self.char_at(start + 1)) => {
+                    let end = self.scan_identifier(start + 1);
+
+                    self.push(TokenKind::Identifier, start, end);
+                },
+                _ if is_identifier_start(character) => {
+                    let end = self.scan_identifier(start);
+
+                    self.push(TokenKind::Identifier, start, end);
+                },
+                _ => {
+                    let punctuator = PUNCTUATORS.iter().find(|punctuator| {
+                        return self.source[start..].starts_with(*punctuator) &&
+                            (**punctuator != "?." || !self.bytes.get(start + 2).map_or(false, |byte| byte.is_ascii_digit()));
+                    });
+
+                    match punctuator {
+                        Some(punctuator) => {
+                            match *punctuator {
+                                "{" => self.braces.push(BraceContext::Block),
+                                "}" => { self.braces.pop(); },
+                                _ => {}
+                            }
+
+                            self.push(TokenKind::Punctuator, start, start + punctuator.len());
+                        },
+                        None => return Err(self.error_at(start, format!("Unexpected character '{}'", character).as_str()))
+                    }
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    fn push(&mut self, kind: TokenKind, start: usize, end: usize) {
+        self.tokens.push(Token { kind: kind, start: start, end: end, line: self.line, column: self.column_of(start) });
+        self.position = end;
+    }
+
+    fn push_multiline(&mut self, kind: TokenKind, start: usize, end: usize) {
+        let (line, column) = (self.line, self.column_of(start));
+
+        self.tokens.push(Token { kind: kind, start: start, end: end, line: line, column: column });
+        self.advance_lines(start, end);
+        self.position = end;
+    }
+
+    fn advance_lines(&mut self, start: usize, end: usize) {
+        let mut index = start;
+
+        while index < end {
+            let character = self.char_at(index);
+
+            index += character.len_utf8();
+
+            if character == '\n' || (character == '\r' && self.bytes.get(index) != Some(&b'\n')) ||
+                character == '\u{2028}' || character == '\u{2029}' {
+                self.line += 1;
+                self.line_start = index;
+            }
+        }
+    }
+
+    fn current_char(&self) -> char {
+        return self.char_at(self.position);
+    }
+
+    fn char_at(&self, index: usize) -> char {
+        return self.source[index..].chars().next().unwrap_or('\0');
+    }
+
+    // NOTE: cached per line, otherwise minified single line bundles would be tokenized in quadratic time
+    fn column_of(&self, index: usize) -> usize {
+        let (cached_index, cached_column) = self.column_cache.get();
+        let (from, column) = match cached_index >= self.line_start && cached_index <= index {
+            true => (cached_index, cached_column),
+            false => (self.line_start, 1)
+        };
+        let result = column + self.source[from..index].chars().count();
+
+        self.column_cache.set((index, result));
+
+        return result;
+    }
+
+    fn error_at(&self, index: usize, message: &str) -> TranspileError {
+        return TranspileError::new(self.file_name, self.line, self.column_of(index), message);
+    }
+
+    fn find_line_end(&self, start: usize) -> usize {
+        return self.source[start..]
+            .find(|character| character == '\n' || character == '\r' || character == '\u{2028}' || character == '\u{2029}')
+            .map(|index| start + index)
+            .unwrap_or(self.bytes.len());
+    }
+
+    fn regexp_allowed(&self) -> bool {
+        let previous_token = self.tokens.iter().rev().find(|token| !token.is_trivia());
+
+        return match previous_token {
+            None => true,
+            Some(token) => match token.kind {
+                TokenKind::Number | TokenKind::String | TokenKind::RegExp => false,
+                TokenKind::Template => !token.text(self.source).ends_with('`'),
+                TokenKind::Identifier => KEYWORDS_BEFORE_EXPRESSION.contains(&token.text(self.source)),
+                _ => match token.text(self.source) {
+                    ")" | "]" | "}" | "++" | "--" => false,
+                    _ => true
+                }
+            }
+        };
+    }
+
+    fn scan_regexp(&self, start: usize) -> Result<usize, TranspileError> {
+        let mut index = start + 1;
+        let mut in_class = false;
+
+        loop {
+            match self.bytes.get(index) {
+                None | Some(b'\n') | Some(b'\r') => return Err(self.error_at(start, "Unterminated regular expression")),
+                Some(b'\\') => { index += 2; },
+                Some(b'[') => { in_class = true; index += 1; },
+                Some(b']') => { in_class = false; index += 1; },
+                Some(b'/') if !in_class => { index += 1; break; },
+                Some(_) => { index += self.char_at(index).len_utf8(); }
+            }
+        }
+
+        while index < self.bytes.len() && is_identifier_part(self.char_at(index)) {
+            index += self.char_at(index).len_utf8();
+        }
+
+        return Ok(index);
+    }
+
+    fn scan_string(&self, start: usize, quote: char) -> Result<usize, TranspileError> {
+        let mut index = start + 1;
+
+        loop {
+            match self.bytes.get(index) {
+                None | Some(b'\n') => return Err(self.error_at(start, "Unterminated string constant")),
+                Some(b'\\') => {
+                    index += 1 + self.source[index + 1..].chars().next().map_or(0, |character| character.len_utf8());
+
+                    if self.source[..index].ends_with('\r') && self.bytes.get(index) == Some(&b'\n') {
+                        index += 1;
+                    }
+                },
+                Some(byte) if *byte as char == quote => return Ok(index + 1),
+                Some(_) => { index += self.char_at(index).len_utf8(); }
+            }
+        }
+    }
+
+    // NOTE: emits one Template token per template chunk so `${}` substitutions get tokenized as code
+    fn scan_template(&mut self, from: usize, token_start: usize) -> Result<(), TranspileError> {
+        let mut index = from;
+
+        loop {
+            match self.bytes.get(index) {
+                None => return Err(self.error_at(token_start, "Unterminated template")),
+                Some(b'\\') => { index += 1 + self.source[index + 1..].chars().next().map_or(0, |character| character.len_utf8()); },
+                Some(b'`') => {
+                    self.push_multiline(TokenKind::Template, token_start, index + 1);
+
+                    return Ok(());
+                },
+                Some(b'$') if self.bytes.get(index + 1) == Some(&b'{') => {
+                    self.push_multiline(TokenKind::Template, token_start, index + 2);
+                    self.braces.push(BraceContext::Template);
+
+                    return Ok(());
+                },
+                Some(_) => { index += self.char_at(index).len_utf8(); }
+            }
+        }
+    }
+
+    fn scan_number(&self, start: usize) -> usize {
+        let mut index = start;
+        let is_radix_literal = self.bytes[start] == b'0' && self.bytes.get(start + 1)
+            .map_or(false, |byte| b"xXoObB".contains(byte));
+
+        if is_radix_literal {
+            index += 2;
+
+            while index < self.bytes.len() && (self.bytes[index].is_ascii_hexdigit() || self.bytes[index] == b'_') {
+                index += 1;
+            }
+        } else {
+            while index < self.bytes.len() && (self.bytes[index].is_ascii_digit() || self.bytes[index] == b'_' || self.bytes[index] == b'.') {
+                index += 1;
+            }
+
+            if index < self.bytes.len() && (self.bytes[index] == b'e' || self.bytes[index] == b'E') {
+                index += 1;
+
+                if index < self.bytes.len() && (self.bytes[index] == b'+' || self.bytes[index] == b'-') {
+                    index += 1;
+                }
+
+                while index < self.bytes.len() && self.bytes[index].is_ascii_digit() {
+                    index += 1;
+                }
+            }
+        }
+
+        if index < self.bytes.len() && self.bytes[index] == b'n' {
+            index += 1;
+        }
+
+        return index;
+    }
+
+    fn scan_identifier(&self, start: usize) -> usize {
+        let mut index = start;
+
+        while index < self.bytes.len() {
+            let character = self.char_at(index);
+
+            if character == '\\' && self.bytes.get(index + 1) == Some(&b'u') {
+                index += 2;
+            } else if is_identifier_part(character) {
+                index += character.len_utf8();
+            } else {
+                break;
+            }
+        }
+
+        return index;
+    }
+}
+
+fn is_identifier_start(character: char) -> bool {
+    return character == '$' || character == '_' || character == '\\' || character.is_alphabetic();
+}
+
+fn is_identifier_part(character: char) -> bool {
+    return is_identifier_start(character) || character.is_alphanumeric() ||
+        character == '\u{200c}' || character == '\u{200d}';
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn texts(source: &str) -> Vec<String> {
+        return significant_tokens(&tokenize(source, "test.js").unwrap()).iter()
+            .map(|token| token.text(source).to_string())
+            .collect();
+    }
+
+    #[test]
+    fn tokenize_is_lossless() {
+        let source = "import a from 'b';\n// comment\nconst c = `x${a + `y${1}`}z` / 2; /* multi\nline */ let r = /[/]+/g;";
+        let tokens = tokenize(source, "test.js").unwrap();
+
+        assert_eq!(tokens.iter().map(|token| token.text(source)).collect::<String>(), source);
+    }
+
+    #[test]
+    fn tokenize_tells_regexps_from_divisions() {
+        assert_eq!(texts("a = b / c / d"), vec!["a", "=", "b", "/", "c", "/", "d"]);
+        assert_eq!(texts("return /ab+c/gi.test(x)"), vec!["return", "/ab+c/gi", ".", "test", "(", "x", ")"]);
+        assert_eq!(texts("x = (1) / 2"), vec!["x", "=", "(", "1", ")", "/", "2"]);
+        assert_eq!(texts("[/^localhost:\\d+$/]"), vec!["[", "/^localhost:\\d+$/", "]"]);
+    }
+
+    #[test]
+    fn tokenize_splits_template_substitutions() {
+        assert_eq!(
+            texts("`${config.modulePrefix}/src/init`"),
+            vec!["`${", "config", ".", "modulePrefix", "}/src/init`"]
+        );
+    }
+
+    #[test]
+    fn tokenize_tracks_lines_and_columns() {
+        let source = "let a = 1;\n  let b = `\n`;\nlet c;";
+        let tokens = tokenize(source, "test.js").unwrap();
+        let c_token = tokens.iter().find(|token| token.text(source) == "c").unwrap();
+        let b_token = tokens.iter().find(|token| token.text(source) == "b").unwrap();
+
+        assert_eq!((b_token.line, b_token.column), (2, 7));
+        assert_eq!((c_token.line, c_token.column), (4, 5));
+    }
+
+    #[test]
+    fn tokenize_reports_unterminated_literals() {
+        let error = tokenize("let a = 1;\nlet b = 'oops;\n", "src/oops.js").unwrap_err();
+
+        assert_eq!((error.file_name.as_str(), error.line, error.column), ("src/oops.js", 2, 9));
+        assert!(error.message.contains("Unterminated string"));
+        assert!(tokenize("let a = `oops", "test.js").is_err());
+        assert!(tokenize("/* oops", "test.js").is_err());
+    }
+}
@@ -1,32 +1,32 @@
 #[derive(Debug)]
 pub struct BuildCache {
-    pub vendor_appends: &'static str,
-    pub vendor_prepends: &'static str,
-    pub application_appends: &'static str,
-    pub application_prepends: &'static str,
-    pub test_appends: &'static str,
-    pub test_prepends: &'static str,
+    pub vendor_appends: String,
+    pub vendor_prepends: String,
+    pub application_appends: String,
+    pub application_prepends: String,
+    pub test_appends: String,
+    pub test_prepends: String,
 }
 
 impl BuildCache {
     pub fn new() -> Self {
         BuildCache {
-            vendor_appends: "",
-            vendor_prepends: "",
-            application_appends: "",
-            application_prepends: "",
-            test_appends: "",
-            test_prepends: ""
+            vendor_appends: String::new(),
+            vendor_prepends: String::new(),
+            application_appends: String::new(),
+            application_prepends: String::new(),
+            test_appends: String::new(),
+            test_prepends: String::new()
         }
     }
-    pub fn insert<'a>(mut self, key: &str, value: &'static str) -> Self {
+    pub fn insert<'a>(mut self, key: &str, value: &str) -> Self {
         match key {
-            "vendor_appends" => { self.vendor_appends = &value },
-            "vendor_prepends" => { self.vendor_prepends = &value },
-            "application_appends" => { self.application_appends = &value },
-            "application_prepends" => { self.application_prepends = &value },
-            "test_appends" => { self.test_appends = &value },
-            "test_prepends" => { self.test_prepends = &value },
+            "vendor_appends" => { self.vendor_appends = value.to_string() },
+            "vendor_prepends" => { self.vendor_prepends = value.to_string() },
+            "application_appends" => { self.application_appends = value.to_string() },
+            "application_prepends" => { self.application_prepends = value.to_string() },
+            "test_appends" => { self.test_appends = value.to_string() },
+            "test_prepends" => { self.test_prepends = value.to_string() },
             _ => {}
         };
 
@@ -1,35 +1,289 @@
-// use hyper::Uri;
+use std::env;
+use std::error::Error;
+use hyper::Uri;
 
-// #[derive(Debug)]
-// enum Proxy {
-//     Uri, None
-// }
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proxy {
+    pub uri: Uri
+}
+
+impl Proxy {
+    pub fn parse(url: &str) -> Result<Self, Box<dyn Error>> {
+        let uri = url.parse::<Uri>().map_err(|_| format!("--proxy value {} is not a valid url", url))?;
 
-#[derive(Debug)]
+        return match (uri.scheme_str(), uri.host()) {
+            (Some("http"), Some(_)) | (Some("https"), Some(_)) => Ok(Proxy { uri: uri }),
+            _ => Err(format!("--proxy value {} must be an http(s) url like http://localhost:3000", url).into())
+        };
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct CLIArguments {
-    pub env: &'static str,
+    pub env: Option<String>, // NOTE: None without --env, build and serve use development and test uses test
     pub port: u16,
-    // proxy: Option<Proxy>,
+    pub socket_port: u16,
+    pub proxy: Option<Proxy>,
     pub server: bool,
     pub fastboot: bool,
     pub watch: bool,
     pub debug: bool,
     pub talk: bool,
-    pub testing: bool // NOTE: is this necessary?
+    pub source_maps: bool,
+    pub analyze: bool,
+    pub testing: bool, // NOTE: set by serve and test, the only commands that serve the tests
+    pub live_reload: bool, // NOTE: set by the commands that start the file watcher and the socket server, not a flag
+    pub filter: Option<String>,
+    pub module: Option<String>,
+    pub reporter: String,
+    pub output: Option<String>,
+    pub compare: Option<String>,
+    pub keep_previous: Option<usize>,
+    pub verify_reproducible: bool,
+    pub lint: String,
+    pub templates: bool,
+    pub format: String,
+    pub fix: bool,
+    pub changed: Option<String>,
+    pub positional_arguments: Vec<String>
 }
 
 impl CLIArguments {
-    pub fn parse() -> Self { // TODO: add different arguments, overwritable
+    pub fn new() -> Self {
         CLIArguments {
-            env: "development",
+            env: None,
             port: 1234,
-            // proxy: None,
-            server: true,
+            socket_port: 65511,
+            proxy: None,
+            server: false,
             fastboot: true,
             watch: true,
             debug: false,
             talk: true,
-            testing: true
+            source_maps: true,
+            analyze: false,
+            testing: false,
+            live_reload: false,
+            filter: None,
+            module: None,
+            reporter: String::from("console"),
+            output: None,
+            compare: None,
+            keep_previous: None,
+            verify_reproducible: false,
+            lint: String::from("warn"),
+            templates: false,
+            format: String::from("stylish"),
+            fix: false,
+            changed: None,
+            positional_arguments: Vec::new()
+        }
+    }
+
+    // NOTE: skips the binary and the subcommand: mber build --env=production
+    pub fn parse() -> Result<Self, Box<dyn Error>> {
+        return CLIArguments::parse_from(env::args().skip(2));
+    }
+
+    pub fn parse_from<I>(arguments: I) -> Result<Self, Box<dyn Error>> where I: IntoIterator<Item = String> {
+        let mut cli_arguments = CLIArguments::new();
+        let mut arguments = arguments.into_iter();
+
+        while let Some(argument) = arguments.next() {
+            if !argument.starts_with("--") {
+                cli_arguments.positional_arguments.push(argument);
+
+                continue;
+            }
+
+            let (flag, inline_value) = match argument.find('=') {
+                Some(index) => (argument[..index].to_string(), Some(argument[index + 1..].to_string())),
+                None => (argument.clone(), None)
+            };
+
+            match flag.as_str() {
+                "--env" | "--port" | "--socket-port" | "--proxy" | "--filter" | "--module" | "--reporter" | "--output" | "--compare"
+                    | "--keep-previous" | "--lint" | "--format" => {
+                    let value = match inline_value {
+                        Some(value) => value,
+                        None => arguments.next().filter(|value| !value.starts_with("--"))
+                            .ok_or_else(|| format!("{} option needs a value", flag))?
+                    };
+
+                    match flag.as_str() {
+                        "--env" => cli_arguments.env = Some(parse_environment(&value)?),
+                        "--port" => cli_arguments.port = parse_port(&flag, &value)?,
+                        "--socket-port" => cli_arguments.socket_port = parse_port(&flag, &value)?,
+                        "--filter" => cli_arguments.filter = Some(value),
+                        "--module" => cli_arguments.module = Some(value),
+                        "--reporter" => cli_arguments.reporter = parse_reporter(&value)?,
+                        "--output" => cli_arguments.output = Some(value),
+                        "--compare" => cli_arguments.compare = Some(value),
+                        "--keep-previous" => cli_arguments.keep_previous = Some(parse_count(&flag, &value)?),
+                        "--lint" => cli_arguments.lint = parse_lint(&value)?,
+                        "--format" => cli_arguments.format = parse_format(&value)?,
+                        _ => cli_arguments.proxy = Some(Proxy::parse(&value)?)
+                    };
+                },
+                // NOTE: the git ref is optional so it can only be given inline: --changed=main
+                "--changed" => cli_arguments.changed = Some(inline_value.unwrap_or(String::from("HEAD"))),
+                _ if inline_value.is_some() => {
+                    return Err(format!("{} option does not take a value", flag).into());
+                },
+                "--server" => cli_arguments.server = true,
+                "--no-server" => cli_arguments.server = false,
+                "--fastboot" => cli_arguments.fastboot = true,
+                "--no-fastboot" => cli_arguments.fastboot = false,
+                "--watch" => cli_arguments.watch = true,
+                "--no-watch" => cli_arguments.watch = false,
+                "--debug" => cli_arguments.debug = true,
+                "--talk" => cli_arguments.talk = true,
+                "--no-talk" => cli_arguments.talk = false,
+                "--source-maps" => cli_arguments.source_maps = true,
+                "--no-source-maps" => cli_arguments.source_maps = false,
+                "--analyze" => cli_arguments.analyze = true,
+                "--verify-reproducible" => cli_arguments.verify_reproducible = true,
+                "--no-lint" => cli_arguments.lint = String::from("off"),
+                "--templates" => cli_arguments.templates = true,
+                "--fix" => cli_arguments.fix = true,
+                _ => return Err(format!("unknown option {}, run mber help to see the available options", flag).into())
+            };
+        }
+
+        if cli_arguments.port == cli_arguments.socket_port {
+            return Err(format!("--port and --socket-port cannot both be {}", cli_arguments.port).into());
         }
+
+        return Ok(cli_arguments);
+    }
+}
+
+fn parse_environment(value: &str) -> Result<String, Box<dyn Error>> {
+    if value.is_empty() || !value.chars().all(|character| character.is_ascii_alphanumeric() || character == '-' || character == '_') {
+        return Err(format!("--env value \"{}\" is not a valid environment name", value).into());
+    }
+
+    return Ok(value.to_string());
+}
+
+fn parse_reporter(value: &str) -> Result<String, Box<dyn Error>> {
+    if !vec!["console", "tap", "junit", "json"].contains(&value) {
+        return Err(format!("--reporter value {} is not valid, use console, tap, junit or json", value).into());
+    }
+
+    return Ok(value.to_string());
+}
+
+fn parse_lint(value: &str) -> Result<String, Box<dyn Error>> {
+    if !vec!["off", "warn", "error"].contains(&value) {
+        return Err(format!("--lint value {} is not valid, use off, warn or error", value).into());
+    }
+
+    return Ok(value.to_string());
+}
+
+fn parse_format(value: &str) -> Result<String, Box<dyn Error>> {
+    if !vec!["stylish", "json", "checkstyle", "sarif"].contains(&value) {
+        return Err(format!("--format value {} is not valid, use stylish, json, checkstyle or sarif", value).into());
+    }
+
+    return Ok(value.to_string());
+}
+
+fn parse_port(flag: &str, value: &str) -> Result<u16, Box<dyn Error>> {
+    return match value.parse::<u16>() {
+        Ok(port) if port > 0 => Ok(port),
+        _ => Err(format!("{} value {} is not a valid port, use a number between 1 and 65535", flag, value).into())
+    };
+}
+
+fn parse_count(flag: &str, value: &str) -> Result<usize, Box<dyn Error>> {
+    return value.parse::<usize>()
+        .map_err(|_| format!("{} value {} is not a valid number, use 0 or a positive number", flag, value).into());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(arguments: Vec<&str>) -> Result<CLIArguments, Box<dyn Error>> {
+        return CLIArguments::parse_from(arguments.into_iter().map(String::from));
+    }
+
+    #[test]
+    fn parse_from_returns_defaults_without_arguments() -> Result<(), Box<dyn Error>> {
+        let cli_arguments = parse(vec![])?;
+
+        assert_eq!(cli_arguments.env, None);
+        assert_eq!(cli_arguments.port, 1234);
+        assert_eq!(cli_arguments.socket_port, 65511);
+        assert_eq!(cli_arguments.proxy, None);
+        assert_eq!((cli_arguments.server, cli_arguments.fastboot, cli_arguments.watch), (false, true, true));
+        assert_eq!((cli_arguments.debug, cli_arguments.talk, cli_arguments.source_maps), (false, true, true));
+        assert_eq!((cli_arguments.filter, cli_arguments.module, cli_arguments.output), (None, None, None));
+        assert_eq!((cli_arguments.compare, cli_arguments.analyze, cli_arguments.keep_previous), (None, false, None));
+        assert_eq!(cli_arguments.reporter, "console");
+        assert_eq!(cli_arguments.lint, "warn");
+        assert_eq!((cli_arguments.testing, cli_arguments.live_reload), (false, false));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn parse_from_works_for_all_options() -> Result<(), Box<dyn Error>> {
+        let cli_arguments = parse(vec![
+            "--env=production", "--port", "3000", "--socket-port=4000", "--no-fastboot", "--no-watch", "--debug",
+            "--no-talk", "--proxy", "http://localhost:8000", "--no-server", "--no-source-maps", "component", "x-button"
+        ])?;
+
+        assert_eq!(cli_arguments.env, Some(String::from("production")));
+        assert_eq!(cli_arguments.port, 3000);
+        assert_eq!(cli_arguments.socket_port, 4000);
+        assert_eq!(cli_arguments.proxy.unwrap().uri.to_string(), "http://localhost:8000/");
+        assert_eq!((cli_arguments.server, cli_arguments.fastboot, cli_arguments.watch), (false, false, false));
+        assert_eq!((cli_arguments.debug, cli_arguments.talk, cli_arguments.source_maps), (true, false, false));
+        assert_eq!(cli_arguments.positional_arguments, vec!["component", "x-button"]);
+        assert_eq!(parse(vec!["--no-talk", "--talk", "--no-server", "--server", "--env", "demo"])?.talk, true);
+        assert_eq!(parse(vec!["--no-server", "--server"])?.server, true);
+
+        let test_arguments = parse(vec!["--filter", "it exists", "--module=Unit | Route | index", "--reporter=junit", "--output", "report.xml"])?;
+
+        assert_eq!(test_arguments.filter, Some(String::from("it exists")));
+        assert_eq!(test_arguments.module, Some(String::from("Unit | Route | index")));
+        assert_eq!(test_arguments.reporter, "junit");
+        assert_eq!(test_arguments.output, Some(String::from("report.xml")));
+        assert_eq!(parse(vec!["--compare", "main-stats.json"])?.compare, Some(String::from("main-stats.json")));
+        assert_eq!(parse(vec!["--analyze"])?.analyze, true);
+        assert_eq!(parse(vec!["--verify-reproducible"])?.verify_reproducible, true);
+        assert_eq!(parse(vec!["--keep-previous", "3"])?.keep_previous, Some(3));
+        assert_eq!(parse(vec!["--templates"])?.templates, true);
+        assert_eq!((parse(vec!["--fix"])?.fix, parse(vec!["--format", "sarif"])?.format), (true, String::from("sarif")));
+        assert_eq!((parse(vec!["--changed"])?.changed, parse(vec!["--changed=main", "src"])?.changed), (
+            Some(String::from("HEAD")), Some(String::from("main"))
+        ));
+        assert_eq!((parse(vec!["--lint=error"])?.lint, parse(vec!["--no-lint"])?.lint), (String::from("error"), String::from("off")));
+
+        return Ok(());
+    }
+
+    #[test]
+    fn parse_from_reports_unknown_options_and_invalid_values() {
+        let error = |arguments: Vec<&str>| parse(arguments).unwrap_err().to_string();
+
+        assert_eq!(error(vec!["--verbose"]), "unknown option --verbose, run mber help to see the available options");
+        assert_eq!(error(vec!["--port"]), "--port option needs a value");
+        assert_eq!(error(vec!["--port", "--debug"]), "--port option needs a value");
+        assert_eq!(error(vec!["--port=abc"]), "--port value abc is not a valid port, use a number between 1 and 65535");
+        assert_eq!(error(vec!["--socket-port", "70000"]), "--socket-port value 70000 is not a valid port, use a number between 1 and 65535");
+        assert_eq!(error(vec!["--port", "0"]), "--port value 0 is not a valid port, use a number between 1 and 65535");
+        assert_eq!(error(vec!["--env="]), "--env value \"\" is not a valid environment name");
+        assert_eq!(error(vec!["--debug=true"]), "--debug option does not take a value");
+        assert_eq!(error(vec!["--proxy", "localhost:3000"]), "--proxy value localhost:3000 must be an http(s) url like http://localhost:3000");
+        assert_eq!(error(vec!["--proxy=http://exa mple.com"]), "--proxy value http://exa mple.com is not a valid url");
+        assert_eq!(error(vec!["--keep-previous=-1"]), "--keep-previous value -1 is not a valid number, use 0 or a positive number");
+        assert_eq!(error(vec!["--reporter", "xml"]), "--reporter value xml is not valid, use console, tap, junit or json");
+        assert_eq!(error(vec!["--lint", "strict"]), "--lint value strict is not valid, use off, warn or error");
+        assert_eq!(error(vec!["--format=html"]), "--format value html is not valid, use stylish, json, checkstyle or sarif");
+        assert_eq!(error(vec!["--port=4000", "--socket-port=4000"]), "--port and --socket-port cannot both be 4000");
     }
 }
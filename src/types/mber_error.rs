@@ -0,0 +1,114 @@
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use super::TranspileError;
+
+// NOTE: every builder returns this, so tools embedding mber can match on the failure instead of parsing messages
+#[derive(Debug)]
+pub enum MberError {
+    Io { path: PathBuf, context: String, error: io::Error },
+    Sass { path: PathBuf, message: String },
+    Transpile(TranspileError),
+    Config { path: PathBuf, message: String },
+    MissingProject { directory: PathBuf },
+    Build { path: PathBuf, message: String },
+    Lint { path: PathBuf, message: String, diagnostics: String }
+}
+
+impl MberError {
+    // NOTE: for map_err, fs::read_to_string(&path).map_err(MberError::io("could not read", &path))?
+    pub fn io<P: AsRef<Path>>(context: &str, path: P) -> impl FnOnce(io::Error) -> MberError {
+        let (context, path) = (context.to_string(), path.as_ref().to_path_buf());
+
+        return move |error| MberError::Io { path: path, context: context, error: error };
+    }
+
+    pub fn config<P: AsRef<Path>, M: fmt::Display>(path: P, message: M) -> MberError {
+        return MberError::Config { path: path.as_ref().to_path_buf(), message: message.to_string() };
+    }
+
+    pub fn build<P: AsRef<Path>, M: fmt::Display>(path: P, message: M) -> MberError {
+        return MberError::Build { path: path.as_ref().to_path_buf(), message: message.to_string() };
+    }
+
+    pub fn path(&self) -> &Path {
+        return match self {
+            MberError::Io { path, .. } | MberError::Sass { path, .. } | MberError::Config { path, .. } |
+                MberError::Build { path, .. } | MberError::Lint { path, .. } => path,
+            MberError::Transpile(error) => Path::new(&error.file_name),
+            MberError::MissingProject { directory } => directory
+        };
+    }
+}
+
+impl fmt::Display for MberError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        return match self {
+            MberError::Io { path, context, error } => write!(formatter, "{} {}: {}", context, path.display(), error),
+            MberError::Sass { path, message } => write!(formatter, "{} could not be compiled: {}", path.display(), message),
+            MberError::Transpile(error) => write!(formatter, "{}", error),
+            MberError::Config { message, .. } | MberError::Build { message, .. } | MberError::Lint { message, .. } => {
+                write!(formatter, "{}", message)
+            },
+            MberError::MissingProject { directory } => write!(
+                formatter, "you are not on a frontend project! Change your directory, {} has no package.json", directory.display()
+            )
+        };
+    }
+}
+
+impl Error for MberError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        return match self {
+            MberError::Io { error, .. } => Some(error),
+            MberError::Transpile(error) => Some(error),
+            _ => None
+        };
+    }
+}
+
+impl From<TranspileError> for MberError {
+    fn from(error: TranspileError) -> Self {
+        return MberError::Transpile(error);
+    }
+}
+
+impl From<MberError> for io::Error {
+    fn from(error: MberError) -> Self {
+        return match error {
+            MberError::Io { error, .. } => io::Error::new(error.kind(), error.to_string()),
+            MberError::MissingProject { .. } => io::Error::new(io::ErrorKind::NotFound, error.to_string()),
+            error => io::Error::new(io::ErrorKind::Other, error.to_string())
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use super::*;
+
+    #[test]
+    fn errors_carry_their_paths_and_context() {
+        let missing_path = PathBuf::from("missing-folder/missing.js");
+        let io_error = fs::read_to_string(&missing_path).map_err(MberError::io("could not read", &missing_path)).unwrap_err();
+        let transpile_error = MberError::from(TranspileError::new("src/app.js", 1, 17, "Unexpected end of input"));
+        let missing_project_error = MberError::MissingProject { directory: PathBuf::from("/home") };
+
+        assert!(io_error.to_string().starts_with("could not read missing-folder/missing.js: "));
+        assert_eq!(io_error.path(), missing_path.as_path());
+        assert!(io_error.source().is_some());
+        assert_eq!(transpile_error.to_string(), "src/app.js:1:17 Unexpected end of input");
+        assert_eq!(transpile_error.path(), Path::new("src/app.js"));
+        assert_eq!(
+            MberError::Sass { path: PathBuf::from("src/ui/styles/application.scss"), message: String::from("invalid css") }.to_string(),
+            "src/ui/styles/application.scss could not be compiled: invalid css"
+        );
+        assert_eq!(MberError::config("config/environment.js", "SRI.algorithm is invalid").to_string(), "SRI.algorithm is invalid");
+        assert_eq!(
+            missing_project_error.to_string(), "you are not on a frontend project! Change your directory, /home has no package.json"
+        );
+        assert_eq!(io::Error::from(missing_project_error).kind(), io::ErrorKind::NotFound);
+    }
+}
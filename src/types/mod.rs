@@ -5,14 +5,20 @@ use super::utils;
 
 pub mod build_cache;
 pub mod cli_arguments;
+pub mod mber_error;
+pub mod transpile_error;
 
 pub use build_cache::BuildCache;
-pub use cli_arguments::CLIArguments;
+pub use cli_arguments::{CLIArguments, Proxy};
+pub use mber_error::MberError;
+pub use transpile_error::TranspileError;
 
 #[derive(Debug)]
 pub struct Config {
     pub application_name: String,
     pub build_cache: Box<BuildCache>,
+    pub build_timestamp: u64,
+    pub cache_directory: PathBuf,
     pub cli_arguments: Box<CLIArguments>,
     pub env: Value,
     pub index_html_injections: HashMap<String, String>,
@@ -20,14 +26,21 @@ pub struct Config {
 }
 
 impl Config {
-    pub fn build<'a>(env: Value, index_html_injections: HashMap<String, String>, build_cache: BuildCache) -> Config {
-        Config {
+    // NOTE: fails outside of a project, builders would otherwise resolve their paths from the filesystem root
+    pub fn build<'a>(env: Value, index_html_injections: HashMap<String, String>, build_cache: BuildCache) -> Result<Config, MberError> {
+        return Ok(Config::new(env, index_html_injections, build_cache, utils::find_project_root()?));
+    }
+
+    pub fn new(env: Value, index_html_injections: HashMap<String, String>, build_cache: BuildCache, project_root: PathBuf) -> Config {
+        return Config {
             application_name: String::from(env["modulePrefix"].as_str().unwrap_or("frontend")),
             build_cache: Box::new(build_cache),
-            cli_arguments: Box::new(CLIArguments::parse()),
+            build_timestamp: utils::build_timestamp(),
+            cache_directory: utils::transpile_cache::default_directory(),
+            cli_arguments: Box::new(CLIArguments::new()),
             env: env,
             index_html_injections: index_html_injections,
-            project_root: utils::find_project_root()
-        }
+            project_root: project_root
+        };
     }
 }
@@ -0,0 +1,29 @@
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TranspileError {
+    pub file_name: String,
+    pub line: usize,
+    pub column: usize,
+    pub message: String
+}
+
+impl TranspileError {
+    pub fn new(file_name: &str, line: usize, column: usize, message: &str) -> Self {
+        TranspileError {
+            file_name: file_name.to_string(),
+            line: line,
+            column: column,
+            message: message.to_string()
+        }
+    }
+}
+
+impl fmt::Display for TranspileError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        return write!(formatter, "{}:{}:{} {}", self.file_name, self.line, self.column, self.message);
+    }
+}
+
+impl Error for TranspileError {}
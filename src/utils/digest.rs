@@ -0,0 +1,99 @@
+use sha2::{Digest, Sha256, Sha384};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Algorithm {
+    Md5,
+    Sha256,
+    Sha384
+}
+
+impl Algorithm {
+    pub fn parse(name: &str) -> Option<Self> {
+        return match name.to_lowercase().replace('-', "").as_str() {
+            "md5" => Some(Algorithm::Md5),
+            "sha256" => Some(Algorithm::Sha256),
+            "sha384" => Some(Algorithm::Sha384),
+            _ => None
+        };
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            Algorithm::Md5 => "md5",
+            Algorithm::Sha256 => "sha256",
+            Algorithm::Sha384 => "sha384"
+        };
+    }
+
+    pub fn digest(&self, data: &[u8]) -> Vec<u8> {
+        return match self {
+            Algorithm::Md5 => md5::compute(data).0.to_vec(),
+            Algorithm::Sha256 => sha256(data).to_vec(),
+            Algorithm::Sha384 => sha384(data).to_vec()
+        };
+    }
+}
+
+pub fn sha256(data: &[u8]) -> [u8; 32] {
+    return Sha256::digest(data).into();
+}
+
+pub fn sha384(data: &[u8]) -> [u8; 48] {
+    return Sha384::digest(data).into();
+}
+
+pub fn to_hex(bytes: &[u8]) -> String {
+    return bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+}
+
+pub fn to_base64(bytes: &[u8]) -> String {
+    return base64::encode(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sha256_works() {
+        assert_eq!(to_hex(&sha256(b"")), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(to_hex(&sha256(b"abc")), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(
+            to_hex(&sha256(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq")),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+        assert_eq!(to_hex(&sha256(&[b'a'; 1000])), "41edece42d63e8d9bf515a9ba6932e1c20cbc9f5a5d134645adb5db1b9737ea3");
+    }
+
+    #[test]
+    fn sha384_works() {
+        assert_eq!(
+            to_hex(&sha384(b"")),
+            "38b060a751ac96384cd9327eb1b1e36a21fdb71114be07434c0cc7bf63f6e1da274edebfe76f65fbd51ad2f14898b95b"
+        );
+        assert_eq!(
+            to_hex(&sha384(b"abc")),
+            "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed8086072ba1e7cc2358baeca134c825a7"
+        );
+        assert_eq!(
+            to_hex(&sha384(
+                b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu"
+            )),
+            "09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712fcc7c71a557e2db966c3e9fa91746039"
+        );
+    }
+
+    #[test]
+    fn to_base64_and_algorithm_work() {
+        assert_eq!(to_base64(b""), "");
+        assert_eq!(to_base64(b"f"), "Zg==");
+        assert_eq!(to_base64(b"fo"), "Zm8=");
+        assert_eq!(to_base64(b"foo"), "Zm9v");
+        assert_eq!(to_base64(b"foobar"), "Zm9vYmFy");
+        assert_eq!(to_base64(&sha384(b"alert('Hello, world.');")), "H8BRh8j48O9oYatfu5AZzq6A9RINhZO5H16dQZngK7T62em8MUt1FLm52t+eX6xO");
+        assert_eq!(Algorithm::parse("SHA-384"), Some(Algorithm::Sha384));
+        assert_eq!(Algorithm::parse("sha1"), None);
+        assert_eq!(to_hex(&Algorithm::Md5.digest(b"abc")), "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(Algorithm::Sha256.digest(b"abc").len(), 32);
+    }
+}
@@ -1,8 +1,14 @@
 use std::path::PathBuf;
-use std::io::Write;
+use std::io::{self, Write};
 use std::fs;
-use std::error::Error;
-use libflate::gzip::{Encoder};
+use flate2::Compression;
+use flate2::write::GzEncoder;
+use brotli::CompressorWriter;
+use super::super::types::MberError;
+
+pub const DEFAULT_GZIP_LEVEL: u32 = 9;
+pub const DEFAULT_BROTLI_LEVEL: u32 = 9; // NOTE: 10 and 11 are ~10x slower for a few percent smaller output
+const BROTLI_WINDOW_SIZE: u32 = 22;
 
 pub fn format_time_passed(time_passed: u128) -> String {
     return time_passed.to_string().as_str().to_owned() + "ms";
@@ -16,20 +22,35 @@ pub fn format_size<'a>(size_in_bytes: u64) -> String {
     return format!("{:.2} kB", (size_in_bytes as f64 / 1000.0));
 }
 
-pub fn gzip_metadata(file_path: &PathBuf) -> Result<(usize), Box<dyn Error>> {
-    let file_content = fs::read(file_path)?;
-    let mut encoder = Encoder::new(Vec::new()).unwrap();
+pub fn gzip_metadata(file_path: &PathBuf) -> Result<usize, MberError> {
+    let content = fs::read(file_path).map_err(MberError::io("could not read", file_path))?;
+
+    return Ok(gzip(&content, DEFAULT_GZIP_LEVEL).map_err(MberError::io("could not gzip", file_path))?.len());
+}
 
-    encoder.write_all(&file_content).unwrap();
+// NOTE: level is between 0 and 9
+pub fn gzip(content: &[u8], level: u32) -> io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::new(level.min(9)));
 
-    let encoded_data = encoder.finish().into_result().unwrap();
+    encoder.write_all(content)?;
 
-    return Ok(encoded_data.capacity());
+    return Ok(encoder.finish()?);
+}
+
+// NOTE: level(quality) is between 0 and 11
+pub fn brotli(content: &[u8], level: u32) -> io::Result<Vec<u8>> {
+    let mut encoder = CompressorWriter::new(Vec::new(), 4096, level.min(11), BROTLI_WINDOW_SIZE);
+
+    encoder.write_all(content)?;
+    encoder.flush()?;
+
+    return Ok(encoder.into_inner());
 }
 
 
 #[cfg(test)]
 mod tests {
+    use std::error::Error;
     use super::*;
 
     #[test]
@@ -72,6 +93,31 @@ mod tests {
         assert_eq!(format_size(99999012), "100.00 MB");
         assert_eq!(format_size(111999012), "112.00 MB");
     }
+
+    #[test]
+    fn gzip_and_brotli_compress_content_with_the_given_level() -> Result<(), Box<dyn Error>> {
+        use std::io::Read;
+
+        let content = "export default function hello() { return 'hello world'; }\n".repeat(200);
+        let gzipped = gzip(content.as_bytes(), DEFAULT_GZIP_LEVEL)?;
+        let brotli_compressed = brotli(content.as_bytes(), DEFAULT_BROTLI_LEVEL)?;
+        let mut decoded = String::new();
+
+        flate2::read::GzDecoder::new(&gzipped[..]).read_to_string(&mut decoded)?;
+
+        assert_eq!(decoded, content);
+        assert!(gzipped.len() < 200);
+        assert!(gzip(content.as_bytes(), 0)?.len() > content.len());
+        assert!(brotli_compressed.len() < gzipped.len());
+
+        let mut brotli_decoded = String::new();
+
+        brotli::Decompressor::new(&brotli_compressed[..], 4096).read_to_string(&mut brotli_decoded)?;
+
+        assert_eq!(brotli_decoded, content);
+
+        return Ok(());
+    }
 }
 
 // export function reportFile(filePath) {
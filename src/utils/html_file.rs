@@ -1,6 +1,7 @@
 use select::document::Document;
 use select::predicate::{Name};
-use regex::Regex;
+use regex::{Captures, Regex};
+use std::collections::HashMap;
 
 pub fn find_internal_assets_from_html(document: &Document) -> (Vec<String>, Vec<String>) {
     let script_tags = document.find(Name("script")).fold(Vec::new(), |mut result, node| {
@@ -34,9 +35,33 @@ pub fn uri_is_external(reference: &str) -> bool {
         Regex::new(r"(?i)^(?:[a-z]+:)?//").unwrap().is_match(&reference);
 }
 
-// pub fn rewrite_html_assets(document: &Document, asset_map: HashMap<String, String>) {
-
-// }
+// NOTE: integrities are keyed by the src/href written in the html, tags that already have an integrity are left as they are
+pub fn add_integrity_attributes(html: &str, integrities: &HashMap<String, String>, crossorigin: &str) -> String {
+    let tag_regex = Regex::new(r#"(?is)<(script|link)\b[^>]*?(/?)>"#).unwrap();
+    let reference_regex = Regex::new(r#"(?is)\s(?:src|href)\s*=\s*["']?([^"'\s>]+)"#).unwrap();
+
+    return tag_regex.replace_all(html, |captures: &Captures| {
+        let tag = &captures[0];
+        let integrity = reference_regex.captures(tag)
+            .and_then(|reference| integrities.get(&reference[1]));
+
+        return match integrity {
+            Some(integrity) if !tag.to_lowercase().contains("integrity=") => {
+                let insert_index = tag.len() - captures[2].len() - 1;
+                let crossorigin_attribute = match tag.to_lowercase().contains("crossorigin") {
+                    true => String::new(),
+                    false => format!(" crossorigin=\"{}\"", crossorigin)
+                };
+
+                format!(
+                    "{} integrity=\"{}\"{}{}",
+                    tag[..insert_index].trim_end(), integrity, crossorigin_attribute, &tag[insert_index..]
+                )
+            },
+            _ => tag.to_string()
+        };
+    }).to_string();
+}
 
 #[cfg(test)]
 mod tests {
@@ -178,4 +203,26 @@ mod tests {
         assert_eq!(uri_is_external("HTTP://WWW.IZELNAKRI.COM/VENDOR.JS"), true);
         assert_eq!(uri_is_external("//US1.CDNJS.COM/UNDERSCORE.JS"), true);
     }
+
+    #[test]
+    fn add_integrity_attributes_works() {
+        let html = r##"
+          <link rel="stylesheet" href="/assets/application-abc.css">
+          <link rel="icon" href="/favicon.ico" />
+          <script src="/assets/vendor-def.js"></script>
+          <script src="/assets/application.js" integrity="sha256-kept"></script>
+          <script src="https://markets.live/vendor.js"></script>
+        "##;
+        let integrities = [
+            ("/assets/application-abc.css", "sha384-css"), ("/assets/vendor-def.js", "sha384-js"), ("/assets/application.js", "sha384-new")
+        ].iter().map(|(reference, integrity)| (reference.to_string(), integrity.to_string())).collect();
+
+        assert_eq!(add_integrity_attributes(html, &integrities, "anonymous"), r##"
+          <link rel="stylesheet" href="/assets/application-abc.css" integrity="sha384-css" crossorigin="anonymous">
+          <link rel="icon" href="/favicon.ico" />
+          <script src="/assets/vendor-def.js" integrity="sha384-js" crossorigin="anonymous"></script>
+          <script src="/assets/application.js" integrity="sha256-kept"></script>
+          <script src="https://markets.live/vendor.js"></script>
+        "##);
+    }
 }
@@ -2,27 +2,36 @@ use std::path::PathBuf;
 use std::io;
 use std::fs;
 use std::env;
-use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
 use yansi::Paint;
+use super::types::MberError;
 
 pub mod console;
+pub mod digest;
 pub mod file;
 pub mod html_file;
 pub mod project;
 pub mod recursive_file_lookup;
 pub mod say;
+pub mod source_map;
+pub mod transpile_cache;
 pub mod walk_injection;
 
-pub fn find_project_root() -> PathBuf {
-    let mut path = project::in_parent_directories(&env::current_dir().unwrap(), "package.json").unwrap_or_else(|| {
-        console::error("you are not on a frontend project! Change your directory");
-
-        process::exit(1);
-    });
+pub fn find_project_root() -> Result<PathBuf, MberError> {
+    let current_directory = env::current_dir().map_err(MberError::io("could not read the current directory", "."))?;
+    let mut path = project::in_parent_directories(&current_directory, "package.json")
+        .ok_or_else(|| MberError::MissingProject { directory: current_directory.clone() })?;
 
     path.pop();
 
-    return path;
+    return Ok(path);
+}
+
+// NOTE: read once into Config.build_timestamp, SOURCE_DATE_EPOCH replaces the current time in build outputs, see https://reproducible-builds.org/specs/source-date-epoch/
+pub fn build_timestamp() -> u64 {
+    return env::var("SOURCE_DATE_EPOCH").ok()
+        .and_then(|epoch| epoch.trim().parse::<u64>().ok())
+        .unwrap_or_else(|| SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_secs()));
 }
 
 pub fn write_file_if_not_exists(file_path: String, content: &str, project_root: &PathBuf) -> io::Result<()> { // TODO: add Future
@@ -56,7 +65,7 @@ mod tests {
 
         env::set_current_dir(&project_directory)?;
 
-        assert_eq!(find_project_root(), PathBuf::from(project_directory));
+        assert_eq!(find_project_root().unwrap(), PathBuf::from(project_directory));
 
         env::set_current_dir(&current_directory)?;
 
@@ -71,7 +80,7 @@ mod tests {
 
         env::set_current_dir(&mocked_directory)?;
 
-        assert_eq!(find_project_root(), PathBuf::from(project_directory));
+        assert_eq!(find_project_root().unwrap(), PathBuf::from(project_directory));
 
         env::set_current_dir(&current_directory)?;
 
@@ -86,7 +95,7 @@ mod tests {
 
         env::set_current_dir(&mocked_directory)?;
 
-        assert_eq!(find_project_root(), PathBuf::from(project_directory));
+        assert_eq!(find_project_root().unwrap(), PathBuf::from(project_directory));
 
         env::set_current_dir(&current_directory)?;
 
@@ -1,12 +1,12 @@
 // NOTE: benchmark compare this one with tokio/mio!!
-use std::error::Error;
+use std::io;
 use std::fs;
-use std::str::FromStr;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
+use super::super::types::MberError;
 
 pub fn in_parent_directories(starting_directory: &Path, target_file_with_extension: &str) -> Option<PathBuf> {
-    let target_path = PathBuf::from_str(format!("{}/{}", starting_directory.to_str().unwrap(), target_file_with_extension).as_str()).unwrap();
+    let target_path = starting_directory.join(target_file_with_extension);
 
     return search_in_directory(target_path, &target_file_with_extension);
 }
@@ -23,16 +23,20 @@ fn search_in_directory(mut path: PathBuf, target_file_with_extension: &str) -> O
     return None;
 }
 
-pub fn recursively_copy_folder(folder_path: String, target_path: &String) -> Result<(), Box<dyn Error>> {
+pub fn recursively_copy_folder(folder_path: String, target_path: &String) -> Result<(), MberError> {
     fs::create_dir_all(&target_path).unwrap_or_else(|_| {});
 
-    for entry in WalkDir::new(&folder_path).into_iter() {
-        let entry = entry.expect("ENTRY NOT FOUND");
-        let target_path = entry.path().to_str().unwrap().replace(&folder_path, &target_path);
+    for entry in WalkDir::new(&folder_path).sort_by(|a, b| a.file_name().cmp(b.file_name())).into_iter() {
+        let entry = entry.map_err(|error| {
+            let path = error.path().map_or(PathBuf::from(&folder_path), |path| path.to_path_buf());
+
+            return MberError::io("could not read", path)(io::Error::from(error));
+        })?;
+        let target_path = entry.path().to_string_lossy().replace(&folder_path, &target_path);
 
         match entry.file_type().is_dir() {
-            true => { fs::create_dir_all(target_path)?; },
-            false => { fs::copy(entry.path(), target_path)?; }
+            true => { fs::create_dir_all(&target_path).map_err(MberError::io("could not create", &target_path))?; },
+            false => { fs::copy(entry.path(), &target_path).map_err(MberError::io("could not copy to", &target_path))?; }
         }
     }
 
@@ -42,6 +46,7 @@ pub fn recursively_copy_folder(folder_path: String, target_path: &String) -> Res
 #[cfg(test)]
 mod tests {
     use std::io;
+    use std::error::Error;
     use std::fs;
     use std::ffi::OsString;
     use super::*;
@@ -1,28 +1,30 @@
+use std::io;
 use std::path::{Path, PathBuf};
 use std::iter::Iterator;
 use walkdir::{DirEntry, WalkDir};
+use super::super::types::MberError;
 
-pub fn lookup_for_extensions(directory: &Path, extensions: Vec<&str>) -> Vec<PathBuf> {
-    return WalkDir::new(directory).into_iter().filter_map(|e| {
-        let entry = e.unwrap();
-
-        return match &extensions.iter().any(|extension| entry.file_name().to_str().unwrap().ends_with(extension)) {
-            true => Some(entry.into_path()),
-            false => None
-        };
-    }).collect();
+pub fn lookup_for_extensions(directory: &Path, extensions: Vec<&str>) -> Result<Vec<PathBuf>, MberError> {
+    return lookup_for_extensions_and_predicate(directory, extensions, |_| true);
 }
 
-pub fn lookup_for_extensions_and_predicate<F>(directory: &Path, extensions: Vec<&str>, filter: F) -> Vec<PathBuf>
+// NOTE: entries are sorted by name, so bundles get the same module order on every file system
+pub fn lookup_for_extensions_and_predicate<F>(directory: &Path, extensions: Vec<&str>, filter: F) -> Result<Vec<PathBuf>, MberError>
     where F: Fn(&DirEntry) -> bool {
-    return WalkDir::new(directory).into_iter().filter_map(|e| {
-        let entry = e.unwrap();
+    return WalkDir::new(directory).sort_by(|a, b| a.file_name().cmp(b.file_name())).into_iter().filter_map(|e| {
+        let entry = match e {
+            Ok(entry) => entry,
+            Err(error) => {
+                let path = error.path().unwrap_or(directory).to_path_buf();
+
+                return Some(Err(MberError::io("could not read", path)(io::Error::from(error))));
+            }
+        };
         let entry_correct_extension = extensions.iter()
-            .any(|extension| entry.file_name().to_str().unwrap().ends_with(extension));
-
+            .any(|extension| entry.file_name().to_string_lossy().ends_with(extension));
 
         return match entry_correct_extension && filter(&entry) {
-            true => Some(entry.into_path()),
+            true => Some(Ok(entry.into_path())),
             false => None
         };
     }).collect();
@@ -62,19 +64,19 @@ mod tests {
         let online_shop_directory = Path::new("online-shop");
         let shoes_directory = Path::new("online-shop/shoes");
         let shoe_directory = Path::new("online-shop/shoes/shoe");
-        let online_shop_js_files: Vec<String> = lookup_for_extensions(&online_shop_directory, vec!["js"])
+        let online_shop_js_files: Vec<String> = lookup_for_extensions(&online_shop_directory, vec!["js"])?
             .into_iter().map(|x| x.to_str().unwrap().to_string()).collect();
-        let online_shop_hbs_files: Vec<String> = lookup_for_extensions(&online_shop_directory, vec!["hbs"])
+        let online_shop_hbs_files: Vec<String> = lookup_for_extensions(&online_shop_directory, vec!["hbs"])?
             .into_iter().map(|x| x.to_str().unwrap().to_string()).collect();
-        let online_shop_files: Vec<String> = lookup_for_extensions(&online_shop_directory, vec!["hbs", "js"])
+        let online_shop_files: Vec<String> = lookup_for_extensions(&online_shop_directory, vec!["hbs", "js"])?
             .into_iter().map(|x| x.to_str().unwrap().to_string()).collect();
-        let shoes_js_files: Vec<String> = lookup_for_extensions(&shoes_directory, vec!["js"])
+        let shoes_js_files: Vec<String> = lookup_for_extensions(&shoes_directory, vec!["js"])?
             .into_iter().map(|x| x.to_str().unwrap().to_string()).collect();
-        let shoes_hbs_files: Vec<String> = lookup_for_extensions(&shoes_directory, vec!["hbs"])
+        let shoes_hbs_files: Vec<String> = lookup_for_extensions(&shoes_directory, vec!["hbs"])?
             .into_iter().map(|x| x.to_str().unwrap().to_string()).collect();
-        let shoes_files: Vec<String> = lookup_for_extensions(&shoes_directory, vec!["js", "hbs"])
+        let shoes_files: Vec<String> = lookup_for_extensions(&shoes_directory, vec!["js", "hbs"])?
             .into_iter().map(|x| x.to_str().unwrap().to_string()).collect();
-        let shoe_files: Vec<String> = lookup_for_extensions(&shoe_directory, vec!["js", "hbs"])
+        let shoe_files: Vec<String> = lookup_for_extensions(&shoe_directory, vec!["js", "hbs"])?
             .into_iter().map(|x| x.to_str().unwrap().to_string()).collect();
 
         vec![
@@ -113,8 +115,8 @@ mod tests {
         setup()?;
 
         let shoe_directory = Path::new("online-shop/shoes/shoe");
-        let shoe_hbs_files = lookup_for_extensions(shoe_directory, vec!["hbs"]);
-        let online_shop_txt_files = lookup_for_extensions(shoe_directory, vec!["txt"]);
+        let shoe_hbs_files = lookup_for_extensions(shoe_directory, vec!["hbs"])?;
+        let online_shop_txt_files = lookup_for_extensions(shoe_directory, vec!["txt"])?;
         let empty_array: Vec<&str> = Vec::new();
 
         assert_eq!(
@@ -129,6 +131,31 @@ mod tests {
         return fs::remove_dir_all("online-shop");
     }
 
+    #[test]
+    fn lookup_for_extensions_returns_files_in_sorted_order() -> io::Result<()> {
+        setup()?;
+
+        let online_shop_js_files = lookup_for_extensions(Path::new("online-shop"), vec!["js"])?;
+
+        assert_eq!(
+            online_shop_js_files.iter().map(|x| x.to_str().unwrap()).collect::<Vec<&str>>(),
+            vec![
+                "online-shop/details.js", "online-shop/index.js", "online-shop/shoes/brown.js", "online-shop/shoes/index.js",
+                "online-shop/shoes/shoe/brown.js", "online-shop/shoes/shoe.js"
+            ]
+        );
+
+        return fs::remove_dir_all("online-shop");
+    }
+
+    #[test]
+    fn lookup_for_extensions_returns_an_error_for_a_missing_directory() {
+        let error = lookup_for_extensions(Path::new("missing-online-shop"), vec!["js"]).unwrap_err();
+
+        assert_eq!(error.path(), Path::new("missing-online-shop"));
+        assert!(error.to_string().starts_with("could not read missing-online-shop: "));
+    }
+
     #[test]
     fn lookup_for_extensions_and_predicate_works_for_js_and_hbs() -> io::Result<()> {
         setup()?;
@@ -137,25 +164,25 @@ mod tests {
         let shoes_directory = Path::new("online-shop/shoes");
         let online_shop_js_files = lookup_for_extensions_and_predicate(online_shop_directory, vec!["js"], |e: &DirEntry| {
             return e.file_name().to_str().unwrap().ends_with("brown.js");
-        });
+        })?;
         let online_shop_files = lookup_for_extensions_and_predicate(online_shop_directory, vec!["hbs", "js"], |e| {
             let file_name = e.file_name().to_str().unwrap();
 
             return file_name.ends_with("brown.js") || file_name.ends_with("details.hbs");
-        });
-        let shoes_js_files = lookup_for_extensions_and_predicate(shoes_directory, vec!["js"], |_| false);
+        })?;
+        let shoes_js_files = lookup_for_extensions_and_predicate(shoes_directory, vec!["js"], |_| false)?;
         let empty_array: Vec<&str> = Vec::new();
 
         assert_eq!(
             online_shop_js_files.iter().map(|x| x.to_str().unwrap()).collect::<Vec<&str>>(),
             vec![
-                "online-shop/shoes/shoe/brown.js", "online-shop/shoes/brown.js"
+                "online-shop/shoes/brown.js", "online-shop/shoes/shoe/brown.js"
             ]
         );
         assert_eq!(
             online_shop_files.iter().map(|x| x.to_str().unwrap()).collect::<Vec<&str>>(),
             vec![
-                "online-shop/shoes/shoe/brown.js", "online-shop/shoes/brown.js", "online-shop/details.hbs"
+                "online-shop/details.hbs", "online-shop/shoes/brown.js", "online-shop/shoes/shoe/brown.js"
             ]
         );
         assert_eq!(
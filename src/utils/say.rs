@@ -2,17 +2,14 @@ use std::process::{Command, Stdio};
 
 pub fn speak(text: &str) {
     if tts_program_in_path("festival") {
-        let text = Command::new("echo")
-            .arg(text)
-            .stdout(Stdio::piped())
-            .spawn()
-            .expect("");
+        // NOTE: speaking is best effort, a failing tts program should never break a build
+        let text = Command::new("echo").arg(text).stdout(Stdio::piped()).spawn();
 
-        Command::new("festival")
-            .args(&["--tts"])
-            .stdin(text.stdout.unwrap())
-            .spawn()
-            .expect("");
+        if let Some(stdout) = text.ok().and_then(|text| text.stdout) {
+            if Command::new("festival").args(&["--tts"]).stdin(stdout).spawn().is_err() {
+                println!("\007");
+            }
+        }
     } else {
         println!("\007");
     }
@@ -0,0 +1,498 @@
+use std::collections::HashMap;
+use std::error::Error;
+use serde_json::{json, Value};
+
+const BASE64_CHARACTERS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+// NOTE: lines and columns are 0 based like the source map format, columns count characters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Mapping {
+    pub generated_line: usize,
+    pub generated_column: usize,
+    pub source: usize,
+    pub original_line: usize,
+    pub original_column: usize,
+    pub name: Option<usize>
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceMap {
+    pub file: String,
+    pub sources: Vec<String>,
+    pub sources_content: Vec<Option<String>>,
+    pub names: Vec<String>,
+    pub mappings: Vec<Mapping>
+}
+
+impl SourceMap {
+    pub fn new(file: &str) -> Self {
+        return SourceMap { file: file.to_string(), ..SourceMap::default() };
+    }
+
+    pub fn add_source(&mut self, source: &str, content: Option<&str>) -> usize {
+        if let Some(index) = self.sources.iter().position(|existing_source| existing_source == source) {
+            return index;
+        }
+
+        self.sources.push(source.to_string());
+        self.sources_content.push(content.map(String::from));
+
+        return self.sources.len() - 1;
+    }
+
+    pub fn add_name(&mut self, name: &str) -> usize {
+        if let Some(index) = self.names.iter().position(|existing_name| existing_name == name) {
+            return index;
+        }
+
+        self.names.push(name.to_string());
+
+        return self.names.len() - 1;
+    }
+
+    // NOTE: sources resolve from the site root so browsers list them as /src/..., the contents are embedded
+    pub fn to_json(&self) -> String {
+        return json!({
+            "version": 3,
+            "file": self.file,
+            "sourceRoot": "/",
+            "sources": self.sources,
+            "sourcesContent": self.sources_content,
+            "names": self.names,
+            "mappings": encode_mappings(&self.mappings)
+        }).to_string();
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, Box<dyn Error>> {
+        let value: Value = serde_json::from_str(json)?;
+        let strings = |key: &str| -> Vec<Option<String>> {
+            return value[key].as_array().map(|items| {
+                return items.iter().map(|item| item.as_str().map(String::from)).collect();
+            }).unwrap_or_default();
+        };
+        let sources = strings("sources").into_iter().map(Option::unwrap_or_default).collect::<Vec<String>>();
+        let mut sources_content = strings("sourcesContent");
+
+        sources_content.resize(sources.len(), None);
+
+        return Ok(SourceMap {
+            file: value["file"].as_str().unwrap_or("").to_string(),
+            sources: sources,
+            sources_content: sources_content,
+            names: strings("names").into_iter().map(Option::unwrap_or_default).collect(),
+            mappings: decode_mappings(value["mappings"].as_str().unwrap_or(""))?
+        });
+    }
+
+    // NOTE: self maps a generated file to the output of another step, the result skips that step and maps straight
+    // to the sources of the inner map. Positions without an inner mapping are dropped
+    pub fn compose(&self, inner: &SourceMap) -> SourceMap {
+        let mut inner_lines: Vec<Vec<&Mapping>> = Vec::new();
+
+        for mapping in inner.mappings.iter() {
+            if inner_lines.len() <= mapping.generated_line {
+                inner_lines.resize(mapping.generated_line + 1, Vec::new());
+            }
+
+            inner_lines[mapping.generated_line].push(mapping);
+        }
+
+        inner_lines.iter_mut().for_each(|line| line.sort_by_key(|mapping| mapping.generated_column));
+
+        let mut result = SourceMap {
+            file: self.file.clone(),
+            sources: inner.sources.clone(),
+            sources_content: inner.sources_content.clone(),
+            ..SourceMap::default()
+        };
+        let mut name_indexes: HashMap<&str, usize> = HashMap::new();
+
+        for mapping in self.mappings.iter() {
+            let inner_mapping = inner_lines.get(mapping.original_line).and_then(|line| {
+                return line.iter().rev().find(|inner_mapping| inner_mapping.generated_column <= mapping.original_column);
+            });
+
+            if let Some(inner_mapping) = inner_mapping {
+                let names = &mut result.names;
+                let name = mapping.name.and_then(|name| self.names.get(name))
+                    .or_else(|| inner_mapping.name.and_then(|name| inner.names.get(name)))
+                    .map(|name| *name_indexes.entry(name.as_str()).or_insert_with(|| {
+                        names.push(name.clone());
+
+                        return names.len() - 1;
+                    }));
+
+                result.mappings.push(Mapping {
+                    generated_line: mapping.generated_line,
+                    generated_column: mapping.generated_column,
+                    source: inner_mapping.source,
+                    original_line: inner_mapping.original_line,
+                    original_column: inner_mapping.original_column + mapping.original_column - inner_mapping.generated_column,
+                    name: name
+                });
+            }
+        }
+
+        return result;
+    }
+}
+
+// NOTE: joins generated code chunks while keeping track of where each chunk lands in the output
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    pub code: String,
+    pub map: SourceMap,
+    line: usize,
+    column: usize
+}
+
+impl Bundle {
+    pub fn new(file: &str) -> Self {
+        return Bundle { code: String::new(), map: SourceMap::new(file), line: 0, column: 0 };
+    }
+
+    // NOTE: glue code that does not come from any source file
+    pub fn push(&mut self, code: &str) {
+        self.code.push_str(code);
+
+        let (line, column) = advance((self.line, self.column), code);
+
+        self.line = line;
+        self.column = column;
+    }
+
+    // NOTE: every line of code maps to the same line of the source, transpiled modules keep their line numbers.
+    // Rewritten lines like exports also map the part they share with the end of the source line
+    pub fn push_source(&mut self, code: &str, source: &str, content: &str) {
+        let source_index = self.map.add_source(source, Some(content));
+
+        for ((index, line), source_line) in code.split('\n').enumerate().zip(content.split('\n')) {
+            let generated_column = if index == 0 { self.column } else { 0 };
+
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            self.map.mappings.push(Mapping {
+                generated_line: self.line + index,
+                generated_column: generated_column,
+                source: source_index,
+                original_line: index,
+                original_column: 0,
+                name: None
+            });
+
+            let shared_length = line.chars().rev().zip(source_line.trim_end_matches('\r').chars().rev())
+                .take_while(|(character, source_character)| character == source_character)
+                .count();
+            let (line_length, source_line_length) = (count_characters(line), count_characters(source_line.trim_end_matches('\r')));
+
+            if shared_length > 0 && shared_length < line_length && line_length - shared_length != source_line_length - shared_length {
+                self.map.mappings.push(Mapping {
+                    generated_line: self.line + index,
+                    generated_column: generated_column + line_length - shared_length,
+                    source: source_index,
+                    original_line: index,
+                    original_column: source_line_length - shared_length,
+                    name: None
+                });
+            }
+        }
+
+        self.push(code);
+    }
+
+    // NOTE: every line of code maps to the start of the source, for outputs like precompiled templates
+    pub fn push_compiled(&mut self, code: &str, source: &str, content: &str) {
+        let source_index = self.map.add_source(source, Some(content));
+
+        for (index, _) in code.split('\n').enumerate() {
+            self.map.mappings.push(Mapping {
+                generated_line: self.line + index,
+                generated_column: if index == 0 { self.column } else { 0 },
+                source: source_index,
+                original_line: 0,
+                original_column: 0,
+                name: None
+            });
+        }
+
+        self.push(code);
+    }
+
+    // NOTE: code with its own map, like compiled css, its mappings get shifted to where the code lands
+    pub fn push_mapped(&mut self, code: &str, map: &SourceMap) {
+        let source_indexes = map.sources.iter().zip(map.sources_content.iter())
+            .map(|(source, content)| self.map.add_source(source, content.as_ref().map(String::as_str)))
+            .collect::<Vec<usize>>();
+        let name_indexes = map.names.iter().map(|name| self.map.add_name(name)).collect::<Vec<usize>>();
+
+        for mapping in map.mappings.iter() {
+            self.map.mappings.push(Mapping {
+                generated_line: self.line + mapping.generated_line,
+                generated_column: match mapping.generated_line {
+                    0 => self.column + mapping.generated_column,
+                    _ => mapping.generated_column
+                },
+                source: source_indexes[mapping.source],
+                name: mapping.name.map(|name| name_indexes[name]),
+                ..*mapping
+            });
+        }
+
+        self.push(code);
+    }
+}
+
+// NOTE: returns the (line, column) right after the code when it starts at the given position
+pub fn advance(position: (usize, usize), code: &str) -> (usize, usize) {
+    return match code.rfind('\n') {
+        Some(index) => (position.0 + code.matches('\n').count(), count_characters(&code[index + 1..])),
+        None => (position.0, position.1 + count_characters(code))
+    };
+}
+
+fn count_characters(text: &str) -> usize {
+    return match text.is_ascii() {
+        true => text.len(),
+        false => text.chars().count()
+    };
+}
+
+pub fn encode_mappings(mappings: &[Mapping]) -> String {
+    let mut sorted_mappings = mappings.to_vec();
+    let mut output = String::with_capacity(mappings.len() * 6);
+    let mut line = 0;
+    let (mut previous_source, mut previous_original_line, mut previous_original_column, mut previous_name) = (0, 0, 0, 0);
+    let mut previous_column = 0;
+    let mut is_first_in_line = true;
+
+    sorted_mappings.sort_by_key(|mapping| (mapping.generated_line, mapping.generated_column));
+
+    for mapping in sorted_mappings.iter() {
+        while line < mapping.generated_line {
+            output.push(';');
+            line += 1;
+            previous_column = 0;
+            is_first_in_line = true;
+        }
+
+        if !is_first_in_line {
+            output.push(',');
+        }
+
+        encode_vlq(&mut output, mapping.generated_column as i64 - previous_column);
+        encode_vlq(&mut output, mapping.source as i64 - previous_source);
+        encode_vlq(&mut output, mapping.original_line as i64 - previous_original_line);
+        encode_vlq(&mut output, mapping.original_column as i64 - previous_original_column);
+
+        if let Some(name) = mapping.name {
+            encode_vlq(&mut output, name as i64 - previous_name);
+            previous_name = name as i64;
+        }
+
+        previous_column = mapping.generated_column as i64;
+        previous_source = mapping.source as i64;
+        previous_original_line = mapping.original_line as i64;
+        previous_original_column = mapping.original_column as i64;
+        is_first_in_line = false;
+    }
+
+    return output;
+}
+
+// NOTE: segments without a source position carry no information for composing, they are skipped
+pub fn decode_mappings(mappings: &str) -> Result<Vec<Mapping>, Box<dyn Error>> {
+    let mut result = Vec::new();
+    let mut previous = [0i64; 5];
+
+    for (line, line_mappings) in mappings.split(';').enumerate() {
+        previous[0] = 0;
+
+        for segment in line_mappings.split(',').filter(|segment| !segment.is_empty()) {
+            let fields = decode_vlq(segment)?;
+
+            for (index, field) in fields.iter().enumerate() {
+                previous[index] += field;
+            }
+
+            if previous.iter().any(|field| *field < 0) {
+                return Err(format!("source map segment {} points to a negative position", segment).into());
+            } else if fields.len() >= 4 {
+                result.push(Mapping {
+                    generated_line: line,
+                    generated_column: previous[0] as usize,
+                    source: previous[1] as usize,
+                    original_line: previous[2] as usize,
+                    original_column: previous[3] as usize,
+                    name: if fields.len() >= 5 { Some(previous[4] as usize) } else { None }
+                });
+            }
+        }
+    }
+
+    return Ok(result);
+}
+
+fn encode_vlq(output: &mut String, value: i64) {
+    let mut remaining = if value < 0 { ((-value) << 1) | 1 } else { value << 1 };
+
+    loop {
+        let mut digit = remaining & 31;
+
+        remaining >>= 5;
+
+        if remaining > 0 {
+            digit |= 32;
+        }
+
+        output.push(BASE64_CHARACTERS[digit as usize] as char);
+
+        if remaining == 0 {
+            return;
+        }
+    }
+}
+
+fn decode_vlq(segment: &str) -> Result<Vec<i64>, Box<dyn Error>> {
+    let mut fields = Vec::new();
+    let (mut value, mut shift) = (0i64, 0);
+
+    for character in segment.bytes() {
+        let digit = BASE64_CHARACTERS.iter().position(|base64_character| *base64_character == character)
+            .ok_or_else(|| format!("source map segment {} has an invalid character", segment))? as i64;
+
+        value += (digit & 31) << shift;
+
+        if digit & 32 != 0 {
+            shift += 5;
+
+            continue;
+        }
+
+        fields.push(if value & 1 == 1 { -(value >> 1) } else { value >> 1 });
+        value = 0;
+        shift = 0;
+    }
+
+    if shift != 0 || fields.len() > 5 {
+        return Err(format!("source map segment {} is incomplete", segment).into());
+    }
+
+    return Ok(fields);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(generated: (usize, usize), source: usize, original: (usize, usize), name: Option<usize>) -> Mapping {
+        return Mapping {
+            generated_line: generated.0,
+            generated_column: generated.1,
+            source: source,
+            original_line: original.0,
+            original_column: original.1,
+            name: name
+        };
+    }
+
+    #[test]
+    fn encode_and_decode_mappings_work() -> Result<(), Box<dyn Error>> {
+        let mappings = vec![
+            mapping((0, 0), 0, (0, 0), None),
+            mapping((0, 9), 0, (0, 13), Some(0)),
+            mapping((2, 4), 1, (120, 2), None),
+            mapping((2, 1000), 0, (3, 0), Some(1))
+        ];
+        let encoded_mappings = encode_mappings(&mappings);
+
+        assert_eq!(encode_mappings(&mappings[..2]), "AAAA,SAAaA");
+        assert_eq!(encoded_mappings, "AAAA,SAAaA;;ICwHX,o+BDrHFC");
+        assert_eq!(decode_mappings(&encoded_mappings)?, mappings);
+        assert!(decode_mappings("AAA!").is_err());
+        assert!(decode_mappings("AAAg").is_err());
+
+        return Ok(());
+    }
+
+    #[test]
+    fn to_json_and_from_json_work() -> Result<(), Box<dyn Error>> {
+        let mut map = SourceMap::new("application.js");
+
+        assert_eq!(map.add_source("src/app.js", Some("export default 1;")), 0);
+        assert_eq!(map.add_source("src/router.js", None), 1);
+        assert_eq!(map.add_source("src/app.js", Some("export default 1;")), 0);
+        assert_eq!(map.add_name("counter"), 0);
+
+        map.mappings.push(mapping((1, 2), 1, (3, 4), Some(0)));
+
+        let json: Value = serde_json::from_str(&map.to_json())?;
+
+        assert_eq!(json, json!({
+            "version": 3,
+            "file": "application.js",
+            "sourceRoot": "/",
+            "sources": ["src/app.js", "src/router.js"],
+            "sourcesContent": ["export default 1;", null],
+            "names": ["counter"],
+            "mappings": ";ECGIA"
+        }));
+        assert_eq!(SourceMap::from_json(&map.to_json())?, map);
+
+        return Ok(());
+    }
+
+    #[test]
+    fn bundle_maps_every_chunk_to_its_source() {
+        let mut bundle = Bundle::new("application.js");
+
+        bundle.push("define = window.define; ");
+        bundle.push_source("define('app', [], function() {\n  _exports.default = App;\n\n});", "src/app.js", "import App from 'app';\nexport default App;\n");
+        bundle.push("\n");
+        bundle.push_compiled("define('app/template', [], function() {});", "src/template.hbs", "<h1>Hi</h1>");
+
+        let mut css_map = SourceMap::new("application.css");
+
+        css_map.add_source("src/ui/styles/application.scss", Some("h1 { color: red; }"));
+        css_map.mappings.push(mapping((0, 3), 0, (0, 0), None));
+        bundle.push(" ");
+        bundle.push_mapped("h1{color:red}", &css_map);
+
+        assert_eq!(bundle.code, vec![
+            "define = window.define; define('app', [], function() {\n  _exports.default = App;\n\n});\n",
+            "define('app/template', [], function() {}); h1{color:red}"
+        ].join(""));
+        assert_eq!(bundle.map.sources, vec!["src/app.js", "src/template.hbs", "src/ui/styles/application.scss"]);
+        assert_eq!(bundle.map.mappings, vec![
+            mapping((0, 24), 0, (0, 0), None),
+            mapping((1, 0), 0, (1, 0), None),
+            mapping((1, 20), 0, (1, 14), None),
+            mapping((4, 0), 1, (0, 0), None),
+            mapping((4, 46), 2, (0, 0), None)
+        ]);
+        assert_eq!(advance((4, 3), "ab\ncd\néf"), (6, 2));
+        assert_eq!(advance((4, 3), "éf"), (4, 5));
+    }
+
+    #[test]
+    fn compose_maps_through_the_inner_map() {
+        let mut inner = SourceMap::new("application.js");
+        let mut outer = SourceMap::new("application.js");
+
+        inner.add_source("src/app.js", Some("let counter = 1;"));
+        inner.mappings.push(mapping((3, 4), 0, (0, 0), None));
+        outer.add_source("application.js", None);
+        outer.add_name("counter");
+        outer.mappings.push(mapping((0, 0), 0, (3, 8), Some(0)));
+        outer.mappings.push(mapping((0, 5), 0, (1, 0), None));
+        outer.mappings.push(mapping((0, 9), 0, (3, 2), None));
+
+        let composed_map = outer.compose(&inner);
+
+        assert_eq!(composed_map.sources, vec!["src/app.js"]);
+        assert_eq!(composed_map.sources_content, vec![Some(String::from("let counter = 1;"))]);
+        assert_eq!(composed_map.names, vec!["counter"]);
+        assert_eq!(composed_map.mappings, vec![mapping((0, 0), 0, (0, 4), Some(0))]);
+    }
+}
@@ -0,0 +1,275 @@
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+use walkdir::WalkDir;
+
+// NOTE: minified bundles are cached too, vendor.js alone is a few megabytes so old entries get pruned past this size
+const MAX_SIZE: u64 = 256 * 1024 * 1024;
+
+static HITS: AtomicUsize = AtomicUsize::new(0);
+static MISSES: AtomicUsize = AtomicUsize::new(0);
+static WRITES: AtomicUsize = AtomicUsize::new(0);
+static DIRECTORY: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CacheStats {
+    pub entries: u64,
+    pub size: u64
+}
+
+// NOTE: builds use the cache_directory of their Config, everything else falls back to default_directory()
+pub fn directory() -> PathBuf {
+    return DIRECTORY.read().ok()
+        .and_then(|directory| directory.clone())
+        .unwrap_or_else(default_directory);
+}
+
+pub fn set_directory(directory: &Path) {
+    if let Ok(mut current_directory) = DIRECTORY.write() {
+        *current_directory = Some(directory.to_path_buf());
+    }
+}
+
+// NOTE: lives outside of tmp/ since every build removes tmp/. MBER_CACHE_DIR overrides the location, the unit tests
+// of this crate default to a temporary directory and spawned mber processes get MBER_CACHE_DIR from the test helpers
+pub fn default_directory() -> PathBuf {
+    if let Some(directory) = env::var_os("MBER_CACHE_DIR") {
+        return PathBuf::from(directory);
+    } else if cfg!(test) {
+        return env::temp_dir().join("mber-cache-test");
+    } else if let Some(directory) = env::var_os("XDG_CACHE_HOME") {
+        return PathBuf::from(directory);
+    } else if let Some(directory) = env::var_os("XDG_CACHE_HOME") {
+        return PathBuf::from(directory).join("mber");
+    }
+
+    return match env::var_os("HOME") {
+        Some(home) => PathBuf::from(home).join(".cache").join("mber"),
+        None => env::temp_dir().join("mber-cache")
+    };
+}
+
+// NOTE: transpiler_id changes whenever the transpiler itself changes, options hold the module name and minify flag
+pub fn key(transpiler_id: &str, options: &str, source: &str) -> String {
+    let mut context = md5::Context::new();
+
+    context.consume(transpiler_id.as_bytes());
+    context.consume(b"\0");
+    context.consume(options.as_bytes());
+    context.consume(b"\0");
+    context.consume(source.as_bytes());
+
+    return format!("{:x}", context.compute());
+}
+
+pub fn get(key: &str) -> Option<String> {
+    let output = read(&directory(), key);
+
+    match output {
+        Some(_) => HITS.fetch_add(1, Ordering::SeqCst),
+        None => MISSES.fetch_add(1, Ordering::SeqCst)
+    };
+
+    return output;
+}
+
+// NOTE: the cache is best effort, a failed write only means a cache miss on the next build
+pub fn set(key: &str, output: &str) {
+    write(&directory(), key, output).unwrap_or_else(|_| {});
+}
+
+pub fn fetch<E, F>(transpiler_id: &str, options: &str, source: &str, transpile: F) -> Result<String, E>
+    where F: FnOnce() -> Result<String, E> {
+    let key = key(transpiler_id, options, source);
+
+    if let Some(output) = get(&key) {
+        return Ok(output);
+    }
+
+    let output = transpile()?;
+
+    set(&key, &output);
+
+    return Ok(output);
+}
+
+// NOTE: returns (hits, misses) since the last call
+pub fn take_session_counts() -> (usize, usize) {
+    return (HITS.swap(0, Ordering::SeqCst), MISSES.swap(0, Ordering::SeqCst));
+}
+
+pub fn stats() -> io::Result<CacheStats> {
+    return stats_in(&directory());
+}
+
+pub fn clean() -> io::Result<CacheStats> {
+    return clean_in(&directory());
+}
+
+// NOTE: returns the removed entries
+pub fn prune() -> io::Result<CacheStats> {
+    return prune_in(&directory(), MAX_SIZE);
+}
+
+fn entry_path(directory: &Path, key: &str) -> PathBuf {
+    return directory.join(&key[..2]).join(key);
+}
+
+// NOTE: a hit bumps the modified time of the entry, prune removes the least recently used entries first
+fn read(directory: &Path, key: &str) -> Option<String> {
+    let path = entry_path(directory, key);
+    let output = fs::read_to_string(&path).ok()?;
+
+    fs::File::options().write(true).open(&path)
+        .and_then(|file| file.set_modified(SystemTime::now()))
+        .unwrap_or_else(|_| {});
+
+    return Some(output);
+}
+
+// NOTE: written to a unique temporary file first, parallel builders never read a half written entry
+fn write(directory: &Path, key: &str, output: &str) -> io::Result<()> {
+    let path = entry_path(directory, key);
+    let temporary_path = path.with_extension(format!("{}-{}.tmp", process::id(), WRITES.fetch_add(1, Ordering::SeqCst)));
+
+    fs::create_dir_all(path.parent().unwrap_or(directory))?;
+    fs::write(&temporary_path, output)?;
+
+    return fs::rename(&temporary_path, &path).or_else(|error| {
+        fs::remove_file(&temporary_path).unwrap_or_else(|_| {});
+
+        return Err(error);
+    });
+}
+
+fn stats_in(directory: &Path) -> io::Result<CacheStats> {
+    if !directory.exists() {
+        return Ok(CacheStats::default());
+    }
+
+    return WalkDir::new(directory).into_iter().try_fold(CacheStats::default(), |stats, entry| {
+        let entry = entry.map_err(|error| io::Error::new(io::ErrorKind::Other, error.to_string()))?;
+
+        if !entry.file_type().is_file() {
+            return Ok(stats);
+        }
+
+        return Ok(CacheStats { entries: stats.entries + 1, size: stats.size + entry.metadata().map(|metadata| metadata.len()).unwrap_or(0) });
+    });
+}
+
+fn prune_in(directory: &Path, max_size: u64) -> io::Result<CacheStats> {
+    if !directory.exists() {
+        return Ok(CacheStats::default());
+    }
+
+    let mut entries = WalkDir::new(directory).into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+
+            return Some((entry.into_path(), metadata.len(), metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH)));
+        })
+        .collect::<Vec<(PathBuf, u64, SystemTime)>>();
+    let mut kept_size = 0;
+
+    entries.sort_by(|(_, _, first), (_, _, second)| second.cmp(first));
+
+    return entries.into_iter().try_fold(CacheStats::default(), |removed, (path, size, _)| {
+        if kept_size + size <= max_size {
+            kept_size += size;
+
+            return Ok(removed);
+        }
+
+        fs::remove_file(&path)?;
+
+        return Ok(CacheStats { entries: removed.entries + 1, size: removed.size + size });
+    });
+}
+
+fn clean_in(directory: &Path) -> io::Result<CacheStats> {
+    let stats = stats_in(directory)?;
+
+    if directory.exists() {
+        fs::remove_dir_all(directory)?;
+    }
+
+    return Ok(stats);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_changes_with_transpiler_options_and_source() {
+        let original = key("es-module", "frontend/src/router|false", "export default 1;");
+
+        assert_eq!(original.len(), 32);
+        assert_eq!(original, key("es-module", "frontend/src/router|false", "export default 1;"));
+        assert_ne!(original, key("hbs-module", "frontend/src/router|false", "export default 1;"));
+        assert_ne!(original, key("es-module", "frontend/src/router|true", "export default 1;"));
+        assert_ne!(original, key("es-module", "other-app/src/router|false", "export default 1;"));
+        assert_ne!(original, key("es-module", "frontend/src/router|false", "export default 2;"));
+        assert_ne!(key("a", "bc", ""), key("ab", "c", ""));
+    }
+
+    #[test]
+    fn read_write_stats_and_clean_work() -> io::Result<()> {
+        let directory = env::temp_dir().join(format!("mber-transpile-cache-test-{}", process::id()));
+        let first_key = key("es-module", "frontend/src/app|false", "export default 1;");
+        let second_key = key("es-module", "frontend/src/router|false", "export default 2;");
+
+        fs::remove_dir_all(&directory).unwrap_or_else(|_| {});
+
+        assert_eq!(stats_in(&directory)?, CacheStats::default());
+        assert_eq!(read(&directory, &first_key), None);
+
+        write(&directory, &first_key, "define('frontend/src/app');")?;
+        write(&directory, &second_key, "define('frontend/src/router');")?;
+        write(&directory, &second_key, "define('frontend/src/router');")?;
+
+        assert_eq!(read(&directory, &first_key), Some(String::from("define('frontend/src/app');")));
+        assert!(directory.join(&first_key[..2]).join(&first_key).exists());
+        assert_eq!(stats_in(&directory)?, CacheStats { entries: 2, size: 57 });
+        assert_eq!(clean_in(&directory)?, CacheStats { entries: 2, size: 57 });
+        assert!(!directory.exists());
+        assert_eq!(clean_in(&directory)?, CacheStats::default());
+
+        return Ok(());
+    }
+
+    #[test]
+    fn prune_removes_least_recently_used_entries_past_the_max_size() -> io::Result<()> {
+        let directory = env::temp_dir().join(format!("mber-transpile-cache-prune-test-{}", process::id()));
+        let keys = ["vendor.js", "application.js", "router.js"].iter()
+            .map(|file_name| key("minify-js", "", file_name))
+            .collect::<Vec<String>>();
+
+        fs::remove_dir_all(&directory).unwrap_or_else(|_| {});
+
+        for (index, key) in keys.iter().enumerate() {
+            write(&directory, key, "0123456789")?;
+            fs::File::options().write(true).open(entry_path(&directory, key))?
+                .set_modified(SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(index as u64 + 1))?;
+        }
+
+        assert_eq!(read(&directory, &keys[0]), Some(String::from("0123456789")));
+        assert_eq!(prune_in(&directory, 30)?, CacheStats::default());
+        assert_eq!(prune_in(&directory, 25)?, CacheStats { entries: 1, size: 10 });
+        assert_eq!(read(&directory, &keys[1]), None);
+        assert_eq!(stats_in(&directory)?, CacheStats { entries: 2, size: 20 });
+        assert_eq!(prune_in(&directory, 0)?, CacheStats { entries: 2, size: 20 });
+
+        clean_in(&directory)?;
+
+        return Ok(());
+    }
+}
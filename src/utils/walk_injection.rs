@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(untagged)]
@@ -8,8 +8,9 @@ pub enum KeyValue {
     RefCell(HashMap<String, KeyValue>),
 }
 
-pub fn flatten_fs_hashmap(fs_hashmap: HashMap<String, KeyValue>, parent_folders: Vec<String>) -> HashMap<String, String> {
-    return fs_hashmap.into_iter().fold(HashMap::new(), |mut result, (key, value)| {
+// NOTE: BTreeMap keeps the paths sorted, so bundles built from the injected files have a stable module order
+pub fn flatten_fs_hashmap(fs_hashmap: HashMap<String, KeyValue>, parent_folders: Vec<String>) -> BTreeMap<String, String> {
+    return fs_hashmap.into_iter().fold(BTreeMap::new(), |mut result, (key, value)| {
         let mut new_parent_folders = parent_folders.to_vec();
 
         match value {
@@ -31,20 +32,20 @@ pub fn flatten_fs_hashmap(fs_hashmap: HashMap<String, KeyValue>, parent_folders:
     });
 }
 
-pub fn flatten_fs_hashmap_in_binary(fs_hashmap: HashMap<String, KeyValue>, parent_folders: Vec<String>) -> HashMap<String, Vec<u8>> {
+pub fn flatten_fs_hashmap_in_binary(fs_hashmap: HashMap<String, KeyValue>, parent_folders: Vec<String>) -> BTreeMap<String, Vec<u8>> {
     return flatten_fs_hashmap(fs_hashmap, parent_folders).into_iter()
         .map(|(key, value)| { return (key, value.into_bytes()); })
         .collect();
 }
 
-pub fn lookup_for_extensions(hashmap: HashMap<String, String>, extensions: Vec<&str>) -> HashMap<String, String> {
+pub fn lookup_for_extensions(hashmap: BTreeMap<String, String>, extensions: Vec<&str>) -> BTreeMap<String, String> {
     return hashmap.into_iter()
         .filter(|(key, _value)| extensions.iter().any(|extension| key.ends_with(extension)))
         .collect();
 }
 
-pub fn lookup_for_extensions_with_predicate<F>(hashmap: HashMap<String, String>, extensions: Vec<&str>, predicate: F)
-    -> HashMap<String, String> where F: Fn(&str) -> bool {
+pub fn lookup_for_extensions_with_predicate<F>(hashmap: BTreeMap<String, String>, extensions: Vec<&str>, predicate: F)
+    -> BTreeMap<String, String> where F: Fn(&str) -> bool {
     return hashmap.into_iter()
         .filter(|(key, _value)| extensions.iter().any(|extension| key.ends_with(extension)) && predicate(key))
         .collect();
@@ -67,7 +68,7 @@ mod tests {
         assert!(result_keys.contains(&"_vendor/mber-documentation/src/ui/components/docs-demo/example/component.js"));
         assert!(result_keys.contains(&"_vendor/mber-documentation/src/ui/components/docs-viewer/template.hbs"));
 
-        let sub_directory: HashMap<String, String> = flat_documentation_hashmap.into_iter()
+        let sub_directory: BTreeMap<String, String> = flat_documentation_hashmap.into_iter()
             .filter(|(key, _)| key.starts_with("_vendor/mber-documentation/src/ui/components/docs-viewer/navigation"))
             .collect();
         let sub_directory_result = lookup_for_extensions(sub_directory.clone(), vec!["js", "hbs"]);
@@ -94,11 +95,11 @@ mod tests {
     fn lookup_for_extensions_works_when_there_are_no_reference_files() {
         let documentation_hashmap: HashMap<String, KeyValue> = serde_json::from_str(documentation::as_str()).unwrap();
         let flat_documentation_hashmap = flatten_fs_hashmap(documentation_hashmap, vec![]);
-        let sub_directory: HashMap<String, String> = flat_documentation_hashmap.into_iter()
+        let sub_directory: BTreeMap<String, String> = flat_documentation_hashmap.into_iter()
             .filter(|(key, _)| key.starts_with("_vendor/mber-documentation/vendor"))
             .collect();
 
-        assert_eq!(lookup_for_extensions(sub_directory.clone(), vec!["hbs"]), HashMap::new());
+        assert_eq!(lookup_for_extensions(sub_directory.clone(), vec!["hbs"]), BTreeMap::new());
     }
 
     #[test]
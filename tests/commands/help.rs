@@ -18,8 +18,19 @@ fn help_command_works() {
         "mber console | c                   # Boots your ember application with DOM in a node.js repl",
         "mber test | t                      # Runs your ember tests (--server to run them in browser)",
         "mber generate | g [type] [name]    # Generate ember files for certain abstraction type",
-        "mber delete | d [type] [name]      # Remove ember files for certain abstraction type"
+        "mber delete | d [type] [name]      # Remove ember files for certain abstraction type",
+        "--env=<environment>                # Environment to build for (Default: development)",
+        "--proxy=<url>                      # Proxies unknown requests to the given url"
     ].iter().for_each(|string| {
         assert_eq!(stdout.contains(string), true);
     })
 }
+
+#[test]
+fn unknown_options_fail_with_a_clear_error() {
+    let (stdout, output) = mber::spawn("build --verbose");
+
+    assert_eq!(output.status.success(), false);
+    assert!(stdout.contains("invalid arguments: unknown option --verbose, run mber help to see the available options"));
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("unknown option --verbose"));
+}
@@ -3,7 +3,9 @@ use std::process::{Command, Output};
 pub fn spawn(command_string: &str) -> (String, Output) {
     let mut command = Command::new("cargo");
 
-    command.env("FORCE_COLOR", "0").args(&["run"]);
+    command.env("FORCE_COLOR", "0")
+        .env("MBER_CACHE_DIR", std::env::temp_dir().join("mber-cache-test"))
+        .args(&["run"]);
 
     if command_string != "" {
         let arguments: Vec<&str> = command_string.split(" ").collect();